@@ -0,0 +1,1207 @@
+//! Arbitrage opportunity evaluation
+
+use crate::{
+    chain::{
+        clock::{Clock, SystemClock},
+        constants::{LAMPORTS_PER_SIGNATURE, MICRO_LAMPORTS_PER_LAMPORT},
+        price_store::PriceStore,
+        wsol::WalletBalance,
+    },
+    config::BotConfig,
+    dex::{Dex, PoolInfo, PoolReserves, PriceInfo},
+    error::{BotError, Result},
+    metrics::metrics,
+    session,
+};
+use serde::{Deserialize, Serialize};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap, VecDeque},
+    hash::{Hash, Hasher},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tracing::debug;
+
+/// Input size used to turn a cycle's spot-price rate into a lamport estimate
+pub const CYCLE_PROBE_LAMPORTS: u64 = 1_000_000_000;
+
+/// Lamports a transaction with `signatures` signatures costs to land: the
+/// base fee plus `compute_unit_limit` units at `compute_unit_price`
+/// micro-lamports each, rounded up
+pub fn estimated_fee_lamports(signatures: u64, compute_unit_limit: u32, compute_unit_price: u64) -> u64 {
+    let priority_micro_lamports = compute_unit_limit as u128 * compute_unit_price as u128;
+    let priority_fee = priority_micro_lamports.div_ceil(MICRO_LAMPORTS_PER_LAMPORT as u128);
+    (LAMPORTS_PER_SIGNATURE * signatures).saturating_add(priority_fee.min(u64::MAX as u128) as u64)
+}
+
+/// What landing a route costs on top of its swaps, all in lamports
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TradeCosts {
+    /// Base and priority fee of the transaction
+    pub fee_lamports: u64,
+    /// Jito tip paid by the transaction's last instruction
+    pub tip_lamports: u64,
+    /// Flash loan fee on the borrowed input
+    pub flashloan_fee_lamports: u64,
+    /// Share of the profit transferred to `bot.fee_recipient`
+    pub profit_fee_lamports: u64,
+    /// Rent for the token accounts the transaction creates
+    pub ata_rent_lamports: u64,
+}
+
+impl TradeCosts {
+    pub fn total(&self) -> u64 {
+        self.fee_lamports
+            .saturating_add(self.tip_lamports)
+            .saturating_add(self.flashloan_fee_lamports)
+            .saturating_add(self.profit_fee_lamports)
+            .saturating_add(self.ata_rent_lamports)
+    }
+}
+
+/// Compute unit price that spends `fee_profit_bps` of a route's profit on
+/// its priority fee, where `profit` is already net of every cost other than
+/// the transaction fee (see `TradeCosts`). The share is taken from what is
+/// left after the base fee and `min_profit_lamports`, so paying it never
+/// pushes the trade below the minimum profit; unprofitable routes get no
+/// priority fee.
+pub fn profit_compute_unit_price(
+    profit: i64,
+    signatures: u64,
+    compute_unit_limit: u32,
+    fee_profit_bps: u16,
+    min_profit_lamports: u64,
+) -> u64 {
+    if compute_unit_limit == 0 {
+        return 0;
+    }
+    let available = (profit.max(0) as u64)
+        .saturating_sub(LAMPORTS_PER_SIGNATURE * signatures)
+        .saturating_sub(min_profit_lamports);
+    let budget = available as u128 * fee_profit_bps.min(10_000) as u128 / 10_000;
+    // Rounding down keeps `estimated_fee_lamports` within the budget
+    let price = budget * MICRO_LAMPORTS_PER_LAMPORT as u128 / compute_unit_limit as u128;
+    price.min(u64::MAX as u128) as u64
+}
+
+/// Cap a SOL-funded trade's input to what the wallet holds. Native SOL must
+/// keep `fee_buffer_lamports` for fees; the input is paid from the rest of it
+/// when `wrap_native` wraps it per trade, and from WSOL otherwise. Errors when
+/// the wallet can't fund `min_viable_input` or its fees.
+pub fn cap_input_to_balance(
+    amount_in: u64,
+    balance: &WalletBalance,
+    fee_buffer_lamports: u64,
+    min_viable_input: u64,
+    wrap_native: bool,
+) -> Result<u64> {
+    if balance.native_lamports < fee_buffer_lamports {
+        return Err(BotError::Validation(format!(
+            "Wallet holds {} lamports of SOL, below the {} lamport fee buffer",
+            balance.native_lamports, fee_buffer_lamports
+        )));
+    }
+    let (available, source) = if wrap_native {
+        (balance.native_lamports - fee_buffer_lamports, "SOL above the fee buffer")
+    } else {
+        (balance.wsol_lamports, "WSOL")
+    };
+    let capped = amount_in.min(available);
+    if capped < min_viable_input.max(1) {
+        return Err(BotError::Validation(format!(
+            "Wallet holds {} lamports of {}, below the minimum viable input of {}",
+            available, source, min_viable_input
+        )));
+    }
+    if capped < amount_in {
+        debug!(
+            "Capping input of {} lamports to the wallet's {} lamports of {}",
+            amount_in, capped, source
+        );
+    }
+    Ok(capped)
+}
+
+/// A quoted leg of an arbitrage opportunity
+#[derive(Debug, Clone)]
+pub struct LegQuote {
+    pub pool_address: Pubkey,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    /// Marginal (pre-trade) price of the pool, in whole output tokens per
+    /// whole input token
+    pub spot_price: f64,
+    pub input_decimals: u8,
+    pub output_decimals: u8,
+}
+
+impl LegQuote {
+    /// Price impact of this leg in basis points, comparing the effective fill
+    /// price against the pool's spot price. Raw amounts are scaled by their
+    /// mints' decimals first, so legs between mints of different precision
+    /// compare like for like.
+    pub fn price_impact_bps(&self) -> u64 {
+        if self.amount_in == 0 || self.spot_price <= 0.0 {
+            return 0;
+        }
+        let scale = 10f64.powi(self.input_decimals as i32 - self.output_decimals as i32);
+        let effective_price = self.amount_out as f64 / self.amount_in as f64 * scale;
+        let impact = (self.spot_price - effective_price) / self.spot_price * 10_000.0;
+        if impact <= 0.0 {
+            0
+        } else {
+            impact.ceil() as u64
+        }
+    }
+}
+
+/// Evaluates opportunities leg by leg, rejecting toxic fills even when the
+/// opportunity as a whole is net profitable
+#[derive(Debug, Clone)]
+pub struct OpportunityEvaluator {
+    max_price_impact_bps: u16,
+    max_slot_lag: u64,
+    min_profit_lamports: u64,
+}
+
+impl OpportunityEvaluator {
+    pub fn new(max_price_impact_bps: u16) -> Self {
+        Self {
+            max_price_impact_bps,
+            max_slot_lag: u64::MAX,
+            min_profit_lamports: 0,
+        }
+    }
+
+    pub fn from_config(config: &BotConfig) -> Self {
+        Self::new(config.max_price_impact_bps)
+            .with_max_slot_lag(config.max_slot_lag)
+            .with_min_profit(config.min_profit_lamports)
+    }
+
+    /// Reject routes whose profit after fees is below `min_profit_lamports`
+    pub fn with_min_profit(mut self, min_profit_lamports: u64) -> Self {
+        self.min_profit_lamports = min_profit_lamports;
+        self
+    }
+
+    /// Reject quotes and routes against reserves more than `max_slot_lag`
+    /// slots behind the current slot
+    pub fn with_max_slot_lag(mut self, max_slot_lag: u64) -> Self {
+        self.max_slot_lag = max_slot_lag;
+        self
+    }
+
+    /// Quote `amount_in` through a pool against freshly read reserves,
+    /// rejecting the quote if the snapshot is already stale at `current_slot`
+    pub async fn quote_fresh(
+        &self,
+        dex: &dyn Dex,
+        pool_info: &PoolInfo,
+        amount_in: u64,
+        current_slot: u64,
+    ) -> Result<u64> {
+        let reserves = dex.fetch_reserves(pool_info).await?;
+        let lag = reserves.age(current_slot);
+        if lag > self.max_slot_lag {
+            return Err(BotError::Validation(format!(
+                "Pool {} reserves from slot {} are {} slots behind slot {} (max {})",
+                pool_info.pool_address, reserves.slot, lag, current_slot, self.max_slot_lag
+            )));
+        }
+        dex.quote(pool_info, amount_in, &reserves).await
+    }
+
+    /// Evaluate the legs in order and return the net profit in input units
+    pub fn evaluate(&self, legs: &[LegQuote]) -> Result<i64> {
+        let (first, last) = match (legs.first(), legs.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return Err(BotError::Validation("Opportunity has no legs".to_string())),
+        };
+
+        for (i, leg) in legs.iter().enumerate() {
+            let impact_bps = leg.price_impact_bps();
+            if impact_bps > self.max_price_impact_bps as u64 {
+                return Err(BotError::Validation(format!(
+                    "Leg {} through pool {} has price impact of {} bps (max {} bps)",
+                    i, leg.pool_address, impact_bps, self.max_price_impact_bps
+                )));
+            }
+        }
+
+        signed_profit(first.amount_in, last.amount_out)
+    }
+
+    /// Reject a route if any leg was quoted against reserves more than
+    /// `max_slot_lag` slots behind `current_slot`. Legs without a recorded
+    /// reserve slot are treated as stale.
+    pub fn check_route_freshness(&self, route: &ArbitrageRoute, current_slot: u64) -> Result<()> {
+        for (i, leg) in route.legs.iter().enumerate() {
+            let Some(slot) = leg.reserve_slot else {
+                return Err(BotError::Validation(format!(
+                    "Leg {} through pool {} has no reserve slot",
+                    i, leg.pool
+                )));
+            };
+            let lag = current_slot.saturating_sub(slot);
+            if lag > self.max_slot_lag {
+                return Err(BotError::Validation(format!(
+                    "Leg {} through pool {} uses reserves from slot {}, {} slots behind slot {} (max {})",
+                    i, leg.pool, slot, lag, current_slot, self.max_slot_lag
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// `check_route_freshness` against the RPC node's current slot
+    pub fn check_route_freshness_now(&self, route: &ArbitrageRoute, rpc_client: &RpcClient) -> Result<()> {
+        let current_slot = rpc_client.get_slot()?;
+        self.check_route_freshness(route, current_slot)
+    }
+
+    /// Evaluate every leg of a route, returning its net profit
+    pub fn evaluate_route(&self, route: &ArbitrageRoute) -> Result<i64> {
+        let quotes: Vec<LegQuote> = route.legs.iter().map(RouteLeg::quote).collect();
+        self.evaluate(&quotes)
+    }
+
+    /// Evaluate a route and subtract what it costs to land as one transaction
+    /// at `compute_unit_price`, returning the profit after fees. Routes below
+    /// `min_profit_lamports` after fees are rejected.
+    pub fn evaluate_route_after_fees(
+        &self,
+        route: &ArbitrageRoute,
+        compute_unit_limit: u32,
+        compute_unit_price: u64,
+    ) -> Result<i64> {
+        let gross_profit = self.evaluate_route(route)?;
+        let fee = estimated_fee_lamports(1, compute_unit_limit, compute_unit_price);
+        self.check_profit(gross_profit, fee)
+    }
+
+    /// Profit left after `fee_lamports`, or an error when it is below
+    /// `min_profit_lamports`
+    pub fn check_profit(&self, gross_profit: i64, fee_lamports: u64) -> Result<i64> {
+        let net_profit = gross_profit.saturating_sub(fee_lamports.min(i64::MAX as u64) as i64);
+        if net_profit < self.min_profit_lamports.min(i64::MAX as u64) as i64 {
+            return Err(BotError::Validation(format!(
+                "Profit of {} lamports is {} after {} lamports of fees (min {})",
+                gross_profit, net_profit, fee_lamports, self.min_profit_lamports
+            )));
+        }
+        Ok(net_profit)
+    }
+}
+
+/// `amount_out - amount_in`, or an error when the difference does not fit
+/// in an `i64` rather than a wrapped profit
+fn signed_profit(amount_in: u64, amount_out: u64) -> Result<i64> {
+    let profit = i128::from(amount_out) - i128::from(amount_in);
+    i64::try_from(profit).map_err(|_| {
+        BotError::Validation(format!(
+            "Profit of {} out for {} in is out of range",
+            amount_out, amount_in
+        ))
+    })
+}
+
+/// One swap within an arbitrage route
+#[derive(Debug, Clone)]
+pub struct RouteLeg {
+    pub dex: String,
+    pub pool: Pubkey,
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub amount_in: u64,
+    pub amount_out: u64,
+    /// Marginal price of the pool before the swap, in whole output tokens
+    /// per whole input token
+    pub spot_price: f64,
+    pub input_decimals: u8,
+    pub output_decimals: u8,
+    /// Slot of the reserves the leg was quoted against, when known
+    pub reserve_slot: Option<u64>,
+}
+
+impl RouteLeg {
+    pub fn quote(&self) -> LegQuote {
+        LegQuote {
+            pool_address: self.pool,
+            amount_in: self.amount_in,
+            amount_out: self.amount_out,
+            spot_price: self.spot_price,
+            input_decimals: self.input_decimals,
+            output_decimals: self.output_decimals,
+        }
+    }
+}
+
+/// A full cycle of swaps that starts and ends in the same mint. A single-hop
+/// arbitrage is a two-leg route; triangular routes have three or more legs.
+#[derive(Debug, Clone)]
+pub struct ArbitrageRoute {
+    pub legs: Vec<RouteLeg>,
+    pub amount_in: u64,
+    pub expected_out: u64,
+    pub net_profit: i64,
+}
+
+impl ArbitrageRoute {
+    /// Build a route, checking that each leg's output feeds the next leg's
+    /// input, that the last leg returns to the starting mint, and that no pool
+    /// is used by more than one leg
+    pub fn new(legs: Vec<RouteLeg>) -> Result<Self> {
+        let (first, last) = match (legs.first(), legs.last()) {
+            (Some(first), Some(last)) if legs.len() >= 2 => (first, last),
+            _ => {
+                return Err(BotError::Validation(
+                    "Route needs at least two legs".to_string(),
+                ))
+            }
+        };
+
+        for (i, pair) in legs.windows(2).enumerate() {
+            if pair[0].output_mint != pair[1].input_mint {
+                return Err(BotError::Validation(format!(
+                    "Leg {} outputs {} but leg {} takes {}",
+                    i,
+                    pair[0].output_mint,
+                    i + 1,
+                    pair[1].input_mint
+                )));
+            }
+        }
+        if last.output_mint != first.input_mint {
+            return Err(BotError::Validation(format!(
+                "Route starts in {} but ends in {}",
+                first.input_mint, last.output_mint
+            )));
+        }
+
+        // Trading through the same pool twice just pays its fee twice
+        for (i, leg) in legs.iter().enumerate() {
+            if let Some(j) = legs[i + 1..].iter().position(|other| other.pool == leg.pool) {
+                return Err(BotError::Validation(format!(
+                    "Legs {} and {} both use pool {}",
+                    i,
+                    i + 1 + j,
+                    leg.pool
+                )));
+            }
+        }
+
+        let amount_in = first.amount_in;
+        let expected_out = last.amount_out;
+        Ok(Self {
+            net_profit: signed_profit(amount_in, expected_out)?,
+            amount_in,
+            expected_out,
+            legs,
+        })
+    }
+
+    /// Mint the route starts and ends in
+    pub fn base_mint(&self) -> Pubkey {
+        self.legs[0].input_mint
+    }
+
+    pub fn is_single_hop(&self) -> bool {
+        self.legs.len() == 2
+    }
+}
+
+/// A quoted route waiting to be sent, tagged with the blockhash its
+/// transaction is signed over and the last block height that can land it
+#[derive(Debug, Clone)]
+pub struct ArbitrageOpportunity {
+    pub mint: Pubkey,
+    pub route: RankedRoute,
+    /// `(reserve_in, reserve_out)` of each hop the route was quoted against
+    pub reserves: Vec<(u64, u64)>,
+    pub blockhash: solana_sdk::hash::Hash,
+    pub last_valid_block_height: u64,
+}
+
+impl ArbitrageOpportunity {
+    /// Whether a transaction for this opportunity can no longer land
+    pub fn is_expired(&self, current_block_height: u64) -> bool {
+        current_block_height > self.last_valid_block_height
+    }
+}
+
+/// FIFO of opportunities awaiting submission
+#[derive(Debug, Default)]
+pub struct OpportunityQueue {
+    queue: VecDeque<ArbitrageOpportunity>,
+}
+
+impl OpportunityQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, opportunity: ArbitrageOpportunity) {
+        self.queue.push_back(opportunity);
+    }
+
+    pub fn len(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// Pop the next opportunity that is still valid at `current_block_height`,
+    /// discarding any whose blockhash has expired while queued
+    pub fn pop_valid(&mut self, current_block_height: u64) -> Option<ArbitrageOpportunity> {
+        let mut dropped = 0;
+        let next = loop {
+            match self.queue.pop_front() {
+                Some(opportunity) if opportunity.is_expired(current_block_height) => {
+                    debug!(
+                        "Dropping expired opportunity for {} (valid until {}, now {})",
+                        opportunity.mint, opportunity.last_valid_block_height, current_block_height
+                    );
+                    dropped += 1;
+                }
+                next => break next,
+            }
+        };
+
+        if dropped > 0 {
+            session::stats().record_expired(dropped);
+            metrics().opportunities_expired.inc_by(dropped as u64);
+        }
+        next
+    }
+}
+
+/// A profitable cycle in the spot price graph, before sizing
+#[derive(Debug, Clone)]
+pub struct ArbRoute {
+    /// Pools in trade order
+    pub pools: Vec<Pubkey>,
+    /// Mints visited, starting and ending in the start mint; one longer than `pools`
+    pub mints: Vec<Pubkey>,
+    /// Fee fraction of each pool, from `PriceInfo::fee`
+    pub fees: Vec<f64>,
+    /// Product of the fee-adjusted spot rates along the cycle
+    pub spot_rate: f64,
+    /// Profit at spot prices for a `CYCLE_PROBE_LAMPORTS` input. Ignores price
+    /// impact, so it is an upper bound for that size.
+    pub estimated_profit_lamports: i64,
+}
+
+/// Reserve movement, in basis points of either side of any hop, after which a
+/// recently sent route is considered a new opportunity
+pub const MATERIAL_RESERVE_CHANGE_BPS: u64 = 10;
+
+/// Identity of a route for deduplication: the pools and the direction they
+/// are traded in
+pub fn route_key(route: &ArbRoute) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    route.pools.hash(&mut hasher);
+    route.mints.hash(&mut hasher);
+    hasher.finish()
+}
+
+struct SentRoute {
+    sent_at: Instant,
+    reserves: Vec<(u64, u64)>,
+}
+
+/// Tracks recently sent routes so a cycle that keeps being detected while the
+/// pools are unchanged is not sent again within `window`
+pub struct RouteDeduplicator {
+    window: Duration,
+    clock: Arc<dyn Clock>,
+    sent: HashMap<u64, SentRoute>,
+}
+
+impl RouteDeduplicator {
+    pub fn new(window: Duration) -> Self {
+        Self::with_clock(window, Arc::new(SystemClock))
+    }
+
+    /// Create a deduplicator whose window is measured with the given clock
+    pub fn with_clock(window: Duration, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            window,
+            clock,
+            sent: HashMap::new(),
+        }
+    }
+
+    pub fn from_config(config: &BotConfig) -> Self {
+        Self::new(Duration::from_millis(config.dedup_window_ms))
+    }
+
+    /// Whether `route` may be sent given the current `reserves` of its hops.
+    /// A route sent within the window is held back unless any hop's reserves
+    /// have since moved by `MATERIAL_RESERVE_CHANGE_BPS` or more.
+    pub fn should_send(&mut self, route: &ArbRoute, reserves: &[(u64, u64)]) -> bool {
+        let now = self.clock.now();
+        let window = self.window;
+        self.sent.retain(|_, sent| now.duration_since(sent.sent_at) < window);
+
+        let key = route_key(route);
+        let Some(sent) = self.sent.get(&key) else {
+            return true;
+        };
+        if reserves_changed(&sent.reserves, reserves) {
+            self.sent.remove(&key);
+            return true;
+        }
+        debug!("Skipping route through {:?}: already sent with the same reserves", route.pools);
+        false
+    }
+
+    /// Record that `route` was sent against `reserves`
+    pub fn record_sent(&mut self, route: &ArbRoute, reserves: &[(u64, u64)]) {
+        self.sent.insert(
+            route_key(route),
+            SentRoute {
+                sent_at: self.clock.now(),
+                reserves: reserves.to_vec(),
+            },
+        );
+    }
+
+    pub fn len(&self) -> usize {
+        self.sent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sent.is_empty()
+    }
+}
+
+fn reserves_changed(before: &[(u64, u64)], after: &[(u64, u64)]) -> bool {
+    if before.len() != after.len() {
+        return true;
+    }
+    let moved = |old: u64, new: u64| {
+        old.abs_diff(new) as u128 * 10_000 >= old.max(1) as u128 * MATERIAL_RESERVE_CHANGE_BPS as u128
+    };
+    before
+        .iter()
+        .zip(after)
+        .any(|(&(old_in, old_out), &(new_in, new_out))| moved(old_in, new_in) || moved(old_out, new_out))
+}
+
+/// One hop of a `RouteSpec`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouteSpecHop {
+    /// Registry name of the DEX, e.g. `raydium` or `whirlpool`
+    pub dex: String,
+    pub pool: String,
+}
+
+/// A pre-built route as read from a JSON file: the hops in trade order, the
+/// mints visited (starting and ending in the same mint) and the quoted amount
+/// at each of them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RouteSpec {
+    pub hops: Vec<RouteSpecHop>,
+    pub mints: Vec<String>,
+    pub amounts: Vec<u64>,
+}
+
+impl RouteSpec {
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> Result<Self> {
+        let data = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&data)?)
+    }
+
+    /// The route as an `ArbRoute`, checking that the hops, mints and amounts
+    /// line up, no pool is used twice and the route returns to its starting
+    /// mint
+    pub fn to_route(&self) -> Result<ArbRoute> {
+        let parse = |value: &str| {
+            value
+                .parse::<Pubkey>()
+                .map_err(|e| BotError::Validation(format!("Invalid address {} in route: {}", value, e)))
+        };
+        let pools = self.hops.iter().map(|hop| parse(&hop.pool)).collect::<Result<Vec<_>>>()?;
+        let mints = self.mints.iter().map(|mint| parse(mint)).collect::<Result<Vec<_>>>()?;
+
+        if pools.len() < 2 || mints.len() != pools.len() + 1 || self.amounts.len() != mints.len() {
+            return Err(BotError::Validation(format!(
+                "Route needs at least two hops, one more mint than hops and an amount per mint \
+                 ({} hops, {} mints, {} amounts)",
+                pools.len(),
+                mints.len(),
+                self.amounts.len()
+            )));
+        }
+        if mints.first() != mints.last() {
+            return Err(BotError::Validation(
+                "Route must start and end in the same mint".to_string(),
+            ));
+        }
+        for (i, pool) in pools.iter().enumerate() {
+            if pools[i + 1..].contains(pool) {
+                return Err(BotError::Validation(format!("Route uses pool {} more than once", pool)));
+            }
+        }
+
+        let amount_in = self.amounts[0];
+        let amount_out = self.amounts[self.amounts.len() - 1];
+        Ok(ArbRoute {
+            fees: vec![0.0; pools.len()],
+            spot_rate: if amount_in == 0 { 0.0 } else { amount_out as f64 / amount_in as f64 },
+            estimated_profit_lamports: signed_profit(amount_in, amount_out)?,
+            pools,
+            mints,
+        })
+    }
+}
+
+/// Directed swap through one pool, weighted by `-ln(rate)` so profitable
+/// cycles have negative total weight
+struct PriceEdge {
+    pool: Pubkey,
+    from: Pubkey,
+    to: Pubkey,
+    fee: f64,
+    rate: f64,
+    weight: f64,
+}
+
+/// Find profitable cycles that start and end in `start_mint` with at most
+/// `max_hops` swaps, using a bounded depth-first search over the
+/// fee-adjusted spot price graph, so triangular and longer routes through
+/// different DEXes are all found rather than only the best path into each
+/// mint. `prices` is keyed by pool address; pools without a usable price are
+/// skipped. No cycle uses the same pool twice or passes through a mint twice.
+pub fn find_cycles(
+    pools: &[PoolInfo],
+    prices: &HashMap<Pubkey, PriceInfo>,
+    start_mint: &Pubkey,
+    max_hops: usize,
+) -> Vec<ArbRoute> {
+    let mut edges = Vec::with_capacity(pools.len() * 2);
+    for pool in pools {
+        let price = match prices.get(&pool.pool_address) {
+            Some(price) if price.price > 0.0 && price.price.is_finite() => price,
+            _ => continue,
+        };
+        let keep = 1.0 - price.fee;
+        for (from, to, rate) in [
+            (pool.token_mint, pool.base_mint, price.price * keep),
+            (pool.base_mint, pool.token_mint, keep / price.price),
+        ] {
+            if rate > 0.0 {
+                edges.push(PriceEdge {
+                    pool: pool.pool_address,
+                    from,
+                    to,
+                    fee: price.fee,
+                    rate,
+                    weight: -rate.ln(),
+                });
+            }
+        }
+    }
+
+    let mut outgoing: HashMap<Pubkey, Vec<usize>> = HashMap::new();
+    for (i, edge) in edges.iter().enumerate() {
+        outgoing.entry(edge.from).or_default().push(i);
+    }
+
+    let search = CycleSearch {
+        edges: &edges,
+        outgoing: &outgoing,
+        start_mint: *start_mint,
+        max_hops,
+    };
+    let mut routes = Vec::new();
+    search.extend(&mut Vec::new(), 0.0, &mut routes);
+
+    routes.sort_by(|a, b| b.spot_rate.partial_cmp(&a.spot_rate).unwrap_or(std::cmp::Ordering::Equal));
+    debug!("Found {} profitable cycles from {}", routes.len(), start_mint);
+    routes
+}
+
+/// Pools of DEXes that can encode swaps. Others are still priced, but a route
+/// through them could never be submitted, so they are left out of routing.
+pub fn swappable_pools(pools: &[(&dyn Dex, PoolInfo)]) -> Vec<PoolInfo> {
+    pools
+        .iter()
+        .filter(|(dex, _)| dex.capabilities().supports_swap)
+        .map(|(_, pool)| pool.clone())
+        .collect()
+}
+
+/// `find_cycles` over the shared `PriceStore`, skipping pools whose latest
+/// price was observed before `min_slot`
+pub fn find_cycles_in_store(
+    pools: &[PoolInfo],
+    store: &PriceStore,
+    start_mint: &Pubkey,
+    max_hops: usize,
+    min_slot: u64,
+) -> Vec<ArbRoute> {
+    find_cycles(pools, &store.prices_since(min_slot), start_mint, max_hops)
+}
+
+/// Depth-first enumeration of the simple cycles through `start_mint`
+struct CycleSearch<'a> {
+    edges: &'a [PriceEdge],
+    outgoing: &'a HashMap<Pubkey, Vec<usize>>,
+    start_mint: Pubkey,
+    max_hops: usize,
+}
+
+impl CycleSearch<'_> {
+    /// Try every edge out of the end of `path`, recording cycles back to the
+    /// start whose total weight is negative, i.e. whose rates multiply above 1
+    fn extend(&self, path: &mut Vec<usize>, weight: f64, routes: &mut Vec<ArbRoute>) {
+        let at = path.last().map_or(self.start_mint, |&i| self.edges[i].to);
+        for &i in self.outgoing.get(&at).into_iter().flatten() {
+            let edge = &self.edges[i];
+            if path.iter().any(|&j| self.edges[j].pool == edge.pool) {
+                continue;
+            }
+            let new_weight = weight + edge.weight;
+
+            if edge.to == self.start_mint {
+                if !path.is_empty() && new_weight < 0.0 {
+                    path.push(i);
+                    routes.push(self.route(path));
+                    path.pop();
+                }
+                continue;
+            }
+            // A revisited mint only wraps a shorter cycle in a longer one, and
+            // at least one more hop is needed to get back to the start
+            if path.iter().any(|&j| self.edges[j].to == edge.to) || path.len() + 2 > self.max_hops {
+                continue;
+            }
+            path.push(i);
+            self.extend(path, new_weight, routes);
+            path.pop();
+        }
+    }
+
+    fn route(&self, path: &[usize]) -> ArbRoute {
+        let mut mints = vec![self.start_mint];
+        mints.extend(path.iter().map(|&j| self.edges[j].to));
+        let spot_rate: f64 = path.iter().map(|&j| self.edges[j].rate).product();
+        ArbRoute {
+            pools: path.iter().map(|&j| self.edges[j].pool).collect(),
+            mints,
+            fees: path.iter().map(|&j| self.edges[j].fee).collect(),
+            spot_rate,
+            estimated_profit_lamports: ((spot_rate - 1.0) * CYCLE_PROBE_LAMPORTS as f64) as i64,
+        }
+    }
+}
+
+/// A cycle quoted hop by hop against fresh reserves
+#[derive(Debug, Clone)]
+pub struct RankedRoute {
+    pub route: ArbRoute,
+    /// Quoted amount at each mint of the route, starting with the input
+    pub amounts: Vec<u64>,
+    /// Output less input of the quoted trade
+    pub net_profit: i64,
+}
+
+/// Quote `amount_in` through each route with `Dex::quote` and return the
+/// `top_k` most profitable, best first. `pools` pairs every pool the routes
+/// may use with its DEX. Routes that fail to quote or lose money are dropped,
+/// and each pool's reserves are read once however many routes use it.
+pub async fn rank_routes(
+    routes: Vec<ArbRoute>,
+    pools: &[(&dyn Dex, PoolInfo)],
+    amount_in: u64,
+    top_k: usize,
+) -> Vec<RankedRoute> {
+    let by_address: HashMap<Pubkey, &(&dyn Dex, PoolInfo)> =
+        pools.iter().map(|entry| (entry.1.pool_address, entry)).collect();
+    let mut reserves = HashMap::new();
+    let mut ranked = Vec::new();
+
+    'routes: for route in routes {
+        let mut amounts = vec![amount_in];
+        for (i, pool) in route.pools.iter().enumerate() {
+            let Some((dex, pool_info)) = by_address.get(pool).copied() else {
+                debug!("Route uses unknown pool {}", pool);
+                continue 'routes;
+            };
+            let from = route.mints[i];
+            let oriented = if pool_info.base_mint == from {
+                pool_info.clone()
+            } else {
+                pool_info.reversed()
+            };
+
+            if !reserves.contains_key(pool) {
+                match dex.fetch_reserves(pool_info).await {
+                    Ok(pool_reserves) => {
+                        reserves.insert(*pool, pool_reserves);
+                    }
+                    Err(e) => {
+                        debug!("Failed to read reserves of pool {}: {}", pool, e);
+                        continue 'routes;
+                    }
+                }
+            }
+            let mut pool_reserves = reserves[pool];
+            if pool_info.base_mint != from {
+                pool_reserves = PoolReserves {
+                    token_reserve: pool_reserves.base_reserve,
+                    base_reserve: pool_reserves.token_reserve,
+                    ..pool_reserves
+                };
+            }
+
+            match dex.quote(&oriented, amounts[i], &pool_reserves).await {
+                Ok(amount_out) if amount_out > 0 => amounts.push(amount_out),
+                Ok(_) => continue 'routes,
+                Err(e) => {
+                    debug!("Failed to quote pool {}: {}", pool, e);
+                    continue 'routes;
+                }
+            }
+        }
+
+        let net_profit = amounts[amounts.len() - 1] as i64 - amount_in as i64;
+        if net_profit > 0 {
+            ranked.push(RankedRoute {
+                route,
+                amounts,
+                net_profit,
+            });
+        }
+    }
+
+    ranked.sort_by_key(|r| std::cmp::Reverse(r.net_profit));
+    ranked.truncate(top_k);
+    ranked
+}
+
+/// Profit-maximising input for a route of constant-product hops.
+///
+/// `reserves` holds `(reserve_in, reserve_out)` for each hop in trade
+/// direction. The hops are folded into one equivalent constant-product pool,
+/// whose optimum has the closed form `(sqrt(g * A_in * A_out) - A_in) / g`
+/// for the first hop's fee multiplier `g`. Returns 0 when no input size is
+/// profitable or the reserves don't match the route.
+pub fn optimal_input(route: &ArbRoute, reserves: &[(u64, u64)]) -> u64 {
+    if reserves.is_empty() || reserves.len() != route.pools.len() || route.fees.len() != reserves.len() {
+        return 0;
+    }
+    if reserves.iter().any(|&(reserve_in, reserve_out)| reserve_in == 0 || reserve_out == 0) {
+        return 0;
+    }
+
+    let (first_in, first_out) = reserves[0];
+    let mut virtual_in = first_in as f64;
+    let mut virtual_out = first_out as f64;
+
+    // Composing out = A_out * y / (A_in + y) with the next hop keeps the same
+    // form, so fold each following hop into the virtual reserves
+    for (&(reserve_in, reserve_out), fee) in reserves[1..].iter().zip(&route.fees[1..]) {
+        let gamma = 1.0 - fee;
+        let (reserve_in, reserve_out) = (reserve_in as f64, reserve_out as f64);
+        let denominator = reserve_in + gamma * virtual_out;
+        virtual_in = reserve_in * virtual_in / denominator;
+        virtual_out = gamma * virtual_out * reserve_out / denominator;
+    }
+
+    let gamma = 1.0 - route.fees[0];
+    if gamma <= 0.0 || gamma * virtual_out <= virtual_in {
+        return 0;
+    }
+
+    let optimal = ((gamma * virtual_in * virtual_out).sqrt() - virtual_in) / gamma;
+    if optimal.is_finite() && optimal > 0.0 {
+        optimal.min(u64::MAX as f64) as u64
+    } else {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        chain::token_fetch::{build_dex_registry, TokenFetchConfig},
+        dex::{rpc::MockRpc, PoolType},
+    };
+
+    fn leg(amount_in: u64, amount_out: u64, spot_price: f64, input_decimals: u8, output_decimals: u8) -> LegQuote {
+        LegQuote {
+            pool_address: Pubkey::new_unique(),
+            amount_in,
+            amount_out,
+            spot_price,
+            input_decimals,
+            output_decimals,
+        }
+    }
+
+    #[test]
+    fn price_impact_normalizes_decimals() {
+        // 1 SOL (9 decimals) for 150 USDC (6 decimals) at a spot of 150 USDC per SOL
+        assert_eq!(leg(1_000_000_000, 150_000_000, 150.0, 9, 6).price_impact_bps(), 0);
+        // The same fill at 148.5 USDC is 1% below spot
+        assert_eq!(leg(1_000_000_000, 148_500_000, 150.0, 9, 6).price_impact_bps(), 100);
+        // And a 6 to 9 decimal leg, 2 tokens for 0.9 at a spot of 0.5, is 10% below
+        let impact = leg(2_000_000, 900_000_000, 0.5, 6, 9).price_impact_bps();
+        assert!((999..=1001).contains(&impact), "impact {}", impact);
+    }
+
+    #[test]
+    fn trade_costs_are_all_checked_against_the_profit() {
+        let costs = TradeCosts {
+            fee_lamports: 5_000,
+            tip_lamports: 10_000,
+            flashloan_fee_lamports: 3_000,
+            profit_fee_lamports: 2_000,
+            ata_rent_lamports: 2_039_280,
+        };
+        assert_eq!(costs.total(), 2_059_280);
+
+        let evaluator = OpportunityEvaluator::new(100).with_min_profit(1_000);
+        assert!(evaluator.check_profit(2_060_280, costs.total()).is_ok());
+        // The transaction fee alone would have let this through
+        assert!(evaluator.check_profit(2_060_279, costs.total()).is_err());
+        assert!(evaluator.check_profit(1_000_000, costs.fee_lamports).is_ok());
+    }
+
+    #[test]
+    fn profit_price_rounds_down_so_the_fee_stays_within_budget() {
+        // A third of the 5_000 left after the base fee is 1_666 lamports, or
+        // 5_553.3 micro-lamports over 300_000 units, rounded down
+        let price = profit_compute_unit_price(10_000, 1, 300_000, 3_333, 0);
+        assert_eq!(price, 5_553);
+        assert!(estimated_fee_lamports(1, 300_000, price) <= 5_000 + 1_666);
+    }
+
+    #[test]
+    fn profit_price_never_eats_into_the_minimum_profit() {
+        let price = profit_compute_unit_price(100_000, 1, 200_000, 10_000, 45_000);
+        let fee = estimated_fee_lamports(1, 200_000, price);
+        assert_eq!(fee, 55_000);
+        assert_eq!(100_000 - fee as i64, 45_000);
+    }
+
+    #[test]
+    fn profit_price_is_zero_without_profit_to_share() {
+        assert_eq!(profit_compute_unit_price(-1_000, 1, 200_000, 5_000, 0), 0);
+        assert_eq!(profit_compute_unit_price(4_000, 1, 200_000, 5_000, 0), 0);
+        assert_eq!(profit_compute_unit_price(100_000, 1, 0, 5_000, 0), 0);
+        // Shares above 100% are clamped to the whole of what's available
+        assert_eq!(
+            profit_compute_unit_price(105_000, 1, 100_000, 20_000, 0),
+            profit_compute_unit_price(105_000, 1, 100_000, 10_000, 0)
+        );
+    }
+
+    #[test]
+    fn price_impact_ignores_fills_better_than_spot() {
+        assert_eq!(leg(1_000, 1_100, 1.0, 6, 6).price_impact_bps(), 0);
+    }
+
+    #[test]
+    fn evaluate_rejects_legs_over_max_impact() {
+        let evaluator = OpportunityEvaluator::new(2_000);
+        let legs = [leg(1_000_000, 750_000, 1.0, 6, 6), leg(750_000, 1_010_000, 1.0, 6, 6)];
+        assert!(matches!(evaluator.evaluate(&legs), Err(BotError::Validation(_))));
+        let evaluator = OpportunityEvaluator::new(3_000);
+        assert_eq!(evaluator.evaluate(&legs).unwrap(), 10_000);
+    }
+
+    #[test]
+    fn only_pools_of_dexes_that_encode_swaps_are_routed() {
+        let registry = build_dex_registry(Arc::new(MockRpc::new()), &TokenFetchConfig::default());
+        let pools: Vec<(&dyn Dex, PoolInfo)> = registry
+            .all_dexes()
+            .into_iter()
+            .map(|dex| (dex, PoolInfo::mock(PoolType::ConstantProduct)))
+            .collect();
+        let names: HashMap<Pubkey, &str> = pools.iter().map(|(dex, pool)| (pool.pool_address, dex.name())).collect();
+
+        let mut routed: Vec<&str> = swappable_pools(&pools).iter().map(|pool| names[&pool.pool_address]).collect();
+        routed.sort();
+        assert_eq!(routed, ["pump", "raydium"]);
+    }
+
+    /// A leg on its own pool, priced at exactly its fill so it has no impact
+    fn route_leg(input_mint: Pubkey, output_mint: Pubkey, amounts: (u64, u64), decimals: (u8, u8)) -> RouteLeg {
+        let (amount_in, amount_out) = amounts;
+        RouteLeg {
+            dex: "raydium".to_string(),
+            pool: Pubkey::new_unique(),
+            input_mint,
+            output_mint,
+            amount_in,
+            amount_out,
+            spot_price: amount_out as f64 / amount_in as f64 * 10f64.powi(decimals.0 as i32 - decimals.1 as i32),
+            input_decimals: decimals.0,
+            output_decimals: decimals.1,
+            reserve_slot: Some(100),
+        }
+    }
+
+    #[test]
+    fn three_leg_route_is_priced_end_to_end() {
+        let (sol, usdc, token) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let route = ArbitrageRoute::new(vec![
+            route_leg(sol, usdc, (1_000_000_000, 150_000_000), (9, 6)),
+            route_leg(usdc, token, (150_000_000, 300_000_000), (6, 6)),
+            route_leg(token, sol, (300_000_000, 1_010_000_000), (6, 9)),
+        ])
+        .unwrap();
+        assert!(!route.is_single_hop());
+        assert_eq!(route.base_mint(), sol);
+        assert_eq!((route.amount_in, route.expected_out, route.net_profit), (1_000_000_000, 1_010_000_000, 10_000_000));
+
+        let evaluator = OpportunityEvaluator::new(100).with_min_profit(1_000_000);
+        assert_eq!(evaluator.evaluate_route(&route).unwrap(), 10_000_000);
+        let fee = estimated_fee_lamports(1, 400_000, 1_000);
+        assert_eq!(
+            evaluator.evaluate_route_after_fees(&route, 400_000, 1_000).unwrap(),
+            10_000_000 - fee as i64
+        );
+    }
+
+    #[test]
+    fn route_through_the_same_pool_twice_is_rejected() {
+        let (sol, token) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let buy = route_leg(sol, token, (1_000_000_000, 500_000_000), (9, 6));
+        let mut sell = route_leg(token, sol, (500_000_000, 1_100_000_000), (6, 9));
+        assert!(ArbitrageRoute::new(vec![buy.clone(), sell.clone()]).is_ok());
+
+        sell.pool = buy.pool;
+        let err = ArbitrageRoute::new(vec![buy, sell]).unwrap_err();
+        assert!(err.to_string().contains("both use pool"), "{}", err);
+
+        let pool = Pubkey::new_unique().to_string();
+        let spec = RouteSpec {
+            hops: vec![
+                RouteSpecHop { dex: "raydium".to_string(), pool: pool.clone() },
+                RouteSpecHop { dex: "raydium".to_string(), pool },
+            ],
+            mints: vec![sol.to_string(), token.to_string(), sol.to_string()],
+            amounts: vec![1_000_000_000, 500_000_000, 1_100_000_000],
+        };
+        assert!(matches!(spec.to_route(), Err(BotError::Validation(_))));
+    }
+
+    #[test]
+    fn profit_beyond_i64_is_an_error_rather_than_wrapped() {
+        let mint = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let legs = vec![
+            route_leg(mint, other, (1, 1), (9, 9)),
+            route_leg(other, mint, (1, u64::MAX), (9, 9)),
+        ];
+        assert!(matches!(ArbitrageRoute::new(legs.clone()), Err(BotError::Validation(_))));
+
+        let quotes: Vec<LegQuote> = legs.iter().map(RouteLeg::quote).collect();
+        assert!(matches!(OpportunityEvaluator::new(u16::MAX).evaluate(&quotes), Err(BotError::Validation(_))));
+        assert!(signed_profit(u64::MAX, 0).is_err());
+        assert_eq!(signed_profit(5, 3).unwrap(), -2);
+    }
+
+    fn two_pool_route(fees: [f64; 2]) -> ArbRoute {
+        let (sol, token) = (Pubkey::new_unique(), Pubkey::new_unique());
+        ArbRoute {
+            pools: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+            mints: vec![sol, token, sol],
+            fees: fees.to_vec(),
+            spot_rate: 0.0,
+            estimated_profit_lamports: 0,
+        }
+    }
+
+    fn opportunity(last_valid_block_height: u64) -> ArbitrageOpportunity {
+        ArbitrageOpportunity {
+            mint: Pubkey::new_unique(),
+            route: RankedRoute {
+                route: two_pool_route([0.0025, 0.0025]),
+                amounts: vec![1_000, 1_100, 1_200],
+                net_profit: 200,
+            },
+            reserves: vec![(1_000_000, 1_100_000), (1_100_000, 1_200_000)],
+            blockhash: solana_sdk::hash::Hash::new_unique(),
+            last_valid_block_height,
+        }
+    }
+
+    #[test]
+    fn pop_valid_discards_opportunities_whose_blockhash_expired() {
+        let mut queue = OpportunityQueue::new();
+        queue.push(opportunity(100));
+        queue.push(opportunity(200));
+        let expired = metrics().opportunities_expired.get();
+
+        let next = queue.pop_valid(150).unwrap();
+        assert_eq!(next.last_valid_block_height, 200);
+        assert!(queue.is_empty());
+        assert_eq!(metrics().opportunities_expired.get(), expired + 1);
+
+        queue.push(opportunity(200));
+        assert!(queue.pop_valid(201).is_none());
+    }
+
+    /// Profit of `amount_in` through constant-product hops, in floating point
+    fn profit_at(route: &ArbRoute, reserves: &[(u64, u64)], amount_in: u64) -> f64 {
+        let mut amount = amount_in as f64;
+        for (&(reserve_in, reserve_out), fee) in reserves.iter().zip(&route.fees) {
+            let effective = amount * (1.0 - fee);
+            amount = reserve_out as f64 * effective / (reserve_in as f64 + effective);
+        }
+        amount - amount_in as f64
+    }
+
+    #[test]
+    fn optimal_input_matches_the_fee_free_closed_form() {
+        // Buy at 2 tokens per SOL, sell at 1: the folded pool has
+        // A_in = 1e9 / 3 and A_out = 2e9 / 3, so the optimum is
+        // sqrt(A_in * A_out) - A_in = 1e9 * (sqrt(2) - 1) / 3
+        let reserves = [(1_000_000_000, 2_000_000_000), (1_000_000_000, 1_000_000_000)];
+        let optimal = optimal_input(&two_pool_route([0.0, 0.0]), &reserves);
+        assert!(optimal.abs_diff(138_071_187) <= 1, "optimal {}", optimal);
+    }
+
+    #[test]
+    fn optimal_input_accounts_for_each_pools_fee() {
+        // Same pools at 30 and 25 bps; g1 = 0.997, g2 = 0.9975 give folded
+        // reserves A_in = 1e18 / 2.995e9 and A_out = 0.9975 * 2e18 / 2.995e9
+        let route = two_pool_route([0.003, 0.0025]);
+        let reserves = [(1_000_000_000, 2_000_000_000), (1_000_000_000, 1_000_000_000)];
+        let optimal = optimal_input(&route, &reserves);
+        assert!(optimal.abs_diff(137_415_395) <= 1, "optimal {}", optimal);
+
+        let best = profit_at(&route, &reserves, optimal);
+        assert!(best > 0.0);
+        for nearby in [optimal - optimal / 100, optimal + optimal / 100] {
+            assert!(profit_at(&route, &reserves, nearby) < best);
+        }
+    }
+
+    #[test]
+    fn optimal_input_is_zero_without_an_edge_after_fees() {
+        let reserves = [(1_000_000_000, 1_000_000_000), (1_000_000_000, 1_000_000_000)];
+        assert_eq!(optimal_input(&two_pool_route([0.0, 0.0]), &reserves), 0);
+        // A 0.4% price gap is eaten by two 25 bps fees
+        let reserves = [(1_000_000_000, 1_004_000_000), (1_000_000_000, 1_000_000_000)];
+        assert_eq!(optimal_input(&two_pool_route([0.0025, 0.0025]), &reserves), 0);
+    }
+
+    #[test]
+    fn optimal_input_is_zero_for_mismatched_or_empty_reserves() {
+        let route = two_pool_route([0.0, 0.0]);
+        assert_eq!(optimal_input(&route, &[(1_000, 2_000)]), 0);
+        assert_eq!(optimal_input(&route, &[(1_000, 2_000), (0, 1_000)]), 0);
+    }
+}
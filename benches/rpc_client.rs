@@ -0,0 +1,68 @@
+//! Calls through the pooled client `build_rpc_client_with_timeout` returns,
+//! built once, against a default `RpcClient` built for every call as spam
+//! sends used to. Both hit a local JSON-RPC server answering `getSlot`, so the
+//! difference is client setup and connection reuse.
+//!
+//! Run with `cargo bench --bench rpc_client`.
+
+use hyper::{
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, Server,
+};
+use solana_client::rpc_client::RpcClient;
+use solana_mev_bot::{chain::rpc::build_rpc_client_with_timeout, config::RpcConfig};
+use std::{
+    convert::Infallible,
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
+const CALLS: u32 = 2_000;
+
+async fn get_slot(_request: Request<Body>) -> Result<Response<Body>, Infallible> {
+    Ok(Response::new(Body::from(r#"{"jsonrpc":"2.0","result":42,"id":1}"#)))
+}
+
+/// Serve `getSlot` on a free local port from a background runtime
+fn start_server() -> SocketAddr {
+    let (addr_tx, addr_rx) = std::sync::mpsc::channel();
+    std::thread::spawn(move || {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async move {
+            let make_service = make_service_fn(|_| async { Ok::<_, Infallible>(service_fn(get_slot)) });
+            let server = Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+            addr_tx.send(server.local_addr()).unwrap();
+            server.await.unwrap();
+        });
+    });
+    addr_rx.recv().unwrap()
+}
+
+fn bench(name: &str, mut call: impl FnMut()) {
+    call();
+    let start = Instant::now();
+    for _ in 0..CALLS {
+        call();
+    }
+    let elapsed = start.elapsed();
+    println!(
+        "{:<32} {:>8.1} us/call ({} calls in {:?})",
+        name,
+        elapsed.as_secs_f64() * 1e6 / CALLS as f64,
+        CALLS,
+        elapsed
+    );
+}
+
+fn main() {
+    let url = format!("http://{}", start_server());
+    let config = RpcConfig::default();
+
+    let pooled = build_rpc_client_with_timeout(&url, &config, Duration::from_millis(config.request_timeout_ms)).unwrap();
+    bench("pooled client, built once", || {
+        assert_eq!(pooled.get_slot().unwrap(), 42);
+    });
+    bench("default client, built per call", || {
+        assert_eq!(RpcClient::new(url.clone()).get_slot().unwrap(), 42);
+    });
+}
@@ -0,0 +1,131 @@
+use crate::chain::clock::{Clock, SystemClock};
+use anyhow::Result;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::hash::Hash;
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tracing::debug;
+
+/// Recent blockhash shared by transaction builders.
+///
+/// The cached hash is reused until it is older than `max_age`, or until a
+/// send fails with an expired blockhash and the caller forces a refresh.
+pub struct BlockhashCache {
+    rpc_client: Arc<RpcClient>,
+    max_age: Duration,
+    latest: Mutex<Option<CachedBlockhash>>,
+    clock: Arc<dyn Clock>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct CachedBlockhash {
+    hash: Hash,
+    last_valid_block_height: u64,
+    fetched_at: Instant,
+}
+
+impl BlockhashCache {
+    pub fn new(rpc_client: Arc<RpcClient>, max_age: Duration) -> Self {
+        Self::with_clock(rpc_client, max_age, Arc::new(SystemClock))
+    }
+
+    /// Create a cache whose blockhash age is measured with the given clock
+    pub fn with_clock(rpc_client: Arc<RpcClient>, max_age: Duration, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            rpc_client,
+            max_age,
+            latest: Mutex::new(None),
+            clock,
+        }
+    }
+
+    /// Return the cached blockhash, fetching a new one if it is missing or stale
+    pub fn get(&self) -> Result<Hash> {
+        self.get_with_expiry().map(|(hash, _)| hash)
+    }
+
+    /// Return the cached blockhash together with the last block height at
+    /// which transactions signed with it are still valid
+    pub fn get_with_expiry(&self) -> Result<(Hash, u64)> {
+        if let Some(cached) = *self.latest.lock().unwrap() {
+            if self.clock.now().duration_since(cached.fetched_at) < self.max_age {
+                return Ok((cached.hash, cached.last_valid_block_height));
+            }
+        }
+        self.refresh_with_expiry()
+    }
+
+    /// Fetch the latest blockhash from the RPC node, replacing the cached one
+    pub fn refresh(&self) -> Result<Hash> {
+        self.refresh_with_expiry().map(|(hash, _)| hash)
+    }
+
+    /// Fetch the latest blockhash with its last valid block height, replacing
+    /// the cached one
+    pub fn refresh_with_expiry(&self) -> Result<(Hash, u64)> {
+        let (hash, last_valid_block_height) = self
+            .rpc_client
+            .get_latest_blockhash_with_commitment(self.rpc_client.commitment())?;
+        debug!(
+            "Refreshed blockhash: {} (valid until block height {})",
+            hash, last_valid_block_height
+        );
+        *self.latest.lock().unwrap() = Some(CachedBlockhash {
+            hash,
+            last_valid_block_height,
+            fetched_at: self.clock.now(),
+        });
+        Ok((hash, last_valid_block_height))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::clock::MockClock;
+    use serde_json::json;
+    use solana_client::rpc_request::RpcRequest;
+    use std::collections::HashMap;
+
+    /// Client whose first `getLatestBlockhash` returns `hash`; later calls
+    /// get the mock sender's default blockhash
+    fn client_returning(hash: Hash) -> Arc<RpcClient> {
+        let mut mocks = HashMap::new();
+        mocks.insert(
+            RpcRequest::GetLatestBlockhash,
+            json!({
+                "context": { "slot": 1 },
+                "value": { "blockhash": hash.to_string(), "lastValidBlockHeight": 150 },
+            }),
+        );
+        Arc::new(RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks))
+    }
+
+    #[test]
+    fn reuses_the_blockhash_until_max_age() {
+        let hash = Hash::new_unique();
+        let clock = Arc::new(MockClock::new());
+        let cache = BlockhashCache::with_clock(client_returning(hash), Duration::from_secs(10), clock.clone());
+
+        assert_eq!(cache.get_with_expiry().unwrap(), (hash, 150));
+        clock.advance(Duration::from_secs(9));
+        assert_eq!(cache.get().unwrap(), hash);
+        clock.advance(Duration::from_secs(1));
+        assert_ne!(cache.get().unwrap(), hash);
+    }
+
+    #[test]
+    fn refresh_replaces_a_fresh_blockhash() {
+        let hash = Hash::new_unique();
+        let cache = BlockhashCache::with_clock(
+            client_returning(hash),
+            Duration::from_secs(10),
+            Arc::new(MockClock::new()),
+        );
+
+        assert_eq!(cache.get().unwrap(), hash);
+        assert_ne!(cache.refresh().unwrap(), hash);
+    }
+}
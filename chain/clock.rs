@@ -0,0 +1,75 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+/// Source of time for caching and freshness checks, so TTL and staleness
+/// behavior can be driven deterministically instead of by sleeping
+pub trait Clock: Send + Sync {
+    /// Monotonic time used for cache ages
+    fn now(&self) -> Instant;
+
+    /// Wall-clock time in seconds since the Unix epoch
+    fn unix_timestamp(&self) -> u64;
+}
+
+/// Clock backed by the system time
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn unix_timestamp(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default()
+    }
+}
+
+/// Manually advanced clock for deterministic tests and simulations
+#[derive(Debug)]
+pub struct MockClock {
+    start: Instant,
+    start_unix: u64,
+    offset: Mutex<Duration>,
+}
+
+impl MockClock {
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            start_unix: SystemClock.unix_timestamp(),
+            offset: Mutex::new(Duration::ZERO),
+        }
+    }
+
+    /// Move the clock forward by `duration`
+    pub fn advance(&self, duration: Duration) {
+        let mut offset = self.offset.lock().unwrap();
+        *offset += duration;
+    }
+
+    fn offset(&self) -> Duration {
+        *self.offset.lock().unwrap()
+    }
+}
+
+impl Default for MockClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> Instant {
+        self.start + self.offset()
+    }
+
+    fn unix_timestamp(&self) -> u64 {
+        self.start_unix + self.offset().as_secs()
+    }
+}
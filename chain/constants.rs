@@ -6,3 +6,15 @@ pub const SOL_MINT: &str = "So11111111111111111111111111111111111111112";
 pub fn sol_mint() -> Pubkey {
     Pubkey::from_str(SOL_MINT).unwrap()
 }
+
+/// Fee charged per signature before any priority fee
+pub const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+/// Compute unit prices are quoted in micro-lamports per unit
+pub const MICRO_LAMPORTS_PER_LAMPORT: u64 = 1_000_000;
+
+pub const TOKEN_2022_PROGRAM_ID: &str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCQEN7L6vB";
+
+pub fn token_2022_program_id() -> Pubkey {
+    Pubkey::from_str(TOKEN_2022_PROGRAM_ID).unwrap()
+}
@@ -0,0 +1,52 @@
+use anyhow::{anyhow, Result};
+use rayon::prelude::*;
+use solana_sdk::{account::Account, pubkey::Pubkey};
+
+/// Decodes batches of fetched accounts, optionally across a dedicated rayon
+/// thread pool. Decoding is pure CPU work, so each account is decoded
+/// independently and a failure only affects that account's result.
+pub struct AccountDecoder {
+    pool: Option<rayon::ThreadPool>,
+}
+
+impl AccountDecoder {
+    /// `threads <= 1` decodes sequentially on the calling thread
+    pub fn new(threads: usize) -> Result<Self> {
+        let pool = if threads > 1 {
+            Some(
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(threads)
+                    .thread_name(|i| format!("account-decode-{}", i))
+                    .build()?,
+            )
+        } else {
+            None
+        };
+        Ok(Self { pool })
+    }
+
+    /// Decode every fetched account, returning one result per input in order.
+    /// Accounts that were not found yield an error for that entry only.
+    pub fn decode<T, F>(
+        &self,
+        accounts: &[(Pubkey, Option<Account>)],
+        decode: F,
+    ) -> Vec<(Pubkey, Result<T>)>
+    where
+        T: Send,
+        F: Fn(&Pubkey, &Account) -> Result<T> + Sync + Send,
+    {
+        let decode_one = |(pubkey, account): &(Pubkey, Option<Account>)| {
+            let result = match account {
+                Some(account) => decode(pubkey, account),
+                None => Err(anyhow!("Account {} not found", pubkey)),
+            };
+            (*pubkey, result)
+        };
+
+        match &self.pool {
+            Some(pool) => pool.install(|| accounts.par_iter().map(decode_one).collect()),
+            None => accounts.iter().map(decode_one).collect(),
+        }
+    }
+}
@@ -0,0 +1,234 @@
+use crate::config::{FlashloanConfig, FlashloanProvider};
+use anyhow::{anyhow, Result};
+use solana_client::rpc_client::RpcClient;
+use solana_program::instruction::{AccountMeta, Instruction};
+use solana_sdk::{pubkey::Pubkey, sysvar};
+use spl_associated_token_account::get_associated_token_address;
+use std::str::FromStr;
+
+pub const KAMINO_LENDING_PROGRAM_ID: &str = "KLend2g3cP87fffoy8q1mQqGKjrxjC8boSyAYavgmjD";
+pub const SOLEND_PROGRAM_ID: &str = "So1endDq2YkqhipRh3WViPa8hdiSpxWy6z3Z6tMCpAo";
+
+/// Anchor discriminators of the Kamino `flash_borrow_reserve_liquidity` and
+/// `flash_repay_reserve_liquidity` instructions
+const KAMINO_FLASH_BORROW_DISCRIMINATOR: [u8; 8] = [135, 231, 52, 167, 7, 52, 212, 193];
+const KAMINO_FLASH_REPAY_DISCRIMINATOR: [u8; 8] = [185, 117, 0, 203, 96, 245, 180, 186];
+
+/// Solend instruction tags for `FlashBorrowReserveLiquidity` and
+/// `FlashRepayReserveLiquidity`
+const SOLEND_FLASH_BORROW_TAG: u8 = 19;
+const SOLEND_FLASH_REPAY_TAG: u8 = 20;
+
+/// Offsets of the lending market, liquidity mint, supply vault and fee vault
+/// in a Kamino `Reserve` account, after the 8 byte discriminator, `version`
+/// and `last_update`, and the two farm addresses following the market
+const KAMINO_LENDING_MARKET_OFFSET: usize = 32;
+const KAMINO_LIQUIDITY_MINT_OFFSET: usize = 128;
+const KAMINO_SUPPLY_VAULT_OFFSET: usize = 160;
+const KAMINO_FEE_VAULT_OFFSET: usize = 192;
+
+/// Offsets of the same fields in a Solend `Reserve` account: `version` and
+/// `last_update` come first, and the fee receiver follows the reserve's
+/// collateral state and config
+const SOLEND_LENDING_MARKET_OFFSET: usize = 10;
+const SOLEND_LIQUIDITY_MINT_OFFSET: usize = 42;
+const SOLEND_SUPPLY_VAULT_OFFSET: usize = 75;
+const SOLEND_FEE_RECEIVER_OFFSET: usize = 339;
+
+/// A lending reserve to flash borrow the route's input from, with the
+/// accounts its borrow and repay instructions need
+#[derive(Debug, Clone)]
+pub struct Flashloan {
+    pub provider: FlashloanProvider,
+    pub program_id: Pubkey,
+    pub reserve: Pubkey,
+    pub lending_market: Pubkey,
+    pub liquidity_mint: Pubkey,
+    pub supply_vault: Pubkey,
+    pub fee_receiver: Pubkey,
+    pub max_borrow_lamports: Option<u64>,
+    /// Flash loan fee charged on the borrowed amount, from `flashloan.fee_bps`
+    pub fee_bps: u16,
+}
+
+impl Flashloan {
+    /// Read the configured reserve and resolve its market and vaults
+    pub fn load(rpc_client: &RpcClient, config: &FlashloanConfig) -> Result<Self> {
+        let reserve_address = config
+            .reserve
+            .as_deref()
+            .ok_or_else(|| anyhow!("flashloan.reserve must be set when flashloan is enabled"))?;
+        let reserve = Pubkey::from_str(reserve_address)
+            .map_err(|e| anyhow!("Invalid flashloan reserve {}: {}", reserve_address, e))?;
+        let account = rpc_client.get_account(&reserve)?;
+
+        let (program_id, offsets) = match config.provider {
+            FlashloanProvider::Kamino => (
+                KAMINO_LENDING_PROGRAM_ID,
+                [
+                    KAMINO_LENDING_MARKET_OFFSET,
+                    KAMINO_LIQUIDITY_MINT_OFFSET,
+                    KAMINO_SUPPLY_VAULT_OFFSET,
+                    KAMINO_FEE_VAULT_OFFSET,
+                ],
+            ),
+            FlashloanProvider::Solend => (
+                SOLEND_PROGRAM_ID,
+                [
+                    SOLEND_LENDING_MARKET_OFFSET,
+                    SOLEND_LIQUIDITY_MINT_OFFSET,
+                    SOLEND_SUPPLY_VAULT_OFFSET,
+                    SOLEND_FEE_RECEIVER_OFFSET,
+                ],
+            ),
+        };
+        let program_id = Pubkey::from_str(program_id).unwrap();
+        if account.owner != program_id {
+            return Err(anyhow!(
+                "Flashloan reserve {} is owned by {}, not the {:?} program",
+                reserve,
+                account.owner,
+                config.provider
+            ));
+        }
+
+        let [market, mint, supply, fee] = offsets.map(|offset| read_pubkey(&account.data, offset));
+        Ok(Self {
+            provider: config.provider,
+            program_id,
+            reserve,
+            lending_market: market?,
+            liquidity_mint: mint?,
+            supply_vault: supply?,
+            fee_receiver: fee?,
+            max_borrow_lamports: config.max_borrow_lamports,
+            fee_bps: config.fee_bps,
+        })
+    }
+
+    /// How much of `amount_in` to borrow, capped at `max_borrow_lamports`.
+    /// Anything above the cap comes from the wallet.
+    pub fn borrow_amount(&self, amount_in: u64) -> u64 {
+        self.max_borrow_lamports
+            .map_or(amount_in, |max| amount_in.min(max))
+    }
+
+    /// Fee owed on top of repaying `borrowed`, rounded up as lenders do
+    pub fn fee_lamports(&self, borrowed: u64) -> u64 {
+        (borrowed as u128 * self.fee_bps as u128).div_ceil(10_000).min(u64::MAX as u128) as u64
+    }
+
+    /// Lend `amount` of the reserve's liquidity to the user's token account
+    pub fn borrow_instruction(&self, user: &Pubkey, amount: u64) -> Instruction {
+        let user_liquidity = get_associated_token_address(user, &self.liquidity_mint);
+        match self.provider {
+            FlashloanProvider::Kamino => {
+                let mut data = KAMINO_FLASH_BORROW_DISCRIMINATOR.to_vec();
+                data.extend_from_slice(&amount.to_le_bytes());
+                Instruction {
+                    program_id: self.program_id,
+                    accounts: vec![
+                        AccountMeta::new_readonly(*user, true),
+                        AccountMeta::new_readonly(self.lending_market_authority(), false),
+                        AccountMeta::new_readonly(self.lending_market, false),
+                        AccountMeta::new(self.reserve, false),
+                        AccountMeta::new_readonly(self.liquidity_mint, false),
+                        AccountMeta::new(self.supply_vault, false),
+                        AccountMeta::new(user_liquidity, false),
+                        AccountMeta::new(self.fee_receiver, false),
+                        // No referrer: optional accounts are passed as the program id
+                        AccountMeta::new_readonly(self.program_id, false),
+                        AccountMeta::new_readonly(self.program_id, false),
+                        AccountMeta::new_readonly(sysvar::instructions::ID, false),
+                        AccountMeta::new_readonly(spl_token::ID, false),
+                    ],
+                    data,
+                }
+            }
+            FlashloanProvider::Solend => {
+                let mut data = vec![SOLEND_FLASH_BORROW_TAG];
+                data.extend_from_slice(&amount.to_le_bytes());
+                Instruction {
+                    program_id: self.program_id,
+                    accounts: vec![
+                        AccountMeta::new(self.supply_vault, false),
+                        AccountMeta::new(user_liquidity, false),
+                        AccountMeta::new(self.reserve, false),
+                        AccountMeta::new_readonly(self.lending_market, false),
+                        AccountMeta::new_readonly(self.lending_market_authority(), false),
+                        AccountMeta::new_readonly(sysvar::instructions::ID, false),
+                        AccountMeta::new_readonly(spl_token::ID, false),
+                    ],
+                    data,
+                }
+            }
+        }
+    }
+
+    /// Return `amount` plus the reserve's flash loan fee, which the program
+    /// adds itself. `borrow_index` is the position of the matching borrow
+    /// instruction in the transaction.
+    pub fn repay_instruction(&self, user: &Pubkey, amount: u64, borrow_index: u8) -> Instruction {
+        let user_liquidity = get_associated_token_address(user, &self.liquidity_mint);
+        match self.provider {
+            FlashloanProvider::Kamino => {
+                let mut data = KAMINO_FLASH_REPAY_DISCRIMINATOR.to_vec();
+                data.extend_from_slice(&amount.to_le_bytes());
+                data.push(borrow_index);
+                Instruction {
+                    program_id: self.program_id,
+                    accounts: vec![
+                        AccountMeta::new_readonly(*user, true),
+                        AccountMeta::new_readonly(self.lending_market_authority(), false),
+                        AccountMeta::new_readonly(self.lending_market, false),
+                        AccountMeta::new(self.reserve, false),
+                        AccountMeta::new_readonly(self.liquidity_mint, false),
+                        AccountMeta::new(self.supply_vault, false),
+                        AccountMeta::new(user_liquidity, false),
+                        AccountMeta::new(self.fee_receiver, false),
+                        AccountMeta::new_readonly(self.program_id, false),
+                        AccountMeta::new_readonly(self.program_id, false),
+                        AccountMeta::new_readonly(sysvar::instructions::ID, false),
+                        AccountMeta::new_readonly(spl_token::ID, false),
+                    ],
+                    data,
+                }
+            }
+            FlashloanProvider::Solend => {
+                let mut data = vec![SOLEND_FLASH_REPAY_TAG];
+                data.extend_from_slice(&amount.to_le_bytes());
+                data.push(borrow_index);
+                Instruction {
+                    program_id: self.program_id,
+                    accounts: vec![
+                        AccountMeta::new(user_liquidity, false),
+                        AccountMeta::new(self.supply_vault, false),
+                        AccountMeta::new(self.fee_receiver, false),
+                        // The host fee goes to the fee receiver as well
+                        AccountMeta::new(self.fee_receiver, false),
+                        AccountMeta::new(self.reserve, false),
+                        AccountMeta::new_readonly(self.lending_market, false),
+                        AccountMeta::new_readonly(*user, true),
+                        AccountMeta::new_readonly(sysvar::instructions::ID, false),
+                        AccountMeta::new_readonly(spl_token::ID, false),
+                    ],
+                    data,
+                }
+            }
+        }
+    }
+
+    fn lending_market_authority(&self) -> Pubkey {
+        let seeds: &[&[u8]] = match self.provider {
+            FlashloanProvider::Kamino => &[b"lma", self.lending_market.as_ref()],
+            FlashloanProvider::Solend => &[self.lending_market.as_ref()],
+        };
+        Pubkey::find_program_address(seeds, &self.program_id).0
+    }
+}
+
+fn read_pubkey(data: &[u8], offset: usize) -> Result<Pubkey> {
+    data.get(offset..offset + 32)
+        .map(|bytes| Pubkey::new_from_array(bytes.try_into().unwrap()))
+        .ok_or_else(|| anyhow!("Reserve account too short to read offset {}", offset))
+}
@@ -1,7 +1,20 @@
+pub mod blockhash;
+pub mod clock;
 pub mod constants;
+pub mod decode;
+pub mod flashloan;
+pub mod pool_filter;
 pub mod pools;
+pub mod priority_fee;
+pub mod price_store;
 pub mod refresh;
+pub mod rpc;
+pub mod rpc_budget;
+pub mod simulate;
+pub mod submit;
+pub mod subscribe;
 pub mod transaction;
+pub mod wsol;
 pub mod token_fetch;
 pub mod token_price;
 
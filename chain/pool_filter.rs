@@ -0,0 +1,156 @@
+use anyhow::{anyhow, Result};
+use solana_sdk::pubkey::Pubkey;
+use std::{collections::HashSet, str::FromStr};
+use tracing::info;
+
+/// Per-mint pool selection applied before pools are added to `MintPoolData`.
+///
+/// Pools are ranked by liquidity (unknown last) and dropped when below
+/// `min_liquidity_lamports` or beyond `max_pools`. Pinned pools are always
+/// kept, and a log line records whenever one would otherwise have been dropped.
+/// Disabled pools are never kept, pinned or not.
+#[derive(Debug, Clone, Default)]
+pub struct PoolFilter {
+    pinned: HashSet<Pubkey>,
+    disabled: HashSet<Pubkey>,
+    min_liquidity_lamports: Option<u64>,
+    max_pools: Option<usize>,
+}
+
+impl PoolFilter {
+    pub fn new(pinned_pools: Option<&Vec<String>>, max_pools: Option<usize>) -> Result<Self> {
+        let pinned = pinned_pools
+            .into_iter()
+            .flatten()
+            .map(|address| {
+                Pubkey::from_str(address)
+                    .map_err(|e| anyhow!("Invalid pinned pool address {}: {}", address, e))
+            })
+            .collect::<Result<HashSet<_>>>()?;
+
+        Ok(Self {
+            pinned,
+            disabled: HashSet::new(),
+            min_liquidity_lamports: None,
+            max_pools,
+        })
+    }
+
+    pub fn with_min_liquidity(mut self, min_liquidity_lamports: Option<u64>) -> Self {
+        self.min_liquidity_lamports = min_liquidity_lamports;
+        self
+    }
+
+    /// Blocklist `disabled_pools` from a mint's config
+    pub fn with_disabled_pools(mut self, disabled_pools: &[String]) -> Result<Self> {
+        self.disabled = disabled_pools
+            .iter()
+            .map(|address| {
+                Pubkey::from_str(address)
+                    .map_err(|e| anyhow!("Invalid disabled pool address {}: {}", address, e))
+            })
+            .collect::<Result<HashSet<_>>>()?;
+        Ok(self)
+    }
+
+    pub fn is_pinned(&self, pool: &Pubkey) -> bool {
+        self.pinned.contains(pool)
+    }
+
+    pub fn is_disabled(&self, pool: &Pubkey) -> bool {
+        self.disabled.contains(pool)
+    }
+
+    pub fn min_liquidity_lamports(&self) -> Option<u64> {
+        self.min_liquidity_lamports
+    }
+
+    /// Whether `apply` ranks or drops pools by liquidity, so callers must
+    /// price the pools first for it to have any effect
+    pub fn needs_liquidity(&self) -> bool {
+        self.min_liquidity_lamports.is_some() || self.max_pools.is_some()
+    }
+
+    /// Filter `pools`, returning the kept pools and how many were dropped.
+    /// `liquidity` of `None` means unknown, which never fails the liquidity check.
+    pub fn apply<T>(
+        &self,
+        mut pools: Vec<T>,
+        address: impl Fn(&T) -> Pubkey,
+        liquidity: impl Fn(&T) -> Option<u64>,
+    ) -> (Vec<T>, usize) {
+        pools.sort_by_key(|pool| std::cmp::Reverse(liquidity(pool)));
+
+        let mut kept = Vec::with_capacity(pools.len());
+        let mut dropped = 0;
+
+        for pool in pools {
+            let pool_address = address(&pool);
+            let below_min = matches!(
+                (liquidity(&pool), self.min_liquidity_lamports),
+                (Some(liquidity), Some(min)) if liquidity < min
+            );
+            let over_cap = self.max_pools.is_some_and(|max| kept.len() >= max);
+
+            if below_min || over_cap {
+                if self.is_pinned(&pool_address) {
+                    info!(
+                        "Keeping pinned pool {} that would have been filtered ({})",
+                        pool_address,
+                        if below_min {
+                            "below min liquidity"
+                        } else {
+                            "over max pools per mint"
+                        }
+                    );
+                } else {
+                    dropped += 1;
+                    continue;
+                }
+            }
+            kept.push(pool);
+        }
+
+        (kept, dropped)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pools(liquidity: &[Option<u64>]) -> Vec<(Pubkey, Option<u64>)> {
+        liquidity.iter().map(|liquidity| (Pubkey::new_unique(), *liquidity)).collect()
+    }
+
+    fn apply(filter: &PoolFilter, pools: Vec<(Pubkey, Option<u64>)>) -> (Vec<(Pubkey, Option<u64>)>, usize) {
+        filter.apply(pools, |(address, _)| *address, |(_, liquidity)| *liquidity)
+    }
+
+    #[test]
+    fn needs_liquidity_only_when_ranking_or_thresholding() {
+        assert!(!PoolFilter::new(None, None).unwrap().needs_liquidity());
+        assert!(PoolFilter::new(None, Some(2)).unwrap().needs_liquidity());
+        assert!(PoolFilter::new(None, None).unwrap().with_min_liquidity(Some(1)).needs_liquidity());
+    }
+
+    #[test]
+    fn max_pools_keeps_the_deepest() {
+        let filter = PoolFilter::new(None, Some(2)).unwrap();
+        let (kept, dropped) = apply(&filter, pools(&[Some(10), None, Some(30), Some(20)]));
+        let liquidity: Vec<Option<u64>> = kept.iter().map(|(_, liquidity)| *liquidity).collect();
+        assert_eq!(liquidity, vec![Some(30), Some(20)]);
+        assert_eq!(dropped, 2);
+    }
+
+    #[test]
+    fn min_liquidity_drops_shallow_pools_but_keeps_unknown_and_pinned() {
+        let input = pools(&[Some(5), Some(50), None, Some(1)]);
+        let pinned = vec![input[3].0.to_string()];
+        let filter = PoolFilter::new(Some(&pinned), None).unwrap().with_min_liquidity(Some(10));
+        let (kept, dropped) = apply(&filter, input.clone());
+        let kept: Vec<Pubkey> = kept.into_iter().map(|(address, _)| address).collect();
+        assert_eq!(kept, vec![input[1].0, input[3].0, input[2].0]);
+        assert_eq!(dropped, 1);
+    }
+}
@@ -2,10 +2,11 @@ use crate::{
     chain::constants::SOL_MINT,
     dex::raydium::{clmm_info::POOL_TICK_ARRAY_BITMAP_SEED, raydium_clmm_program_id},
 };
+use serde::{Deserialize, Serialize};
 use solana_sdk::pubkey::Pubkey;
 use std::str::FromStr;
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RaydiumPool {
     pub pool: Pubkey,
     pub token_vault: Pubkey,
@@ -14,7 +15,7 @@ pub struct RaydiumPool {
     pub base_mint: Pubkey,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RaydiumCpPool {
     pub pool: Pubkey,
     pub token_vault: Pubkey,
@@ -25,7 +26,7 @@ pub struct RaydiumCpPool {
     pub base_mint: Pubkey,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PumpPool {
     pub pool: Pubkey,
     pub token_vault: Pubkey,
@@ -37,7 +38,7 @@ pub struct PumpPool {
     pub base_mint: Pubkey,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DlmmPool {
     pub pair: Pubkey,
     pub token_vault: Pubkey,
@@ -49,7 +50,7 @@ pub struct DlmmPool {
     pub base_mint: Pubkey,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WhirlpoolPool {
     pub pool: Pubkey,
     pub oracle: Pubkey,
@@ -61,7 +62,7 @@ pub struct WhirlpoolPool {
     pub base_mint: Pubkey,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RaydiumClmmPool {
     pub pool: Pubkey,
     pub amm_config: Pubkey,
@@ -75,7 +76,7 @@ pub struct RaydiumClmmPool {
     pub base_mint: Pubkey,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MeteoraDAmmPool {
     pub pool: Pubkey,
     pub token_x_vault: Pubkey,
@@ -92,7 +93,7 @@ pub struct MeteoraDAmmPool {
     pub base_mint: Pubkey,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SolfiPool {
     pub pool: Pubkey,
     pub token_x_vault: Pubkey,
@@ -101,7 +102,7 @@ pub struct SolfiPool {
     pub base_mint: Pubkey,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MeteoraDAmmV2Pool {
     pub pool: Pubkey,
     pub token_x_vault: Pubkey,
@@ -110,7 +111,7 @@ pub struct MeteoraDAmmV2Pool {
     pub base_mint: Pubkey,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VertigoPool {
     pub pool: Pubkey,
     pub pool_owner: Pubkey,
@@ -120,7 +121,7 @@ pub struct VertigoPool {
     pub base_mint: Pubkey,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MintPoolData {
     pub mint: Pubkey,
     pub token_program: Pubkey, // Support for both Token and Token 2022
@@ -162,6 +163,36 @@ impl MintPoolData {
         })
     }
 
+    /// Total number of pools loaded across all DEXes
+    pub fn pool_count(&self) -> usize {
+        self.raydium_pools.len()
+            + self.raydium_cp_pools.len()
+            + self.pump_pools.len()
+            + self.dlmm_pairs.len()
+            + self.whirlpool_pools.len()
+            + self.raydium_clmm_pools.len()
+            + self.meteora_damm_pools.len()
+            + self.solfi_pools.len()
+            + self.meteora_damm_v2_pools.len()
+            + self.vertigo_pools.len()
+    }
+
+    /// Every loaded pool address, tagged with the DEX that owns it
+    pub fn pool_addresses(&self) -> Vec<(&'static str, Pubkey)> {
+        let mut pools = Vec::with_capacity(self.pool_count());
+        pools.extend(self.raydium_pools.iter().map(|p| ("raydium", p.pool)));
+        pools.extend(self.raydium_cp_pools.iter().map(|p| ("raydium_cp", p.pool)));
+        pools.extend(self.pump_pools.iter().map(|p| ("pump", p.pool)));
+        pools.extend(self.dlmm_pairs.iter().map(|p| ("meteora_dlmm", p.pair)));
+        pools.extend(self.whirlpool_pools.iter().map(|p| ("whirlpool", p.pool)));
+        pools.extend(self.raydium_clmm_pools.iter().map(|p| ("raydium_clmm", p.pool)));
+        pools.extend(self.meteora_damm_pools.iter().map(|p| ("meteora_damm", p.pool)));
+        pools.extend(self.solfi_pools.iter().map(|p| ("solfi", p.pool)));
+        pools.extend(self.meteora_damm_v2_pools.iter().map(|p| ("meteora_damm_v2", p.pool)));
+        pools.extend(self.vertigo_pools.iter().map(|p| ("vertigo", p.pool)));
+        pools
+    }
+
     pub fn add_raydium_pool(
         &mut self,
         pool: &str,
@@ -180,6 +211,7 @@ impl MintPoolData {
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn add_raydium_cp_pool(
         &mut self,
         pool: &str,
@@ -202,6 +234,7 @@ impl MintPoolData {
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn add_pump_pool(
         &mut self,
         pool: &str,
@@ -226,6 +259,7 @@ impl MintPoolData {
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn add_dlmm_pool(
         &mut self,
         pair: &str,
@@ -261,6 +295,7 @@ impl MintPoolData {
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn add_whirlpool_pool(
         &mut self,
         pool: &str,
@@ -296,6 +331,7 @@ impl MintPoolData {
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn add_raydium_clmm_pool(
         &mut self,
         pool: &str,
@@ -312,7 +348,7 @@ impl MintPoolData {
         let bitmap_extension = Pubkey::find_program_address(
             &[
                 POOL_TICK_ARRAY_BITMAP_SEED.as_bytes(),
-                &pool_pubkey.as_ref(),
+                pool_pubkey.as_ref(),
             ],
             &raydium_clmm_program_id(),
         )
@@ -343,6 +379,7 @@ impl MintPoolData {
         Ok(())
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub fn add_meteora_damm_pool(
         &mut self,
         pool: &str,
@@ -0,0 +1,61 @@
+use crate::dex::traits::PriceInfo;
+use solana_sdk::pubkey::Pubkey;
+use std::{
+    collections::HashMap,
+    sync::{Arc, RwLock},
+};
+
+/// Latest price of each pool with the slot it was observed at, shared between
+/// the tasks that ingest prices and the ones that route on them. Clones share
+/// the same underlying map.
+#[derive(Debug, Clone, Default)]
+pub struct PriceStore {
+    prices: Arc<RwLock<HashMap<Pubkey, (PriceInfo, u64)>>>,
+}
+
+impl PriceStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a pool's price at `slot`. Updates older than the stored price
+    /// are ignored, so out-of-order notifications can't roll a pool back.
+    pub fn update(&self, pool: Pubkey, price: PriceInfo, slot: u64) {
+        let mut prices = self.prices.write().unwrap();
+        match prices.get(&pool) {
+            Some((_, stored_slot)) if *stored_slot > slot => {}
+            _ => {
+                prices.insert(pool, (price, slot));
+            }
+        }
+    }
+
+    /// Latest price of a pool and the slot it was observed at
+    pub fn get(&self, pool: &Pubkey) -> Option<(PriceInfo, u64)> {
+        self.prices.read().unwrap().get(pool).cloned()
+    }
+
+    /// Copy of every stored price with its slot
+    pub fn snapshot_all(&self) -> HashMap<Pubkey, (PriceInfo, u64)> {
+        self.prices.read().unwrap().clone()
+    }
+
+    /// Prices observed at or after `min_slot`, in the form `find_cycles` takes
+    pub fn prices_since(&self, min_slot: u64) -> HashMap<Pubkey, PriceInfo> {
+        self.prices
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|(_, (_, slot))| *slot >= min_slot)
+            .map(|(pool, (price, _))| (*pool, price.clone()))
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.prices.read().unwrap().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
@@ -0,0 +1,79 @@
+use crate::config::SpamConfig;
+use anyhow::Result;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use tracing::{debug, warn};
+
+/// Most accounts `getRecentPrioritizationFees` accepts in one call
+const MAX_FEE_ACCOUNTS: usize = 128;
+
+/// Compute unit price at `percentile` of the fees paid over recent slots by
+/// transactions that write any of `accounts`. Slots where the accounts saw no
+/// priority fee count as zero, so quiet pools stay cheap.
+pub fn recent_fee_percentile(rpc_client: &RpcClient, accounts: &[Pubkey], percentile: u8) -> Result<u64> {
+    let accounts = &accounts[..accounts.len().min(MAX_FEE_ACCOUNTS)];
+    let mut fees: Vec<u64> = rpc_client
+        .get_recent_prioritization_fees(accounts)?
+        .into_iter()
+        .map(|fee| fee.prioritization_fee)
+        .collect();
+    Ok(percentile_of(&mut fees, percentile))
+}
+
+/// Nearest-rank `percentile` of `values`, 0 when empty. Percentiles above 100
+/// are treated as 100.
+pub fn percentile_of(values: &mut [u64], percentile: u8) -> u64 {
+    if values.is_empty() {
+        return 0;
+    }
+    values.sort_unstable();
+    let rank = (values.len() * percentile.min(100) as usize).div_ceil(100);
+    values[rank.saturating_sub(1)]
+}
+
+/// Compute unit price from recent fees on `accounts` when
+/// `dynamic_priority_fee` is set, clamped to `max_compute_unit_price`. Falls
+/// back to the static `compute_unit_price` when dynamic fees are off or the
+/// fee lookup fails.
+pub fn dynamic_compute_unit_price(spam: &SpamConfig, rpc_client: &RpcClient, accounts: &[Pubkey]) -> u64 {
+    if !spam.dynamic_priority_fee {
+        return spam.compute_unit_price;
+    }
+    let price = match recent_fee_percentile(rpc_client, accounts, spam.priority_fee_percentile) {
+        Ok(price) => price,
+        Err(e) => {
+            warn!("Failed to read recent priority fees, using compute_unit_price: {}", e);
+            return spam.compute_unit_price;
+        }
+    };
+    let price = spam.max_compute_unit_price.map_or(price, |max| price.min(max));
+    debug!(
+        "p{} of recent priority fees over {} accounts: {} micro-lamports",
+        spam.priority_fee_percentile,
+        accounts.len(),
+        price
+    );
+    price
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentile_of_takes_the_nearest_rank() {
+        let mut fees = [50, 10, 40, 20, 30];
+        assert_eq!(percentile_of(&mut fees, 50), 30);
+        assert_eq!(percentile_of(&mut fees, 75), 40);
+        assert_eq!(percentile_of(&mut fees, 100), 50);
+        assert_eq!(percentile_of(&mut fees, 1), 10);
+        assert_eq!(percentile_of(&mut fees, 0), 10);
+    }
+
+    #[test]
+    fn percentile_of_edge_cases() {
+        assert_eq!(percentile_of(&mut [], 75), 0);
+        assert_eq!(percentile_of(&mut [7], 50), 7);
+        assert_eq!(percentile_of(&mut [1, 2, 3], 250), 3);
+    }
+}
@@ -1,17 +1,12 @@
 use super::constants::sol_mint;
 use crate::{
     chain::{
-        pools::{
-            DlmmPool, MeteoraDAmmPool, MeteoraDAmmV2Pool, MintPoolData, PumpPool, RaydiumClmmPool,
-            RaydiumCpPool, RaydiumPool, SolfiPool, VertigoPool, WhirlpoolPool,
-        },
-        SOL_MINT,
+        pools::{MeteoraDAmmPool, MintPoolData},
     },
     dex::{
         meteora::{
             constants::{
-                damm_program_id, damm_v2_event_authority, damm_v2_pool_authority,
-                damm_v2_program_id, dlmm_event_authority, dlmm_program_id,
+                damm_program_id, damm_v2_program_id, dlmm_program_id,
             },
             dammv2_info::get_dammv2_info,
             dlmm_info::DlmmInfo,
@@ -24,9 +19,7 @@ use crate::{
         },
         raydium::{
             amm_info::RaydiumAmmInfo,
-            clmm_info::{
-                get_tick_array_pubkeys, PoolState, POOL_TICK_ARRAY_BITMAP_SEED,
-            },
+            clmm_info::{get_tick_array_pubkeys, PoolState},
             constants::*,
             cp_amm_info::RaydiumCpAmmInfo,
         },
@@ -39,16 +32,17 @@ use crate::{
     },
 };
 use solana_client::rpc_client::RpcClient;
-use solana_sdk::{account::Account, pubkey::Pubkey};
+use solana_sdk::pubkey::Pubkey;
 use spl_associated_token_account;
-use std::{collections::HashMap, str::FromStr, sync::Arc};
-use tracing::{debug, error, info};
+use std::{str::FromStr, sync::Arc};
+use tracing::{error, info};
 
 const TOKEN_2022_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
     6, 221, 246, 225, 215, 101, 161, 147, 217, 203, 225, 70, 206, 235, 121, 172, 28, 180, 134, 244,
     64, 118, 252, 1, 16, 241, 37, 236, 114, 157, 18, 16,
 ]);
 
+#[allow(clippy::too_many_arguments)]
 pub async fn initialize_pool_data(
     mint: &str,
     wallet_account: &str,
@@ -106,11 +100,6 @@ pub async fn initialize_pool_data(
                                     amm_info.pool_base_token_account,
                                     amm_info.pool_quote_token_account,
                                 )
-                            } else if sol_mint() == amm_info.quote_mint {
-                                (
-                                    amm_info.pool_quote_token_account,
-                                    amm_info.pool_base_token_account,
-                                )
                             } else {
                                 (
                                     amm_info.pool_quote_token_account,
@@ -168,7 +157,7 @@ pub async fn initialize_pool_data(
                                 "Error parsing AmmInfo from Pump pool {}: {:?}",
                                 pump_pool_pubkey, e
                             );
-                            return Err(e);
+                            return Err(e.into());
                         }
                     }
                 }
@@ -257,7 +246,7 @@ pub async fn initialize_pool_data(
                                 "Error parsing AmmInfo from Raydium pool {}: {:?}",
                                 raydium_pool_pubkey, e
                             );
-                            return Err(e);
+                            return Err(e.into());
                         }
                     }
                 }
@@ -348,7 +337,7 @@ pub async fn initialize_pool_data(
                                 "Error parsing AmmInfo from Raydium CP pool {}: {:?}",
                                 raydium_cp_pool_pubkey, e
                             );
-                            return Err(e);
+                            return Err(e.into());
                         }
                     }
                 }
@@ -392,7 +381,7 @@ pub async fn initialize_pool_data(
                                         "Error calculating bin arrays for DLMM pool {}: {:?}",
                                         dlmm_pool_pubkey, e
                                     );
-                                    return Err(e);
+                                    return Err(e.into());
                                 }
                             };
 
@@ -437,7 +426,7 @@ pub async fn initialize_pool_data(
                                 "Error parsing AmmInfo from DLMM pool {}: {:?}",
                                 dlmm_pool_pubkey, e
                             );
-                            return Err(e);
+                            return Err(e.into());
                         }
                     }
                 }
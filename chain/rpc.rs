@@ -0,0 +1,390 @@
+use crate::{config::RpcConfig, dex::rpc::RpcLike, error::BotError};
+use anyhow::{anyhow, Result};
+use solana_account_decoder::parse_token::UiTokenAmount;
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind, Result as ClientResult},
+    rpc_client::{RpcClient, RpcClientConfig},
+    rpc_config::RpcProgramAccountsConfig,
+    rpc_request::RpcError,
+    rpc_response::RpcResult,
+};
+use solana_rpc_client::http_sender::HttpSender;
+use solana_sdk::{account::Account, commitment_config::CommitmentConfig, pubkey::Pubkey};
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicU32, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+use tracing::warn;
+
+/// Delay before retry `attempt` (0-based): `base_ms * 2^attempt`, capped at
+/// `max_ms`, with ±20% jitter so concurrent retries spread out
+pub fn backoff_delay_ms(base_ms: u64, attempt: u32, max_ms: u64) -> u64 {
+    let delay = base_ms.saturating_mul(1u64 << attempt.min(32)).min(max_ms);
+    let jitter = 0.8 + rand::random::<f64>() * 0.4;
+    (delay as f64 * jitter) as u64
+}
+
+/// How much longer to back off after a rate-limit response than after other
+/// failures
+pub const RATE_LIMIT_BACKOFF_MULTIPLIER: u64 = 4;
+
+/// JSON-RPC error code some providers answer rate-limited calls with instead
+/// of an HTTP 429
+pub const RATE_LIMIT_ERROR_CODE: i64 = -32429;
+
+/// Whether the RPC rejected a call for exceeding its rate limit: an HTTP 429,
+/// or a JSON-RPC error with `RATE_LIMIT_ERROR_CODE`. Error text is not
+/// inspected, since slots, amounts and pubkeys can contain "429" too.
+///
+/// `ClientError` does not expose response headers, so a `Retry-After` value
+/// cannot be read here; the client's HTTP sender already waits for it before
+/// giving up with the 429.
+pub fn is_rate_limited(err: &ClientError) -> bool {
+    match err.kind() {
+        ClientErrorKind::Reqwest(e) => e.status().is_some_and(|status| status.as_u16() == 429),
+        ClientErrorKind::RpcError(RpcError::RpcResponseError { code, .. }) => *code == RATE_LIMIT_ERROR_CODE,
+        _ => false,
+    }
+}
+
+/// Run a blocking RPC call on the blocking pool, giving up after `timeout`
+/// so one hung endpoint can't stall the scan. The call starts right away; a
+/// timeout or a panicked call comes back as `BotError::Rpc`.
+pub fn call_with_timeout<T, F>(timeout: Duration, op: F) -> impl Future<Output = std::result::Result<T, BotError>>
+where
+    T: Send + 'static,
+    F: FnOnce() -> ClientResult<T> + Send + 'static,
+{
+    let call = tokio::time::timeout(timeout, tokio::task::spawn_blocking(op));
+    async move {
+        match call.await {
+            Ok(Ok(result)) => result.map_err(BotError::from),
+            Ok(Err(e)) => Err(BotError::Rpc(format!("RPC task failed: {}", e))),
+            Err(_) => Err(BotError::Rpc(format!("RPC call timed out after {:?}", timeout))),
+        }
+    }
+}
+
+/// Build an RPC client whose HTTP connections are pooled and kept alive, so
+/// repeated calls reuse an established TLS session instead of opening a new one
+pub fn build_rpc_client(url: &str, config: &RpcConfig) -> Result<RpcClient> {
+    build_rpc_client_with_timeout(url, config, Duration::from_millis(config.request_timeout_ms))
+}
+
+/// Same as `build_rpc_client`, with an explicit per-request timeout
+pub fn build_rpc_client_with_timeout(
+    url: &str,
+    config: &RpcConfig,
+    timeout: Duration,
+) -> Result<RpcClient> {
+    let http_client = reqwest::Client::builder()
+        .pool_max_idle_per_host(config.pool_max_idle_per_host)
+        .pool_idle_timeout(Duration::from_millis(config.pool_idle_timeout_ms))
+        .tcp_keepalive(Duration::from_millis(config.tcp_keepalive_ms))
+        .timeout(timeout)
+        .build()?;
+
+    let sender = HttpSender::new_with_client(url.to_string(), http_client);
+    Ok(RpcClient::new_sender(
+        sender,
+        RpcClientConfig::with_commitment(config.read_commitment()),
+    ))
+}
+
+/// A single RPC node together with how long to wait on it before failing over
+pub struct RpcEndpoint {
+    pub url: String,
+    pub client: Arc<RpcClient>,
+    pub timeout: Duration,
+    consecutive_failures: AtomicU32,
+    unhealthy_until: Mutex<Option<Instant>>,
+}
+
+impl RpcEndpoint {
+    /// Whether the endpoint is outside its failure cooldown
+    pub fn is_healthy(&self) -> bool {
+        self.unhealthy_until
+            .lock()
+            .unwrap()
+            .is_none_or(|until| Instant::now() >= until)
+    }
+}
+
+/// Ordered set of RPC endpoints. Calls go to the first endpoint and fail over
+/// to the next one when an endpoint errors or exceeds its own timeout, so a
+/// fast local node can fail quickly while a slower backup gets more slack.
+///
+/// An endpoint that fails `unhealthy_after_failures` times in a row is skipped
+/// for `unhealthy_cooldown_ms`, unless every endpoint is in cooldown.
+pub struct RpcPool {
+    endpoints: Vec<RpcEndpoint>,
+    next: AtomicUsize,
+    failure_threshold: u32,
+    cooldown: Duration,
+}
+
+impl RpcPool {
+    /// Every URL of `urls`, the first being the primary, followed by any
+    /// configured fallback endpoints
+    pub fn from_config(config: &RpcConfig) -> Result<Self> {
+        if config.urls.is_empty() {
+            return Err(anyhow!("rpc.urls is empty"));
+        }
+        let mut endpoints = config
+            .urls
+            .iter()
+            .map(|url| Self::endpoint(url, config, Duration::from_millis(config.request_timeout_ms)))
+            .collect::<Result<Vec<_>>>()?;
+        for fallback in &config.fallback_endpoints {
+            let timeout = fallback.timeout_ms.unwrap_or(config.request_timeout_ms);
+            endpoints.push(Self::endpoint(
+                &fallback.url,
+                config,
+                Duration::from_millis(timeout),
+            )?);
+        }
+        Ok(Self {
+            endpoints,
+            next: AtomicUsize::new(0),
+            failure_threshold: config.unhealthy_after_failures.max(1),
+            cooldown: Duration::from_millis(config.unhealthy_cooldown_ms),
+        })
+    }
+
+    fn endpoint(url: &str, config: &RpcConfig, timeout: Duration) -> Result<RpcEndpoint> {
+        Ok(RpcEndpoint {
+            url: url.to_string(),
+            client: Arc::new(build_rpc_client_with_timeout(url, config, timeout)?),
+            timeout,
+            consecutive_failures: AtomicU32::new(0),
+            unhealthy_until: Mutex::new(None),
+        })
+    }
+
+    /// Client for the primary endpoint
+    pub fn primary(&self) -> Arc<RpcClient> {
+        self.endpoints[0].client.clone()
+    }
+
+    pub fn endpoints(&self) -> &[RpcEndpoint] {
+        &self.endpoints
+    }
+
+    /// Next healthy endpoint in round-robin order, returned with its index for
+    /// `record_success` / `record_failure`. Falls back to the primary when
+    /// every endpoint is in cooldown.
+    pub fn next_client(&self) -> (usize, Arc<RpcClient>) {
+        let len = self.endpoints.len();
+        let start = self.next.fetch_add(1, Ordering::Relaxed);
+        (0..len)
+            .map(|offset| (start + offset) % len)
+            .find(|&index| self.endpoints[index].is_healthy())
+            .map_or((0, self.primary()), |index| (index, self.endpoints[index].client.clone()))
+    }
+
+    /// Reset the failure streak of endpoint `index`
+    pub fn record_success(&self, index: usize) {
+        let endpoint = &self.endpoints[index];
+        endpoint.consecutive_failures.store(0, Ordering::Relaxed);
+        *endpoint.unhealthy_until.lock().unwrap() = None;
+    }
+
+    /// Count a failure of endpoint `index`, putting it in cooldown once the
+    /// streak reaches the threshold
+    pub fn record_failure(&self, index: usize) {
+        let endpoint = &self.endpoints[index];
+        let failures = endpoint.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+        if failures >= self.failure_threshold {
+            warn!(
+                "RPC endpoint {} failed {} times in a row, skipping it for {:?}",
+                endpoint.url, failures, self.cooldown
+            );
+            endpoint.consecutive_failures.store(0, Ordering::Relaxed);
+            *endpoint.unhealthy_until.lock().unwrap() = Some(Instant::now() + self.cooldown);
+        }
+    }
+
+    /// Run a blocking read on the next healthy endpoint, recording its outcome
+    /// against that endpoint's health
+    fn read<T>(&self, op: impl FnOnce(&RpcClient) -> ClientResult<T>) -> ClientResult<T> {
+        let (index, client) = self.next_client();
+        let result = op(&client);
+        match result {
+            Ok(_) => self.record_success(index),
+            Err(_) => self.record_failure(index),
+        }
+        result
+    }
+
+    /// Run `op` against each healthy endpoint in order until one succeeds
+    /// within its timeout. Endpoints in cooldown are only tried when none are
+    /// healthy.
+    pub async fn call<T, F>(&self, op: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: Fn(&RpcClient) -> ClientResult<T> + Clone + Send + 'static,
+    {
+        let mut last_error = None;
+        let mut order: Vec<usize> = (0..self.endpoints.len())
+            .filter(|&index| self.endpoints[index].is_healthy())
+            .collect();
+        if order.is_empty() {
+            order = (0..self.endpoints.len()).collect();
+        }
+
+        for index in order {
+            let endpoint = &self.endpoints[index];
+            let client = endpoint.client.clone();
+            let op = op.clone();
+            match call_with_timeout(endpoint.timeout, move || op(&client)).await {
+                Ok(value) => {
+                    self.record_success(index);
+                    return Ok(value);
+                }
+                Err(e) => {
+                    warn!("RPC call to {} failed, failing over: {}", endpoint.url, e);
+                    last_error = Some(anyhow!(e));
+                }
+            }
+            self.record_failure(index);
+        }
+
+        Err(last_error.unwrap_or_else(|| anyhow!("No RPC endpoints configured")))
+    }
+}
+
+/// Adapter and token fetcher reads spread over the pool, so their failures
+/// count against the endpoint that served them. Each read is bounded by the
+/// endpoint's timeout, which its HTTP client enforces, so adapters calling
+/// these from async code can't hang on an endpoint either.
+impl RpcLike for RpcPool {
+    fn commitment(&self) -> CommitmentConfig {
+        self.endpoints[0].client.commitment()
+    }
+
+    fn get_account(&self, pubkey: &Pubkey) -> ClientResult<Account> {
+        self.read(|client| client.get_account(pubkey))
+    }
+
+    fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> ClientResult<Vec<Option<Account>>> {
+        self.read(|client| client.get_multiple_accounts(pubkeys))
+    }
+
+    fn get_multiple_accounts_with_commitment(
+        &self,
+        pubkeys: &[Pubkey],
+        commitment: CommitmentConfig,
+    ) -> RpcResult<Vec<Option<Account>>> {
+        self.read(|client| client.get_multiple_accounts_with_commitment(pubkeys, commitment))
+    }
+
+    fn get_token_account_balance(&self, pubkey: &Pubkey) -> ClientResult<UiTokenAmount> {
+        self.read(|client| client.get_token_account_balance(pubkey))
+    }
+
+    fn get_program_accounts_with_config(
+        &self,
+        program_id: &Pubkey,
+        config: RpcProgramAccountsConfig,
+    ) -> ClientResult<Vec<(Pubkey, Account)>> {
+        self.read(|client| client.get_program_accounts_with_config(program_id, config))
+    }
+
+    fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> ClientResult<u64> {
+        self.read(|client| client.get_minimum_balance_for_rent_exemption(data_len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RpcEndpointConfig;
+    use solana_client::rpc_request::RpcResponseErrorData;
+
+    fn response_error(code: i64, message: &str) -> ClientError {
+        ClientError::from(RpcError::RpcResponseError {
+            code,
+            message: message.to_string(),
+            data: RpcResponseErrorData::Empty,
+        })
+    }
+
+    #[test]
+    fn rate_limit_error_code_is_rate_limited() {
+        assert!(is_rate_limited(&response_error(RATE_LIMIT_ERROR_CODE, "Too many requests")));
+    }
+
+    #[test]
+    fn error_text_mentioning_429_is_not_rate_limited() {
+        assert!(!is_rate_limited(&response_error(-32002, "Transaction simulation failed at slot 342914290")));
+        assert!(!is_rate_limited(&ClientError::from(ClientErrorKind::Custom(
+            "insufficient lamports 4290, need 5000".to_string()
+        ))));
+        assert!(!is_rate_limited(&ClientError::from(ClientErrorKind::Custom(
+            "rate limit of account 429xyz".to_string()
+        ))));
+    }
+
+    #[tokio::test]
+    async fn calls_past_the_timeout_fail() {
+        let result = call_with_timeout(Duration::from_millis(10), || {
+            std::thread::sleep(Duration::from_millis(200));
+            Ok(())
+        })
+        .await;
+
+        assert!(matches!(result, Err(BotError::Rpc(message)) if message.contains("timed out")));
+    }
+
+    #[tokio::test]
+    async fn calls_within_the_timeout_return_their_result() {
+        assert_eq!(call_with_timeout(Duration::from_secs(5), || Ok(7)).await.unwrap(), 7);
+    }
+
+    #[tokio::test]
+    async fn each_endpoint_fails_over_after_its_own_timeout() {
+        let config = RpcConfig {
+            urls: vec!["http://127.0.0.1:8".to_string()],
+            request_timeout_ms: 50,
+            fallback_endpoints: vec![RpcEndpointConfig {
+                url: "http://127.0.0.1:9".to_string(),
+                timeout_ms: Some(2_000),
+            }],
+            unhealthy_after_failures: 1,
+            ..RpcConfig::default()
+        };
+        let pool = RpcPool::from_config(&config).unwrap();
+        let timeouts: Vec<_> = pool.endpoints().iter().map(|endpoint| endpoint.timeout).collect();
+        assert_eq!(timeouts, vec![Duration::from_millis(50), Duration::from_secs(2)]);
+
+        // Too slow for the primary's timeout, well within the backup's
+        let served_by = pool
+            .call(|client| {
+                std::thread::sleep(Duration::from_millis(200));
+                Ok(client.url())
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(served_by, "http://127.0.0.1:9");
+        assert!(!pool.endpoints()[0].is_healthy());
+        assert!(pool.endpoints()[1].is_healthy());
+    }
+
+    #[test]
+    fn adapter_reads_record_endpoint_health() {
+        let config = RpcConfig {
+            urls: vec!["http://127.0.0.1:9".to_string()],
+            unhealthy_after_failures: 1,
+            ..RpcConfig::default()
+        };
+        let pool = RpcPool::from_config(&config).unwrap();
+
+        assert!(RpcLike::get_account(&pool, &Pubkey::new_unique()).is_err());
+        assert!(!pool.endpoints()[0].is_healthy());
+    }
+}
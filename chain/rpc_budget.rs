@@ -0,0 +1,56 @@
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc,
+};
+
+/// Hard ceiling on the number of RPC calls issued during a single scan pass.
+///
+/// Unlike rate limiting, which only paces calls, an exhausted budget stops new
+/// calls entirely until the next pass begins. Clones share the same counter.
+#[derive(Debug, Clone)]
+pub struct RpcCallBudget {
+    max_calls: Option<u64>,
+    used: Arc<AtomicU64>,
+}
+
+impl RpcCallBudget {
+    pub fn new(max_calls: Option<u64>) -> Self {
+        Self {
+            max_calls,
+            used: Arc::new(AtomicU64::new(0)),
+        }
+    }
+
+    /// Reset the counter at the start of a scan pass
+    pub fn begin_pass(&self) {
+        self.used.store(0, Ordering::Relaxed);
+    }
+
+    /// Reserve `calls` from the budget, returning false if that would exceed it
+    pub fn try_acquire(&self, calls: u64) -> bool {
+        let max_calls = match self.max_calls {
+            Some(max_calls) => max_calls,
+            None => {
+                self.used.fetch_add(calls, Ordering::Relaxed);
+                return true;
+            }
+        };
+
+        self.used
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |used| {
+                let next = used.checked_add(calls)?;
+                (next <= max_calls).then_some(next)
+            })
+            .is_ok()
+    }
+
+    /// Number of calls issued so far in the current pass
+    pub fn calls_this_pass(&self) -> u64 {
+        self.used.load(Ordering::Relaxed)
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.max_calls
+            .is_some_and(|max_calls| self.calls_this_pass() >= max_calls)
+    }
+}
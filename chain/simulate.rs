@@ -0,0 +1,291 @@
+use crate::{chain::constants::token_2022_program_id, error::BotError};
+use anyhow::{anyhow, Result};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    rpc_client::RpcClient,
+    rpc_config::{RpcSimulateTransactionAccountsConfig, RpcSimulateTransactionConfig},
+};
+use solana_sdk::{
+    account::Account,
+    pubkey::Pubkey,
+    transaction::{TransactionError, VersionedTransaction},
+};
+
+/// Offset of the `amount` field in an SPL token account
+const TOKEN_ACCOUNT_AMOUNT_OFFSET: usize = 64;
+
+/// Outcome of a plain simulation of a transaction
+#[derive(Debug, Clone)]
+pub struct SimulationResult {
+    pub units_consumed: Option<u64>,
+    /// Program or runtime error the transaction hit, if any
+    pub err: Option<TransactionError>,
+    pub logs: Vec<String>,
+}
+
+/// Simulate `tx` against the current bank with a fresh blockhash
+pub fn simulate(tx: &VersionedTransaction, rpc: &RpcClient) -> Result<SimulationResult> {
+    let response = rpc.simulate_transaction_with_config(
+        tx,
+        RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: true,
+            ..Default::default()
+        },
+    )?;
+    let result = response.value;
+
+    Ok(SimulationResult {
+        units_consumed: result.units_consumed,
+        err: result.err,
+        logs: result.logs.unwrap_or_default(),
+    })
+}
+
+/// Simulate `tx` and fail if it errors or consumes more than
+/// `compute_unit_limit`, carrying the program logs in the error so the revert
+/// reason can be read from it
+pub fn simulate_within_limit(
+    tx: &VersionedTransaction,
+    rpc: &RpcClient,
+    compute_unit_limit: u32,
+) -> Result<SimulationResult> {
+    let result = simulate(tx, rpc)?;
+
+    if let Some(err) = &result.err {
+        return Err(anyhow!(
+            "Simulation failed: {} (logs: {:?})",
+            err,
+            result.logs
+        ));
+    }
+    if let Some(units) = result.units_consumed {
+        if units > compute_unit_limit as u64 {
+            return Err(anyhow!(
+                "Simulation consumed {} compute units, above the limit of {} (logs: {:?})",
+                units,
+                compute_unit_limit,
+                result.logs
+            ));
+        }
+    }
+
+    Ok(result)
+}
+
+/// Change to one account between the current state and the simulated result
+#[derive(Debug, Clone)]
+pub struct AccountDelta {
+    pub address: Pubkey,
+    pub lamports_delta: i64,
+    /// Token amount change, for SPL token accounts
+    pub token_delta: Option<i128>,
+}
+
+/// Outcome of simulating an arbitrage transaction
+#[derive(Debug, Clone)]
+pub struct ProfitSimulation {
+    /// Net change in the route's start mint across the profit accounts, in its
+    /// base units
+    pub profit: i64,
+    pub units_consumed: Option<u64>,
+    pub logs: Vec<String>,
+    /// Every watched account that changed, when requested
+    pub account_deltas: Option<Vec<AccountDelta>>,
+}
+
+/// Simulate `tx` and measure the balance change of `profit_accounts`
+/// (typically the wallet and its start-mint token account, WSOL for SOL
+/// routes). Token accounts count their token amount and the wallet its
+/// lamports, so the transaction fee is counted once, as the wallet's lamport
+/// change. With `verbose`, the deltas of
+/// all `watched_accounts` are returned as well, so a gap between expected and
+/// simulated profit can be traced to the account that absorbed it.
+pub fn simulate_profit(
+    rpc: &RpcClient,
+    tx: &VersionedTransaction,
+    profit_accounts: &[Pubkey],
+    watched_accounts: &[Pubkey],
+    verbose: bool,
+) -> Result<ProfitSimulation> {
+    let mut addresses: Vec<Pubkey> = profit_accounts.to_vec();
+    if verbose {
+        addresses.extend(
+            watched_accounts
+                .iter()
+                .filter(|address| !profit_accounts.contains(address)),
+        );
+    }
+
+    let before = rpc.get_multiple_accounts(&addresses)?;
+    let response = rpc.simulate_transaction_with_config(
+        tx,
+        RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: true,
+            accounts: Some(RpcSimulateTransactionAccountsConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                addresses: addresses.iter().map(|a| a.to_string()).collect(),
+            }),
+            ..Default::default()
+        },
+    )?;
+    let result = response.value;
+    let logs = result.logs.unwrap_or_default();
+
+    if let Some(err) = result.err {
+        return Err(anyhow!("Simulation failed: {} (logs: {:?})", err, logs));
+    }
+
+    let after: Vec<Option<Account>> = result
+        .accounts
+        .unwrap_or_default()
+        .into_iter()
+        .map(|ui| ui.and_then(|ui| ui.decode()))
+        .collect();
+    if before.len() != addresses.len() || after.len() != addresses.len() {
+        return Err(BotError::Rpc(format!(
+            "Simulation returned {} accounts before and {} after for {} requested",
+            before.len(),
+            after.len(),
+            addresses.len()
+        ))
+        .into());
+    }
+
+    let deltas: Vec<AccountDelta> = addresses
+        .iter()
+        .zip(before.iter().zip(after.iter()))
+        .map(|(address, (before, after))| account_delta(*address, before.as_ref(), after.as_ref()))
+        .collect();
+
+    let profit = profit_in_start_mint(&deltas[..profit_accounts.len()]);
+
+    let account_deltas = verbose.then(|| {
+        deltas
+            .into_iter()
+            .filter(|d| d.lamports_delta != 0 || d.token_delta.unwrap_or(0) != 0)
+            .collect()
+    });
+
+    Ok(ProfitSimulation {
+        profit,
+        units_consumed: result.units_consumed,
+        logs,
+        account_deltas,
+    })
+}
+
+/// Sum of the profit accounts' changes in the start mint: the token amount for
+/// token accounts, whose lamports only hold rent, and lamports for the wallet
+fn profit_in_start_mint(deltas: &[AccountDelta]) -> i64 {
+    deltas
+        .iter()
+        .map(|delta| match delta.token_delta {
+            Some(token_delta) => token_delta as i64,
+            None => delta.lamports_delta,
+        })
+        .sum()
+}
+
+fn account_delta(address: Pubkey, before: Option<&Account>, after: Option<&Account>) -> AccountDelta {
+    let lamports = |account: Option<&Account>| account.map_or(0, |a| a.lamports as i64);
+    let is_token_account = |account: &Account| {
+        account.owner == spl_token::ID || account.owner == token_2022_program_id()
+    };
+
+    let token_delta = match (before, after) {
+        (Some(b), Some(a)) if is_token_account(b) && is_token_account(a) => {
+            Some(token_amount(a) as i128 - token_amount(b) as i128)
+        }
+        (None, Some(a)) if is_token_account(a) => Some(token_amount(a) as i128),
+        _ => None,
+    };
+
+    AccountDelta {
+        address,
+        lamports_delta: lamports(after) - lamports(before),
+        token_delta,
+    }
+}
+
+fn token_amount(account: &Account) -> u64 {
+    account
+        .data
+        .get(TOKEN_ACCOUNT_AMOUNT_OFFSET..TOKEN_ACCOUNT_AMOUNT_OFFSET + 8)
+        .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn system_account(lamports: u64) -> Account {
+        Account {
+            lamports,
+            owner: solana_sdk::system_program::ID,
+            ..Account::default()
+        }
+    }
+
+    fn token_account(lamports: u64, amount: u64) -> Account {
+        let mut data = vec![0u8; 165];
+        data[TOKEN_ACCOUNT_AMOUNT_OFFSET..TOKEN_ACCOUNT_AMOUNT_OFFSET + 8].copy_from_slice(&amount.to_le_bytes());
+        Account {
+            lamports,
+            data,
+            owner: spl_token::ID,
+            ..Account::default()
+        }
+    }
+
+    #[test]
+    fn token_accounts_count_their_amount_not_their_lamports() {
+        let wsol = Pubkey::new_unique();
+        // Synced WSOL moves lamports and amount together; only the amount counts
+        let delta = account_delta(
+            wsol,
+            Some(&token_account(2_039_280 + 1_000_000, 1_000_000)),
+            Some(&token_account(2_039_280 + 1_250_000, 1_250_000)),
+        );
+        assert_eq!(delta.token_delta, Some(250_000));
+        assert_eq!(profit_in_start_mint(&[delta]), 250_000);
+    }
+
+    #[test]
+    fn profit_sums_wallet_lamports_and_token_amounts_once() {
+        let wallet = account_delta(
+            Pubkey::new_unique(),
+            Some(&system_account(10_000_000)),
+            Some(&system_account(10_000_000 - 5_000)),
+        );
+        let wsol = account_delta(
+            Pubkey::new_unique(),
+            Some(&token_account(2_039_280 + 1_000_000, 1_000_000)),
+            Some(&token_account(2_039_280 + 1_250_000, 1_250_000)),
+        );
+        // The 5000 lamport fee is paid by the wallet and counted only there
+        assert_eq!(profit_in_start_mint(&[wallet, wsol]), 245_000);
+    }
+
+    #[test]
+    fn created_token_account_counts_its_whole_amount() {
+        let delta = account_delta(Pubkey::new_unique(), None, Some(&token_account(2_039_280, 42)));
+        assert_eq!(delta.lamports_delta, 2_039_280);
+        assert_eq!(profit_in_start_mint(&[delta]), 42);
+    }
+
+    #[test]
+    fn missing_simulated_accounts_are_an_error() {
+        use solana_sdk::{hash::Hash, signature::Keypair, signer::Signer, transaction::Transaction};
+
+        // The mock node simulates without returning any accounts
+        let rpc = RpcClient::new_mock("succeeds".to_string());
+        let payer = Keypair::new();
+        let tx = Transaction::new_signed_with_payer(&[], Some(&payer.pubkey()), &[&payer], Hash::default()).into();
+
+        let err = simulate_profit(&rpc, &tx, &[payer.pubkey(), Pubkey::new_unique()], &[], false).unwrap_err();
+        assert!(matches!(err.downcast_ref::<BotError>(), Some(BotError::Rpc(_))));
+    }
+}
@@ -0,0 +1,90 @@
+use crate::config::JitoConfig;
+use anyhow::{anyhow, Result};
+use serde::Deserialize;
+use serde_json::json;
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey, system_instruction, transaction::VersionedTransaction};
+use std::str::FromStr;
+use tracing::info;
+
+/// One of the tip accounts published by Jito, used when `tip_account` is unset
+pub const DEFAULT_TIP_ACCOUNT: &str = "96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5";
+
+const BUNDLES_PATH: &str = "/api/v1/bundles";
+
+#[derive(Debug, Deserialize)]
+struct BundleResponse {
+    result: Option<String>,
+    error: Option<serde_json::Value>,
+}
+
+/// Tip account from config, or the default
+pub fn tip_account(cfg: &JitoConfig) -> Result<Pubkey> {
+    let account = cfg.tip_account.as_deref().unwrap_or(DEFAULT_TIP_ACCOUNT);
+    Pubkey::from_str(account).map_err(|e| anyhow!("Invalid Jito tip account {}: {}", account, e))
+}
+
+/// Transfer of `tip_lamports` from the payer to the tip account, to go last
+/// in the arb transaction so the tip is only paid when the trade succeeds
+pub fn tip_instruction(payer: &Pubkey, cfg: &JitoConfig) -> Result<Instruction> {
+    Ok(system_instruction::transfer(payer, &tip_account(cfg)?, cfg.tip_lamports))
+}
+
+/// Send `tx` as a single-transaction bundle, returning the bundle id. `tx`
+/// must already carry its tip; see `tip_instruction`.
+pub async fn send_bundle(tx: &VersionedTransaction, cfg: &JitoConfig) -> Result<String> {
+    let encoded = [tx]
+        .iter()
+        .map(|tx| bincode::serialize(tx).map(|bytes| bs58::encode(bytes).into_string()))
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+
+    let url = format!("{}{}", cfg.block_engine_url.trim_end_matches('/'), BUNDLES_PATH);
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "sendBundle",
+        "params": [encoded],
+    });
+    let response: BundleResponse = reqwest::Client::new()
+        .post(&url)
+        .json(&request)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    match (response.result, response.error) {
+        (Some(bundle_id), _) => {
+            info!(
+                "Bundle {} sent to {} with a {} lamport tip",
+                bundle_id, cfg.block_engine_url, cfg.tip_lamports
+            );
+            Ok(bundle_id)
+        }
+        (None, Some(error)) => Err(anyhow!("Block engine rejected bundle: {}", error)),
+        (None, None) => Err(anyhow!("Empty response from block engine")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tip_instruction_pays_the_configured_account() {
+        let tip_account = Pubkey::new_unique();
+        let cfg = JitoConfig {
+            enabled: true,
+            block_engine_url: "http://localhost".to_string(),
+            tip_lamports: 10_000,
+            tip_account: Some(tip_account.to_string()),
+            confirm_timeout_ms: None,
+        };
+        let payer = Pubkey::new_unique();
+
+        assert_eq!(
+            tip_instruction(&payer, &cfg).unwrap(),
+            system_instruction::transfer(&payer, &tip_account, 10_000)
+        );
+    }
+}
@@ -0,0 +1,430 @@
+pub mod jito;
+
+use crate::{
+    chain::{
+        rpc::build_rpc_client_with_timeout,
+        simulate::{simulate, simulate_within_limit},
+        transaction::{confirm_many, ConfirmStatus},
+    },
+    config::{Config, SpamConfig},
+    metrics::metrics,
+};
+use anyhow::{anyhow, Result};
+use solana_client::{
+    client_error::{ClientError, Result as ClientResult},
+    rpc_client::RpcClient,
+    rpc_config::RpcSendTransactionConfig,
+};
+use solana_sdk::{
+    commitment_config::CommitmentConfig,
+    signature::Signature,
+    transaction::{TransactionError, VersionedTransaction},
+};
+use std::{sync::Arc, time::Duration};
+use tracing::{debug, info, warn};
+
+/// How long each broadcast round waits for confirmation when
+/// `confirm_timeout_ms` is not set
+const DEFAULT_CONFIRM_TIMEOUT_MS: u64 = 2_000;
+
+/// How long a bundle's transaction is waited on when `jito.confirm_timeout_ms`
+/// is not set. Bundles the block engine drops never land, so this bounds the wait.
+const DEFAULT_BUNDLE_CONFIRM_TIMEOUT_MS: u64 = 10_000;
+
+/// Endpoint a signed transaction is handed to, so the spam rounds can run
+/// against a fake in tests
+pub trait TransactionSender: Send + Sync {
+    fn send_transaction_with_config(
+        &self,
+        tx: &VersionedTransaction,
+        config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature>;
+}
+
+impl TransactionSender for RpcClient {
+    fn send_transaction_with_config(
+        &self,
+        tx: &VersionedTransaction,
+        config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature> {
+        RpcClient::send_transaction_with_config(self, tx, config)
+    }
+}
+
+/// A `spam.sending_rpc_urls` endpoint with the client transactions are
+/// broadcast through, built once so its connections are reused across sends
+pub struct SendingEndpoint {
+    pub url: String,
+    pub sender: Arc<dyn TransactionSender>,
+}
+
+impl SendingEndpoint {
+    /// Endpoints for every `spam.sending_rpc_urls` entry, with pooled,
+    /// kept-alive connections and the `rpc.request_timeout_ms` timeout.
+    /// Without spam submission there are none.
+    pub fn from_config(config: &Config) -> Result<Vec<Self>> {
+        let Some(spam) = config.spam.as_ref().filter(|spam| spam.enabled) else {
+            return Ok(Vec::new());
+        };
+        let timeout = Duration::from_millis(config.rpc.request_timeout_ms);
+        spam.sending_rpc_urls
+            .iter()
+            .map(|url| {
+                let client = build_rpc_client_with_timeout(url, &config.rpc, timeout)?;
+                Ok(Self {
+                    url: url.clone(),
+                    sender: Arc::new(client),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Signs the transaction being submitted again over a fresh blockhash
+pub type Resign<'a> = &'a dyn Fn() -> Result<VersionedTransaction>;
+
+/// How a transaction was handed off for landing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Submission {
+    /// Confirmed through the spam endpoints
+    Spam(Signature),
+    /// Landed through the Jito block engine as `signature`, under `bundle_id`
+    Bundle { bundle_id: String, signature: Signature },
+    /// Only simulated and logged, because `bot.dry_run` is set
+    DryRun(Signature),
+}
+
+impl Submission {
+    /// Signature of the transaction that landed on chain, if one was sent
+    pub fn landed_signature(&self) -> Option<Signature> {
+        match self {
+            Submission::Spam(signature) | Submission::Bundle { signature, .. } => Some(*signature),
+            Submission::DryRun(_) => None,
+        }
+    }
+}
+
+/// Submit a signed transaction the way the config asks for: as a Jito bundle
+/// when `jito.enabled`, otherwise through `spam_send` to `senders`. The transaction is
+/// simulated on `rpc` first and not sent if it fails or would exceed
+/// `bot.compute_unit_limit`. Bundled transactions must already end with their
+/// tip; see `jito::tip_instruction`. With `bot.dry_run`, the transaction is
+/// simulated and logged but never sent. `resign` is how `spam_send` replaces
+/// a transaction whose blockhash expired.
+pub async fn submit(
+    tx: &VersionedTransaction,
+    config: &Config,
+    rpc: &RpcClient,
+    senders: &[SendingEndpoint],
+    resign: Resign<'_>,
+) -> Result<Submission> {
+    if config.bot.dry_run {
+        let simulation = simulate(tx, rpc)?;
+        let signature = tx.signatures.first().copied().unwrap_or_default();
+        info!(
+            "Dry run, not sending {}: {} instructions over {} accounts, simulated {:?} compute units, error: {:?}",
+            signature,
+            tx.message.instructions().len(),
+            tx.message.static_account_keys().len(),
+            simulation.units_consumed,
+            simulation.err
+        );
+        debug!("Dry run simulation logs: {:?}", simulation.logs);
+        return Ok(Submission::DryRun(signature));
+    }
+
+    let simulation = simulate_within_limit(tx, rpc, config.bot.compute_unit_limit)?;
+    debug!("Simulation consumed {:?} compute units", simulation.units_consumed);
+
+    if let Some(jito_cfg) = config.jito.as_ref().filter(|j| j.enabled) {
+        let bundle_id = jito::send_bundle(tx, jito_cfg).await?;
+        metrics().txs_submitted.inc();
+        let timeout = Duration::from_millis(
+            jito_cfg
+                .confirm_timeout_ms
+                .unwrap_or(DEFAULT_BUNDLE_CONFIRM_TIMEOUT_MS),
+        );
+        return confirm_bundle(tx, &bundle_id, rpc, config.rpc.confirmation_commitment(), timeout)
+            .await
+            .map(|signature| Submission::Bundle { bundle_id, signature });
+    }
+    match config.spam.as_ref().filter(|s| s.enabled) {
+        Some(spam) => spam_send(tx, spam, senders, rpc, config.rpc.confirmation_commitment(), resign)
+            .await
+            .map(Submission::Spam),
+        None => Err(anyhow!("Neither jito nor spam submission is enabled")),
+    }
+}
+
+/// Wait for the transaction of a single-transaction bundle to land on `rpc`
+/// and count the outcome, returning its signature. The bundle lands exactly
+/// when its transaction does, so the signature stands in for the bundle status.
+async fn confirm_bundle(
+    tx: &VersionedTransaction,
+    bundle_id: &str,
+    rpc: &RpcClient,
+    commitment: CommitmentConfig,
+    timeout: Duration,
+) -> Result<Signature> {
+    let signature = tx.signatures.first().copied().unwrap_or_default();
+    let statuses = confirm_many(rpc, &[signature], commitment, timeout).await;
+    match statuses.get(&signature) {
+        Some(ConfirmStatus::Confirmed) => {
+            metrics().txs_landed.inc();
+            info!("Bundle {} landed as {}", bundle_id, signature);
+            Ok(signature)
+        }
+        Some(ConfirmStatus::Failed(err)) => {
+            metrics().txs_reverted.inc();
+            Err(anyhow!("Bundle {} transaction {} failed: {}", bundle_id, signature, err))
+        }
+        _ => Err(anyhow!(
+            "Bundle {} did not land within {}ms",
+            bundle_id,
+            timeout.as_millis()
+        )),
+    }
+}
+
+/// How `spam_send` hands a transaction to each endpoint: preflight as
+/// `skip_preflight` and `preflight_commitment` ask, and no retries by the node
+/// since rebroadcasting is done per round here
+pub fn send_config(cfg: &SpamConfig) -> RpcSendTransactionConfig {
+    RpcSendTransactionConfig {
+        skip_preflight: cfg.skip_preflight,
+        preflight_commitment: cfg.preflight_commitment,
+        max_retries: Some(0),
+        ..Default::default()
+    }
+}
+
+/// Broadcast a signed transaction to every endpoint of `senders` at once,
+/// with preflight as `skip_preflight` and `preflight_commitment` ask, then wait
+/// for it to confirm on `rpc`, since send-only endpoints often don't serve
+/// `getSignatureStatuses`. Unconfirmed rounds are rebroadcast up to
+/// `max_retries` times. Returns the signature once it reaches `commitment`.
+///
+/// Rebroadcasting cannot help a transaction whose blockhash expired, so when
+/// no endpoint accepts it and one rejects it with `BlockhashNotFound`, it is
+/// signed again with `resign` and resent once. No copy signed over the old
+/// blockhash was accepted, so the new one cannot land twice.
+pub async fn spam_send(
+    tx: &VersionedTransaction,
+    cfg: &SpamConfig,
+    senders: &[SendingEndpoint],
+    rpc: &RpcClient,
+    commitment: CommitmentConfig,
+    resign: Resign<'_>,
+) -> Result<Signature> {
+    if senders.is_empty() {
+        return Err(anyhow!("spam.sending_rpc_urls is empty"));
+    }
+
+    let send_config = send_config(cfg);
+    let timeout = Duration::from_millis(cfg.confirm_timeout_ms.unwrap_or(DEFAULT_CONFIRM_TIMEOUT_MS));
+    let rounds = cfg.max_retries.unwrap_or(0) + 1;
+    let mut tx = tx.clone();
+    let mut resigned = false;
+    let mut submitted = false;
+
+    for round in 0..rounds {
+        let (mut signature, mut blockhash_expired) = send_to_all(senders, &tx, send_config).await;
+        if signature.is_none() && blockhash_expired && !resigned {
+            warn!("Blockhash of {} expired, re-signing and resending once", tx.signatures[0]);
+            tx = resign()?;
+            resigned = true;
+            (signature, blockhash_expired) = send_to_all(senders, &tx, send_config).await;
+        }
+
+        let Some(signature) = signature else {
+            warn!(
+                "No endpoint accepted the transaction (round {}/{}{})",
+                round + 1,
+                rounds,
+                if blockhash_expired { ", blockhash expired" } else { "" }
+            );
+            continue;
+        };
+        if !submitted {
+            metrics().txs_submitted.inc();
+            submitted = true;
+        }
+
+        let statuses = confirm_many(rpc, &[signature], commitment, timeout).await;
+        match statuses.get(&signature) {
+            Some(ConfirmStatus::Confirmed) => {
+                metrics().txs_landed.inc();
+                info!("Transaction {} confirmed after {} round(s)", signature, round + 1);
+                return Ok(signature);
+            }
+            Some(ConfirmStatus::Failed(err)) => {
+                metrics().txs_reverted.inc();
+                return Err(anyhow!("Transaction {} failed: {}", signature, err));
+            }
+            _ => debug!(
+                "Transaction {} unconfirmed after round {}/{}",
+                signature,
+                round + 1,
+                rounds
+            ),
+        }
+    }
+
+    Err(anyhow!("Transaction not confirmed after {} broadcast rounds", rounds))
+}
+
+/// Send `tx` through every sender at once, returning the signature if any
+/// accepted it and whether any rejected it for an expired blockhash
+async fn send_to_all(
+    senders: &[SendingEndpoint],
+    tx: &VersionedTransaction,
+    send_config: RpcSendTransactionConfig,
+) -> (Option<Signature>, bool) {
+    let sends: Vec<_> = senders
+        .iter()
+        .map(|endpoint| {
+            let sender = endpoint.sender.clone();
+            let tx = tx.clone();
+            tokio::task::spawn_blocking(move || sender.send_transaction_with_config(&tx, send_config))
+        })
+        .collect();
+
+    let mut signature = None;
+    let mut blockhash_expired = false;
+    for (SendingEndpoint { url, .. }, send) in senders.iter().zip(sends) {
+        match send.await {
+            Ok(Ok(sig)) => {
+                debug!("Transaction {} sent through {}", sig, url);
+                signature.get_or_insert(sig);
+            }
+            Ok(Err(e)) => {
+                blockhash_expired |= is_blockhash_expired(&e);
+                warn!("Failed to send transaction through {}: {}", url, e);
+            }
+            Err(e) => warn!("Send task for {} failed: {}", url, e),
+        }
+    }
+    (signature, blockhash_expired)
+}
+
+/// Whether a send failed because the transaction's blockhash is no longer valid
+fn is_blockhash_expired(err: &ClientError) -> bool {
+    err.get_transaction_error() == Some(TransactionError::BlockhashNotFound)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_client::client_error::ClientErrorKind;
+    use solana_sdk::{
+        commitment_config::CommitmentLevel,
+        hash::Hash,
+        signature::Keypair,
+        signer::Signer,
+        transaction::Transaction,
+    };
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    };
+
+    /// Sender rejecting transactions signed over `expired` the way a node
+    /// does, and accepting the rest
+    struct ExpiringSender {
+        expired: Hash,
+        sent: Mutex<Vec<Hash>>,
+    }
+
+    impl TransactionSender for ExpiringSender {
+        fn send_transaction_with_config(
+            &self,
+            tx: &VersionedTransaction,
+            _config: RpcSendTransactionConfig,
+        ) -> ClientResult<Signature> {
+            let blockhash = *tx.message.recent_blockhash();
+            self.sent.lock().unwrap().push(blockhash);
+            if blockhash == self.expired {
+                return Err(ClientErrorKind::TransactionError(TransactionError::BlockhashNotFound).into());
+            }
+            Ok(tx.signatures[0])
+        }
+    }
+
+    fn signed(payer: &Keypair, blockhash: Hash) -> VersionedTransaction {
+        Transaction::new_signed_with_payer(&[], Some(&payer.pubkey()), &[payer], blockhash).into()
+    }
+
+    fn spam(extra: &str) -> SpamConfig {
+        Config::from_toml(&format!(
+            r#"
+            [bot]
+            compute_unit_limit = 200000
+
+            [rpc]
+            url = "http://localhost:8899"
+
+            [wallet]
+            private_key = ""
+
+            [spam]
+            enabled = true
+            sending_rpc_urls = ["http://localhost:8899"]
+            compute_unit_price = 1000
+            max_retries = 1
+            {extra}
+
+            [routing]
+            mint_config_list = []
+            "#
+        ))
+        .spam
+        .unwrap()
+    }
+
+    #[test]
+    fn send_config_follows_the_spam_preflight_settings() {
+        let config = send_config(&spam("skip_preflight = false\npreflight_commitment = \"finalized\""));
+        assert!(!config.skip_preflight);
+        assert_eq!(config.preflight_commitment, Some(CommitmentLevel::Finalized));
+        assert_eq!(config.max_retries, Some(0));
+
+        let config = send_config(&spam("skip_preflight = true"));
+        assert!(config.skip_preflight);
+        assert_eq!(config.preflight_commitment, None);
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn expired_blockhash_is_resigned_and_resent_once() {
+        let payer = Keypair::new();
+        let (expired, fresh) = (Hash::new_unique(), Hash::new_unique());
+        let sender = Arc::new(ExpiringSender {
+            expired,
+            sent: Mutex::new(Vec::new()),
+        });
+        let senders = [SendingEndpoint {
+            url: "http://sender".to_string(),
+            sender: sender.clone(),
+        }];
+        let rpc = RpcClient::new_mock("succeeds".to_string());
+        let resigns = AtomicUsize::new(0);
+        let resign = || {
+            resigns.fetch_add(1, Ordering::SeqCst);
+            Ok(signed(&payer, fresh))
+        };
+
+        let signature = spam_send(
+            &signed(&payer, expired),
+            &spam(""),
+            &senders,
+            &rpc,
+            CommitmentConfig::confirmed(),
+            &resign,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(resigns.load(Ordering::SeqCst), 1);
+        assert_eq!(*sender.sent.lock().unwrap(), vec![expired, fresh]);
+        assert_eq!(signature, signed(&payer, fresh).signatures[0]);
+    }
+}
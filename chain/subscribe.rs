@@ -0,0 +1,574 @@
+use crate::{
+    chain::{pools::MintPoolData, price_store::PriceStore},
+    dex::{
+        traits::PriceInfo,
+        vault::{decimal_adjusted_price, token_account_amount, MintDecimalsCache},
+    },
+};
+use anyhow::{anyhow, Result};
+use futures::{stream::select_all, StreamExt};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{nonblocking::pubsub_client::PubsubClient, rpc_client::RpcClient, rpc_config::RpcAccountInfoConfig};
+use solana_sdk::{commitment_config::CommitmentConfig, pubkey::Pubkey};
+use std::{
+    collections::HashMap,
+    sync::{mpsc as std_mpsc, Arc, Mutex},
+    thread::{self, JoinHandle},
+};
+use tokio::sync::{
+    mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
+    oneshot,
+};
+use tracing::{debug, info, warn};
+
+/// The two vaults backing a reserve-priced pool
+#[derive(Debug, Clone, Copy)]
+pub struct VaultPair {
+    pub pool: Pubkey,
+    pub token_vault: Pubkey,
+    pub base_vault: Pubkey,
+    pub token_mint: Pubkey,
+    pub base_mint: Pubkey,
+}
+
+/// Vault pairs for every reserve-priced pool in `pool_data`.
+///
+/// Concentrated liquidity pools (CLMM, DLMM, Whirlpool, DAMM v2) price from the
+/// pool account rather than vault balances and are not included.
+pub fn vault_pairs(pool_data: &MintPoolData) -> Vec<VaultPair> {
+    let mut pairs = Vec::new();
+    let mut push = |pool, token_vault, base_vault, token_mint, base_mint| {
+        pairs.push(VaultPair {
+            pool,
+            token_vault,
+            base_vault,
+            token_mint,
+            base_mint,
+        })
+    };
+
+    for pool in &pool_data.raydium_pools {
+        push(pool.pool, pool.token_vault, pool.sol_vault, pool.token_mint, pool.base_mint);
+    }
+    for pool in &pool_data.raydium_cp_pools {
+        push(pool.pool, pool.token_vault, pool.sol_vault, pool.token_mint, pool.base_mint);
+    }
+    for pool in &pool_data.pump_pools {
+        push(pool.pool, pool.token_vault, pool.sol_vault, pool.token_mint, pool.base_mint);
+    }
+    for pool in &pool_data.solfi_pools {
+        push(pool.pool, pool.token_x_vault, pool.token_sol_vault, pool.token_mint, pool.base_mint);
+    }
+    for pool in &pool_data.vertigo_pools {
+        push(pool.pool, pool.token_x_vault, pool.token_sol_vault, pool.token_mint, pool.base_mint);
+    }
+
+    pairs
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct PoolReserveState {
+    token_reserve: Option<u64>,
+    base_reserve: Option<u64>,
+    /// Pool fee from the price in the store when the reserves were seeded
+    fee: f64,
+    /// Reserves at the last update sent on the channel
+    notified: Option<(u64, u64)>,
+}
+
+/// Whether a reserve moved by more than `threshold_bps` of its old value
+fn moved_beyond(old: u64, new: u64, threshold_bps: u64) -> bool {
+    old.abs_diff(new) as u128 * 10_000 > old.max(1) as u128 * threshold_bps as u128
+}
+
+/// Streams vault balance changes over WebSocket and keeps the latest spot price
+/// of each subscribed pool.
+///
+/// Each `subscribe` call opens one WebSocket connection on a dedicated thread
+/// and multiplexes an `accountSubscribe` per vault over it. Updates
+/// carry spot price and base-side depth, with the fee of the pool's price in
+/// the store when it was subscribed since fees don't change with vault
+/// balances, so pools should be priced before subscribing. Every update is also written to the
+/// subscriber's `PriceStore` with the slot it was observed at, but only sent
+/// on the channel once a reserve has moved by more than the rescan threshold
+/// since the last update sent, so dust transfers don't trigger a re-scan.
+/// Subscriptions and the seeding snapshot read at `processed` unless
+/// `with_commitment` says otherwise.
+pub struct PoolSubscriber {
+    ws_url: String,
+    decimals: MintDecimalsCache,
+    rpc_client: Arc<RpcClient>,
+    prices: PriceStore,
+    commitment: CommitmentConfig,
+    rescan_threshold_bps: u64,
+    connections: Mutex<Vec<VaultConnection>>,
+}
+
+impl PoolSubscriber {
+    pub fn new(ws_url: impl Into<String>, rpc_client: Arc<RpcClient>) -> Self {
+        Self::with_price_store(ws_url, rpc_client, PriceStore::new())
+    }
+
+    /// Write prices into an existing store shared with other components
+    pub fn with_price_store(ws_url: impl Into<String>, rpc_client: Arc<RpcClient>, prices: PriceStore) -> Self {
+        Self {
+            ws_url: ws_url.into(),
+            decimals: MintDecimalsCache::new(rpc_client.clone()),
+            rpc_client,
+            prices,
+            commitment: CommitmentConfig::processed(),
+            rescan_threshold_bps: 0,
+            connections: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Commitment of the vault subscriptions and the seeding snapshot
+    pub fn with_commitment(mut self, commitment: CommitmentConfig) -> Self {
+        self.commitment = commitment;
+        self
+    }
+
+    /// Only send a pool's update once one of its reserves has moved by more
+    /// than `threshold_bps` since the last update sent; 0 sends every update
+    pub fn with_rescan_threshold_bps(mut self, threshold_bps: u64) -> Self {
+        self.rescan_threshold_bps = threshold_bps;
+        self
+    }
+
+    /// The store this subscriber writes prices to
+    pub fn price_store(&self) -> PriceStore {
+        self.prices.clone()
+    }
+
+    /// Unsubscribe from every vault and close the WebSocket connections. The
+    /// update threads end and the channels from `subscribe` close.
+    pub fn close(&self) {
+        let connections = std::mem::take(&mut *self.connections.lock().unwrap());
+        let count = connections.len();
+        for connection in connections {
+            if connection.close().is_err() {
+                warn!("Vault subscription thread panicked during shutdown");
+            }
+        }
+        if count > 0 {
+            info!("Closed {} vault subscription connections", count);
+        }
+    }
+
+    /// Latest price seen for a pool
+    pub fn latest(&self, pool: &Pubkey) -> Option<PriceInfo> {
+        self.prices.get(pool).map(|(price, _)| price)
+    }
+
+    /// Subscribe to every vault in `pool_data`, returning a channel of
+    /// `(pool, price)` updates. Reserves are seeded with one RPC snapshot so
+    /// prices are available before the first notification. Subscriptions end
+    /// once the receiver is dropped or `close` is called. If any vault fails to
+    /// subscribe, the ones already subscribed are unsubscribed and the
+    /// connection closed before the error is returned.
+    pub fn subscribe(&self, pool_data: &MintPoolData) -> Result<UnboundedReceiver<(Pubkey, PriceInfo)>> {
+        let pairs = vault_pairs(pool_data);
+        let (sender, receiver) = unbounded_channel();
+        if pairs.is_empty() {
+            return Ok(receiver);
+        }
+
+        let mut vaults: HashMap<Pubkey, (usize, bool)> = HashMap::new();
+        let mut decimals = Vec::with_capacity(pairs.len());
+        for (index, pair) in pairs.iter().enumerate() {
+            vaults.insert(pair.token_vault, (index, true));
+            vaults.insert(pair.base_vault, (index, false));
+            decimals.push(self.decimals.get_pair(&pair.token_mint, &pair.base_mint)?);
+        }
+
+        let shared = Arc::new(SubscriptionState {
+            pairs,
+            decimals,
+            reserves: Mutex::new(Vec::new()),
+            prices: self.prices.clone(),
+            rescan_threshold_bps: self.rescan_threshold_bps,
+            sender,
+        });
+        self.seed_reserves(&shared)?;
+
+        let config = RpcAccountInfoConfig {
+            encoding: Some(UiAccountEncoding::Base64),
+            commitment: Some(self.commitment),
+            ..Default::default()
+        };
+        let (ready_sender, ready) = std_mpsc::channel();
+        let (shutdown, shutdown_receiver) = oneshot::channel();
+        let ws_url = self.ws_url.clone();
+        let vaults: Vec<_> = vaults.into_iter().collect();
+        let state = shared.clone();
+        let thread = thread::Builder::new()
+            .name("vault-subscriptions".to_string())
+            .spawn(move || {
+                let runtime = match tokio::runtime::Builder::new_current_thread().enable_all().build() {
+                    Ok(runtime) => runtime,
+                    Err(e) => {
+                        let _ = ready_sender.send(Err(anyhow!("Failed to start subscription runtime: {}", e)));
+                        return;
+                    }
+                };
+                runtime.block_on(stream_vaults(ws_url, vaults, config, state, ready_sender, shutdown_receiver));
+            })
+            .map_err(|e| anyhow!("Failed to spawn vault subscription thread: {}", e))?;
+
+        let connection = VaultConnection {
+            shutdown: Some(shutdown),
+            thread: Some(thread),
+        };
+        match ready.recv() {
+            Ok(Ok(())) => self.connections.lock().unwrap().push(connection),
+            Ok(Err(e)) => {
+                let _ = connection.close();
+                return Err(e);
+            }
+            Err(_) => {
+                let _ = connection.close();
+                return Err(anyhow!("Vault subscription thread exited before subscribing"));
+            }
+        }
+
+        info!(
+            "Subscribed to {} vaults across {} pools for mint: {}",
+            shared.pairs.len() * 2,
+            shared.pairs.len(),
+            pool_data.mint
+        );
+        Ok(receiver)
+    }
+
+    fn seed_reserves(&self, shared: &SubscriptionState) -> Result<()> {
+        let vaults: Vec<Pubkey> = shared
+            .pairs
+            .iter()
+            .flat_map(|pair| [pair.token_vault, pair.base_vault])
+            .collect();
+
+        let mut seeded = Vec::with_capacity(shared.pairs.len());
+        let mut slot = 0;
+        for chunk in vaults.chunks(100) {
+            let response = self
+                .rpc_client
+                .get_multiple_accounts_with_commitment(chunk, self.commitment)?;
+            slot = slot.max(response.context.slot);
+            seeded.extend(
+                response
+                    .value
+                    .iter()
+                    .map(|account| account.as_ref().and_then(|a| token_account_amount(&a.data).ok())),
+            );
+        }
+
+        let mut reserves = shared.reserves.lock().unwrap();
+        *reserves = seeded
+            .chunks(2)
+            .zip(&shared.pairs)
+            .map(|(pair, vaults)| PoolReserveState {
+                token_reserve: pair[0],
+                base_reserve: pair.get(1).copied().flatten(),
+                fee: shared.prices.get(&vaults.pool).map_or(0.0, |(price, _)| price.fee),
+                notified: None,
+            })
+            .collect();
+        drop(reserves);
+
+        for index in 0..shared.pairs.len() {
+            shared.publish(index, slot);
+        }
+        Ok(())
+    }
+}
+
+impl Drop for PoolSubscriber {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+/// One WebSocket connection carrying the vault subscriptions of a `subscribe`
+/// call, driven on its own thread. Dropping it without `close` still ends the
+/// thread, just without waiting for it.
+struct VaultConnection {
+    shutdown: Option<oneshot::Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl VaultConnection {
+    /// Unsubscribe from every vault, close the connection and wait for the thread
+    fn close(mut self) -> thread::Result<()> {
+        if let Some(shutdown) = self.shutdown.take() {
+            let _ = shutdown.send(());
+        }
+        self.thread.take().map_or(Ok(()), JoinHandle::join)
+    }
+}
+
+/// Subscribe to every vault over one connection and report the outcome on
+/// `ready`, then apply updates until `shutdown` fires or the receiver of
+/// `state` is dropped. Subscriptions made before a failure are unsubscribed
+/// again, and the connection is always closed on the way out.
+async fn stream_vaults(
+    ws_url: String,
+    vaults: Vec<(Pubkey, (usize, bool))>,
+    config: RpcAccountInfoConfig,
+    state: Arc<SubscriptionState>,
+    ready: std_mpsc::Sender<Result<()>>,
+    mut shutdown: oneshot::Receiver<()>,
+) {
+    let client = match PubsubClient::new(&ws_url).await {
+        Ok(client) => client,
+        Err(e) => {
+            let _ = ready.send(Err(anyhow!("Failed to connect to {}: {}", ws_url, e)));
+            return;
+        }
+    };
+
+    let mut streams = Vec::with_capacity(vaults.len());
+    let mut unsubscribes = Vec::with_capacity(vaults.len());
+    let mut outcome = Ok(());
+    for (vault, (index, is_token_side)) in vaults {
+        match client.account_subscribe(&vault, Some(config.clone())).await {
+            Ok((stream, unsubscribe)) => {
+                streams.push(stream.map(move |update| (vault, index, is_token_side, update)));
+                unsubscribes.push(unsubscribe);
+            }
+            Err(e) => {
+                outcome = Err(anyhow!("Failed to subscribe to vault {}: {}", vault, e));
+                break;
+            }
+        }
+    }
+
+    let subscribed = outcome.is_ok();
+    if ready.send(outcome).is_ok() && subscribed {
+        let mut updates = select_all(streams);
+        loop {
+            let (vault, index, is_token_side, update) = tokio::select! {
+                _ = &mut shutdown => break,
+                update = updates.next() => match update {
+                    Some(update) => update,
+                    None => break,
+                },
+            };
+            let Some(data) = update.value.data.decode() else {
+                warn!("Undecodable account update for vault {}", vault);
+                continue;
+            };
+            match token_account_amount(&data) {
+                Ok(amount) => {
+                    if !state.apply(index, is_token_side, amount, update.context.slot) {
+                        break;
+                    }
+                }
+                Err(e) => warn!("Bad vault update for {}: {}", vault, e),
+            }
+        }
+    } else {
+        drop(streams);
+    }
+
+    for unsubscribe in unsubscribes {
+        unsubscribe().await;
+    }
+    if let Err(e) = client.shutdown().await {
+        debug!("Vault subscription connection closed uncleanly: {}", e);
+    }
+    debug!("Vault subscriptions for {} ended", ws_url);
+}
+
+struct SubscriptionState {
+    pairs: Vec<VaultPair>,
+    decimals: Vec<(u8, u8)>,
+    reserves: Mutex<Vec<PoolReserveState>>,
+    prices: PriceStore,
+    rescan_threshold_bps: u64,
+    sender: UnboundedSender<(Pubkey, PriceInfo)>,
+}
+
+impl SubscriptionState {
+    /// Record a vault balance seen at `slot` and publish the pool's new price.
+    /// Returns false once the receiver has been dropped.
+    fn apply(&self, index: usize, is_token_side: bool, amount: u64, slot: u64) -> bool {
+        let mut reserves = self.reserves.lock().unwrap();
+        let state = &mut reserves[index];
+        if is_token_side {
+            state.token_reserve = Some(amount);
+        } else {
+            state.base_reserve = Some(amount);
+        }
+        let update = self.price_update(index, state, slot);
+        drop(reserves);
+        self.send(update)
+    }
+
+    /// Publish the pool's price from its current reserves
+    fn publish(&self, index: usize, slot: u64) -> bool {
+        let mut reserves = self.reserves.lock().unwrap();
+        let update = self.price_update(index, &mut reserves[index], slot);
+        drop(reserves);
+        self.send(update)
+    }
+
+    /// Send `update` if there is one. Returns false once the receiver has
+    /// been dropped.
+    fn send(&self, update: Option<(Pubkey, PriceInfo)>) -> bool {
+        match update {
+            Some(update) => self.sender.send(update).is_ok(),
+            None => !self.sender.is_closed(),
+        }
+    }
+
+    /// Write the pool's price to the store, returning it to send on the
+    /// channel when the reserves moved past the rescan threshold. Runs with
+    /// the pool's state locked, so concurrent updates can't interleave between
+    /// reading the reserves and recording them as sent.
+    fn price_update(&self, index: usize, state: &mut PoolReserveState, slot: u64) -> Option<(Pubkey, PriceInfo)> {
+        let (token_reserve, base_reserve) = (state.token_reserve?, state.base_reserve?);
+        let (token_decimals, base_decimals) = self.decimals[index];
+        let price = decimal_adjusted_price(token_reserve, base_reserve, token_decimals, base_decimals)?;
+
+        let pool = self.pairs[index].pool;
+        let info = PriceInfo {
+            price,
+            liquidity: base_reserve,
+            fee: state.fee,
+        };
+        self.prices.update(pool, info.clone(), slot);
+        self.should_notify(state, token_reserve, base_reserve).then_some((pool, info))
+    }
+
+    /// Whether a pool's reserves moved enough since the last update sent to
+    /// send another, recording them as sent if so. The first update of each
+    /// pool is always sent.
+    fn should_notify(&self, state: &mut PoolReserveState, token_reserve: u64, base_reserve: u64) -> bool {
+        let material = match state.notified {
+            Some(_) if self.rescan_threshold_bps == 0 => true,
+            Some((token, base)) => {
+                moved_beyond(token, token_reserve, self.rescan_threshold_bps)
+                    || moved_beyond(base, base_reserve, self.rescan_threshold_bps)
+            }
+            None => true,
+        };
+        if material {
+            state.notified = Some((token_reserve, base_reserve));
+        }
+        material
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn connection_failures_are_reported_as_not_ready() {
+        let (sender, _receiver) = unbounded_channel();
+        let state = Arc::new(SubscriptionState {
+            pairs: Vec::new(),
+            decimals: Vec::new(),
+            reserves: Mutex::new(Vec::new()),
+            prices: PriceStore::new(),
+            rescan_threshold_bps: 0,
+            sender,
+        });
+        let (ready_sender, ready) = std_mpsc::channel();
+        let (_shutdown, shutdown_receiver) = oneshot::channel();
+
+        stream_vaults(
+            "ws://127.0.0.1:9".to_string(),
+            vec![(Pubkey::new_unique(), (0, true))],
+            RpcAccountInfoConfig::default(),
+            state,
+            ready_sender,
+            shutdown_receiver,
+        )
+        .await;
+
+        assert!(ready.recv().unwrap().is_err());
+    }
+
+    #[test]
+    fn published_prices_keep_the_seeded_fee() {
+        let (sender, mut receiver) = unbounded_channel();
+        let pool = Pubkey::new_unique();
+        let state = SubscriptionState {
+            pairs: vec![VaultPair {
+                pool,
+                token_vault: Pubkey::new_unique(),
+                base_vault: Pubkey::new_unique(),
+                token_mint: Pubkey::new_unique(),
+                base_mint: Pubkey::new_unique(),
+            }],
+            decimals: vec![(6, 9)],
+            reserves: Mutex::new(vec![PoolReserveState {
+                token_reserve: Some(1_000_000_000),
+                base_reserve: Some(2_000_000_000),
+                fee: 0.0025,
+                notified: None,
+            }]),
+            prices: PriceStore::new(),
+            rescan_threshold_bps: 0,
+            sender,
+        };
+
+        assert!(state.apply(0, false, 3_000_000_000, 7));
+
+        let (stored, slot) = state.prices.get(&pool).unwrap();
+        assert_eq!(stored.fee, 0.0025);
+        assert_eq!(stored.liquidity, 3_000_000_000);
+        assert_eq!(slot, 7);
+        assert_eq!(receiver.try_recv().unwrap().1.fee, 0.0025);
+    }
+
+    #[test]
+    fn moved_beyond_is_strictly_past_the_threshold() {
+        assert!(!moved_beyond(10_000, 10_050, 50));
+        assert!(moved_beyond(10_000, 10_051, 50));
+        assert!(!moved_beyond(10_000, 9_950, 50));
+        assert!(moved_beyond(10_000, 9_949, 50));
+        assert!(!moved_beyond(10_000, 10_000, 0));
+        assert!(moved_beyond(10_000, 10_001, 0));
+        // An empty vault is measured against a single unit
+        assert!(moved_beyond(0, 2, 10_000));
+        assert!(!moved_beyond(0, 1, 10_000));
+    }
+
+    #[test]
+    fn updates_are_only_sent_past_the_rescan_threshold() {
+        let (sender, mut receiver) = unbounded_channel();
+        let pool = Pubkey::new_unique();
+        let state = SubscriptionState {
+            pairs: vec![VaultPair {
+                pool,
+                token_vault: Pubkey::new_unique(),
+                base_vault: Pubkey::new_unique(),
+                token_mint: Pubkey::new_unique(),
+                base_mint: Pubkey::new_unique(),
+            }],
+            decimals: vec![(6, 9)],
+            reserves: Mutex::new(vec![PoolReserveState {
+                token_reserve: Some(1_000_000),
+                ..PoolReserveState::default()
+            }]),
+            prices: PriceStore::new(),
+            rescan_threshold_bps: 100,
+            sender,
+        };
+
+        // The first price of a pool is always sent
+        assert!(state.apply(0, false, 1_000_000, 1));
+        assert!(receiver.try_recv().is_ok());
+
+        // A 0.5% move refreshes the store without a rescan
+        assert!(state.apply(0, false, 1_005_000, 2));
+        assert!(receiver.try_recv().is_err());
+        assert_eq!(state.prices.get(&pool).unwrap().0.liquidity, 1_005_000);
+
+        // Measured from the last update sent, 1.1% is past the threshold
+        assert!(state.apply(0, false, 1_011_000, 3));
+        assert_eq!(receiver.try_recv().unwrap().1.liquidity, 1_011_000);
+    }
+}
@@ -1,26 +1,62 @@
 use crate::{
     chain::{
-        pools::{MintPoolData, PumpPool, RaydiumPool},
+        clock::{Clock, SystemClock},
+        pools::{
+            DlmmPool, MeteoraDAmmPool, MeteoraDAmmV2Pool, MintPoolData, PumpPool, RaydiumClmmPool, RaydiumCpPool,
+            RaydiumPool, SolfiPool, VertigoPool, WhirlpoolPool,
+        },
         constants::sol_mint,
+        decode::AccountDecoder,
+        pool_filter::PoolFilter,
+        rpc::{backoff_delay_ms, call_with_timeout, RATE_LIMIT_BACKOFF_MULTIPLIER},
+        rpc_budget::RpcCallBudget,
     },
+    error::BotError,
     dex::{
-        traits::{Dex, DexRegistry, PoolInfo},
-        pump::PumpDex,
-        raydium::RaydiumDex,
+        metadata::{metadata_address, metadata_symbol},
+        rpc::RpcLike,
+        vault::{mint_decimals, token_account_amount, transfer_fee_bps},
+        traits::{
+            check_additional_accounts, fee_wallet_for, verify_pool_owner, Dex, DexRegistry, PoolInfo,
+            DEFAULT_MAX_ADDITIONAL_ACCOUNTS,
+        },
+        meteora::{MeteoraDammDex, MeteoraDammV2Dex, MeteoraDlmmDex},
+        pump::{constants::pump_program_id, PumpDex},
+        raydium::{
+            clmm_info::POOL_TICK_ARRAY_BITMAP_SEED, constants::raydium_clmm_program_id,
+            RaydiumClmmDex, RaydiumCpDex, RaydiumDex,
+        },
+        solfi::SolfiDex,
+        vertigo::VertigoDex,
+        whirlpool::WhirlpoolDex,
     },
+    metrics::metrics,
 };
 use anyhow::{anyhow, Result};
-use solana_client::rpc_client::RpcClient;
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+use serde::{Deserialize, Serialize};
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::{
+    client_error::Result as ClientResult,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
+};
 use solana_sdk::{account::Account, pubkey::Pubkey};
-use spl_associated_token_account;
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
+    fs,
+    future::Future,
+    io::{Read, Write},
+    path::Path,
     str::FromStr,
-    sync::Arc,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
     time::{Duration, Instant},
 };
 use tokio::time::sleep;
-use tracing::{error, info, warn};
+use tracing::{debug, error, info, warn};
 
 const TOKEN_2022_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
     6, 221, 246, 225, 215, 101, 161, 147, 217, 203, 225, 70, 206, 235, 121, 172, 28, 180, 134, 244,
@@ -31,11 +67,37 @@ const TOKEN_2022_PROGRAM_ID: Pubkey = Pubkey::new_from_array([
 #[derive(Debug, Clone)]
 pub struct TokenFetchConfig {
     pub max_retries: u32,
+    /// Base delay between retries, doubled on every attempt
     pub retry_delay_ms: u64,
+    /// Cap on the backed-off retry delay
+    pub max_retry_delay_ms: u64,
     pub batch_size: usize,
-    pub timeout_seconds: u64,
+    /// How long a single read may take before it counts as failed
+    pub timeout_ms: u64,
     pub enable_caching: bool,
     pub cache_ttl_seconds: u64,
+    /// Most pool-data entries kept; the least recently used is evicted past it
+    pub max_cache_entries: usize,
+    /// TTL for raw account bytes, tuned independently of the pool-data TTL
+    pub account_cache_ttl_seconds: u64,
+    pub max_rpc_calls_per_scan: Option<u64>,
+    /// Gzip the cache file written by `save_cache`
+    pub compress_cache: bool,
+    /// File the pool cache is loaded from on startup and saved to whenever
+    /// expired entries are cleared; `None` keeps the cache in memory only
+    pub cache_path: Option<String>,
+    /// Replace migrated Pump pools with their Raydium pool from `pump_migrations`
+    pub follow_pump_migrations: bool,
+    /// Pump pool address -> Raydium pool address the token migrated to
+    pub pump_migrations: HashMap<String, String>,
+    /// Threads used to decode batched accounts; 1 decodes sequentially
+    pub decode_threads: usize,
+    /// Raydium swap instruction layout override; `None` uses the current layout
+    pub raydium_swap_version: Option<u8>,
+    /// Most entries a decoder may add to `PoolInfo::additional_accounts`
+    pub max_additional_accounts: usize,
+    /// DEXes whose pools are skipped, from `routing.dexes`
+    pub disabled_dexes: HashSet<String>,
 }
 
 impl Default for TokenFetchConfig {
@@ -43,10 +105,22 @@ impl Default for TokenFetchConfig {
         Self {
             max_retries: 3,
             retry_delay_ms: 1000,
+            max_retry_delay_ms: 10_000,
             batch_size: 10,
-            timeout_seconds: 30,
+            timeout_ms: 30_000,
             enable_caching: true,
             cache_ttl_seconds: 300, // 5 minutes
+            max_cache_entries: 1000,
+            account_cache_ttl_seconds: 30,
+            max_rpc_calls_per_scan: None,
+            compress_cache: false,
+            cache_path: None,
+            follow_pump_migrations: true,
+            pump_migrations: HashMap::new(),
+            decode_threads: 1,
+            raydium_swap_version: None,
+            max_additional_accounts: DEFAULT_MAX_ADDITIONAL_ACCOUNTS,
+            disabled_dexes: HashSet::new(),
         }
     }
 }
@@ -56,54 +130,212 @@ impl Default for TokenFetchConfig {
 struct CacheEntry {
     data: MintPoolData,
     timestamp: Instant,
+    /// Last time the entry was inserted or served, for LRU eviction
+    last_access: Instant,
+}
+
+/// Combined statistics for the pool-data and account caches
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CacheStats {
+    pub pool_entries: usize,
+    pub pool_expired: usize,
+    pub account_entries: usize,
+    pub account_expired: usize,
+    pub account_hits: u64,
+    pub account_misses: u64,
+}
+
+/// Most accounts a single `getMultipleAccounts` request may ask for
+const MAX_MULTIPLE_ACCOUNTS: usize = 100;
+
+/// Magic bytes at the start of every gzip stream
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// On-disk form of a cache entry; `Instant` cannot be persisted, so the
+/// entry's age is recorded against the wall clock instead
+#[derive(Debug, Serialize, Deserialize)]
+struct PersistedCacheEntry {
+    data: MintPoolData,
+    age_seconds: u64,
+    saved_at: u64,
+}
+
+/// Registry of every supported DEX adapter. `MintConfig::pool_list_fields`
+/// maps config fields to the names registered here.
+pub fn build_dex_registry(rpc_client: Arc<dyn RpcLike>, config: &TokenFetchConfig) -> DexRegistry {
+    let mut dex_registry = DexRegistry::new();
+    dex_registry.register(PumpDex::new(rpc_client.clone()));
+    dex_registry.register(RaydiumDex::new(rpc_client.clone()).with_swap_version(config.raydium_swap_version));
+    dex_registry.register(MeteoraDammV2Dex::new(rpc_client.clone()));
+    dex_registry.register(WhirlpoolDex::new(rpc_client.clone()));
+    dex_registry.register(MeteoraDlmmDex::new(rpc_client.clone()));
+    dex_registry.register(SolfiDex::new(rpc_client.clone()));
+    dex_registry.register(VertigoDex::new(rpc_client.clone()));
+    dex_registry.register(RaydiumClmmDex::new(rpc_client.clone()));
+    dex_registry.register(RaydiumCpDex::new(rpc_client.clone()));
+    dex_registry.register(MeteoraDammDex::new(rpc_client));
+    dex_registry
 }
 
-/// Enhanced token fetcher with caching and retry logic
+/// Token balance of a pool vault, or `None` when the account is not an SPL
+/// Token or Token-2022 account, as with vaults held through another program
+fn vault_balance(account: &Account) -> Option<u64> {
+    if account.owner != spl_token::ID && account.owner != TOKEN_2022_PROGRAM_ID {
+        return None;
+    }
+    token_account_amount(&account.data).ok()
+}
+
+/// Enhanced token fetcher with caching and retry logic. Every read goes
+/// through `rpc`, usually the `RpcPool`, and the DEX adapters it builds share it.
 pub struct TokenFetcher {
-    rpc_client: Arc<RpcClient>,
+    rpc: Arc<dyn RpcLike>,
     config: TokenFetchConfig,
     cache: HashMap<String, CacheEntry>,
+    account_cache: Mutex<HashMap<Pubkey, (Account, Instant)>>,
+    account_hits: AtomicU64,
+    account_misses: AtomicU64,
+    rpc_budget: RpcCallBudget,
+    decoder: Arc<AccountDecoder>,
+    clock: Arc<dyn Clock>,
 }
 
 impl TokenFetcher {
-    pub fn new(rpc_client: Arc<RpcClient>, config: TokenFetchConfig) -> Self {
-        Self {
-            rpc_client,
+    pub fn new(rpc: Arc<dyn RpcLike>, config: TokenFetchConfig) -> Self {
+        Self::with_clock(rpc, config, Arc::new(SystemClock))
+    }
+
+    /// Create a fetcher whose cache ages are measured with the given clock
+    pub fn with_clock(
+        rpc: Arc<dyn RpcLike>,
+        config: TokenFetchConfig,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        let rpc_budget = RpcCallBudget::new(config.max_rpc_calls_per_scan);
+        let decoder = AccountDecoder::new(config.decode_threads).unwrap_or_else(|e| {
+            warn!("Failed to start decode thread pool, decoding sequentially: {}", e);
+            AccountDecoder::new(1).expect("sequential decoder has no thread pool")
+        });
+        let mut fetcher = Self {
+            rpc,
             config,
             cache: HashMap::new(),
+            account_cache: Mutex::new(HashMap::new()),
+            account_hits: AtomicU64::new(0),
+            account_misses: AtomicU64::new(0),
+            rpc_budget,
+            decoder: Arc::new(decoder),
+            clock,
+        };
+
+        if let Some(path) = fetcher.config.cache_path.clone().filter(|_| fetcher.config.enable_caching) {
+            if Path::new(&path).exists() {
+                if let Err(e) = fetcher.load_cache(&path) {
+                    warn!("Failed to load pool cache from {}: {}", path, e);
+                }
+            }
+        }
+        fetcher
+    }
+
+    /// Start a new scan pass, resetting the RPC call budget
+    pub fn begin_scan_pass(&self) {
+        self.rpc_budget.begin_pass();
+    }
+
+    /// Number of RPC calls issued during the current scan pass
+    pub fn rpc_calls_this_pass(&self) -> u64 {
+        self.rpc_budget.calls_this_pass()
+    }
+
+    /// Shared decoder for batched account fetches
+    pub fn decoder(&self) -> Arc<AccountDecoder> {
+        self.decoder.clone()
+    }
+
+    /// Shared handle to the per-pass RPC call budget
+    pub fn rpc_budget(&self) -> RpcCallBudget {
+        self.rpc_budget.clone()
+    }
+
+    /// Find every pool of `dex` that trades `mint` with one `getProgramAccounts`
+    /// call per mint field offset, so pools need not be listed by hand. Calls
+    /// are retried like any other read. Accounts that fail to parse, or carry
+    /// more than `max_additional_accounts` extra accounts, are skipped.
+    pub async fn discover_pools(&self, dex: &dyn Dex, mint: &Pubkey) -> Result<Vec<PoolInfo>> {
+        let offsets = dex.mint_offsets();
+        if offsets.is_empty() {
+            return Err(anyhow!("{} pools cannot be discovered by mint", dex.name()));
+        }
+
+        let program_id = dex.program_id();
+        let mut seen = HashSet::new();
+        let mut pools = Vec::new();
+        for &offset in offsets {
+            if self.rpc_budget.is_exhausted() {
+                warn!("RPC call budget exhausted, stopping {} pool discovery for mint: {}", dex.name(), mint);
+                break;
+            }
+            let mint = *mint;
+            let accounts = self
+                .with_retry(&format!("{} pools of mint {}", dex.name(), mint), move |rpc| {
+                    rpc.get_program_accounts_with_config(
+                        &program_id,
+                        RpcProgramAccountsConfig {
+                            filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                                offset,
+                                mint.as_ref(),
+                            ))]),
+                            account_config: RpcAccountInfoConfig {
+                                encoding: Some(UiAccountEncoding::Base64),
+                                ..Default::default()
+                            },
+                            ..Default::default()
+                        },
+                    )
+                })
+                .await?;
+
+            for (pubkey, account) in accounts {
+                if !seen.insert(pubkey) {
+                    continue;
+                }
+                let pool = dex.parse_pool_account(&pubkey, &account.data, &mint).and_then(|pool| {
+                    check_additional_accounts(&pool, self.config.max_additional_accounts)?;
+                    Ok(pool)
+                });
+                match pool {
+                    Ok(pool) => pools.push(pool),
+                    Err(e) => debug!("Skipping {} account {}: {}", dex.name(), pubkey, e),
+                }
+            }
         }
+
+        info!("Discovered {} {} pools for mint: {}", pools.len(), dex.name(), mint);
+        Ok(pools)
     }
 
-    /// Initialize pool data with enhanced error handling and caching
+    /// Initialize pool data with enhanced error handling and caching.
+    /// `pool_lists` pairs each DEX name with its pool addresses, as produced by
+    /// `MintConfig::pool_lists`.
+    #[tracing::instrument(skip_all, fields(mint = %mint))]
     pub async fn initialize_pool_data(
         &mut self,
         mint: &str,
         wallet_account: &str,
-        raydium_pools: Option<&Vec<String>>,
-        raydium_cp_pools: Option<&Vec<String>>,
-        pump_pools: Option<&Vec<String>>,
-        dlmm_pools: Option<&Vec<String>>,
-        whirlpool_pools: Option<&Vec<String>>,
-        raydium_clmm_pools: Option<&Vec<String>>,
-        meteora_damm_pools: Option<&Vec<String>>,
-        solfi_pools: Option<&Vec<String>>,
-        meteora_damm_v2_pools: Option<&Vec<String>>,
-        vertigo_pools: Option<&Vec<String>>,
+        pool_lists: &[(&'static str, Vec<String>)],
+        pool_filter: &PoolFilter,
     ) -> Result<MintPoolData> {
         let cache_key = format!("{}_{}", mint, wallet_account);
         
         // Check cache first
-        if self.config.enable_caching {
-            if let Some(entry) = self.cache.get(&cache_key) {
-                if entry.timestamp.elapsed().as_secs() < self.config.cache_ttl_seconds {
-                    info!("Using cached pool data for mint: {}", mint);
-                    return Ok(entry.data.clone());
-                }
-            }
+        if let Some(data) = self.cached_pool_data(&cache_key) {
+            info!("Using cached pool data for mint: {}", mint);
+            return Ok(data);
         }
 
         info!("Initializing pool data for mint: {}", mint);
-        let start_time = Instant::now();
+        let start_time = self.clock.now();
 
         // Fetch mint account with retry logic
         let mint_pubkey = Pubkey::from_str(mint)?;
@@ -116,47 +348,103 @@ impl TokenFetcher {
         let mut pool_data = MintPoolData::new(mint, wallet_account, token_program)?;
         info!("Pool data initialized for mint: {}", mint);
 
-        // Create DEX registry with unified implementations
-        let mut dex_registry = DexRegistry::new();
-        dex_registry.register(PumpDex::new(self.rpc_client.clone()));
-        dex_registry.register(RaydiumDex::new(self.rpc_client.clone()));
+        let dex_registry = build_dex_registry(self.rpc.clone(), &self.config);
 
-        // Unified pool fetching using the registry
-        let pool_configs = vec![
-            ("pump", pump_pools),
-            ("raydium", raydium_pools),
-            // TODO: Add other DEXes as they are implemented
-        ];
+        // Disabled DEXes are dropped before any of their pools is read
+        let mut pool_configs = self.enabled_pool_lists(pool_lists, mint);
 
-        for (dex_name, pool_list) in pool_configs {
-            if let Some(pool_addresses) = pool_list {
-                if let Some(dex) = dex_registry.get(dex_name) {
-                    match dex.fetch_pools(pool_addresses, &mint_pubkey).await {
-                        Ok(pools) => {
-                            // Convert unified PoolInfo to legacy pool types
-                            self.convert_and_add_pools(&mut pool_data, dex_name, pools).await?;
-                            info!("Successfully fetched {} pools from {}", pools.len(), dex_name);
-                        }
-                        Err(e) => {
-                            warn!("Failed to fetch {} pools: {}", dex_name, e);
-                        }
-                    }
+        // Follow graduated Pump tokens to their Raydium pools
+        let migrated = match pool_configs.iter().find(|(dex_name, _)| *dex_name == "pump") {
+            Some((_, pump_pools)) => self.find_migrated_pump_pools(pump_pools, mint).await,
+            None => HashMap::new(),
+        };
+        if !migrated.is_empty() {
+            for (dex_name, pools) in pool_configs.iter_mut() {
+                if *dex_name == "pump" {
+                    pools.retain(|pool| !migrated.contains_key(pool));
                 }
             }
+            if !self.config.disabled_dexes.contains("raydium") {
+                match pool_configs.iter_mut().find(|(dex_name, _)| *dex_name == "raydium") {
+                    Some((_, raydium_pools)) => raydium_pools.extend(migrated.into_values()),
+                    None => pool_configs.push(("raydium", migrated.into_values().collect())),
+                }
+            }
+        }
+
+        // Pool addresses from every DEX are fetched together so each RPC call
+        // covers up to `batch_size` accounts
+        let mut requests: Vec<(&dyn Dex, Pubkey)> = Vec::new();
+        for (dex_name, pool_addresses) in &pool_configs {
+            let Some(dex) = dex_registry.get(dex_name) else {
+                warn!("No DEX registered for {} pools", dex_name);
+                continue;
+            };
+            if token_program == TOKEN_2022_PROGRAM_ID && !dex.capabilities().supports_token_2022 {
+                warn!(
+                    "Skipping {} pools for Token-2022 mint {}: not supported by the DEX",
+                    dex_name, mint
+                );
+                continue;
+            }
+            for address in pool_addresses {
+                match Pubkey::from_str(address) {
+                    Ok(pubkey) => requests.push((dex, pubkey)),
+                    Err(e) => warn!("Invalid {} pool address {}: {}", dex_name, address, e),
+                }
+            }
+        }
+
+        // Pools from every DEX are collected first so the per-mint filter can
+        // rank them together
+        let mut fetched = self.fetch_pools_batched(&requests, &mint_pubkey, mint).await;
+        metrics().pools_fetched.inc_by(fetched.len() as u64);
+        // The filter ranks and thresholds on liquidity, so measure pools first
+        if pool_filter.needs_liquidity() {
+            self.fill_liquidity(&mut fetched).await;
+        }
+        if let Some(min_liquidity) = pool_filter.min_liquidity_lamports() {
+            let below_min = fetched
+                .iter()
+                .filter(|(_, _, liquidity)| liquidity.is_some_and(|liquidity| liquidity < min_liquidity))
+                .count();
+            if below_min > 0 {
+                info!(
+                    "{} pools for mint {} are below the minimum liquidity of {} lamports",
+                    below_min, mint, min_liquidity
+                );
+            }
+        }
+
+        let (selected, dropped) = pool_filter.apply(
+            fetched,
+            |(_, pool, _)| pool.pool_address,
+            |(_, _, liquidity)| *liquidity,
+        );
+        if dropped > 0 {
+            info!("Filtered out {} pools for mint: {}", dropped, mint);
+        }
+
+        // Convert unified PoolInfo to legacy pool types
+        for dex in dex_registry.all_dexes() {
+            let pools: Vec<PoolInfo> = selected
+                .iter()
+                .filter(|(dex_name, _, _)| *dex_name == dex.name())
+                .map(|(_, pool, _)| pool.clone())
+                .collect();
+            if !pools.is_empty() {
+                self.convert_and_add_pools(&mut pool_data, dex, pools, pool_filter).await?;
+            }
         }
 
         // Cache the result
         if self.config.enable_caching {
-            self.cache.insert(
-                cache_key,
-                CacheEntry {
-                    data: pool_data.clone(),
-                    timestamp: Instant::now(),
-                },
-            );
+            let now = self.clock.now();
+            self.insert_cache_entry(cache_key, pool_data.clone(), now);
         }
 
-        let elapsed = start_time.elapsed();
+        let elapsed = self.clock.now().duration_since(start_time);
+        metrics().pool_data_seconds.observe(elapsed.as_secs_f64());
         info!(
             "Pool data initialization completed for mint: {} in {:?}",
             mint, elapsed
@@ -165,34 +453,349 @@ impl TokenFetcher {
         Ok(pool_data)
     }
 
-    /// Fetch account with retry logic
+    /// `pool_lists` without the lists of DEXes disabled in `routing.dexes`
+    fn enabled_pool_lists(
+        &self,
+        pool_lists: &[(&'static str, Vec<String>)],
+        mint: &str,
+    ) -> Vec<(&'static str, Vec<String>)> {
+        pool_lists
+            .iter()
+            .filter(|(dex_name, _)| {
+                let disabled = self.config.disabled_dexes.contains(*dex_name);
+                if disabled {
+                    info!("Skipping {} pools for mint {}: DEX is disabled", dex_name, mint);
+                }
+                !disabled
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Fetch and parse pool accounts in `batch_size` chunks, one
+    /// `getMultipleAccounts` call per chunk. Chunks go through the same retry
+    /// loop and account cache as single reads, and a chunk that still fails
+    /// only drops its own pools.
+    async fn fetch_pools_batched(
+        &self,
+        requests: &[(&dyn Dex, Pubkey)],
+        mint_pubkey: &Pubkey,
+        mint: &str,
+    ) -> Vec<(&'static str, PoolInfo, Option<u64>)> {
+        let mut fetched = Vec::new();
+        let max_additional = self.config.max_additional_accounts;
+
+        for chunk in requests.chunks(self.config.batch_size.max(1)) {
+            if self.rpc_budget.is_exhausted() {
+                warn!(
+                    "RPC call budget exhausted ({} calls this pass), completing with partial data for mint: {}",
+                    self.rpc_budget.calls_this_pass(),
+                    mint
+                );
+                break;
+            }
+
+            let pubkeys: Vec<Pubkey> = chunk.iter().map(|(_, pubkey)| *pubkey).collect();
+            let accounts = match self.fetch_accounts_with_retry(&pubkeys).await {
+                Ok(accounts) => accounts,
+                Err(e) => {
+                    warn!("Failed to fetch {} pool accounts: {}", pubkeys.len(), e);
+                    continue;
+                }
+            };
+
+            let owners: HashMap<Pubkey, &dyn Dex> =
+                chunk.iter().map(|(dex, pubkey)| (*pubkey, *dex)).collect();
+            let accounts: Vec<(Pubkey, Option<Account>)> = pubkeys.into_iter().zip(accounts).collect();
+            let decoded = self.decoder.decode(&accounts, |pubkey, account| {
+                let dex = owners[pubkey];
+                verify_pool_owner(account, &dex.program_id())?;
+                Ok(dex.parse_pool_account(pubkey, &account.data, mint_pubkey)?)
+            });
+
+            for (pubkey, result) in decoded {
+                let dex = owners[&pubkey];
+                match result {
+                    Ok(pool) => match check_additional_accounts(&pool, max_additional) {
+                        Ok(()) => fetched.push((dex.name(), pool, None)),
+                        Err(e) => error!("Dropping {} pool: {}", dex.name(), e),
+                    },
+                    Err(e) => warn!("Failed to parse {} pool {}: {}", dex.name(), pubkey, e),
+                }
+            }
+        }
+
+        info!("Fetched {} of {} pools for mint: {}", fetched.len(), requests.len(), mint);
+        self.annotate_pools(&mut fetched).await;
+        fetched
+    }
+
+    /// Record each fetched pool's depth for the pool filter: the balance of
+    /// its base vault, in the base mint's smallest unit. Vaults are read in
+    /// batched calls counted against the RPC budget. The filter compares
+    /// depths in lamports, so only pools quoted in SOL get one; pools in other
+    /// base mints, and pools whose vault can't be read, keep unknown
+    /// liquidity, which the filter keeps.
+    async fn fill_liquidity(&self, pools: &mut [(&'static str, PoolInfo, Option<u64>)]) {
+        let sol = sol_mint();
+        let vaults: Vec<Pubkey> = pools
+            .iter()
+            .filter(|(_, pool, _)| pool.base_mint == sol)
+            .map(|(_, pool, _)| pool.base_vault)
+            .collect();
+
+        let mut depths = HashMap::new();
+        for chunk in vaults.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            if !self.rpc_budget.try_acquire(1) {
+                warn!("RPC call budget exhausted, leaving the liquidity of {} pools unknown", chunk.len());
+                break;
+            }
+            let request = chunk.to_vec();
+            match self.timed_rpc(move |rpc| rpc.get_multiple_accounts(&request)).await {
+                Ok(accounts) => {
+                    for (vault, account) in chunk.iter().zip(accounts) {
+                        if let Some(depth) = account.as_ref().and_then(vault_balance) {
+                            depths.insert(*vault, depth);
+                        }
+                    }
+                }
+                Err(e) => warn!("Failed to read pool vaults: {}", e),
+            }
+        }
+
+        for (dex_name, pool, liquidity) in pools.iter_mut() {
+            match depths.get(&pool.base_vault) {
+                Some(depth) => *liquidity = Some(*depth),
+                None => debug!(
+                    "Unknown liquidity for {} pool {} in base mint {}",
+                    dex_name, pool.pool_address, pool.base_mint
+                ),
+            }
+        }
+    }
+
+    /// Fill in mint decimals, transfer fees and token symbols for freshly
+    /// parsed pools with one batched read of the mint and metadata accounts, so
+    /// pricing and logs need no further lookups. Failures leave the fields unset.
+    async fn annotate_pools(&self, pools: &mut [(&'static str, PoolInfo, Option<u64>)]) {
+        let mut mints: Vec<Pubkey> = pools
+            .iter()
+            .flat_map(|(_, pool, _)| [pool.token_mint, pool.base_mint])
+            .collect();
+        mints.sort();
+        mints.dedup();
+        if mints.is_empty() {
+            return;
+        }
+
+        // Each mint needs its mint account and its metadata account
+        let addresses: Vec<Pubkey> = mints
+            .iter()
+            .flat_map(|mint| [*mint, metadata_address(mint)])
+            .collect();
+        let mut accounts = Vec::with_capacity(addresses.len());
+        for chunk in addresses.chunks(MAX_MULTIPLE_ACCOUNTS) {
+            if !self.rpc_budget.try_acquire(1) {
+                return;
+            }
+            let request = chunk.to_vec();
+            match self.timed_rpc(move |rpc| rpc.get_multiple_accounts(&request)).await {
+                Ok(chunk_accounts) => accounts.extend(chunk_accounts),
+                Err(e) => {
+                    warn!("Failed to fetch mint metadata: {}", e);
+                    return;
+                }
+            }
+        }
+
+        let mut decimals = HashMap::new();
+        let mut transfer_fees = HashMap::new();
+        let mut symbols = HashMap::new();
+        for (mint, pair) in mints.iter().zip(accounts.chunks(2)) {
+            if let Some(mint_account) = pair[0].as_ref() {
+                if let Ok(d) = mint_decimals(&mint_account.data) {
+                    decimals.insert(*mint, d);
+                }
+                if mint_account.owner == TOKEN_2022_PROGRAM_ID {
+                    transfer_fees.insert(*mint, transfer_fee_bps(&mint_account.data));
+                }
+            }
+            if let Some(symbol) = pair.get(1).and_then(|a| a.as_ref()).and_then(|a| metadata_symbol(&a.data)) {
+                symbols.insert(*mint, symbol);
+            }
+        }
+
+        for (_, pool, _) in pools.iter_mut() {
+            pool.token_decimals = pool.token_decimals.or(decimals.get(&pool.token_mint).copied());
+            pool.base_decimals = pool.base_decimals.or(decimals.get(&pool.base_mint).copied());
+            pool.token_transfer_fee_bps = transfer_fees.get(&pool.token_mint).copied().unwrap_or(0);
+            pool.base_transfer_fee_bps = transfer_fees.get(&pool.base_mint).copied().unwrap_or(0);
+            if pool.token_symbol.is_none() {
+                pool.token_symbol = symbols.get(&pool.token_mint).cloned();
+            }
+        }
+    }
+
+    /// Fetch account with retry logic, serving shared accounts from the account cache
     async fn fetch_account_with_retry(&self, pubkey: &Pubkey) -> Result<Account> {
+        if let Some(account) = self.lookup_account(pubkey) {
+            return Ok(account);
+        }
+
+        let request = *pubkey;
+        let account = self
+            .with_retry(&format!("account {}", pubkey), move |rpc| rpc.get_account(&request))
+            .await?;
+        self.cache_account(*pubkey, &account);
+        Ok(account)
+    }
+
+    /// Fetch several accounts with retry logic, serving fresh ones from the
+    /// account cache and reading the rest in one `getMultipleAccounts` call.
+    /// Accounts that don't exist come back as `None`.
+    async fn fetch_accounts_with_retry(&self, pubkeys: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+        let mut accounts: Vec<Option<Account>> = pubkeys.iter().map(|pubkey| self.lookup_account(pubkey)).collect();
+        let misses: Vec<usize> = (0..pubkeys.len()).filter(|&i| accounts[i].is_none()).collect();
+        if misses.is_empty() {
+            return Ok(accounts);
+        }
+
+        let request: Vec<Pubkey> = misses.iter().map(|&i| pubkeys[i]).collect();
+        let fetched = self
+            .with_retry(&format!("{} accounts", request.len()), move |rpc| {
+                rpc.get_multiple_accounts(&request)
+            })
+            .await?;
+        for (i, account) in misses.into_iter().zip(fetched) {
+            if let Some(account) = &account {
+                self.cache_account(pubkeys[i], account);
+            }
+            accounts[i] = account;
+        }
+        Ok(accounts)
+    }
+
+    /// Run `op` until it succeeds, backing off between attempts and longer
+    /// after rate limits. With an `RpcPool` each attempt goes to the next
+    /// healthy endpoint. Each attempt counts against the RPC call budget;
+    /// `what` names the read in logs and errors.
+    async fn with_retry<T, F>(&self, what: &str, op: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: Fn(&dyn RpcLike) -> ClientResult<T> + Clone + Send + 'static,
+    {
         let mut last_error = None;
-        
+
         for attempt in 0..self.config.max_retries {
-            match self.rpc_client.get_account(pubkey) {
-                Ok(account) => return Ok(account),
+            if !self.rpc_budget.try_acquire(1) {
+                warn!(
+                    "RPC call budget exhausted ({} calls this pass), not fetching {}",
+                    self.rpc_budget.calls_this_pass(),
+                    what
+                );
+                break;
+            }
+
+            match self.timed_rpc(op.clone()).await {
+                Ok(value) => return Ok(value),
                 Err(e) => {
+                    // Rate limits need a longer pause than transient failures
+                    let multiplier = if matches!(e, BotError::RateLimited(_)) {
+                        RATE_LIMIT_BACKOFF_MULTIPLIER
+                    } else {
+                        1
+                    };
                     last_error = Some(e);
                     if attempt < self.config.max_retries - 1 {
+                        let delay_ms = backoff_delay_ms(
+                            self.config.retry_delay_ms * multiplier,
+                            attempt,
+                            self.config.max_retry_delay_ms * multiplier,
+                        );
                         warn!(
-                            "Failed to fetch account {} (attempt {}/{}), retrying in {}ms",
-                            pubkey, attempt + 1, self.config.max_retries, self.config.retry_delay_ms
+                            "Failed to fetch {} (attempt {}/{}), retrying in {}ms",
+                            what, attempt + 1, self.config.max_retries, delay_ms
                         );
-                        sleep(Duration::from_millis(self.config.retry_delay_ms)).await;
+                        sleep(Duration::from_millis(delay_ms)).await;
                     }
                 }
             }
         }
 
+        if let Some(e) = last_error.as_ref().filter(|e| matches!(e, BotError::RateLimited(_))) {
+            return Err(BotError::RateLimited(format!(
+                "fetching {} after {} attempts: {}",
+                what, self.config.max_retries, e
+            ))
+            .into());
+        }
+
         Err(anyhow!(
-            "Failed to fetch account {} after {} attempts: {:?}",
-            pubkey,
+            "Failed to fetch {} after {} attempts: {:?}",
+            what,
             self.config.max_retries,
             last_error
         ))
     }
 
+    /// Run `op` against `rpc` with `call_with_timeout`, giving up after
+    /// `timeout_ms`. A timeout is retried like any other RPC failure.
+    fn timed_rpc<T, F>(&self, op: F) -> impl Future<Output = std::result::Result<T, BotError>>
+    where
+        T: Send + 'static,
+        F: FnOnce(&dyn RpcLike) -> ClientResult<T> + Send + 'static,
+    {
+        let rpc = self.rpc.clone();
+        call_with_timeout(Duration::from_millis(self.config.timeout_ms), move || op(rpc.as_ref()))
+    }
+
+    /// Find configured Pump pools that have migrated, returning each one mapped
+    /// to its Raydium pool. A pool counts as migrated once its account is closed
+    /// or no longer owned by the Pump program.
+    async fn find_migrated_pump_pools(
+        &self,
+        pump_pools: &[String],
+        mint: &str,
+    ) -> HashMap<String, String> {
+        let mut migrated = HashMap::new();
+        if !self.config.follow_pump_migrations {
+            return migrated;
+        }
+
+        let candidates: Vec<(&String, Pubkey)> = pump_pools
+            .iter()
+            .filter(|pool| self.config.pump_migrations.contains_key(*pool))
+            .filter_map(|pool| Pubkey::from_str(pool).ok().map(|pubkey| (pool, pubkey)))
+            .collect();
+        if candidates.is_empty() || !self.rpc_budget.try_acquire(1) {
+            return migrated;
+        }
+
+        let pubkeys: Vec<Pubkey> = candidates.iter().map(|(_, pubkey)| *pubkey).collect();
+        let accounts = match self.timed_rpc(move |rpc| rpc.get_multiple_accounts(&pubkeys)).await {
+            Ok(accounts) => accounts,
+            Err(e) => {
+                warn!("Failed to check Pump pools for migration: {}", e);
+                return migrated;
+            }
+        };
+
+        for ((pool, _), account) in candidates.into_iter().zip(accounts) {
+            let is_live = account.is_some_and(|a| a.owner == pump_program_id());
+            if is_live {
+                continue;
+            }
+            let raydium_pool = self.config.pump_migrations[pool].clone();
+            info!(
+                "Pump pool {} for mint {} has migrated, following it to Raydium pool {}",
+                pool, mint, raydium_pool
+            );
+            migrated.insert(pool.clone(), raydium_pool);
+        }
+
+        migrated
+    }
+
     /// Determine token program from mint account
     fn determine_token_program(&self, mint_account: &Account, mint: &str) -> Result<Pubkey> {
         if mint_account.owner == spl_token::ID {
@@ -204,8 +807,24 @@ impl TokenFetcher {
         }
     }
 
-    /// Convert unified PoolInfo to legacy pool types and add to pool_data
-    async fn convert_and_add_pools(&self, pool_data: &mut MintPoolData, dex_name: &str, pools: Vec<PoolInfo>) -> Result<()> {
+    /// Convert unified PoolInfo to legacy pool types and add to pool_data,
+    /// dropping pools disabled in `pool_filter`
+    async fn convert_and_add_pools(
+        &self,
+        pool_data: &mut MintPoolData,
+        dex: &dyn Dex,
+        mut pools: Vec<PoolInfo>,
+        pool_filter: &PoolFilter,
+    ) -> Result<()> {
+        let dex_name = dex.name();
+        pools.retain(|pool_info| {
+            let disabled = pool_filter.is_disabled(&pool_info.pool_address);
+            if disabled {
+                info!("Skipping disabled {} pool {}", dex_name, pool_info.pool_address);
+            }
+            !disabled
+        });
+
         match dex_name {
             "pump" => {
                 for pool_info in pools {
@@ -215,16 +834,18 @@ impl TokenFetcher {
                         .copied()
                         .ok_or_else(|| anyhow!("Missing coin_creator_vault_ata for Pump pool"))?;
 
+                    let fee_token_wallet = fee_wallet_for(dex, &pool_info)?.ok_or_else(|| {
+                        anyhow!("Missing fee wallet for Pump pool {}", pool_info.pool_address)
+                    })?;
+
                     let pump_pool = PumpPool {
                         pool: pool_info.pool_address,
                         token_vault: pool_info.token_vault,
                         sol_vault: pool_info.base_vault,
-                        fee_token_wallet: pool_info.fee_wallet.unwrap_or_default(),
+                        fee_token_wallet,
                         coin_creator_vault_ata,
-                        coin_creator_vault_authority: pool_info.additional_accounts
-                            .get("coin_creator_vault_authority")
-                            .copied()
-                            .unwrap_or_default(), // This would need to be fetched separately
+                        coin_creator_vault_authority: pool_info
+                            .additional_account("coin_creator_vault_authority")?,
                         token_mint: pool_info.token_mint,
                         base_mint: pool_info.base_mint,
                     };
@@ -243,6 +864,145 @@ impl TokenFetcher {
                     pool_data.raydium_pools.push(raydium_pool);
                 }
             }
+            "meteora_damm_v2" => {
+                for pool_info in pools {
+                    let damm_v2_pool = MeteoraDAmmV2Pool {
+                        pool: pool_info.pool_address,
+                        token_x_vault: pool_info.token_vault,
+                        token_sol_vault: pool_info.base_vault,
+                        token_mint: pool_info.token_mint,
+                        base_mint: pool_info.base_mint,
+                    };
+                    pool_data.meteora_damm_v2_pools.push(damm_v2_pool);
+                }
+            }
+            "whirlpool" => {
+                for pool_info in pools {
+                    let tick_arrays = (0..3)
+                        .filter_map(|i| pool_info.additional_accounts.get(&format!("tick_array_{}", i)).copied())
+                        .collect();
+
+                    let whirlpool_pool = WhirlpoolPool {
+                        pool: pool_info.pool_address,
+                        oracle: pool_info.additional_account("oracle")?,
+                        x_vault: pool_info.additional_account("token_vault_a")?,
+                        y_vault: pool_info.additional_account("token_vault_b")?,
+                        tick_arrays,
+                        memo_program: None,
+                        token_mint: pool_info.token_mint,
+                        base_mint: pool_info.base_mint,
+                    };
+                    pool_data.whirlpool_pools.push(whirlpool_pool);
+                }
+            }
+            "meteora_dlmm" => {
+                for pool_info in pools {
+                    let bin_arrays = (0..3)
+                        .filter_map(|i| pool_info.additional_accounts.get(&format!("bin_array_{}", i)).copied())
+                        .collect();
+
+                    let dlmm_pool = DlmmPool {
+                        pair: pool_info.pool_address,
+                        token_vault: pool_info.token_vault,
+                        sol_vault: pool_info.base_vault,
+                        oracle: pool_info.additional_account("oracle")?,
+                        bin_arrays,
+                        memo_program: None,
+                        token_mint: pool_info.token_mint,
+                        base_mint: pool_info.base_mint,
+                    };
+                    pool_data.dlmm_pairs.push(dlmm_pool);
+                }
+            }
+            "solfi" => {
+                for pool_info in pools {
+                    let solfi_pool = SolfiPool {
+                        pool: pool_info.pool_address,
+                        token_x_vault: pool_info.token_vault,
+                        token_sol_vault: pool_info.base_vault,
+                        token_mint: pool_info.token_mint,
+                        base_mint: pool_info.base_mint,
+                    };
+                    pool_data.solfi_pools.push(solfi_pool);
+                }
+            }
+            "vertigo" => {
+                for pool_info in pools {
+                    let vertigo_pool = VertigoPool {
+                        pool: pool_info.pool_address,
+                        pool_owner: pool_info.additional_account("pool_owner")?,
+                        token_x_vault: pool_info.token_vault,
+                        token_sol_vault: pool_info.base_vault,
+                        token_mint: pool_info.token_mint,
+                        base_mint: pool_info.base_mint,
+                    };
+                    pool_data.vertigo_pools.push(vertigo_pool);
+                }
+            }
+            "raydium_clmm" => {
+                for pool_info in pools {
+                    let tick_arrays = (0..3)
+                        .filter_map(|i| pool_info.additional_accounts.get(&format!("tick_array_{}", i)).copied())
+                        .collect();
+
+                    let bitmap_extension = Pubkey::find_program_address(
+                        &[
+                            POOL_TICK_ARRAY_BITMAP_SEED.as_bytes(),
+                            pool_info.pool_address.as_ref(),
+                        ],
+                        &raydium_clmm_program_id(),
+                    )
+                    .0;
+
+                    let clmm_pool = RaydiumClmmPool {
+                        pool: pool_info.pool_address,
+                        amm_config: pool_info.additional_account("amm_config")?,
+                        observation_state: pool_info.additional_account("observation_state")?,
+                        bitmap_extension,
+                        x_vault: pool_info.token_vault,
+                        y_vault: pool_info.base_vault,
+                        tick_arrays,
+                        memo_program: None,
+                        token_mint: pool_info.token_mint,
+                        base_mint: pool_info.base_mint,
+                    };
+                    pool_data.raydium_clmm_pools.push(clmm_pool);
+                }
+            }
+            "raydium_cp" => {
+                for pool_info in pools {
+                    let cp_pool = RaydiumCpPool {
+                        pool: pool_info.pool_address,
+                        token_vault: pool_info.token_vault,
+                        sol_vault: pool_info.base_vault,
+                        amm_config: pool_info.additional_account("amm_config")?,
+                        observation: pool_info.additional_account("observation_state")?,
+                        token_mint: pool_info.token_mint,
+                        base_mint: pool_info.base_mint,
+                    };
+                    pool_data.raydium_cp_pools.push(cp_pool);
+                }
+            }
+            "meteora_damm" => {
+                for pool_info in pools {
+                    let damm_pool = MeteoraDAmmPool {
+                        pool: pool_info.pool_address,
+                        token_x_vault: pool_info.token_vault,
+                        token_sol_vault: pool_info.base_vault,
+                        token_x_token_vault: pool_info.additional_account("token_x_token_vault")?,
+                        token_sol_token_vault: pool_info.additional_account("token_sol_token_vault")?,
+                        token_x_lp_mint: pool_info.additional_account("token_x_lp_mint")?,
+                        token_sol_lp_mint: pool_info.additional_account("token_sol_lp_mint")?,
+                        token_x_pool_lp: pool_info.additional_account("token_x_pool_lp")?,
+                        token_sol_pool_lp: pool_info.additional_account("token_sol_pool_lp")?,
+                        admin_token_fee_x: pool_info.additional_account("admin_token_fee_x")?,
+                        admin_token_fee_sol: pool_info.additional_account("admin_token_fee_sol")?,
+                        token_mint: pool_info.token_mint,
+                        base_mint: pool_info.base_mint,
+                    };
+                    pool_data.meteora_damm_pools.push(damm_pool);
+                }
+            }
             _ => {
                 warn!("Unknown DEX type: {}", dex_name);
             }
@@ -250,12 +1010,192 @@ impl TokenFetcher {
         Ok(())
     }
 
+    /// Whether a cache entry written at `timestamp` is still within its TTL
+    fn is_fresh(&self, timestamp: Instant) -> bool {
+        self.clock.now().duration_since(timestamp).as_secs() < self.config.cache_ttl_seconds
+    }
+
+    /// Whether an account cached at `timestamp` is still within the account TTL
+    fn is_account_fresh(&self, timestamp: Instant) -> bool {
+        self.clock.now().duration_since(timestamp).as_secs()
+            < self.config.account_cache_ttl_seconds
+    }
+
+    /// Fresh cached account, counted as an account cache hit or miss. Always
+    /// a miss when caching is off.
+    fn lookup_account(&self, pubkey: &Pubkey) -> Option<Account> {
+        if !self.config.enable_caching {
+            return None;
+        }
+        let account = self.cached_account(pubkey);
+        let counter = if account.is_some() { &self.account_hits } else { &self.account_misses };
+        counter.fetch_add(1, Ordering::Relaxed);
+        account
+    }
+
+    fn cache_account(&self, pubkey: Pubkey, account: &Account) {
+        if self.config.enable_caching {
+            self.account_cache
+                .lock()
+                .unwrap()
+                .insert(pubkey, (account.clone(), self.clock.now()));
+        }
+    }
+
+    fn cached_account(&self, pubkey: &Pubkey) -> Option<Account> {
+        let cache = self.account_cache.lock().unwrap();
+        cache
+            .get(pubkey)
+            .filter(|(_, fetched_at)| self.is_account_fresh(*fetched_at))
+            .map(|(account, _)| account.clone())
+    }
+
+    /// Drop the cached pool data of `mint`, so its pools are loaded again on
+    /// the next `initialize_pool_data`
+    pub fn forget_mint(&mut self, mint: &str) {
+        let prefix = format!("{}_", mint);
+        self.cache.retain(|key, _| !key.starts_with(&prefix));
+    }
+
+    /// Pool data cached under `key` if still within its TTL, marking the entry
+    /// as just used for LRU eviction
+    fn cached_pool_data(&mut self, key: &str) -> Option<MintPoolData> {
+        if !self.config.enable_caching {
+            return None;
+        }
+        let now = self.clock.now();
+        let ttl = self.config.cache_ttl_seconds;
+        let entry = self.cache.get_mut(key)?;
+        if now.duration_since(entry.timestamp).as_secs() >= ttl {
+            return None;
+        }
+        entry.last_access = now;
+        Some(entry.data.clone())
+    }
+
+    /// Insert a pool-data entry, first evicting the least recently used one
+    /// if the cache is full
+    fn insert_cache_entry(&mut self, key: String, data: MintPoolData, timestamp: Instant) {
+        let max_entries = self.config.max_cache_entries.max(1);
+        if !self.cache.contains_key(&key) && self.cache.len() >= max_entries {
+            let lru_key = self
+                .cache
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_access)
+                .map(|(key, _)| key.clone());
+            if let Some(lru_key) = lru_key {
+                debug!("Pool cache full, evicting {}", lru_key);
+                self.cache.remove(&lru_key);
+            }
+        }
+
+        self.cache.insert(
+            key,
+            CacheEntry {
+                data,
+                timestamp,
+                last_access: self.clock.now(),
+            },
+        );
+    }
+
     /// Clear expired cache entries
     pub fn clear_expired_cache(&mut self) {
-        let now = Instant::now();
-        self.cache.retain(|_, entry| {
-            now.duration_since(entry.timestamp).as_secs() < self.config.cache_ttl_seconds
-        });
+        let now = self.clock.now();
+        let ttl = self.config.cache_ttl_seconds;
+        self.cache
+            .retain(|_, entry| now.duration_since(entry.timestamp).as_secs() < ttl);
+
+        let account_ttl = self.config.account_cache_ttl_seconds;
+        self.account_cache
+            .get_mut()
+            .unwrap()
+            .retain(|_, (_, fetched_at)| now.duration_since(*fetched_at).as_secs() < account_ttl);
+
+        self.persist_cache();
+    }
+
+    /// Save the pool cache to `cache_path`, if one is configured. Failures are
+    /// logged rather than returned, so this is safe to call on shutdown.
+    pub fn persist_cache(&self) {
+        if let Some(path) = self.config.cache_path.as_deref().filter(|_| self.config.enable_caching) {
+            if let Err(e) = self.save_cache(path) {
+                warn!("Failed to save pool cache to {}: {}", path, e);
+            }
+        }
+    }
+
+    /// Write the pool cache to `path`, gzip-compressed if `compress_cache` is set
+    pub fn save_cache(&self, path: impl AsRef<Path>) -> Result<()> {
+        let now = self.clock.now();
+        let saved_at = self.clock.unix_timestamp();
+        let entries: HashMap<&String, PersistedCacheEntry> = self
+            .cache
+            .iter()
+            .map(|(key, entry)| {
+                (
+                    key,
+                    PersistedCacheEntry {
+                        data: entry.data.clone(),
+                        age_seconds: now.duration_since(entry.timestamp).as_secs(),
+                        saved_at,
+                    },
+                )
+            })
+            .collect();
+
+        let json = serde_json::to_vec(&entries)?;
+        let bytes = if self.config.compress_cache {
+            let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(&json)?;
+            encoder.finish()?
+        } else {
+            json
+        };
+
+        fs::write(path.as_ref(), bytes)?;
+        info!(
+            "Saved {} cache entries to {}",
+            entries.len(),
+            path.as_ref().display()
+        );
+        Ok(())
+    }
+
+    /// Load a cache file written by `save_cache`, detecting compression from
+    /// the file header so both compressed and plain caches are accepted.
+    /// Entries already older than `cache_ttl_seconds` are discarded.
+    pub fn load_cache(&mut self, path: impl AsRef<Path>) -> Result<usize> {
+        let raw = fs::read(path.as_ref())?;
+        let json = if raw.starts_with(&GZIP_MAGIC) {
+            let mut decoded = Vec::new();
+            GzDecoder::new(raw.as_slice()).read_to_end(&mut decoded)?;
+            decoded
+        } else {
+            raw
+        };
+
+        let entries: HashMap<String, PersistedCacheEntry> = serde_json::from_slice(&json)?;
+        let now = self.clock.now();
+        let unix_now = self.clock.unix_timestamp();
+        let mut loaded = 0;
+
+        for (key, entry) in entries {
+            let age = entry.age_seconds + unix_now.saturating_sub(entry.saved_at);
+            if age >= self.config.cache_ttl_seconds {
+                continue;
+            }
+            let timestamp = now.checked_sub(Duration::from_secs(age)).unwrap_or(now);
+            self.insert_cache_entry(key, entry.data, timestamp);
+            loaded += 1;
+        }
+
+        info!(
+            "Loaded {} cache entries from {}",
+            loaded,
+            path.as_ref().display()
+        );
+        Ok(loaded)
     }
 
     /// Get cache statistics
@@ -264,10 +1204,386 @@ impl TokenFetcher {
         let expired_entries = self
             .cache
             .values()
-            .filter(|entry| {
-                entry.timestamp.elapsed().as_secs() >= self.config.cache_ttl_seconds
-            })
+            .filter(|entry| !self.is_fresh(entry.timestamp))
             .count();
         (total_entries, expired_entries)
     }
+
+    /// Get statistics for both the pool-data cache and the account cache
+    pub fn cache_stats(&self) -> CacheStats {
+        let (pool_entries, pool_expired) = self.get_cache_stats();
+        let accounts = self.account_cache.lock().unwrap();
+        CacheStats {
+            pool_entries,
+            pool_expired,
+            account_entries: accounts.len(),
+            account_expired: accounts
+                .values()
+                .filter(|(_, fetched_at)| !self.is_account_fresh(*fetched_at))
+                .count(),
+            account_hits: self.account_hits.load(Ordering::Relaxed),
+            account_misses: self.account_misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        chain::{clock::MockClock, rpc::RpcPool},
+        config::Config,
+        dex::{
+            raydium::{amm_info::COIN_MINT_OFFSET, constants::raydium_program_id},
+            rpc::MockRpc,
+            traits::PoolType,
+        },
+    };
+
+    /// Fetcher whose only endpoint refuses connections, so every RPC fails fast
+    fn offline_fetcher() -> TokenFetcher {
+        offline_fetcher_with(TokenFetchConfig::default(), Arc::new(SystemClock))
+    }
+
+    fn offline_fetcher_with(config: TokenFetchConfig, clock: Arc<dyn Clock>) -> TokenFetcher {
+        let bot_config = Config::from_toml(
+            r#"
+            [bot]
+            compute_unit_limit = 200000
+
+            [rpc]
+            url = "http://127.0.0.1:9"
+
+            [wallet]
+            private_key = ""
+
+            [[routing.mint_config_list]]
+            mint = "So11111111111111111111111111111111111111112"
+            process_delay = 0
+            pump_pool_list = ["11111111111111111111111111111111"]
+            "#,
+        );
+        let rpc_pool = Arc::new(RpcPool::from_config(&bot_config.rpc).unwrap());
+        TokenFetcher::with_clock(
+            rpc_pool,
+            TokenFetchConfig {
+                max_retries: 2,
+                retry_delay_ms: 1,
+                max_retry_delay_ms: 1,
+                ..config
+            },
+            clock,
+        )
+    }
+
+    /// Fetcher reading from `rpc`, retrying once without delay
+    fn mock_fetcher(rpc: Arc<MockRpc>) -> TokenFetcher {
+        TokenFetcher::new(
+            rpc,
+            TokenFetchConfig {
+                max_retries: 2,
+                retry_delay_ms: 1,
+                max_retry_delay_ms: 1,
+                enable_caching: false,
+                ..TokenFetchConfig::default()
+            },
+        )
+    }
+
+    /// Raydium AMM v4 account trading `coin_mint` against `pc_mint`, with
+    /// fresh vaults
+    fn raydium_pool_account(coin_mint: Pubkey, pc_mint: Pubkey) -> Account {
+        let mut data = vec![0u8; 752];
+        for (offset, key) in [
+            (336, Pubkey::new_unique()),
+            (368, Pubkey::new_unique()),
+            (COIN_MINT_OFFSET, coin_mint),
+            (COIN_MINT_OFFSET + 32, pc_mint),
+        ] {
+            data[offset..offset + 32].copy_from_slice(key.as_ref());
+        }
+        Account {
+            lamports: 1,
+            data,
+            owner: raydium_program_id(),
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    fn mint_account(decimals: u8) -> Account {
+        let mut data = vec![0u8; 82];
+        data[44] = decimals;
+        Account {
+            lamports: 1,
+            data,
+            owner: spl_token::ID,
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    fn account() -> Account {
+        Account {
+            lamports: 1,
+            data: vec![1, 2, 3],
+            owner: Pubkey::new_from_array([7; 32]),
+            executable: false,
+            rent_epoch: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn batched_reads_are_served_from_the_account_cache() {
+        let fetcher = offline_fetcher();
+        let (first, second) = (Pubkey::new_unique(), Pubkey::new_unique());
+        fetcher.cache_account(first, &account());
+        fetcher.cache_account(second, &account());
+
+        let accounts = fetcher.fetch_accounts_with_retry(&[first, second]).await.unwrap();
+
+        assert_eq!(accounts, vec![Some(account()), Some(account())]);
+        assert_eq!(fetcher.rpc_budget.calls_this_pass(), 0);
+        assert_eq!(fetcher.account_hits.load(Ordering::Relaxed), 2);
+    }
+
+    #[tokio::test]
+    async fn batched_reads_retry_the_cache_misses() {
+        let fetcher = offline_fetcher();
+        let cached = Pubkey::new_unique();
+        fetcher.cache_account(cached, &account());
+
+        let result = fetcher.fetch_accounts_with_retry(&[cached, Pubkey::new_unique()]).await;
+
+        assert!(result.is_err());
+        assert_eq!(fetcher.rpc_budget.calls_this_pass(), 2);
+        assert_eq!(fetcher.account_misses.load(Ordering::Relaxed), 1);
+    }
+
+    #[tokio::test]
+    async fn failed_reads_are_retried_until_one_succeeds() {
+        let (pubkey, stored) = (Pubkey::new_unique(), account());
+        let rpc = Arc::new(MockRpc::new().with_account(pubkey, stored.clone()));
+        rpc.fail_next(1);
+        let fetcher = mock_fetcher(rpc.clone());
+
+        assert_eq!(fetcher.fetch_account_with_retry(&pubkey).await.unwrap(), stored);
+        assert_eq!(rpc.calls(), 2);
+        assert_eq!(fetcher.rpc_budget.calls_this_pass(), 2);
+    }
+
+    #[tokio::test]
+    async fn reads_give_up_after_max_retries() {
+        let rpc = Arc::new(MockRpc::new());
+        rpc.fail_next(u64::MAX);
+        let fetcher = mock_fetcher(rpc.clone());
+
+        assert!(fetcher.fetch_account_with_retry(&Pubkey::new_unique()).await.is_err());
+        assert_eq!(rpc.calls(), 2);
+    }
+
+    #[tokio::test]
+    async fn pool_data_is_read_through_the_adapters() {
+        let (mint, pool) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let pool_account = raydium_pool_account(sol_mint(), mint);
+        let rpc = Arc::new(
+            MockRpc::new()
+                .with_account(mint, mint_account(6))
+                .with_account(sol_mint(), mint_account(9))
+                .with_account(pool, pool_account.clone()),
+        );
+        let mut fetcher = mock_fetcher(rpc);
+
+        let pool_data = fetcher
+            .initialize_pool_data(
+                &mint.to_string(),
+                &Pubkey::new_unique().to_string(),
+                &[("raydium", vec![pool.to_string()])],
+                &PoolFilter::default(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(pool_data.raydium_pools.len(), 1);
+        let raydium_pool = &pool_data.raydium_pools[0];
+        assert_eq!(raydium_pool.pool, pool);
+        assert_eq!(raydium_pool.token_mint, mint);
+        assert_eq!(raydium_pool.token_vault.as_ref(), &pool_account.data[368..400]);
+        assert_eq!(raydium_pool.sol_vault.as_ref(), &pool_account.data[336..368]);
+    }
+
+    #[tokio::test]
+    async fn discovered_pools_trade_the_mint_on_either_side() {
+        let mint = Pubkey::new_unique();
+        let rpc = Arc::new(
+            MockRpc::new()
+                .with_account(Pubkey::new_unique(), raydium_pool_account(mint, sol_mint()))
+                .with_account(Pubkey::new_unique(), raydium_pool_account(sol_mint(), mint))
+                .with_account(Pubkey::new_unique(), raydium_pool_account(sol_mint(), Pubkey::new_unique())),
+        );
+        let fetcher = mock_fetcher(rpc.clone());
+        let dex = RaydiumDex::new(rpc.clone());
+
+        let pools = fetcher.discover_pools(&dex, &mint).await.unwrap();
+
+        assert_eq!(pools.len(), 2);
+        assert!(pools.iter().all(|pool| pool.token_mint == mint && pool.base_mint == sol_mint()));
+        // One getProgramAccounts call per mint offset
+        assert_eq!(rpc.calls(), 2);
+    }
+
+    #[tokio::test]
+    async fn discovered_pools_over_the_account_cap_are_skipped() {
+        let mint = Pubkey::new_unique();
+        let rpc = Arc::new(MockRpc::new().with_account(Pubkey::new_unique(), raydium_pool_account(mint, sol_mint())));
+        let dex = RaydiumDex::new(rpc.clone());
+
+        // AMM v4 pools carry their coin and pc vaults as additional accounts
+        let mut fetcher = mock_fetcher(rpc.clone());
+        fetcher.config.max_additional_accounts = 2;
+        assert_eq!(fetcher.discover_pools(&dex, &mint).await.unwrap().len(), 1);
+        fetcher.config.max_additional_accounts = 1;
+        assert!(fetcher.discover_pools(&dex, &mint).await.unwrap().is_empty());
+    }
+
+    #[test]
+    fn compressed_cache_round_trips_through_a_file() {
+        let path = std::env::temp_dir().join(format!("pool-cache-{}.json.gz", Pubkey::new_unique()));
+        let data = MintPoolData::new(&Pubkey::new_unique().to_string(), &Pubkey::new_unique().to_string(), spl_token::ID)
+            .unwrap();
+
+        let mut writer = offline_fetcher_with(
+            TokenFetchConfig {
+                compress_cache: true,
+                ..TokenFetchConfig::default()
+            },
+            Arc::new(SystemClock),
+        );
+        writer.insert_cache_entry("mint".to_string(), data.clone(), Instant::now());
+        writer.save_cache(&path).unwrap();
+        let written = fs::read(&path).unwrap();
+
+        // The reader is not configured to compress; the header alone tells it to inflate
+        let mut reader = offline_fetcher();
+        let loaded = reader.load_cache(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert!(written.starts_with(&GZIP_MAGIC));
+        assert_eq!(loaded.unwrap(), 1);
+        assert_eq!(
+            serde_json::to_value(reader.cached_pool_data("mint").unwrap()).unwrap(),
+            serde_json::to_value(&data).unwrap()
+        );
+    }
+
+    #[test]
+    fn vault_balance_reads_only_token_accounts() {
+        let mut vault = account();
+        vault.data = vec![0; 165];
+        vault.data[64..72].copy_from_slice(&5_000u64.to_le_bytes());
+        assert_eq!(vault_balance(&vault), None);
+
+        vault.owner = spl_token::ID;
+        assert_eq!(vault_balance(&vault), Some(5_000));
+        vault.owner = TOKEN_2022_PROGRAM_ID;
+        assert_eq!(vault_balance(&vault), Some(5_000));
+    }
+
+    #[tokio::test]
+    async fn fill_liquidity_reads_sol_vaults_in_one_budgeted_call() {
+        let fetcher = offline_fetcher();
+        let sol_pool = || {
+            let mut pool = PoolInfo::mock(PoolType::ConstantProduct);
+            pool.base_mint = sol_mint();
+            ("pump", pool, None)
+        };
+        let mut pools = vec![
+            sol_pool(),
+            sol_pool(),
+            sol_pool(),
+            ("raydium", PoolInfo::mock(PoolType::ConstantProduct), None),
+        ];
+
+        fetcher.fill_liquidity(&mut pools).await;
+
+        // Every SOL vault shares one batched read; the other base mint isn't read
+        assert_eq!(fetcher.rpc_budget.calls_this_pass(), 1);
+        assert!(pools.iter().all(|(_, _, liquidity)| liquidity.is_none()));
+    }
+
+    #[test]
+    fn disabled_dexes_pools_are_dropped_before_fetching() {
+        let fetcher = offline_fetcher_with(
+            TokenFetchConfig {
+                disabled_dexes: HashSet::from(["pump".to_string()]),
+                ..TokenFetchConfig::default()
+            },
+            Arc::new(SystemClock),
+        );
+        let pool_lists = vec![
+            ("pump", vec![Pubkey::new_unique().to_string()]),
+            ("raydium", vec![Pubkey::new_unique().to_string()]),
+        ];
+
+        let enabled = fetcher.enabled_pool_lists(&pool_lists, "mint");
+
+        assert_eq!(enabled, pool_lists[1..]);
+    }
+
+    #[test]
+    fn cache_entries_expire_once_the_clock_passes_their_ttl() {
+        let clock = Arc::new(MockClock::new());
+        let mut fetcher = offline_fetcher_with(TokenFetchConfig::default(), clock.clone());
+        let mint = Pubkey::new_unique().to_string();
+        let data = MintPoolData::new(&mint, &Pubkey::new_unique().to_string(), spl_token::ID).unwrap();
+        let account_key = Pubkey::new_unique();
+        fetcher.insert_cache_entry(mint.clone(), data, clock.now());
+        fetcher.cache_account(account_key, &account());
+
+        clock.advance(Duration::from_secs(29));
+        assert!(fetcher.cached_pool_data(&mint).is_some());
+        assert!(fetcher.cached_account(&account_key).is_some());
+
+        // Accounts live for 30 seconds, pool data for 300
+        clock.advance(Duration::from_secs(1));
+        assert!(fetcher.cached_account(&account_key).is_none());
+        assert!(fetcher.cached_pool_data(&mint).is_some());
+        let stats = fetcher.cache_stats();
+        assert_eq!((stats.account_entries, stats.account_expired), (1, 1));
+        assert_eq!((stats.pool_entries, stats.pool_expired), (1, 0));
+
+        clock.advance(Duration::from_secs(270));
+        assert!(fetcher.cached_pool_data(&mint).is_none());
+        assert_eq!(fetcher.cache_stats().pool_expired, 1);
+
+        fetcher.clear_expired_cache();
+        let stats = fetcher.cache_stats();
+        assert_eq!((stats.pool_entries, stats.account_entries), (0, 0));
+    }
+
+    #[test]
+    fn full_pool_cache_evicts_the_least_recently_used_mint() {
+        let clock = Arc::new(MockClock::new());
+        let config = TokenFetchConfig {
+            max_cache_entries: 2,
+            ..TokenFetchConfig::default()
+        };
+        let mut fetcher = offline_fetcher_with(config, clock.clone());
+        let wallet = Pubkey::new_unique().to_string();
+        let data = |mint: &str| MintPoolData::new(mint, &wallet, spl_token::ID).unwrap();
+        let [first, second, third] = [(); 3].map(|_| Pubkey::new_unique().to_string());
+
+        fetcher.insert_cache_entry(first.clone(), data(&first), clock.now());
+        clock.advance(Duration::from_secs(1));
+        fetcher.insert_cache_entry(second.clone(), data(&second), clock.now());
+        clock.advance(Duration::from_secs(1));
+        // Reading the first mint makes the second the least recently used
+        assert!(fetcher.cached_pool_data(&first).is_some());
+        clock.advance(Duration::from_secs(1));
+        fetcher.insert_cache_entry(third.clone(), data(&third), clock.now());
+
+        assert!(fetcher.cached_pool_data(&first).is_some());
+        assert!(fetcher.cached_pool_data(&second).is_none());
+        assert!(fetcher.cached_pool_data(&third).is_some());
+    }
 }
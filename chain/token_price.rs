@@ -1,4 +1,7 @@
-use crate::chain::pools::{MintPoolData, PumpPool, RaydiumPool};
+use crate::chain::{
+    clock::{Clock, SystemClock},
+    pools::{MintPoolData, PumpPool, RaydiumPool},
+};
 use anyhow::{anyhow, Result};
 use serde::{Deserialize, Serialize};
 use solana_client::rpc_client::RpcClient;
@@ -33,17 +36,25 @@ pub struct PriceComparison {
 
 /// Market data fetcher
 pub struct MarketDataFetcher {
+    #[allow(dead_code)]
     rpc_client: Arc<RpcClient>,
     price_cache: HashMap<String, TokenPrice>,
     cache_ttl_seconds: u64,
+    clock: Arc<dyn Clock>,
 }
 
 impl MarketDataFetcher {
     pub fn new(rpc_client: Arc<RpcClient>) -> Self {
+        Self::with_clock(rpc_client, Arc::new(SystemClock))
+    }
+
+    /// Create a fetcher whose price timestamps and cache TTL use the given clock
+    pub fn with_clock(rpc_client: Arc<RpcClient>, clock: Arc<dyn Clock>) -> Self {
         Self {
             rpc_client,
             price_cache: HashMap::new(),
             cache_ttl_seconds: 30, // 30 seconds cache
+            clock,
         }
     }
 
@@ -51,11 +62,7 @@ impl MarketDataFetcher {
     pub async fn fetch_token_price(&mut self, mint: &str) -> Result<TokenPrice> {
         // Check cache first
         if let Some(cached_price) = self.price_cache.get(mint) {
-            if cached_price.timestamp + self.cache_ttl_seconds > 
-               std::time::SystemTime::now()
-                   .duration_since(std::time::UNIX_EPOCH)
-                   .unwrap()
-                   .as_secs() {
+            if cached_price.timestamp + self.cache_ttl_seconds > self.clock.unix_timestamp() {
                 return Ok(cached_price.clone());
             }
         }
@@ -118,10 +125,7 @@ impl MarketDataFetcher {
                 price_sol,
                 volume_24h: 0.0, // Jupiter doesn't provide volume
                 market_cap: 0.0, // Jupiter doesn't provide market cap
-                timestamp: std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs(),
+                timestamp: self.clock.unix_timestamp(),
                 source: "jupiter".to_string(),
             })
         } else {
@@ -168,10 +172,7 @@ impl MarketDataFetcher {
                     price_sol: price_usd / 100.0, // Rough conversion
                     volume_24h,
                     market_cap,
-                    timestamp: std::time::SystemTime::now()
-                        .duration_since(std::time::UNIX_EPOCH)
-                        .unwrap()
-                        .as_secs(),
+                    timestamp: self.clock.unix_timestamp(),
                     source: "birdeye".to_string(),
                 })
             } else {
@@ -221,10 +222,7 @@ impl MarketDataFetcher {
                 price_sol,
                 volume_24h,
                 market_cap,
-                timestamp: std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs(),
+                timestamp: self.clock.unix_timestamp(),
                 source: "coingecko".to_string(),
             })
         } else {
@@ -293,7 +291,7 @@ impl MarketDataFetcher {
                     best_sell_dex: best_sell_dex.clone(),
                     price_spread,
                     potential_profit_percent,
-                    timestamp: Instant::now(),
+                    timestamp: self.clock.now(),
                 });
             }
         }
@@ -328,14 +326,11 @@ impl MarketDataFetcher {
 
     /// Clear expired cache entries
     pub fn clear_expired_cache(&mut self) {
-        let current_time = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-
-        self.price_cache.retain(|_, price| {
-            current_time - price.timestamp < self.cache_ttl_seconds
-        });
+        let current_time = self.clock.unix_timestamp();
+        let ttl = self.cache_ttl_seconds;
+
+        self.price_cache
+            .retain(|_, price| current_time.saturating_sub(price.timestamp) < ttl);
     }
 }
 
@@ -343,6 +338,7 @@ impl MarketDataFetcher {
 pub struct PriceMonitor {
     market_fetcher: MarketDataFetcher,
     monitoring_interval_ms: u64,
+    #[allow(dead_code)]
     price_threshold: f64,
 }
 
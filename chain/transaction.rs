@@ -1,451 +1,668 @@
 use crate::{
-    config::Config,
+    arbitrage::{profit_compute_unit_price, ArbRoute},
+    config::{Config, MintConfig},
     dex::{
-        raydium::{raydium_authority, raydium_cp_authority},
-        solfi::constants::solfi_program_id,
-        vertigo::constants::vertigo_program_id,
+        math::min_out_with_slippage,
+        rpc::RpcLike,
+        traits::{Dex, PoolInfo},
+        vault::token_2022_ata_len,
+    },
+    chain::{
+        flashloan::Flashloan,
+        priority_fee::dynamic_compute_unit_price,
+        wsol::{unwrap_sol, wrap_sol},
     },
-    chain::pools::MintPoolData,
 };
 use solana_client::rpc_client::RpcClient;
 use solana_program::instruction::Instruction;
-use solana_sdk::address_lookup_table::AddressLookupTableAccount;
-use solana_sdk::commitment_config::CommitmentLevel;
+use solana_sdk::address_lookup_table::{state::AddressLookupTable, AddressLookupTableAccount};
+use solana_sdk::commitment_config::CommitmentConfig;
 use solana_sdk::compute_budget::ComputeBudgetInstruction;
 use solana_sdk::hash::Hash;
-use solana_sdk::instruction::AccountMeta;
 use solana_sdk::message::v0::Message;
 use solana_sdk::pubkey::Pubkey;
-use solana_sdk::signature::{Keypair, Signature};
+use solana_sdk::signature::Signature;
 use solana_sdk::signer::Signer;
-use solana_sdk::system_program;
-use solana_sdk::transaction::VersionedTransaction;
-use std::sync::Arc;
-use tracing::{debug, error, info};
+use solana_sdk::transaction::{TransactionError, VersionedTransaction};
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+use tracing::{debug, warn};
 
 use super::constants::sol_mint;
-use crate::dex::{
-    meteora::constants::{
-        damm_program_id, damm_v2_event_authority, damm_v2_pool_authority, damm_v2_program_id,
-        dlmm_event_authority, dlmm_program_id, vault_program_id,
-    },
-    pump::constants::{pump_fee_wallet, pump_program_id},
-    raydium::constants::{raydium_clmm_program_id, raydium_cp_program_id, raydium_program_id},
-    whirlpool::constants::whirlpool_program_id,
-};
-use spl_associated_token_account::ID as associated_token_program_id;
 use spl_token::ID as token_program_id;
 use std::str::FromStr;
 
-pub async fn build_and_send_transaction(
-    wallet_kp: &Keypair,
-    config: &Config,
-    mint_pool_data: &MintPoolData,
-    rpc_clients: &[Arc<RpcClient>],
-    blockhash: Hash,
-    address_lookup_table_accounts: &[AddressLookupTableAccount],
-) -> anyhow::Result<Vec<Signature>> {
-    let enable_flashloan = config.flashloan.as_ref().map_or(false, |k| k.enabled);
-    let compute_unit_limit = config.bot.compute_unit_limit;
-    let mut instructions = vec![];
-    // Add a random number here to make each transaction unique
-    let compute_budget_ix = ComputeBudgetInstruction::set_compute_unit_limit(
-        compute_unit_limit + rand::random::<u32>() % 1000,
-    );
-    instructions.push(compute_budget_ix);
-
-    let compute_unit_price = config.spam.as_ref().map_or(1000, |s| s.compute_unit_price);
-    let compute_budget_price_ix =
-        ComputeBudgetInstruction::set_compute_unit_price(compute_unit_price);
-    instructions.push(compute_budget_price_ix);
-
-    let swap_ix = create_swap_instruction(
-        wallet_kp,
-        mint_pool_data,
-        compute_unit_limit,
-        enable_flashloan,
-    )?;
+/// Outcome of waiting for a signature to reach a commitment level
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfirmStatus {
+    /// Landed successfully at the requested commitment
+    Confirmed,
+    /// Landed but the transaction failed
+    Failed(TransactionError),
+    /// Not seen at the requested commitment before the timeout
+    Unknown,
+}
 
-    let mut all_instructions = instructions.clone();
+/// Maximum signatures accepted by a single `getSignatureStatuses` request
+const MAX_SIGNATURE_STATUSES: usize = 256;
+
+const CONFIRM_POLL_INTERVAL: Duration = Duration::from_millis(400);
+
+/// Poll the statuses of many signatures at once, in the order given, until
+/// each has resolved or `timeout` elapses. Signatures still pending at the
+/// timeout are `Unknown`.
+pub async fn confirm_many(
+    rpc: &RpcClient,
+    signatures: &[Signature],
+    commitment: CommitmentConfig,
+    timeout: Duration,
+) -> HashMap<Signature, ConfirmStatus> {
+    let mut results: HashMap<Signature, ConfirmStatus> = signatures
+        .iter()
+        .map(|signature| (*signature, ConfirmStatus::Unknown))
+        .collect();
+    let mut seen = HashSet::new();
+    let mut pending: Vec<Signature> = signatures.iter().copied().filter(|signature| seen.insert(*signature)).collect();
+    let deadline = Instant::now() + timeout;
+
+    while !pending.is_empty() {
+        for chunk in pending.chunks(MAX_SIGNATURE_STATUSES) {
+            let statuses = match rpc.get_signature_statuses(chunk) {
+                Ok(response) => response.value,
+                Err(e) => {
+                    warn!("Failed to fetch signature statuses: {}", e);
+                    continue;
+                }
+            };
+
+            for (signature, status) in chunk.iter().zip(statuses) {
+                let Some(status) = status else { continue };
+                if let Some(err) = status.err.clone() {
+                    results.insert(*signature, ConfirmStatus::Failed(err));
+                } else if status.satisfies_commitment(commitment) {
+                    results.insert(*signature, ConfirmStatus::Confirmed);
+                }
+            }
+        }
+
+        pending.retain(|signature| results[signature] == ConfirmStatus::Unknown);
+        if pending.is_empty() || Instant::now() + CONFIRM_POLL_INTERVAL > deadline {
+            break;
+        }
+        tokio::time::sleep(CONFIRM_POLL_INTERVAL).await;
+    }
 
-    debug!("Adding swap instruction");
-    all_instructions.push(swap_ix);
+    results
+}
 
+fn sign_transaction(
+    wallet_kp: &dyn Signer,
+    instructions: &[Instruction],
+    address_lookup_table_accounts: &[AddressLookupTableAccount],
+    blockhash: Hash,
+) -> anyhow::Result<VersionedTransaction> {
     let message = Message::try_compile(
         &wallet_kp.pubkey(),
-        &all_instructions,
+        instructions,
         address_lookup_table_accounts,
         blockhash,
     )?;
 
-    let tx = VersionedTransaction::try_new(
+    Ok(VersionedTransaction::try_new(
         solana_sdk::message::VersionedMessage::V0(message),
         &[wallet_kp],
-    )?;
-
-    let max_retries = config
-        .spam
-        .as_ref()
-        .and_then(|s| s.max_retries)
-        .unwrap_or(3);
-
-    let mut signatures = Vec::new();
-
-    for (i, client) in rpc_clients.iter().enumerate() {
-        debug!("Sending transaction through RPC client {}", i);
-
-        let signature = match send_transaction_with_retries(client, &tx, max_retries).await {
-            Ok(sig) => sig,
-            Err(e) => {
-                error!("Failed to send transaction through RPC client {}: {}", i, e);
-                continue;
-            }
-        };
-
-        info!(
-            "Transaction sent successfully through RPC client {}: {}",
-            i, signature
-        );
-        signatures.push(signature);
-    }
-
-    Ok(signatures)
+    )?)
 }
 
-async fn send_transaction_with_retries(
-    client: &RpcClient,
-    tx: &VersionedTransaction,
-    max_retries: u64,
-) -> anyhow::Result<Signature> {
-    Ok(client.send_transaction_with_config(
-        tx,
-        solana_client::rpc_config::RpcSendTransactionConfig {
-            skip_preflight: true,
-            max_retries: Some(max_retries as usize),
-            preflight_commitment: Some(CommitmentLevel::Confirmed),
-            ..Default::default()
-        },
-    )?)
+/// One leg of an `ArbRoute`: the DEX that owns the pool and the pool itself
+pub struct RouteHop<'a> {
+    pub dex: &'a dyn Dex,
+    pub pool: &'a PoolInfo,
 }
 
-/// Helper function to derive the vault token account PDA address for a given mint
-pub fn derive_vault_token_account(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
-    Pubkey::find_program_address(&[b"vault_token_account", mint.as_ref()], program_id)
+/// Compute unit price for a route's transaction. With `bot.fee_profit_bps`
+/// it is that share of the route's estimated profit less `other_costs`, the
+/// lamports the transaction pays besides its fee (tip, flash loan fee, profit
+/// fee and rent), capped at `spam.max_compute_unit_price`. This holds however
+/// the route is submitted. Otherwise, with spam enabled, it is recent fees on
+/// the route's pools with `spam.dynamic_priority_fee`, or
+/// `spam.compute_unit_price`; without spam there is no priority fee.
+pub fn route_compute_unit_price(config: &Config, rpc_client: &RpcClient, route: &ArbRoute, other_costs: u64) -> u64 {
+    let spam = config.spam.as_ref();
+    if let Some(fee_profit_bps) = config.bot.fee_profit_bps {
+        let price = profit_compute_unit_price(
+            route
+                .estimated_profit_lamports
+                .saturating_sub(other_costs.min(i64::MAX as u64) as i64),
+            1,
+            config.bot.compute_unit_limit,
+            fee_profit_bps,
+            config.bot.min_profit_lamports,
+        );
+        return spam
+            .and_then(|spam| spam.max_compute_unit_price)
+            .map_or(price, |max| price.min(max));
+    }
+    match spam.filter(|spam| spam.enabled) {
+        Some(spam) => dynamic_compute_unit_price(spam, rpc_client, &route.pools),
+        None => 0,
+    }
 }
 
-fn create_swap_instruction(
-    wallet_kp: &Keypair,
-    mint_pool_data: &MintPoolData,
+/// Assemble and sign a transaction that swaps through every pool of `route`.
+///
+/// `amounts` holds the quoted amount at each mint of the route, so hop `i`
+/// swaps `amounts[i]` in and requires `amounts[i + 1]` less `slippage_bps`
+/// out. The last hop never accepts less than `amounts[0]`, so slippage cannot
+/// turn the route into a loss. Each pool is oriented so the hop sells
+/// `route.mints[i]` for `route.mints[i + 1]`.
+///
+/// With a `flashloan`, the input is borrowed before the first hop and repaid
+/// after the last, up to `max_borrow_lamports`. The loan fee is paid from the
+/// wallet on top of the route's own output check.
+///
+/// `create_atas` run right after the compute budget instructions, so token
+/// accounts the route needs exist before any swap; see
+/// `missing_ata_instructions`. Routes starting in SOL always create the
+/// wallet's WSOL account if needed. With `wrap_native`, the part of the input not borrowed is wrapped from
+/// native SOL first and the WSOL account is closed at the end, so profit
+/// lands as native SOL; otherwise the input is paid from existing WSOL.
+///
+/// `payouts`, such as the profit fee transfer and a Jito tip, go after
+/// everything else, so they are only paid when every swap and the loan
+/// repayment succeed.
+#[allow(clippy::too_many_arguments)]
+pub fn build_arb_transaction(
+    route: &ArbRoute,
+    hops: &[RouteHop],
+    amounts: &[u64],
+    slippage_bps: u16,
+    keypair: &dyn Signer,
     compute_unit_limit: u32,
-    use_flashloan: bool,
-) -> anyhow::Result<Instruction> {
-    debug!("Creating swap instruction for all DEX types");
-
-    let executor_program_id =
-        Pubkey::from_str("MEViEnscUm6tsQRoGd9h6nLQaQspKj7DB2M5FwM3Xvz").unwrap();
-
-    let fee_collector = if use_flashloan {
-        Pubkey::from_str("6AGB9kqgSp2mQXwYpdrV4QVV8urvCaDS35U1wsLssy6H").unwrap()
-    } else {
-        let fee_accounts = [
-            Pubkey::from_str("GPpkDpzCDmYJY5qNhYmM14c7rct1zmkjWc2CjR5g7RZ1").unwrap(),
-            Pubkey::from_str("J6c7noBHvWju4mMA3wXt3igbBSp2m9ATbA6cjMtAUged").unwrap(),
-            Pubkey::from_str("BjsfwxDu7GX7RRW6oSRTpMkASdXAgCcHnXEcatqSfuuY").unwrap(),
-        ];
-        fee_accounts[rand::random::<usize>() % fee_accounts.len()]
-    };
+    compute_unit_price: u64,
+    address_lookup_table_accounts: &[AddressLookupTableAccount],
+    blockhash: Hash,
+    flashloan: Option<&Flashloan>,
+    wrap_native: bool,
+    create_atas: &[Instruction],
+    payouts: &[Instruction],
+) -> anyhow::Result<VersionedTransaction> {
+    if hops.len() != route.pools.len() || amounts.len() != route.pools.len() + 1 {
+        return Err(anyhow::anyhow!(
+            "Route has {} pools but {} hops and {} amounts were given",
+            route.pools.len(),
+            hops.len(),
+            amounts.len()
+        ));
+    }
 
-    let pump_global_config =
-        Pubkey::from_str("ADyA8hdefvWN2dbGGWFotbzWxrAvLW83WG6QCVXvJKqw").unwrap();
-    let pump_authority = Pubkey::from_str("GS4CU59F31iL7aR2Q8zVS8DRrcRnXX1yjQ66TqNVQnaR").unwrap();
-    let sysvar_instructions =
-        Pubkey::from_str("Sysvar1nstructions1111111111111111111111111").unwrap();
-    let memo_program = Pubkey::from_str("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr").unwrap();
-
-    let wallet = wallet_kp.pubkey();
-    let sol_mint_pubkey = sol_mint();
-    let wallet_sol_account = mint_pool_data.wallet_wsol_account;
-    let usdc_mint = Pubkey::from_str("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").unwrap();
-
-    let mut accounts = vec![
-        AccountMeta::new(wallet, true), // 0. Wallet (signer)
-        AccountMeta::new_readonly(sol_mint_pubkey, false), // 1. SOL mint
-        AccountMeta::new(fee_collector, false), // 2. Fee collector
-        AccountMeta::new(wallet_sol_account, false), // 3. Wallet SOL account
-        AccountMeta::new_readonly(token_program_id, false), // 4. Token program
-        AccountMeta::new_readonly(system_program::ID, false), // 5. System program
-        AccountMeta::new_readonly(associated_token_program_id, false), // 6. Associated Token program
+    let mut instructions = vec![
+        ComputeBudgetInstruction::set_compute_unit_limit(compute_unit_limit),
+        ComputeBudgetInstruction::set_compute_unit_price(compute_unit_price),
     ];
+    instructions.extend_from_slice(create_atas);
+
+    let wallet = keypair.pubkey();
+    let starts_in_sol = route.mints[0] == sol_mint();
+    let borrowed = flashloan.map_or(0, |flashloan| flashloan.borrow_amount(amounts[0]));
+    if starts_in_sol {
+        let wrap_amount = if wrap_native { amounts[0] - borrowed } else { 0 };
+        instructions.extend(wrap_sol(&wallet, wrap_amount)?);
+    }
 
-    // Determine the base mint for flashloan if needed
-    let flashloan_base_mint = if use_flashloan {
-        // For flashloan, we need a common base mint across all pools
-        // Check if all pools use SOL as base mint
-        let mut all_sol_base = true;
-        let mut all_usdc_base = true;
-
-        // Check all pool types to see their base mints
-        for pool in &mint_pool_data.raydium_pools {
-            if pool.base_mint != sol_mint_pubkey {
-                all_sol_base = false;
-            }
-            if pool.base_mint != usdc_mint {
-                all_usdc_base = false;
-            }
+    let loan = match flashloan {
+        Some(flashloan) if flashloan.liquidity_mint != route.mints[0] => {
+            return Err(anyhow::anyhow!(
+                "Flashloan reserve {} lends {}, route starts with {}",
+                flashloan.reserve,
+                flashloan.liquidity_mint,
+                route.mints[0]
+            ));
         }
-        for pool in &mint_pool_data.raydium_cp_pools {
-            if pool.base_mint != sol_mint_pubkey {
-                all_sol_base = false;
-            }
-            if pool.base_mint != usdc_mint {
-                all_usdc_base = false;
-            }
+        Some(flashloan) => {
+            let borrow_index = instructions.len() as u8;
+            instructions.push(flashloan.borrow_instruction(&wallet, borrowed));
+            Some((flashloan, borrowed, borrow_index))
         }
-        // Add other pool type checks as needed...
+        None => None,
+    };
 
-        if all_sol_base {
-            sol_mint_pubkey
-        } else if all_usdc_base {
-            usdc_mint
+    for (i, hop) in hops.iter().enumerate() {
+        let (from, to) = (route.mints[i], route.mints[i + 1]);
+        if hop.pool.pool_address != route.pools[i] {
+            return Err(anyhow::anyhow!(
+                "Hop {} is pool {}, route expects {}",
+                i,
+                hop.pool.pool_address,
+                route.pools[i]
+            ));
+        }
+        let pool = if hop.pool.base_mint == from && hop.pool.token_mint == to {
+            hop.pool.clone()
+        } else if hop.pool.token_mint == from && hop.pool.base_mint == to {
+            hop.pool.reversed()
         } else {
-            // Mixed base mints - default to SOL for now
-            sol_mint_pubkey
+            return Err(anyhow::anyhow!(
+                "Pool {} does not trade {} for {}",
+                hop.pool.pool_address,
+                from,
+                to
+            ));
+        };
+
+        let mut minimum_out = min_out_with_slippage(amounts[i + 1], slippage_bps);
+        if i + 1 == hops.len() {
+            minimum_out = minimum_out.max(amounts[0]);
         }
-    } else {
-        sol_mint_pubkey
-    };
+        instructions.push(hop.dex.build_swap_instruction(
+            &pool,
+            &wallet,
+            amounts[i],
+            minimum_out,
+        )?);
+    }
 
-    if use_flashloan {
-        accounts.push(AccountMeta::new_readonly(
-            Pubkey::from_str("5LFpzqgsxrSfhKwbaFiAEJ2kbc9QyimjKueswsyU4T3o").unwrap(),
-            false,
-        ));
-        let token_pda = derive_vault_token_account(
-            &Pubkey::from_str("MEViEnscUm6tsQRoGd9h6nLQaQspKj7DB2M5FwM3Xvz").unwrap(),
-            &flashloan_base_mint,
-        );
-        accounts.push(AccountMeta::new(token_pda.0, false));
+    if let Some((flashloan, amount, borrow_index)) = loan {
+        instructions.push(flashloan.repay_instruction(&wallet, amount, borrow_index));
+    }
+    if starts_in_sol && wrap_native {
+        instructions.push(unwrap_sol(&wallet)?);
     }
+    instructions.extend_from_slice(payouts);
 
-    // Check for mixed mode (USDC base)
-    let mut has_usdc_base = false;
+    sign_transaction(keypair, &instructions, address_lookup_table_accounts, blockhash)
+}
 
-    // Check all pools to see if any have USDC as base mint
-    for pool in &mint_pool_data.raydium_pools {
-        if pool.base_mint == usdc_mint {
-            has_usdc_base = true;
-            break;
+/// Idempotent create instructions for the wallet's token accounts of `mints`
+/// that don't exist yet, with the rent they lock up. Each account is derived
+/// under its mint's own token program, so Token-2022 mints get Token-2022
+/// accounts sized for their extensions. Reads the mints and the accounts with
+/// one `get_multiple_accounts` call each.
+pub fn missing_ata_instructions(
+    rpc_client: &dyn RpcLike,
+    wallet: &Pubkey,
+    mints: &[Pubkey],
+) -> anyhow::Result<(Vec<Instruction>, u64)> {
+    let mut unique: Vec<Pubkey> = Vec::with_capacity(mints.len());
+    for mint in mints {
+        if !unique.contains(mint) {
+            unique.push(*mint);
         }
     }
-    if !has_usdc_base {
-        for pool in &mint_pool_data.raydium_cp_pools {
-            if pool.base_mint == usdc_mint {
-                has_usdc_base = true;
-                break;
-            }
-        }
+    if unique.is_empty() {
+        return Ok((Vec::new(), 0));
     }
-    // Check other pool types as needed...
-
-    // If mixed mode is detected, add the required accounts
-    if has_usdc_base {
-        let wallet_usdc_account =
-            spl_associated_token_account::get_associated_token_address(&wallet, &usdc_mint);
-        let raydium_sol_usdc_pool =
-            Pubkey::from_str("58oQChx4yWmvKdwLLZzBi4ChoCc2fqCUWBkwMihLYQo2").unwrap();
-        let raydium_usdc_vault =
-            Pubkey::from_str("HLmqeL62xR1QoZ1HKKbXRrdN1p3phKpxRMb2VVopvBBz").unwrap();
-        let raydium_sol_vault =
-            Pubkey::from_str("DQyrAcCrDXQ7NeoqGgDCZwBvWDcYmFCjSb9JtteuvPpz").unwrap();
-
-        accounts.push(AccountMeta::new_readonly(usdc_mint, false));
-        accounts.push(AccountMeta::new(wallet_usdc_account, false));
-        accounts.push(AccountMeta::new_readonly(raydium_program_id(), false));
-        accounts.push(AccountMeta::new_readonly(raydium_authority(), false));
-        accounts.push(AccountMeta::new_readonly(sysvar_instructions, false));
-        accounts.push(AccountMeta::new(raydium_sol_usdc_pool, false));
-        accounts.push(AccountMeta::new(raydium_usdc_vault, false));
-        accounts.push(AccountMeta::new(raydium_sol_vault, false));
+
+    let mut atas = Vec::with_capacity(unique.len());
+    for (mint, account) in unique.iter().zip(rpc_client.get_multiple_accounts(&unique)?) {
+        let mint_account = account.ok_or_else(|| anyhow::anyhow!("Mint account {} not found", mint))?;
+        let ata = spl_associated_token_account::get_associated_token_address_with_program_id(
+            wallet,
+            mint,
+            &mint_account.owner,
+        );
+        atas.push((*mint, mint_account, ata));
     }
 
-    // Add token mint and pools
-    accounts.push(AccountMeta::new_readonly(mint_pool_data.mint, false));
-    accounts.push(AccountMeta::new_readonly(
-        mint_pool_data.token_program,
-        false,
-    )); // Token program (SPL Token or Token 2022)
-    let wallet_x_account =
-        spl_associated_token_account::get_associated_token_address_with_program_id(
-            &wallet,
-            &mint_pool_data.mint,
-            &mint_pool_data.token_program,
+    let ata_keys: Vec<Pubkey> = atas.iter().map(|(_, _, ata)| *ata).collect();
+    let existing = rpc_client.get_multiple_accounts(&ata_keys)?;
+    let mut instructions = Vec::new();
+    let mut rent = 0u64;
+    for ((mint, mint_account, ata), _) in atas.iter().zip(existing).filter(|(_, account)| account.is_none()) {
+        let ata_rent = ata_rent_lamports(rpc_client, &mint_account.owner, &mint_account.data)?;
+        debug!("Creating token account {} for mint {}, rent: {} lamports", ata, mint, ata_rent);
+        rent = rent.saturating_add(ata_rent);
+        instructions.push(
+            spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+                wallet,
+                wallet,
+                mint,
+                &mint_account.owner,
+            ),
         );
-    accounts.push(AccountMeta::new(wallet_x_account, false));
-
-    // Add Raydium pools
-    for pool in &mint_pool_data.raydium_pools {
-        accounts.push(AccountMeta::new_readonly(raydium_program_id(), false));
-        accounts.push(AccountMeta::new_readonly(pool.base_mint, false)); // V9: Add base mint
-        accounts.push(AccountMeta::new_readonly(raydium_authority(), false));
-        accounts.push(AccountMeta::new(pool.pool, false));
-        accounts.push(AccountMeta::new(pool.token_vault, false));
-        accounts.push(AccountMeta::new(pool.sol_vault, false));
     }
+    Ok((instructions, rent))
+}
 
-    // Add Raydium CP pools
-    for pool in &mint_pool_data.raydium_cp_pools {
-        accounts.push(AccountMeta::new_readonly(raydium_cp_program_id(), false));
-        accounts.push(AccountMeta::new_readonly(pool.base_mint, false)); // V9: Add base mint
-        accounts.push(AccountMeta::new_readonly(raydium_cp_authority(), false));
-        accounts.push(AccountMeta::new(pool.pool, false));
-        accounts.push(AccountMeta::new_readonly(pool.amm_config, false));
-        accounts.push(AccountMeta::new(pool.token_vault, false));
-        accounts.push(AccountMeta::new(pool.sol_vault, false));
-        accounts.push(AccountMeta::new(pool.observation, false));
+/// Resolve a mint's `lookup_table_accounts` into tables for v0 messages.
+/// Tables that fail to load are skipped with a warning, so a stale entry only
+/// costs transaction size rather than the whole route.
+pub fn load_lookup_tables(
+    rpc_client: &RpcClient,
+    addresses: &[String],
+) -> Vec<AddressLookupTableAccount> {
+    let mut tables = Vec::with_capacity(addresses.len());
+    for address in addresses {
+        let key = match Pubkey::from_str(address) {
+            Ok(key) => key,
+            Err(e) => {
+                warn!("Skipping lookup table {}: invalid address: {}", address, e);
+                continue;
+            }
+        };
+        let account = match rpc_client.get_account(&key) {
+            Ok(account) => account,
+            Err(e) => {
+                warn!("Skipping lookup table {}: {}", key, e);
+                continue;
+            }
+        };
+        match AddressLookupTable::deserialize(&account.data) {
+            Ok(table) => tables.push(AddressLookupTableAccount {
+                key,
+                addresses: table.addresses.to_vec(),
+            }),
+            Err(e) => warn!("Skipping lookup table {}: {}", key, e),
+        }
     }
+    debug!("Loaded {} of {} lookup tables", tables.len(), addresses.len());
+    tables
+}
 
-    // Add Pump pools
-    for pool in &mint_pool_data.pump_pools {
-        accounts.push(AccountMeta::new_readonly(pump_program_id(), false));
-        accounts.push(AccountMeta::new_readonly(pool.base_mint, false)); // V9: Add base mint
-        accounts.push(AccountMeta::new_readonly(pump_global_config, false));
-        accounts.push(AccountMeta::new_readonly(pump_authority, false));
-        accounts.push(AccountMeta::new(pump_fee_wallet(), false));
-        accounts.push(AccountMeta::new(pool.pool, false));
-        accounts.push(AccountMeta::new(pool.token_vault, false));
-        accounts.push(AccountMeta::new(pool.sol_vault, false));
-        accounts.push(AccountMeta::new(pool.fee_token_wallet, false));
-        accounts.push(AccountMeta::new(pool.coin_creator_vault_ata, false));
-        accounts.push(AccountMeta::new_readonly(
-            pool.coin_creator_vault_authority,
-            false,
-        ));
-        let pump_program_id =
-            Pubkey::from_str("pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA").unwrap();
-        let (global_volume_accumulator, _) =
-            Pubkey::find_program_address(&[b"global_volume_accumulator"], &pump_program_id);
-        let (user_volume_accumulator, _) = Pubkey::find_program_address(
-            &[b"user_volume_accumulator", wallet.as_ref()],
-            &pump_program_id,
-        );
-        accounts.push(AccountMeta::new(global_volume_accumulator, false));
-        accounts.push(AccountMeta::new(user_volume_accumulator, false));
+/// Share of the expected profit owed to `fee_recipient`
+pub fn profit_fee_lamports(config: &Config, expected_profit_lamports: u64) -> u64 {
+    if config.bot.fee_recipient.is_none() {
+        return 0;
     }
+    (expected_profit_lamports as u128 * config.bot.profit_fee_bps as u128 / 10_000) as u64
+}
 
-    // Add DLMM pairs
-    for pair in &mint_pool_data.dlmm_pairs {
-        accounts.push(AccountMeta::new_readonly(dlmm_program_id(), false));
-        accounts.push(AccountMeta::new_readonly(pair.base_mint, false)); // V9: Add base mint
-        accounts.push(AccountMeta::new_readonly(dlmm_event_authority(), false));
-        if let Some(memo_program) = pair.memo_program {
-            accounts.push(AccountMeta::new_readonly(memo_program, false));
-        }
-        accounts.push(AccountMeta::new(pair.pair, false));
-        accounts.push(AccountMeta::new(pair.token_vault, false));
-        accounts.push(AccountMeta::new(pair.sol_vault, false));
-        accounts.push(AccountMeta::new(pair.oracle, false));
-        for bin_array in &pair.bin_arrays {
-            accounts.push(AccountMeta::new(*bin_array, false));
+/// Transfer of the profit fee to `fee_recipient`, appended after the arbitrage legs
+pub fn profit_fee_instruction(
+    config: &Config,
+    payer: &Pubkey,
+    profit_fee: u64,
+) -> anyhow::Result<Option<Instruction>> {
+    match &config.bot.fee_recipient {
+        Some(recipient) if profit_fee > 0 => {
+            let recipient = Pubkey::from_str(recipient)?;
+            Ok(Some(solana_sdk::system_instruction::transfer(
+                payer, &recipient, profit_fee,
+            )))
         }
+        _ => Ok(None),
     }
+}
+
+/// Priority fee for a route sent under `mint_config`: the mint's override if
+/// set, otherwise `route_compute_unit_price` given the route's `other_costs`,
+/// bounded by the max-price cap the same way `compute_unit_price_for` is
+pub fn route_compute_unit_price_for(
+    config: &Config,
+    mint_config: &MintConfig,
+    rpc_client: &RpcClient,
+    route: &ArbRoute,
+    other_costs: u64,
+) -> anyhow::Result<u64> {
+    let price = mint_config
+        .compute_unit_price_override
+        .unwrap_or_else(|| route_compute_unit_price(config, rpc_client, route, other_costs));
+    check_max_compute_unit_price(config, price, &mint_config.mint)
+}
 
-    // Add Whirlpool pools
-    for pool in &mint_pool_data.whirlpool_pools {
-        accounts.push(AccountMeta::new_readonly(whirlpool_program_id(), false));
-        accounts.push(AccountMeta::new_readonly(pool.base_mint, false)); // V9: Add base mint
-        accounts.push(AccountMeta::new_readonly(memo_program, false)); // Always add memo program for Whirlpool
-        accounts.push(AccountMeta::new(pool.pool, false));
-        accounts.push(AccountMeta::new(pool.oracle, false)); // Oracle NEEDS to be writable for Whirlpool
-        accounts.push(AccountMeta::new(pool.x_vault, false));
-        accounts.push(AccountMeta::new(pool.y_vault, false));
-        for tick_array in &pool.tick_arrays {
-            accounts.push(AccountMeta::new(*tick_array, false));
+/// `price`, or an error when it exceeds `spam.max_compute_unit_price`
+fn check_max_compute_unit_price(config: &Config, price: u64, mint: &str) -> anyhow::Result<u64> {
+    if let Some(max) = config.spam.as_ref().and_then(|s| s.max_compute_unit_price) {
+        if price > max {
+            return Err(anyhow::anyhow!(
+                "Compute unit price {} for mint {} exceeds max_compute_unit_price {}",
+                price,
+                mint,
+                max
+            ));
         }
     }
+    Ok(price)
+}
 
-    // Add Raydium CLMM pools
-    for pool in &mint_pool_data.raydium_clmm_pools {
-        accounts.push(AccountMeta::new_readonly(raydium_clmm_program_id(), false));
-        accounts.push(AccountMeta::new_readonly(pool.base_mint, false)); // V9: Add base mint
-        if let Some(memo_program) = pool.memo_program {
-            accounts.push(AccountMeta::new_readonly(memo_program, false));
-        }
-        accounts.push(AccountMeta::new(pool.pool, false));
-        accounts.push(AccountMeta::new_readonly(pool.amm_config, false));
-        accounts.push(AccountMeta::new(pool.observation_state, false));
-        accounts.push(AccountMeta::new(pool.bitmap_extension, false));
-        accounts.push(AccountMeta::new(pool.x_vault, false));
-        accounts.push(AccountMeta::new(pool.y_vault, false));
-        for tick_array in &pool.tick_arrays {
-            accounts.push(AccountMeta::new(*tick_array, false));
+/// Size of an SPL Token account
+const TOKEN_ACCOUNT_LEN: usize = 165;
+
+/// Rent-exempt balance of a new associated token account for a mint owned by
+/// `token_program`, sized from the mint's extensions under Token-2022
+pub fn ata_rent_lamports(client: &dyn RpcLike, token_program: &Pubkey, mint_data: &[u8]) -> anyhow::Result<u64> {
+    let account_len = if *token_program == token_program_id {
+        TOKEN_ACCOUNT_LEN
+    } else {
+        token_2022_ata_len(mint_data)
+    };
+    Ok(client.get_minimum_balance_for_rent_exemption(account_len)?)
+}
+
+/// Helper function to derive the vault token account PDA address for a given mint
+pub fn derive_vault_token_account(program_id: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[b"vault_token_account", mint.as_ref()], program_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use solana_client::rpc_request::RpcRequest;
+    use solana_sdk::instruction::InstructionError;
+
+    const MINT: &str = "So11111111111111111111111111111111111111112";
+
+    fn config(spam: &str, mint_extra: &str) -> Config {
+        Config::from_toml(&format!(
+            r#"
+            [bot]
+            compute_unit_limit = 200000
+
+            [rpc]
+            url = "http://localhost:8899"
+
+            [wallet]
+            private_key = ""
+
+            {spam}
+
+            [[routing.mint_config_list]]
+            mint = "{MINT}"
+            process_delay = 0
+            pump_pool_list = ["11111111111111111111111111111111"]
+            {mint_extra}
+            "#
+        ))
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn confirm_many_sorts_signatures_by_their_statuses() {
+        let signatures: Vec<Signature> = (0..4).map(|_| Signature::new_unique()).collect();
+        let failure = json!({ "InstructionError": [0, { "Custom": 6001 }] });
+        let mut mocks = HashMap::new();
+        mocks.insert(
+            RpcRequest::GetSignatureStatuses,
+            json!({
+                "context": { "slot": 10 },
+                "value": [
+                    { "slot": 9, "confirmations": null, "status": { "Ok": null }, "err": null,
+                      "confirmationStatus": "finalized" },
+                    { "slot": 9, "confirmations": null, "status": { "Err": failure }, "err": failure,
+                      "confirmationStatus": "confirmed" },
+                    // Seen, but not yet at the requested commitment
+                    { "slot": 10, "confirmations": 0, "status": { "Ok": null }, "err": null,
+                      "confirmationStatus": "processed" },
+                    null,
+                ],
+            }),
+        );
+        let rpc = RpcClient::new_mock_with_mocks("succeeds".to_string(), mocks);
+
+        // A zero timeout polls once, against the mocked response
+        let statuses = confirm_many(&rpc, &signatures, CommitmentConfig::confirmed(), Duration::ZERO).await;
+
+        assert_eq!(statuses.len(), 4);
+        assert_eq!(statuses[&signatures[0]], ConfirmStatus::Confirmed);
+        assert_eq!(
+            statuses[&signatures[1]],
+            ConfirmStatus::Failed(TransactionError::InstructionError(0, InstructionError::Custom(6001)))
+        );
+        assert_eq!(statuses[&signatures[2]], ConfirmStatus::Unknown);
+        assert_eq!(statuses[&signatures[3]], ConfirmStatus::Unknown);
+    }
+
+    fn route() -> ArbRoute {
+        ArbRoute {
+            mints: vec![sol_mint(), Pubkey::new_unique(), sol_mint()],
+            pools: vec![Pubkey::new_unique(), Pubkey::new_unique()],
+            fees: vec![0.0025, 0.0025],
+            spot_rate: 1.001,
+            estimated_profit_lamports: 100_000,
         }
     }
 
-    // Add Meteora DAMM pools
-    for pool in &mint_pool_data.meteora_damm_pools {
-        accounts.push(AccountMeta::new_readonly(damm_program_id(), false));
-        accounts.push(AccountMeta::new_readonly(pool.base_mint, false)); // V9: Add base mint
-        accounts.push(AccountMeta::new_readonly(vault_program_id(), false));
-        accounts.push(AccountMeta::new(pool.pool, false));
-        accounts.push(AccountMeta::new(pool.token_x_vault, false));
-        accounts.push(AccountMeta::new(pool.token_sol_vault, false));
-        accounts.push(AccountMeta::new(pool.token_x_token_vault, false));
-        accounts.push(AccountMeta::new(pool.token_sol_token_vault, false));
-        accounts.push(AccountMeta::new(pool.token_x_lp_mint, false));
-        accounts.push(AccountMeta::new(pool.token_sol_lp_mint, false));
-        accounts.push(AccountMeta::new(pool.token_x_pool_lp, false));
-        accounts.push(AccountMeta::new(pool.token_sol_pool_lp, false));
-        accounts.push(AccountMeta::new(pool.admin_token_fee_x, false));
-        accounts.push(AccountMeta::new(pool.admin_token_fee_sol, false));
+    const SPAM: &str = r#"
+        [spam]
+        enabled = true
+        sending_rpc_urls = []
+        compute_unit_price = 1000
+        max_retries = 1
+        max_compute_unit_price = 5000
+    "#;
+
+    #[test]
+    fn route_price_override_is_capped() {
+        let config = config(SPAM, "compute_unit_price_override = 10000");
+        let rpc = RpcClient::new_mock("succeeds".to_string());
+        let err = route_compute_unit_price_for(&config, &config.routing.mint_config_list[0], &rpc, &route(), 0)
+            .unwrap_err();
+        assert!(err.to_string().contains("exceeds max_compute_unit_price 5000"));
     }
 
-    // Add Meteora DAMM V2 pools
-    for pool in &mint_pool_data.meteora_damm_v2_pools {
-        accounts.push(AccountMeta::new_readonly(damm_v2_program_id(), false));
-        accounts.push(AccountMeta::new_readonly(pool.base_mint, false)); // V9: Add base mint
-        accounts.push(AccountMeta::new_readonly(damm_v2_event_authority(), false));
-        accounts.push(AccountMeta::new_readonly(damm_v2_pool_authority(), false));
-        accounts.push(AccountMeta::new(pool.pool, false));
-        accounts.push(AccountMeta::new(pool.token_x_vault, false));
-        accounts.push(AccountMeta::new(pool.token_sol_vault, false));
+    #[test]
+    fn route_price_override_within_cap_is_used() {
+        let config = config(SPAM, "compute_unit_price_override = 4000");
+        let rpc = RpcClient::new_mock("succeeds".to_string());
+        let price =
+            route_compute_unit_price_for(&config, &config.routing.mint_config_list[0], &rpc, &route(), 0).unwrap();
+        assert_eq!(price, 4000);
     }
 
-    // Add Solfi pools
-    for pool in &mint_pool_data.solfi_pools {
-        accounts.push(AccountMeta::new_readonly(solfi_program_id(), false));
-        accounts.push(AccountMeta::new_readonly(pool.base_mint, false)); // V9: Add base mint
-        accounts.push(AccountMeta::new_readonly(sysvar_instructions, false));
-        accounts.push(AccountMeta::new(pool.pool, false));
-        accounts.push(AccountMeta::new(pool.token_x_vault, false));
-        accounts.push(AccountMeta::new(pool.token_sol_vault, false));
+    #[test]
+    fn route_price_override_applies_only_to_its_mint() {
+        let config = config(
+            SPAM,
+            r#"compute_unit_price_override = 4000
+
+            [[routing.mint_config_list]]
+            mint = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"
+            process_delay = 0
+            pump_pool_list = ["11111111111111111111111111111111"]"#,
+        );
+        let rpc = RpcClient::new_mock("succeeds".to_string());
+        let [hot, quiet] = config.routing.mint_config_list.as_slice() else {
+            panic!("expected two mints");
+        };
+
+        assert_eq!(route_compute_unit_price_for(&config, hot, &rpc, &route(), 0).unwrap(), 4000);
+        assert_eq!(quiet.compute_unit_price_override, None);
+        assert_eq!(route_compute_unit_price_for(&config, quiet, &rpc, &route(), 0).unwrap(), 1000);
     }
 
-    // Add Vertigo pools
-    for pool in &mint_pool_data.vertigo_pools {
-        accounts.push(AccountMeta::new_readonly(vertigo_program_id(), false));
-        accounts.push(AccountMeta::new_readonly(pool.base_mint, false)); // V9: Add base mint
-        accounts.push(AccountMeta::new(pool.pool, false));
-        accounts.push(AccountMeta::new_readonly(pool.pool_owner, false));
-        accounts.push(AccountMeta::new(pool.token_x_vault, false));
-        accounts.push(AccountMeta::new(pool.token_sol_vault, false));
+    #[test]
+    fn profit_price_is_a_share_of_the_profit_left_after_other_costs() {
+        let mut config = config("", "");
+        config.bot.fee_profit_bps = Some(5_000);
+        config.bot.min_profit_lamports = 0;
+        let rpc = RpcClient::new_mock("succeeds".to_string());
+        // 100_000 of profit less 20_000 of tip and rent less the 5_000 base
+        // fee leaves 75_000; half of it over 200_000 units is 187_500 micro-lamports
+        assert_eq!(route_compute_unit_price(&config, &rpc, &route(), 20_000), 187_500);
+        assert_eq!(route_compute_unit_price(&config, &rpc, &route(), 200_000), 0);
     }
 
-    // Create instruction data
-    let mut data = vec![28u8];
+    #[test]
+    fn profit_price_applies_without_spam_and_is_capped() {
+        let rpc = RpcClient::new_mock("succeeds".to_string());
+        let mut jito_only = config("", "");
+        jito_only.bot.fee_profit_bps = Some(5_000);
+        assert!(route_compute_unit_price(&jito_only, &rpc, &route(), 0) > 0);
 
-    let minimum_profit: u64 = 0;
-    // When true, the bot will not fail the transaction even when it can't find a profitable arbitrage. It will just do nothing and succeed.
-    let no_failure_mode = false;
+        let mut capped = config(SPAM, "");
+        capped.bot.fee_profit_bps = Some(5_000);
+        assert_eq!(route_compute_unit_price(&capped, &rpc, &route(), 0), 5000);
+    }
 
-    data.extend_from_slice(&minimum_profit.to_le_bytes());
-    data.extend_from_slice(&compute_unit_limit.to_le_bytes());
-    data.extend_from_slice(if no_failure_mode { &[1] } else { &[0] });
-    data.extend_from_slice(&0u16.to_le_bytes()); // reserved
-    data.extend_from_slice(if use_flashloan { &[1] } else { &[0] });
+    #[test]
+    fn tip_is_the_last_instruction_of_the_arb_transaction() {
+        use crate::dex::{raydium::RaydiumDex, rpc::MockRpc, traits::PoolType};
+        use solana_sdk::{signature::Keypair, system_instruction, system_program};
+        use std::sync::Arc;
+
+        let (start, token) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let pools: Vec<PoolInfo> = (0..2)
+            .map(|_| {
+                let mut pool = PoolInfo::mock(PoolType::ConstantProduct);
+                pool.base_mint = start;
+                pool.token_mint = token;
+                pool.additional_accounts.insert("coin_vault".to_string(), Pubkey::new_unique());
+                pool.additional_accounts.insert("pc_vault".to_string(), Pubkey::new_unique());
+                pool
+            })
+            .collect();
+        let route = ArbRoute {
+            mints: vec![start, token, start],
+            pools: pools.iter().map(|pool| pool.pool_address).collect(),
+            ..route()
+        };
+        let dex = RaydiumDex::new(Arc::new(MockRpc::new()));
+        let hops: Vec<RouteHop> = pools.iter().map(|pool| RouteHop { dex: &dex, pool }).collect();
+        let wallet = Keypair::new();
+        let tip = system_instruction::transfer(&wallet.pubkey(), &Pubkey::new_unique(), 10_000);
+
+        let tx = build_arb_transaction(
+            &route,
+            &hops,
+            &[1_000, 2_000, 1_100],
+            50,
+            &wallet,
+            200_000,
+            0,
+            &[],
+            Hash::default(),
+            None,
+            false,
+            &[],
+            std::slice::from_ref(&tip),
+        )
+        .unwrap();
+
+        let keys = tx.message.static_account_keys();
+        let last = tx.message.instructions().last().unwrap();
+        assert_eq!(keys[last.program_id_index as usize], system_program::id());
+        assert_eq!(last.data, tip.data);
+    }
 
-    Ok(Instruction {
-        program_id: executor_program_id,
-        accounts,
-        data,
-    })
+    #[test]
+    fn missing_ata_is_created_and_its_rent_counted() {
+        use crate::dex::rpc::MockRpc;
+        use solana_sdk::{account::Account, rent::Rent};
+
+        let wallet = Pubkey::new_unique();
+        let (held, new) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mint = Account {
+            data: vec![0u8; 82],
+            owner: token_program_id,
+            ..Account::default()
+        };
+        let held_ata = spl_associated_token_account::get_associated_token_address(&wallet, &held);
+        let rpc = MockRpc::new()
+            .with_account(held, mint.clone())
+            .with_account(new, mint)
+            .with_account(held_ata, Account::default());
+
+        let (instructions, rent) = missing_ata_instructions(&rpc, &wallet, &[held, new, new]).unwrap();
+
+        assert_eq!(
+            instructions,
+            vec![spl_associated_token_account::instruction::create_associated_token_account_idempotent(
+                &wallet,
+                &wallet,
+                &new,
+                &token_program_id,
+            )]
+        );
+        assert_eq!(rent, Rent::default().minimum_balance(TOKEN_ACCOUNT_LEN));
+    }
 }
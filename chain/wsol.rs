@@ -0,0 +1,198 @@
+use crate::chain::constants::sol_mint;
+use anyhow::Result;
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::{
+    instruction::Instruction,
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::Signer,
+    system_instruction,
+    transaction::Transaction,
+};
+use spl_associated_token_account::{
+    get_associated_token_address, instruction::create_associated_token_account_idempotent,
+};
+use tracing::info;
+
+/// Offset of the `amount` field in an SPL token account
+const TOKEN_ACCOUNT_AMOUNT_OFFSET: usize = 64;
+
+/// Size of an SPL token account
+const TOKEN_ACCOUNT_LEN: usize = 165;
+
+/// Keeps trade proceeds as WSOL and unwraps them in bulk, instead of adding
+/// unwrap instructions to every trade
+pub struct WsolUnwrapper {
+    threshold_lamports: u64,
+}
+
+impl WsolUnwrapper {
+    pub fn new(threshold_lamports: u64) -> Self {
+        Self { threshold_lamports }
+    }
+
+    pub fn should_unwrap(&self, wsol_balance: u64) -> bool {
+        wsol_balance > self.threshold_lamports
+    }
+
+    /// Unwrap the wallet's WSOL if its balance is above the threshold
+    pub fn maybe_unwrap(&self, rpc: &RpcClient, wallet: &dyn Signer) -> Result<Option<Signature>> {
+        let balance = wsol_balance(rpc, &wallet.pubkey())?;
+        if !self.should_unwrap(balance) {
+            return Ok(None);
+        }
+        info!(
+            "WSOL balance {} lamports is above threshold {}, unwrapping",
+            balance, self.threshold_lamports
+        );
+        unwrap_all(rpc, wallet).map(Some)
+    }
+}
+
+/// Current WSOL balance of the wallet's associated token account, zero if it doesn't exist
+pub fn wsol_balance(rpc: &RpcClient, wallet: &Pubkey) -> Result<u64> {
+    let wsol_account = get_associated_token_address(wallet, &sol_mint());
+    let account = rpc
+        .get_multiple_accounts(&[wsol_account])?
+        .into_iter()
+        .next()
+        .flatten();
+    Ok(account.map_or(0, |a| token_amount(&a.data)))
+}
+
+/// Native SOL and WSOL held by a wallet, read together in one call
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WalletBalance {
+    /// Lamports in the wallet itself, which pay transaction fees
+    pub native_lamports: u64,
+    /// Lamports in the wallet's WSOL account, which fund swaps
+    pub wsol_lamports: u64,
+}
+
+impl WalletBalance {
+    pub fn fetch(rpc: &RpcClient, wallet: &Pubkey) -> Result<Self> {
+        let wsol_account = get_associated_token_address(wallet, &sol_mint());
+        let mut accounts = rpc.get_multiple_accounts(&[*wallet, wsol_account])?.into_iter();
+        let native = accounts.next().flatten();
+        let wsol = accounts.next().flatten();
+        Ok(Self {
+            native_lamports: native.map_or(0, |a| a.lamports),
+            wsol_lamports: wsol.map_or(0, |a| token_amount(&a.data)),
+        })
+    }
+}
+
+fn token_amount(data: &[u8]) -> u64 {
+    data.get(TOKEN_ACCOUNT_AMOUNT_OFFSET..TOKEN_ACCOUNT_AMOUNT_OFFSET + 8)
+        .map_or(0, |bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+/// Instructions that create the user's WSOL account if it is missing and
+/// move `amount` lamports of native SOL into it. With an `amount` of zero the
+/// account is only created.
+pub fn wrap_sol(user: &Pubkey, amount: u64) -> Result<Vec<Instruction>> {
+    let wsol_account = get_associated_token_address(user, &sol_mint());
+    let mut instructions = vec![create_associated_token_account_idempotent(
+        user,
+        user,
+        &sol_mint(),
+        &spl_token::ID,
+    )];
+    if amount > 0 {
+        instructions.push(system_instruction::transfer(user, &wsol_account, amount));
+        instructions.push(spl_token::instruction::sync_native(&spl_token::ID, &wsol_account)?);
+    }
+    Ok(instructions)
+}
+
+/// Instruction that closes the user's WSOL account, releasing its whole
+/// balance to the user as native SOL
+pub fn unwrap_sol(user: &Pubkey) -> Result<Instruction> {
+    let wsol_account = get_associated_token_address(user, &sol_mint());
+    Ok(spl_token::instruction::close_account(
+        &spl_token::ID,
+        &wsol_account,
+        user,
+        user,
+        &[],
+    )?)
+}
+
+/// Instructions that move `amount` WSOL out of the user's WSOL account into
+/// the fresh account `temp`, funded with `rent_lamports`, and close `temp` as
+/// the last step. The user gets `amount` back as native SOL, the rent is
+/// refunded, and the WSOL account stays open for later trades.
+pub fn unwrap_amount(user: &Pubkey, temp: &Pubkey, amount: u64, rent_lamports: u64) -> Result<Vec<Instruction>> {
+    let wsol_account = get_associated_token_address(user, &sol_mint());
+    Ok(vec![
+        system_instruction::create_account(user, temp, rent_lamports, TOKEN_ACCOUNT_LEN as u64, &spl_token::ID),
+        spl_token::instruction::initialize_account3(&spl_token::ID, temp, &sol_mint(), user)?,
+        spl_token::instruction::transfer(&spl_token::ID, &wsol_account, temp, user, &[], amount)?,
+        spl_token::instruction::close_account(&spl_token::ID, temp, user, user, &[])?,
+    ])
+}
+
+/// Unwrap all WSOL back to native SOL through a temporary account, see
+/// `unwrap_amount`
+pub fn unwrap_all(rpc: &RpcClient, wallet: &dyn Signer) -> Result<Signature> {
+    let owner = wallet.pubkey();
+    let amount = wsol_balance(rpc, &owner)?;
+    let temp = Keypair::new();
+    let rent = rpc.get_minimum_balance_for_rent_exemption(TOKEN_ACCOUNT_LEN)?;
+    let instructions = unwrap_amount(&owner, &temp.pubkey(), amount, rent)?;
+
+    let blockhash = rpc.get_latest_blockhash()?;
+    let tx = Transaction::new_signed_with_payer(&instructions, Some(&owner), &[wallet, &temp], blockhash);
+    let signature = rpc.send_and_confirm_transaction(&tx)?;
+    info!("Unwrapped WSOL: {}", signature);
+    Ok(signature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::signature::Keypair;
+
+    #[test]
+    fn unwraps_only_above_the_threshold() {
+        let unwrapper = WsolUnwrapper::new(1_000_000);
+        assert!(!unwrapper.should_unwrap(0));
+        assert!(!unwrapper.should_unwrap(999_999));
+        assert!(!unwrapper.should_unwrap(1_000_000));
+        assert!(unwrapper.should_unwrap(1_000_001));
+    }
+
+    #[test]
+    fn nothing_is_unwrapped_without_a_wsol_account() {
+        // The mock client reports no WSOL account, so the balance is zero
+        let rpc = RpcClient::new_mock("succeeds".to_string());
+        let unwrapped = WsolUnwrapper::new(0).maybe_unwrap(&rpc, &Keypair::new()).unwrap();
+        assert_eq!(unwrapped, None);
+    }
+
+    #[test]
+    fn unwrap_amount_keeps_the_wsol_account_and_closes_last() {
+        let user = Pubkey::new_unique();
+        let temp = Pubkey::new_unique();
+        let wsol_account = get_associated_token_address(&user, &sol_mint());
+        let instructions = unwrap_amount(&user, &temp, 1_000_000, 2_039_280).unwrap();
+
+        let close = instructions.last().unwrap();
+        assert_eq!(close.program_id, spl_token::ID);
+        assert_eq!(close.accounts[0].pubkey, temp);
+        // Nothing closes or recreates the wallet's WSOL account
+        assert!(instructions
+            .iter()
+            .all(|ix| ix.program_id != spl_associated_token_account::ID));
+        let closed: Vec<Pubkey> = instructions
+            .iter()
+            .filter(|ix| ix.data.first() == Some(&9))
+            .map(|ix| ix.accounts[0].pubkey)
+            .collect();
+        assert_eq!(closed, vec![temp]);
+
+        let transfer = &instructions[2];
+        assert_eq!(transfer.accounts[0].pubkey, wsol_account);
+        assert_eq!(transfer.accounts[1].pubkey, temp);
+    }
+}
@@ -1,25 +1,215 @@
-use config::{Config as ConfigBuilder, ConfigError, Environment, File};
+use config::{Config as ConfigBuilder, Environment, File};
+#[cfg(test)]
+use config::FileFormat;
+use dotenv::dotenv;
 use serde::{Deserialize, Serialize};
-use std::env;
+use crate::error::BotError;
+use solana_sdk::{
+    commitment_config::{CommitmentConfig, CommitmentLevel},
+    pubkey::Pubkey,
+    signature::Keypair,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    net::{IpAddr, Ipv4Addr, SocketAddr},
+    str::FromStr,
+};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Config {
     pub bot: BotConfig,
     pub routing: RoutingConfig,
+    #[serde(default)]
     pub rpc: RpcConfig,
     pub spam: Option<SpamConfig>,
     pub wallet: WalletConfig,
     pub flashloan: Option<FlashloanConfig>,
+    #[serde(default)]
+    pub telemetry: Option<TelemetryConfig>,
+    #[serde(default)]
+    pub report: Option<ReportConfig>,
+    #[serde(default)]
+    pub jito: Option<JitoConfig>,
+    #[serde(default)]
+    pub metrics: Option<MetricsConfig>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BotConfig {
     pub compute_unit_limit: u32,
+    #[serde(default = "default_max_price_impact_bps")]
+    pub max_price_impact_bps: u16,
+    #[serde(default = "default_max_slot_lag")]
+    pub max_slot_lag: u64,
+    /// Least profit after base and priority fees worth submitting
+    #[serde(default)]
+    pub min_profit_lamports: u64,
+    /// Spend this share of a route's profit after costs on its priority fee
+    #[serde(default)]
+    pub fee_profit_bps: Option<u16>,
+    /// Native SOL always left in the wallet to pay fees and rent
+    #[serde(default = "default_fee_buffer_lamports")]
+    pub fee_buffer_lamports: u64,
+    /// Build and simulate transactions but never send them
+    #[serde(default)]
+    pub dry_run: bool,
+    #[serde(default)]
+    pub max_rpc_calls_per_scan: Option<u64>,
+    #[serde(default)]
+    pub compress_cache: bool,
+    /// File the pool cache is persisted to across restarts
+    #[serde(default)]
+    pub cache_path: Option<String>,
+    /// File each landed trade's profit and loss is appended to
+    #[serde(default)]
+    pub pnl_log_path: Option<String>,
+    #[serde(default)]
+    pub raydium_swap_version: Option<u8>,
+    #[serde(default = "default_max_additional_accounts")]
+    pub max_additional_accounts: usize,
+    #[serde(default = "default_decode_threads")]
+    pub decode_threads: usize,
+    #[serde(default = "default_follow_pump_migrations")]
+    pub follow_pump_migrations: bool,
+    #[serde(default = "default_account_cache_ttl_seconds")]
+    pub account_cache_ttl_seconds: u64,
+    /// Most mints whose pool data is cached; the least recently used is
+    /// evicted past it
+    #[serde(default = "default_max_cache_entries")]
+    pub max_cache_entries: usize,
+    #[serde(default)]
+    pub max_pools_per_mint: Option<usize>,
+    #[serde(default)]
+    pub wsol_unwrap_threshold_lamports: Option<u64>,
+    #[serde(default)]
+    pub profit_fee_bps: u16,
+    #[serde(default)]
+    pub fee_recipient: Option<String>,
+    #[serde(default = "default_min_pools_for_arb")]
+    pub min_pools_for_arb: usize,
+    #[serde(default = "default_max_scan_interval_ms")]
+    pub max_scan_interval_ms: u64,
+    #[serde(default = "default_dedup_window_ms")]
+    pub dedup_window_ms: u64,
+    #[serde(default = "default_volatility_window")]
+    pub volatility_window: usize,
+    #[serde(default = "default_volatility_target_bps")]
+    pub volatility_target_bps: u64,
+    /// Smallest vault reserve change, in basis points, that triggers a re-scan
+    #[serde(default)]
+    pub rescan_threshold_bps: u64,
+    /// Seconds allowed for cleanup after Ctrl-C before the process is killed
+    #[serde(default = "default_shutdown_timeout_secs")]
+    pub shutdown_timeout_secs: u64,
+}
+
+fn default_max_price_impact_bps() -> u16 {
+    100
+}
+
+fn default_max_slot_lag() -> u64 {
+    10
+}
+
+fn default_fee_buffer_lamports() -> u64 {
+    5_000_000
+}
+
+fn default_max_additional_accounts() -> usize {
+    8
+}
+
+fn default_decode_threads() -> usize {
+    1
+}
+
+fn default_follow_pump_migrations() -> bool {
+    true
+}
+
+fn default_account_cache_ttl_seconds() -> u64 {
+    30
+}
+
+fn default_max_cache_entries() -> usize {
+    1000
+}
+
+fn default_min_pools_for_arb() -> usize {
+    2
+}
+
+fn default_max_scan_interval_ms() -> u64 {
+    10_000
+}
+
+fn default_dedup_window_ms() -> u64 {
+    2_000
+}
+
+fn default_volatility_window() -> usize {
+    20
+}
+
+fn default_volatility_target_bps() -> u64 {
+    50
+}
+
+fn default_shutdown_timeout_secs() -> u64 {
+    10
+}
+
+fn default_slippage_bps() -> u16 {
+    50
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RoutingConfig {
     pub mint_config_list: Vec<MintConfig>,
+    /// Pump pool address -> Raydium pool address the token graduated to
+    #[serde(default)]
+    pub pump_migrations: HashMap<String, String>,
+    /// Per-DEX settings keyed by DEX name, e.g. `[routing.dexes.raydium]`
+    #[serde(default)]
+    pub dexes: HashMap<String, DexConfig>,
+}
+
+/// Names of the supported DEXes, as used for `routing.dexes` keys and by
+/// `MintConfig::pool_list_fields`
+pub const DEX_NAMES: [&str; 10] = [
+    "pump",
+    "raydium",
+    "meteora_damm_v2",
+    "whirlpool",
+    "meteora_dlmm",
+    "solfi",
+    "vertigo",
+    "raydium_clmm",
+    "raydium_cp",
+    "meteora_damm",
+];
+
+impl RoutingConfig {
+    /// Names of the DEXes switched off with `enabled = false`
+    pub fn disabled_dexes(&self) -> HashSet<String> {
+        self.dexes
+            .iter()
+            .filter(|(_, dex)| !dex.enabled)
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DexConfig {
+    /// Set to false to skip every pool of this DEX without editing pool lists
+    #[serde(default = "default_dex_enabled")]
+    pub enabled: bool,
+}
+
+fn default_dex_enabled() -> bool {
+    true
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -55,11 +245,218 @@ pub struct MintConfig {
     #[serde(default)]
     pub lookup_table_accounts: Option<Vec<String>>,
     pub process_delay: u64,
+    /// Overrides `spam.compute_unit_price` for this mint's transactions
+    #[serde(default)]
+    pub compute_unit_price_override: Option<u64>,
+    /// Pools always kept for this mint, regardless of pool filters
+    #[serde(default)]
+    pub pinned_pools: Option<Vec<String>>,
+    /// Slippage allowed on each hop's quoted output, in basis points
+    #[serde(default = "default_slippage_bps")]
+    pub slippage_bps: u16,
+    /// Pools never traded for this mint, even when listed or pinned
+    #[serde(default)]
+    pub disabled_pools: Vec<String>,
+    /// Pools with less base-side liquidity than this are dropped before routing
+    #[serde(default)]
+    pub min_liquidity_lamports: Option<u64>,
+}
+
+impl MintConfig {
+    /// Every pool list as `(dex name, config field, pools)`. This is the only
+    /// mapping from config fields to DEXes; a new DEX adds its field here and
+    /// registers its adapter in `build_dex_registry`.
+    pub fn pool_list_fields(&self) -> [(&'static str, &'static str, Option<&Vec<String>>); 10] {
+        [
+            ("pump", "pump_pool_list", self.pump_pool_list.as_ref()),
+            ("raydium", "raydium_pool_list", self.raydium_pool_list.as_ref()),
+            ("meteora_damm_v2", "meteora_damm_v2_pool_list", self.meteora_damm_v2_pool_list.as_ref()),
+            ("whirlpool", "whirlpool_pool_list", self.whirlpool_pool_list.as_ref()),
+            ("meteora_dlmm", "meteora_dlmm_pool_list", self.meteora_dlmm_pool_list.as_ref()),
+            ("solfi", "solfi_pool_list", self.solfi_pool_list.as_ref()),
+            ("vertigo", "vertigo_pool_list", self.vertigo_pool_list.as_ref()),
+            ("raydium_clmm", "raydium_clmm_pool_list", self.raydium_clmm_pool_list.as_ref()),
+            ("raydium_cp", "raydium_cp_pool_list", self.raydium_cp_pool_list.as_ref()),
+            ("meteora_damm", "meteora_damm_pool_list", self.meteora_damm_pool_list.as_ref()),
+        ]
+    }
+
+    /// The configured pool lists keyed by DEX name, skipping unset ones
+    pub fn pool_lists(&self) -> Vec<(&'static str, Vec<String>)> {
+        self.pool_list_fields()
+            .into_iter()
+            .filter_map(|(dex, _, pools)| pools.map(|pools| (dex, pools.clone())))
+            .collect()
+    }
+
+    /// Whether `other` selects the same pools: the same pool lists, pinned and
+    /// disabled pools, and minimum liquidity
+    pub fn same_pools(&self, other: &MintConfig) -> bool {
+        self.pool_lists() == other.pool_lists()
+            && self.pinned_pools == other.pinned_pools
+            && self.disabled_pools == other.disabled_pools
+            && self.min_liquidity_lamports == other.min_liquidity_lamports
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RpcConfig {
+    /// Endpoints reads round-robin over, the first being the primary. `url`
+    /// is accepted too, as a single string or a list.
+    #[serde(alias = "url", default = "default_rpc_urls", deserialize_with = "one_or_many")]
+    pub urls: Vec<String>,
+    #[serde(default = "default_pool_max_idle_per_host")]
+    pub pool_max_idle_per_host: usize,
+    #[serde(default = "default_pool_idle_timeout_ms")]
+    pub pool_idle_timeout_ms: u64,
+    #[serde(default = "default_tcp_keepalive_ms")]
+    pub tcp_keepalive_ms: u64,
+    #[serde(default = "default_request_timeout_ms")]
+    pub request_timeout_ms: u64,
+    #[serde(default)]
+    pub fallback_endpoints: Vec<RpcEndpointConfig>,
+    /// Consecutive failures after which an endpoint is skipped for a cooldown
+    #[serde(default = "default_unhealthy_after_failures")]
+    pub unhealthy_after_failures: u32,
+    #[serde(default = "default_unhealthy_cooldown_ms")]
+    pub unhealthy_cooldown_ms: u64,
+    /// WebSocket endpoint for account subscriptions, derived from `url` when unset
+    #[serde(default)]
+    pub ws_url: Option<String>,
+    /// Commitment of account and balance reads
+    #[serde(default = "default_commitment")]
+    pub commitment: CommitmentLevel,
+    /// Commitment of WebSocket account subscriptions
+    #[serde(default = "default_subscribe_commitment")]
+    pub subscribe_commitment: CommitmentLevel,
+    /// Commitment a submitted transaction must reach to count as landed
+    #[serde(default = "default_confirm_commitment")]
+    pub confirm_commitment: CommitmentLevel,
+}
+
+impl Default for RpcConfig {
+    fn default() -> Self {
+        Self {
+            urls: default_rpc_urls(),
+            pool_max_idle_per_host: default_pool_max_idle_per_host(),
+            pool_idle_timeout_ms: default_pool_idle_timeout_ms(),
+            tcp_keepalive_ms: default_tcp_keepalive_ms(),
+            request_timeout_ms: default_request_timeout_ms(),
+            fallback_endpoints: Vec::new(),
+            unhealthy_after_failures: default_unhealthy_after_failures(),
+            unhealthy_cooldown_ms: default_unhealthy_cooldown_ms(),
+            ws_url: None,
+            commitment: default_commitment(),
+            subscribe_commitment: default_subscribe_commitment(),
+            confirm_commitment: default_confirm_commitment(),
+        }
+    }
+}
+
+impl RpcConfig {
+    /// The primary endpoint, the first of `urls`
+    pub fn url(&self) -> &str {
+        self.urls.first().map_or("", String::as_str)
+    }
+
+    /// Longest timeout of any endpoint, for reads that may be served by any
+    /// of them
+    pub fn max_request_timeout_ms(&self) -> u64 {
+        self.fallback_endpoints
+            .iter()
+            .filter_map(|endpoint| endpoint.timeout_ms)
+            .fold(self.request_timeout_ms, u64::max)
+    }
+
+    /// WebSocket URL for pubsub, falling back to the primary URL with its
+    /// scheme swapped
+    pub fn ws_url(&self) -> String {
+        let url = self.url();
+        self.ws_url.clone().unwrap_or_else(|| {
+            if let Some(rest) = url.strip_prefix("https://") {
+                format!("wss://{}", rest)
+            } else if let Some(rest) = url.strip_prefix("http://") {
+                format!("ws://{}", rest)
+            } else {
+                url.to_string()
+            }
+        })
+    }
+
+    pub fn read_commitment(&self) -> CommitmentConfig {
+        CommitmentConfig { commitment: self.commitment }
+    }
+
+    pub fn subscription_commitment(&self) -> CommitmentConfig {
+        CommitmentConfig { commitment: self.subscribe_commitment }
+    }
+
+    pub fn confirmation_commitment(&self) -> CommitmentConfig {
+        CommitmentConfig { commitment: self.confirm_commitment }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RpcEndpointConfig {
     pub url: String,
+    /// Overrides `request_timeout_ms` for this endpoint
+    #[serde(default)]
+    pub timeout_ms: Option<u64>,
+}
+
+fn default_commitment() -> CommitmentLevel {
+    CommitmentLevel::Finalized
+}
+
+fn default_subscribe_commitment() -> CommitmentLevel {
+    CommitmentLevel::Processed
+}
+
+fn default_confirm_commitment() -> CommitmentLevel {
+    CommitmentLevel::Confirmed
+}
+
+fn default_rpc_urls() -> Vec<String> {
+    vec!["https://api.mainnet-beta.solana.com".to_string()]
+}
+
+/// Deserialize either a single string or a list of strings into a list
+fn one_or_many<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(url) => vec![url],
+        OneOrMany::Many(urls) => urls,
+    })
+}
+
+fn default_unhealthy_after_failures() -> u32 {
+    3
+}
+
+fn default_unhealthy_cooldown_ms() -> u64 {
+    30_000
+}
+
+fn default_pool_max_idle_per_host() -> usize {
+    32
+}
+
+fn default_pool_idle_timeout_ms() -> u64 {
+    90_000
+}
+
+fn default_tcp_keepalive_ms() -> u64 {
+    60_000
+}
+
+fn default_request_timeout_ms() -> u64 {
+    30_000
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -68,16 +465,225 @@ pub struct SpamConfig {
     pub sending_rpc_urls: Vec<String>,
     pub compute_unit_price: u64,
     pub max_retries: Option<u64>,
+    #[serde(default)]
+    pub confirm_timeout_ms: Option<u64>,
+    #[serde(default)]
+    pub max_compute_unit_price: Option<u64>,
+    #[serde(default)]
+    pub skip_preflight: bool,
+    #[serde(default)]
+    pub preflight_commitment: Option<CommitmentLevel>,
+    /// Price compute units from recent fees on the transaction's pools
+    /// instead of the static `compute_unit_price`
+    #[serde(default)]
+    pub dynamic_priority_fee: bool,
+    #[serde(default = "default_priority_fee_percentile")]
+    pub priority_fee_percentile: u8,
+}
+
+fn default_priority_fee_percentile() -> u8 {
+    75
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WalletConfig {
+    #[serde(default)]
     pub private_key: String,
+    /// Extra wallets, used together with `private_key` for round-robin signing
+    #[serde(default)]
+    pub private_keys: Vec<String>,
+    #[serde(default)]
+    pub signer_type: SignerType,
+    #[serde(default)]
+    pub remote_signer_url: Option<String>,
+    #[serde(default)]
+    pub remote_signer_pubkey: Option<String>,
+}
+
+impl WalletConfig {
+    /// Decode `private_key` into a keypair. Accepts a base58 secret key, a JSON
+    /// byte array as written by `solana-keygen`, or `file:<path>` pointing at
+    /// either of those.
+    pub fn keypair(&self) -> Result<Keypair, BotError> {
+        Self::load_keypair(&self.private_key)
+    }
+
+    /// Every configured wallet: `private_key` first if set, then each of
+    /// `private_keys`, in the formats accepted by `keypair`
+    pub fn keypairs(&self) -> Result<Vec<Keypair>, BotError> {
+        std::iter::once(&self.private_key)
+            .filter(|key| !key.trim().is_empty())
+            .chain(&self.private_keys)
+            .map(|key| Self::load_keypair(key))
+            .collect()
+    }
+
+    fn load_keypair(key: &str) -> Result<Keypair, BotError> {
+        let key = key.trim();
+        if let Some(path) = key.strip_prefix("file:") {
+            let contents = std::fs::read_to_string(path.trim()).map_err(|e| {
+                BotError::Validation(format!("Cannot read keypair file {}: {}", path.trim(), e))
+            })?;
+            return Self::parse_keypair(contents.trim());
+        }
+        Self::parse_keypair(key)
+    }
+
+    fn parse_keypair(key: &str) -> Result<Keypair, BotError> {
+        if key.is_empty() {
+            return Err(BotError::Validation("wallet private_key is empty".to_string()));
+        }
+
+        let bytes = if key.starts_with('[') {
+            serde_json::from_str::<Vec<u8>>(key).map_err(|e| {
+                BotError::Validation(format!("Invalid keypair byte array: {}", e))
+            })?
+        } else {
+            bs58::decode(key)
+                .into_vec()
+                .map_err(|e| BotError::Validation(format!("Invalid base58 private key: {}", e)))?
+        };
+
+        if bytes.len() != 64 {
+            return Err(BotError::Validation(format!(
+                "Private key must be 64 bytes, got {}",
+                bytes.len()
+            )));
+        }
+        Keypair::from_bytes(&bytes)
+            .map_err(|e| BotError::Validation(format!("Invalid private key: {}", e)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SignerType {
+    /// Sign in-process with `private_key`
+    #[default]
+    Keypair,
+    /// Sign through the service at `remote_signer_url`
+    Remote,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FlashloanConfig {
     pub enabled: bool,
+    #[serde(default)]
+    pub provider: FlashloanProvider,
+    /// Lending reserve the route's input mint is borrowed from
+    #[serde(default)]
+    pub reserve: Option<String>,
+    /// Most borrowed per transaction; the wallet covers any larger input
+    #[serde(default)]
+    pub max_borrow_lamports: Option<u64>,
+    /// The reserve's flash loan fee, counted as a cost of each borrowing route
+    #[serde(default = "default_flashloan_fee_bps")]
+    pub fee_bps: u16,
+}
+
+fn default_flashloan_fee_bps() -> u16 {
+    30
+}
+
+/// Lending protocol flash loans are taken from
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FlashloanProvider {
+    #[default]
+    Kamino,
+    Solend,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportConfig {
+    #[serde(default)]
+    pub format: ReportFormat,
+    #[serde(default)]
+    pub output_path: Option<String>,
+    #[serde(default = "default_report_top_mints")]
+    pub top_mints: usize,
+}
+
+impl Default for ReportConfig {
+    fn default() -> Self {
+        Self {
+            format: ReportFormat::default(),
+            output_path: None,
+            top_mints: default_report_top_mints(),
+        }
+    }
+}
+
+fn default_report_top_mints() -> usize {
+    5
+}
+
+/// Land transactions as Jito bundles instead of spamming RPCs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JitoConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    pub block_engine_url: String,
+    pub tip_lamports: u64,
+    /// One of Jito's tip accounts; a well-known one is used when unset
+    #[serde(default)]
+    pub tip_account: Option<String>,
+    /// How long to wait for a bundle's transaction to land before counting it
+    /// as dropped
+    #[serde(default)]
+    pub confirm_timeout_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TelemetryConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default = "default_otlp_endpoint")]
+    pub otlp_endpoint: String,
+    #[serde(default = "default_service_name")]
+    pub service_name: String,
+}
+
+/// Prometheus endpoint for scan and landing metrics
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MetricsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Interface the endpoint listens on; loopback unless set
+    #[serde(default = "default_metrics_bind_address")]
+    pub bind_address: IpAddr,
+    #[serde(default = "default_metrics_port")]
+    pub port: u16,
+}
+
+impl MetricsConfig {
+    pub fn socket_addr(&self) -> SocketAddr {
+        SocketAddr::new(self.bind_address, self.port)
+    }
+}
+
+fn default_metrics_bind_address() -> IpAddr {
+    IpAddr::V4(Ipv4Addr::LOCALHOST)
+}
+
+fn default_metrics_port() -> u16 {
+    9100
+}
+
+fn default_otlp_endpoint() -> String {
+    "http://localhost:4317".to_string()
+}
+
+fn default_service_name() -> String {
+    "solana-mev-bot".to_string()
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    #[default]
+    Text,
+    Json,
 }
 
 impl Config {
@@ -85,24 +691,37 @@ impl Config {
     /// 1. Environment variables (highest priority)
     /// 2. TOML/YAML config files
     /// 3. Default values (lowest priority)
-    pub fn load() -> Result<Self, ConfigError> {
+    pub fn load() -> Result<Self, BotError> {
         // Load environment variables from .env file
         dotenv().ok();
 
+        Self::load_from_file(Self::default_file())
+    }
+
+    /// The config file `load` reads: the first of `config.toml`,
+    /// `config.yaml` and `config.yml` in the working directory
+    pub fn default_file() -> Option<&'static std::path::Path> {
+        CONFIG_FILES
+            .iter()
+            .map(|file| std::path::Path::new(*file))
+            .find(|file| file.exists())
+    }
+
+    /// Same as `load`, reading the given config file instead of searching the
+    /// working directory for one
+    pub fn load_from(path: impl AsRef<std::path::Path>) -> Result<Self, BotError> {
+        dotenv().ok();
+        Self::load_from_file(Some(path.as_ref()))
+    }
+
+    fn load_from_file(file: Option<&std::path::Path>) -> Result<Self, BotError> {
         let mut builder = ConfigBuilder::builder()
             // Start with default values
             .set_default("bot.compute_unit_limit", 600000)?
-            .set_default("rpc.url", "https://api.mainnet-beta.solana.com")?
             .set_default("wallet.private_key", "")?;
 
-        // Try to load from config files (TOML or YAML)
-        let config_files = ["config.toml", "config.yaml", "config.yml"];
-
-        for file in &config_files {
-            if std::path::Path::new(file).exists() {
-                builder = builder.add_source(File::with_name(file));
-                break; // Use first found config file
-            }
+        if let Some(file) = file {
+            builder = builder.add_source(File::from(file));
         }
 
         // Add environment variables (highest priority)
@@ -112,7 +731,275 @@ impl Config {
                 .try_parsing(true)
         );
 
-        let config = builder.build()?;
-        config.try_deserialize()
+        let config: Self = builder.build()?.try_deserialize()?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Parse a TOML config held in memory, without defaults, environment
+    /// overrides or validation
+    #[cfg(test)]
+    pub(crate) fn from_toml(toml: &str) -> Self {
+        ConfigBuilder::builder()
+            .add_source(File::from_str(toml, FileFormat::Toml))
+            .build()
+            .and_then(|config| config.try_deserialize())
+            .expect("test config should parse")
+    }
+
+    /// Watch `path` and send a freshly loaded config each time the file
+    /// changes. Reloads that fail to parse or validate are logged and
+    /// dropped, so the receiver only ever sees valid configs. Polling stops
+    /// once the receiver is dropped.
+    pub fn watch(path: impl Into<std::path::PathBuf>) -> UnboundedReceiver<Config> {
+        let path = path.into();
+        let (sender, receiver) = unbounded_channel();
+        let modified_at = |path: &std::path::Path| std::fs::metadata(path).and_then(|m| m.modified()).ok();
+
+        std::thread::spawn(move || {
+            let mut last_modified = modified_at(&path);
+            while !sender.is_closed() {
+                std::thread::sleep(CONFIG_WATCH_INTERVAL);
+                let modified = modified_at(&path);
+                if modified == last_modified {
+                    continue;
+                }
+                last_modified = modified;
+
+                match Self::load_from(&path) {
+                    Ok(config) => {
+                        tracing::info!("Reloaded config from {}", path.display());
+                        if sender.send(config).is_err() {
+                            break;
+                        }
+                    }
+                    Err(e) => tracing::warn!(
+                        "Ignoring invalid config change in {}, keeping the previous config: {}",
+                        path.display(),
+                        e
+                    ),
+                }
+            }
+        });
+
+        receiver
+    }
+
+    /// Check the values serde cannot: addresses parse as pubkeys, a keypair
+    /// signer has a key, the compute unit limit fits a transaction, and every
+    /// mint has at least one pool to route through
+    pub fn validate(&self) -> Result<(), BotError> {
+        if self.wallet.signer_type == SignerType::Keypair
+            && self.wallet.private_key.trim().is_empty()
+            && self.wallet.private_keys.is_empty()
+        {
+            return Err(BotError::Validation(
+                "wallet.private_key or wallet.private_keys must be set when signer_type is keypair"
+                    .to_string(),
+            ));
+        }
+        if self.wallet.private_keys.iter().any(|key| key.trim().is_empty()) {
+            return Err(BotError::Validation(
+                "wallet.private_keys must not contain empty entries".to_string(),
+            ));
+        }
+
+        if self.rpc.urls.is_empty() || self.rpc.urls.iter().any(|url| url.trim().is_empty()) {
+            return Err(BotError::Validation(
+                "rpc.urls must list at least one endpoint and no empty entries".to_string(),
+            ));
+        }
+
+        if self.bot.compute_unit_limit > MAX_COMPUTE_UNIT_LIMIT {
+            return Err(BotError::Validation(format!(
+                "bot.compute_unit_limit {} exceeds the transaction maximum of {}",
+                self.bot.compute_unit_limit, MAX_COMPUTE_UNIT_LIMIT
+            )));
+        }
+
+        if let Some(flashloan) = self.flashloan.as_ref().filter(|f| f.enabled) {
+            match &flashloan.reserve {
+                Some(reserve) => {
+                    parse_pubkey(reserve, "flashloan.reserve")?;
+                }
+                None => {
+                    return Err(BotError::Validation(
+                        "flashloan.reserve must be set when flashloan is enabled".to_string(),
+                    ))
+                }
+            }
+        }
+
+        let mut unknown_dexes: Vec<&str> = self
+            .routing
+            .dexes
+            .keys()
+            .map(String::as_str)
+            .filter(|name| !DEX_NAMES.contains(name))
+            .collect();
+        if !unknown_dexes.is_empty() {
+            unknown_dexes.sort_unstable();
+            return Err(BotError::Validation(format!(
+                "routing.dexes has unknown DEX names {:?}; expected one of {:?}",
+                unknown_dexes, DEX_NAMES
+            )));
+        }
+
+        for (index, mint_config) in self.routing.mint_config_list.iter().enumerate() {
+            let context = format!("routing.mint_config_list[{}]", index);
+            parse_pubkey(&mint_config.mint, &format!("{}.mint", context))?;
+
+            let mut pool_count = 0;
+            for (_, name, list) in mint_config.pool_list_fields() {
+                for address in list.into_iter().flatten() {
+                    parse_pubkey(address, &format!("{}.{}", context, name))?;
+                    pool_count += 1;
+                }
+            }
+            if pool_count == 0 {
+                return Err(BotError::Validation(format!(
+                    "{} (mint {}) has no pools configured",
+                    context, mint_config.mint
+                )));
+            }
+
+            for (name, list) in [
+                ("lookup_table_accounts", &mint_config.lookup_table_accounts),
+                ("pinned_pools", &mint_config.pinned_pools),
+            ] {
+                for address in list.iter().flatten() {
+                    parse_pubkey(address, &format!("{}.{}", context, name))?;
+                }
+            }
+            for address in &mint_config.disabled_pools {
+                parse_pubkey(address, &format!("{}.disabled_pools", context))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Config files searched by `Config::load`, in priority order
+const CONFIG_FILES: [&str; 3] = ["config.toml", "config.yaml", "config.yml"];
+
+/// How often `Config::watch` checks the file for changes
+const CONFIG_WATCH_INTERVAL: std::time::Duration = std::time::Duration::from_secs(1);
+
+/// Most compute units a single transaction may request
+const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+fn parse_pubkey(value: &str, field: &str) -> Result<Pubkey, BotError> {
+    Pubkey::from_str(value.trim())
+        .map_err(|e| BotError::Validation(format!("{}: invalid address {:?}: {}", field, value, e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(rpc: &str) -> Config {
+        Config::from_toml(&format!(
+            r#"
+            [bot]
+            compute_unit_limit = 200000
+
+            {rpc}
+
+            [wallet]
+            private_key = ""
+
+            [[routing.mint_config_list]]
+            mint = "So11111111111111111111111111111111111111112"
+            process_delay = 0
+            pump_pool_list = ["11111111111111111111111111111111"]
+            "#
+        ))
+    }
+
+    #[test]
+    fn rpc_url_accepts_a_single_string() {
+        let config = config("[rpc]\nurl = \"http://primary\"");
+        assert_eq!(config.rpc.urls, vec!["http://primary"]);
+        assert_eq!(config.rpc.url(), "http://primary");
+    }
+
+    #[test]
+    fn rpc_urls_accepts_a_list() {
+        let config = config("[rpc]\nurls = [\"http://primary\", \"http://backup\"]");
+        assert_eq!(config.rpc.urls, vec!["http://primary", "http://backup"]);
+        assert_eq!(config.rpc.ws_url(), "ws://primary");
+    }
+
+    #[test]
+    fn subscriptions_read_at_the_configured_commitment() {
+        assert_eq!(config("").rpc.subscription_commitment(), CommitmentConfig::processed());
+        let config = config("[rpc]\nsubscribe_commitment = \"confirmed\"");
+        assert_eq!(config.rpc.subscription_commitment(), CommitmentConfig::confirmed());
+    }
+
+    #[test]
+    fn max_request_timeout_covers_the_slowest_endpoint() {
+        let config = config(
+            "[rpc]\nurl = \"http://primary\"\nrequest_timeout_ms = 2000\n\
+             [[rpc.fallback_endpoints]]\nurl = \"http://fast\"\ntimeout_ms = 500\n\
+             [[rpc.fallback_endpoints]]\nurl = \"http://slow\"\ntimeout_ms = 8000\n\
+             [[rpc.fallback_endpoints]]\nurl = \"http://default\"",
+        );
+        assert_eq!(config.rpc.max_request_timeout_ms(), 8000);
+        assert_eq!(RpcConfig::default().max_request_timeout_ms(), 30_000);
+    }
+
+    #[test]
+    fn rpc_defaults_to_mainnet_without_a_section() {
+        assert_eq!(config("").rpc.url(), "https://api.mainnet-beta.solana.com");
+    }
+
+    #[test]
+    fn same_pools_ignores_settings_that_keep_the_pool_set() {
+        let mint_config = config("").routing.mint_config_list.remove(0);
+        let mut tuned = mint_config.clone();
+        tuned.process_delay = 500;
+        tuned.slippage_bps = 100;
+        assert!(mint_config.same_pools(&tuned));
+
+        let mut relisted = mint_config.clone();
+        relisted.raydium_pool_list = Some(vec!["11111111111111111111111111111111".to_string()]);
+        assert!(!mint_config.same_pools(&relisted));
+
+        let mut filtered = mint_config.clone();
+        filtered.min_liquidity_lamports = Some(1);
+        assert!(!mint_config.same_pools(&filtered));
+    }
+
+    #[test]
+    fn dex_names_match_the_pool_lists() {
+        let mint_config = config("").routing.mint_config_list.remove(0);
+        let names: Vec<&str> = mint_config.pool_list_fields().iter().map(|(dex, _, _)| *dex).collect();
+        assert_eq!(names, DEX_NAMES);
+    }
+
+    #[test]
+    fn validate_rejects_unknown_dex_names() {
+        let mut config = config("[routing.dexes.raydium]\nenabled = false");
+        config.wallet.private_key = "key".to_string();
+        assert!(config.validate().is_ok());
+
+        config.routing.dexes.insert("raydum".to_string(), DexConfig { enabled: false });
+        let Err(BotError::Validation(message)) = config.validate() else {
+            panic!("unknown DEX name should fail validation");
+        };
+        assert!(message.contains("raydum"));
+    }
+
+    #[test]
+    fn metrics_listen_on_loopback_unless_configured() {
+        let metrics = config("[metrics]\nenabled = true").metrics.unwrap();
+        assert_eq!(metrics.socket_addr(), "127.0.0.1:9100".parse().unwrap());
+
+        let metrics = config("[metrics]\nenabled = true\nbind_address = \"0.0.0.0\"\nport = 9200")
+            .metrics
+            .unwrap();
+        assert_eq!(metrics.socket_addr(), "0.0.0.0:9200".parse().unwrap());
     }
 }
@@ -11,6 +11,9 @@ pub enum BotError {
     #[error("RPC client error: {0}")]
     Rpc(String),
 
+    #[error("Rate limited by RPC: {0}")]
+    RateLimited(String),
+
     #[error("Account fetch error: {0}")]
     AccountFetch(String),
 
@@ -48,7 +51,11 @@ pub type Result<T> = std::result::Result<T, BotError>;
 /// Convert solana_client errors to BotError
 impl From<solana_client::client_error::ClientError> for BotError {
     fn from(err: solana_client::client_error::ClientError) -> Self {
-        BotError::Rpc(err.to_string())
+        if crate::chain::rpc::is_rate_limited(&err) {
+            BotError::RateLimited(err.to_string())
+        } else {
+            BotError::Rpc(err.to_string())
+        }
     }
 }
 
@@ -0,0 +1,25 @@
+//! Pool accounts saved under `tests/fixtures`, in the JSON form printed by
+//! `solana account <address> --output json`
+
+use serde::Deserialize;
+use solana_account_decoder::UiAccount;
+use solana_sdk::{account::Account, pubkey::Pubkey};
+use std::{path::Path, str::FromStr};
+
+#[derive(Deserialize)]
+struct KeyedAccount {
+    pubkey: String,
+    account: UiAccount,
+}
+
+/// Address and account stored in `tests/fixtures/<name>`
+pub fn load_account(name: &str) -> (Pubkey, Account) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(name);
+    let json = std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+    let keyed: KeyedAccount = serde_json::from_str(&json).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+    let account = keyed
+        .account
+        .decode()
+        .unwrap_or_else(|| panic!("{}: account data is not base64", path.display()));
+    (Pubkey::from_str(&keyed.pubkey).unwrap(), account)
+}
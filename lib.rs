@@ -1,5 +1,18 @@
+// `ClientError` from solana-client is the Err type of every RPC read and is
+// large by design
+#![allow(clippy::result_large_err)]
+
+pub mod arbitrage;
 pub mod chain;
 pub mod config;
+#[path = "mod.rs"]
 pub mod dex;
 pub mod error;
 pub mod macros;
+pub mod metrics;
+pub mod pnl;
+pub mod scheduler;
+pub mod session;
+pub mod shutdown;
+pub mod signer;
+pub mod telemetry;
@@ -10,7 +10,7 @@ macro_rules! impl_dex {
             }
         }
 
-        impl crate::dex::traits::Dex for $struct_name {
+        impl $crate::dex::traits::Dex for $struct_name {
             fn name(&self) -> &'static str {
                 $name
             }
@@ -22,40 +22,45 @@ macro_rules! impl_dex {
     };
 }
 
-/// Macro to generate pool parsing boilerplate
+/// Macro to generate pool parsing boilerplate. Evaluates to a
+/// `Result<$pool_struct>`, with a `BotError::PoolParse` naming the field and
+/// offset when the account data is too short to hold it. Passing the whole
+/// account with `owner = <program id>` first checks it belongs to that program.
 #[macro_export]
 macro_rules! parse_pool_account {
+    ($account:expr, owner = $owner:expr, $pool_struct:ty, $offset_map:expr) => {{
+        let account: &solana_sdk::account::Account = &$account;
+        $crate::dex::traits::verify_pool_owner(account, &$owner)
+            .and_then(|()| $crate::parse_pool_account!(account.data, $pool_struct, $offset_map))
+    }};
     ($account_data:expr, $pool_struct:ty, $offset_map:expr) => {{
-        use std::collections::HashMap;
+        let data: &[u8] = &$account_data;
         let mut pool_info = <$pool_struct>::default();
+        let mut result = Ok(());
 
         for (field_name, offset) in $offset_map.iter() {
-            match *field_name {
-                "token_mint" => {
-                    pool_info.token_mint = solana_sdk::pubkey::Pubkey::new_from_array(
-                        $account_data[*offset..*offset + 32].try_into().unwrap()
-                    );
-                }
-                "base_mint" => {
-                    pool_info.base_mint = solana_sdk::pubkey::Pubkey::new_from_array(
-                        $account_data[*offset..*offset + 32].try_into().unwrap()
-                    );
-                }
-                "token_vault" => {
-                    pool_info.token_vault = solana_sdk::pubkey::Pubkey::new_from_array(
-                        $account_data[*offset..*offset + 32].try_into().unwrap()
-                    );
+            let field = match *field_name {
+                "token_mint" => &mut pool_info.token_mint,
+                "base_mint" => &mut pool_info.base_mint,
+                "token_vault" => &mut pool_info.token_vault,
+                "base_vault" => &mut pool_info.base_vault,
+                _ => continue,
+            };
+            match data.get(*offset..offset.saturating_add(32)) {
+                Some(bytes) => *field = solana_sdk::pubkey::Pubkey::new_from_array(bytes.try_into().unwrap()),
+                None => {
+                    result = Err($crate::error::BotError::PoolParse(format!(
+                        "Account data is {} bytes, too short for {} at offset {}",
+                        data.len(),
+                        field_name,
+                        offset
+                    )));
+                    break;
                 }
-                "base_vault" => {
-                    pool_info.base_vault = solana_sdk::pubkey::Pubkey::new_from_array(
-                        $account_data[*offset..*offset + 32].try_into().unwrap()
-                    );
-                }
-                _ => {}
             }
         }
 
-        pool_info
+        result.map(|()| pool_info)
     }};
 }
 
@@ -67,16 +72,20 @@ macro_rules! handle_dex_error {
             Ok(value) => Ok(value),
             Err(e) => {
                 tracing::error!("{} {} failed: {}", $dex_name, $operation, e);
-                Err(crate::error::BotError::Dex(format!("{} {} error: {}", $dex_name, $operation, e)))
+                Err($crate::error::BotError::Dex(format!("{} {} error: {}", $dex_name, $operation, e)))
             }
         }
     };
 }
 
-/// Macro to generate retry logic for RPC calls
+/// Macro to generate retry logic for RPC calls, backing off exponentially from
+/// `delay_ms` up to `max_delay_ms` (30s when omitted)
 #[macro_export]
 macro_rules! retry_rpc_call {
-    ($rpc_client:expr, $call:expr, $max_retries:expr, $delay_ms:expr) => {{
+    ($rpc_client:expr, $call:expr, $max_retries:expr, $delay_ms:expr) => {
+        $crate::retry_rpc_call!($rpc_client, $call, $max_retries, $delay_ms, 30_000)
+    };
+    ($rpc_client:expr, $call:expr, $max_retries:expr, $delay_ms:expr, $max_delay_ms:expr) => {{
         let mut last_error = None;
         for attempt in 0..$max_retries {
             match $call {
@@ -84,12 +93,83 @@ macro_rules! retry_rpc_call {
                 Err(e) => {
                     last_error = Some(e);
                     if attempt < $max_retries - 1 {
-                        tracing::warn!("RPC call failed (attempt {}/{}), retrying in {}ms", attempt + 1, $max_retries, $delay_ms);
-                        tokio::time::sleep(tokio::time::Duration::from_millis($delay_ms)).await;
+                        let delay_ms = $crate::chain::rpc::backoff_delay_ms($delay_ms, attempt as u32, $max_delay_ms);
+                        tracing::warn!("RPC call failed (attempt {}/{}), retrying in {}ms", attempt + 1, $max_retries, delay_ms);
+                        tokio::time::sleep(tokio::time::Duration::from_millis(delay_ms)).await;
                     }
                 }
             }
         }
-        Err(crate::error::BotError::Rpc(format!("RPC call failed after {} attempts: {:?}", $max_retries, last_error)))
+        Err($crate::error::BotError::Rpc(format!("RPC call failed after {} attempts: {:?}", $max_retries, last_error)))
     }};
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::error::BotError;
+    use solana_sdk::{account::Account, pubkey::Pubkey};
+
+    #[derive(Debug, Default)]
+    struct Keys {
+        token_mint: Pubkey,
+        base_mint: Pubkey,
+        token_vault: Pubkey,
+        base_vault: Pubkey,
+    }
+
+    const OFFSETS: [(&str, usize); 4] = [("token_mint", 8), ("base_mint", 40), ("token_vault", 72), ("base_vault", 104)];
+
+    fn keyed_data(len: usize) -> (Vec<u8>, [Pubkey; 4]) {
+        let keys = [Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique()];
+        let mut data = vec![0u8; len];
+        for ((_, offset), key) in OFFSETS.iter().zip(&keys) {
+            if let Some(bytes) = data.get_mut(*offset..offset + 32) {
+                bytes.copy_from_slice(key.as_ref());
+            }
+        }
+        (data, keys)
+    }
+
+    #[test]
+    fn parse_pool_account_reads_each_field_at_its_offset() {
+        let (data, keys) = keyed_data(136);
+
+        let parsed = crate::parse_pool_account!(data, Keys, OFFSETS).unwrap();
+
+        assert_eq!(
+            [parsed.token_mint, parsed.base_mint, parsed.token_vault, parsed.base_vault],
+            keys
+        );
+    }
+
+    #[test]
+    fn parse_pool_account_rejects_truncated_data() {
+        let (data, _) = keyed_data(135);
+
+        match crate::parse_pool_account!(data, Keys, OFFSETS) {
+            Err(BotError::PoolParse(message)) => {
+                assert!(message.contains("135 bytes"), "{}", message);
+                assert!(message.contains("base_vault at offset 104"), "{}", message);
+            }
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parse_pool_account_rejects_empty_data() {
+        let result = crate::parse_pool_account!(Vec::<u8>::new(), Keys, OFFSETS);
+
+        assert!(matches!(result, Err(BotError::PoolParse(message)) if message.contains("token_mint at offset 8")));
+    }
+
+    #[test]
+    fn parse_pool_account_checks_the_owner_first() {
+        let program = Pubkey::new_unique();
+        let (data, _) = keyed_data(136);
+        let account = Account { data, owner: Pubkey::new_unique(), ..Account::default() };
+
+        let result = crate::parse_pool_account!(account, owner = program, Keys, OFFSETS);
+
+        assert!(matches!(result, Err(BotError::Validation(_))));
+    }
+}
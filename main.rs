@@ -1,151 +1,1048 @@
+use anyhow::{anyhow, Result};
+use clap::{Parser, Subcommand};
+use solana_client::rpc_client::RpcClient;
 use solana_mev_bot::{
+    arbitrage::{
+        cap_input_to_balance, estimated_fee_lamports, find_cycles_in_store, optimal_input, rank_routes,
+        swappable_pools, ArbRoute, ArbitrageOpportunity, OpportunityEvaluator, OpportunityQueue, RouteDeduplicator,
+        RouteSpec, TradeCosts,
+    },
     chain::{
-        token_fetch::{TokenFetchConfig, TokenFetcher},
-        token_price::{MarketDataFetcher, PriceMonitor},
+        blockhash::BlockhashCache,
+        constants::sol_mint,
+        flashloan::Flashloan,
+        pool_filter::PoolFilter,
+        pools::MintPoolData,
+        price_store::PriceStore,
+        rpc::RpcPool,
+        simulate::simulate_profit,
+        submit::{jito, submit, SendingEndpoint, Submission},
+        subscribe::PoolSubscriber,
+        token_fetch::{build_dex_registry, TokenFetchConfig, TokenFetcher},
+        transaction::{
+            build_arb_transaction, load_lookup_tables, missing_ata_instructions, profit_fee_instruction,
+            profit_fee_lamports, route_compute_unit_price_for, RouteHop,
+        },
+        wsol::{self, WalletBalance, WsolUnwrapper},
     },
-    config::Config,
+    config::{Config, MintConfig},
+    dex::{Dex, DexRegistry, PoolInfo, PoolType},
+    metrics::{self, metrics},
+    pnl::{PnlTracker, DEFAULT_MAX_TRADES},
+    scheduler::ScanScheduler,
+    session::{self, ShutdownReport},
+    shutdown::Shutdown,
+    signer::{load_signer, WalletPool},
+    telemetry,
 };
-use solana_client::rpc_client::RpcClient;
-use solana_sdk::{signature::Keypair, signer::Signer};
-use std::sync::Arc;
-use tracing_subscriber::{EnvFilter, FmtSubscriber};
+use solana_sdk::{
+    address_lookup_table::AddressLookupTableAccount, hash::Hash, instruction::Instruction, pubkey::Pubkey,
+    signer::Signer, transaction::VersionedTransaction,
+};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+use tracing::debug;
+
+/// Longest cycle, in swaps, the scan searches for
+const MAX_ROUTE_HOPS: usize = 3;
+
+/// Most cycles of a mint sized and quoted per scan, best spot rate first
+const MAX_ROUTES_PER_MINT: usize = 3;
+
+/// How long the loop waits for a vault update before checking for due mints again
+const IDLE_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// How long a fetched blockhash is signed with before a fresh one is fetched,
+/// well within the ~60s it stays valid
+const BLOCKHASH_MAX_AGE: Duration = Duration::from_secs(20);
+
+/// Solana DEX arbitrage bot
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    /// Config file to load instead of searching the working directory
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Discover and print the pools configured for a mint
+    Fetch { mint: String },
+    /// Search the DEX programs for every pool trading a mint
+    Discover {
+        mint: String,
+        /// Only search this DEX, by its config name
+        #[arg(long)]
+        dex: Option<String>,
+    },
+    /// Print the output of swapping `amount` of the pool's base mint for the
+    /// configured token through a pool from the routing config
+    Quote {
+        pool: String,
+        amount: u64,
+        /// Sell the configured token for the base mint instead
+        #[arg(long)]
+        sell: bool,
+    },
+    /// Run the arbitrage scan over the configured mints (the default)
+    Scan,
+    /// Build, sign and submit a pre-built route from a JSON file
+    Send { route: PathBuf },
+    /// Simulate a pre-built route from a JSON file without sending it, and
+    /// compare its simulated profit with the quote
+    ExplainSwap {
+        route: PathBuf,
+        /// Also print the change of every account the transaction touches,
+        /// and its logs
+        #[arg(long)]
+        verbose: bool,
+    },
+}
+
+/// What `send` does with the route it loads
+enum RouteAction {
+    Submit,
+    Explain { verbose: bool },
+}
 
 #[tokio::main]
 async fn main() {
-    let subscriber = FmtSubscriber::builder()
-        .with_env_filter(EnvFilter::from_default_env())
-        .with_line_number(true)
-        .finish();
-    tracing::subscriber::set_global_default(subscriber).expect("setting default subscriber failed");
+    let cli = Cli::parse();
 
     // Load configuration from environment variables and config files
-    let config = match Config::load() {
+    let loaded = match &cli.config {
+        Some(path) => Config::load_from(path),
+        None => Config::load(),
+    };
+    let config = match loaded {
         Ok(config) => config,
+        // Nothing has run yet, so there is no session to report on
         Err(e) => {
             eprintln!("Failed to load configuration: {}", e);
-            return;
+            std::process::exit(1);
         }
     };
 
+    let _telemetry = telemetry::init(config.telemetry.as_ref());
+    metrics::spawn(config.metrics.as_ref());
+
+    // Print the session summary on every exit path, including errors
+    let _report = ShutdownReport::new(config.report.clone().unwrap_or_default());
+
     println!("Configuration loaded successfully!");
-    println!("RPC URL: {}", config.rpc.url);
+    println!("RPC URLs: {}", config.rpc.urls.join(", "));
     println!("Compute unit limit: {}", config.bot.compute_unit_limit);
+    if config.bot.dry_run {
+        println!("Dry run: transactions will be simulated and logged, not sent");
+    }
 
-    // Parse wallet private key and derive wallet address
-    let wallet_keypair = Keypair::from_base58_string(&config.wallet.private_key);
-    
-    let wallet_address = wallet_keypair.pubkey().to_string();
-    println!("Wallet address: {}", wallet_address);
+    // The file a running scan watches for config changes
+    let config_path = cli
+        .config
+        .clone()
+        .or_else(|| Config::default_file().map(Path::to_path_buf));
+    let result = match cli.command.unwrap_or(Command::Scan) {
+        Command::Fetch { mint } => fetch(&config, &mint).await,
+        Command::Discover { mint, dex } => discover(&config, &mint, dex.as_deref()).await,
+        Command::Quote { pool, amount, sell } => quote(&config, &pool, amount, sell).await,
+        Command::Scan => {
+            let shutdown = Shutdown::new();
+            shutdown.listen(Duration::from_secs(config.bot.shutdown_timeout_secs));
+            scan(&config, config_path, &shutdown).await
+        }
+        Command::Send { route } => send(&config, &route, RouteAction::Submit).await,
+        Command::ExplainSwap { route, verbose } => send(&config, &route, RouteAction::Explain { verbose }).await,
+    };
+    if let Err(e) = result {
+        eprintln!("{}", e);
+    }
+}
 
-    // Initialize RPC client
-    let rpc_client = Arc::new(RpcClient::new(config.rpc.url.clone()));
+/// Run the arbitrage loop until `shutdown` is requested. Each due mint's
+/// pools are priced into a shared `PriceStore`, cycles through them are
+/// detected and quoted against fresh reserves, and the best profitable one
+/// is built and submitted. Mints are rescanned on their schedule, or as soon
+/// as a streamed vault update moves one of their pools. Changes to the file at
+/// `config_path` are applied between passes.
+async fn scan(config: &Config, config_path: Option<PathBuf>, shutdown: &Shutdown) -> Result<()> {
+    let wallets = WalletPool::from_config(&config.wallet)?;
+    for pubkey in wallets.pubkeys() {
+        println!("Wallet address: {}", pubkey);
+    }
 
-    // Initialize enhanced token fetcher
-    let token_fetch_config = TokenFetchConfig {
-        max_retries: 3,
-        retry_delay_ms: 1000,
-        batch_size: 10,
-        timeout_seconds: 30,
-        enable_caching: true,
-        cache_ttl_seconds: 300,
+    let rpc_pool = Arc::new(RpcPool::from_config(&config.rpc)?);
+    let rpc_client = rpc_pool.primary();
+    let token_fetch_config = token_fetch_config(config);
+    let dex_registry = build_dex_registry(rpc_pool.clone(), &token_fetch_config);
+    let flashloan = match config.flashloan.as_ref().filter(|f| f.enabled) {
+        Some(flashloan_config) => Some(Flashloan::load(&rpc_client, flashloan_config)?),
+        None => None,
     };
 
-    let mut token_fetcher = TokenFetcher::new(rpc_client.clone(), token_fetch_config);
+    let prices = PriceStore::new();
+    let (moved, mut moved_mints) = unbounded_channel();
+    let mut scanner = Scanner {
+        config: config.clone(),
+        subscriber: pool_subscriber(config, rpc_client.clone(), prices.clone()),
+        token_fetcher: TokenFetcher::new(rpc_pool.clone(), token_fetch_config),
+        blockhash_cache: BlockhashCache::new(rpc_client.clone(), BLOCKHASH_MAX_AGE),
+        senders: SendingEndpoint::from_config(config)?,
+        rpc_client,
+        dex_registry: &dex_registry,
+        wallets,
+        flashloan,
+        prices,
+        pnl: pnl_tracker(config),
+        dedup: RouteDeduplicator::from_config(&config.bot),
+        moved,
+        markets: HashMap::new(),
+    };
+    let mut scheduler = ScanScheduler::from_config(&config.bot, &config.routing.mint_config_list);
+    let mut rescan = HashSet::new();
+    let mut reloads = config_path.map(Config::watch);
 
-    // Initialize market data fetcher
-    let mut market_fetcher = MarketDataFetcher::new(rpc_client.clone());
+    while !shutdown.is_requested() {
+        let mut mints = scheduler.due_mints();
+        for mint in rescan.drain() {
+            if !mints.contains(&mint) {
+                mints.push(mint);
+            }
+        }
+        if !mints.is_empty() {
+            scanner.scan_pass(&mints, &mut scheduler, shutdown).await;
+        }
 
-    // Initialize price monitor
-    let mut price_monitor = PriceMonitor::new(rpc_client, 5000, 0.5); // 5 second intervals, 0.5% threshold
+        tokio::select! {
+            Some(mint) = moved_mints.recv() => {
+                rescan.insert(mint);
+            }
+            Some(reloaded) = next_reload(&mut reloads) => {
+                scanner.reload(reloaded, &mut scheduler);
+            }
+            _ = tokio::time::sleep(IDLE_POLL_INTERVAL) => {}
+        }
+        while let Ok(mint) = moved_mints.try_recv() {
+            rescan.insert(mint);
+        }
+    }
 
-    // Process each mint configuration
-    for mint_config in &config.routing.mint_config_list {
-        println!("\nProcessing mint: {}", mint_config.mint);
+    println!("Shutdown requested, stopping the scan loop");
+    scanner.finish();
+    Ok(())
+}
+
+/// The next config from `Config::watch`, or never without a watched file
+async fn next_reload(reloads: &mut Option<UnboundedReceiver<Config>>) -> Option<Config> {
+    match reloads {
+        Some(reloads) => reloads.recv().await,
+        None => std::future::pending().await,
+    }
+}
 
-        // Fetch pool data using enhanced token fetcher
-        match token_fetcher
+/// State the scan loop keeps across passes
+struct Scanner<'a> {
+    config: Config,
+    rpc_client: Arc<RpcClient>,
+    blockhash_cache: BlockhashCache,
+    /// Clients of the spam endpoints, built once for the whole run
+    senders: Vec<SendingEndpoint>,
+    dex_registry: &'a DexRegistry,
+    token_fetcher: TokenFetcher,
+    /// Wallets trades rotate through; pool data is loaded for the first
+    wallets: WalletPool,
+    flashloan: Option<Flashloan>,
+    prices: PriceStore,
+    pnl: PnlTracker,
+    /// Routes sent recently, so a cycle is not resent against the same reserves
+    dedup: RouteDeduplicator,
+    subscriber: PoolSubscriber,
+    /// Mints whose streamed reserves moved, to rescan ahead of their schedule
+    moved: UnboundedSender<String>,
+    /// Pools of each mint loaded for routing, with the DEX that owns them
+    markets: HashMap<String, Vec<(&'a dyn Dex, PoolInfo)>>,
+}
+
+impl<'a> Scanner<'a> {
+    /// Scan `mints` in order, stopping early once `shutdown` is requested
+    async fn scan_pass(&mut self, mints: &[String], scheduler: &mut ScanScheduler, shutdown: &Shutdown) {
+        let scan_timer = metrics().scan_seconds.start_timer();
+        self.token_fetcher.begin_scan_pass();
+        for mint in mints {
+            if shutdown.is_requested() {
+                println!("Shutdown requested, skipping the remaining mints");
+                break;
+            }
+            let Some(mint_config) = self
+                .config
+                .routing
+                .mint_config_list
+                .iter()
+                .find(|mc| mc.mint == *mint)
+                .cloned()
+            else {
+                continue;
+            };
+            scheduler.mark_scanned(mint);
+            if let Err(e) = self.scan_mint(&mint_config, scheduler).await {
+                println!("Scan of mint {} failed: {}", mint, e);
+            }
+        }
+        scan_timer.observe_duration();
+        self.token_fetcher.persist_cache();
+        debug!("RPC calls this scan pass: {}", self.token_fetcher.rpc_calls_this_pass());
+
+        // Unwrap accumulated WSOL once the pass is over
+        if let Some(threshold) = self.config.bot.wsol_unwrap_threshold_lamports.filter(|_| !self.config.bot.dry_run) {
+            let unwrapper = WsolUnwrapper::new(threshold);
+            for wallet in self.wallets.signers() {
+                if let Err(e) = unwrapper.maybe_unwrap(&self.rpc_client, wallet.as_ref()) {
+                    println!("Failed to unwrap WSOL of {}: {}", wallet.pubkey(), e);
+                }
+            }
+        }
+    }
+
+    /// Detect the cycles through one mint's pools and quote them against
+    /// fresh reserves for the mint's next wallet, then submit the first that
+    /// still lands. Quoted routes queue with the blockhash they will be signed
+    /// over, and any whose blockhash expires while earlier ones are being sent
+    /// is dropped unsent.
+    async fn scan_mint(&mut self, mint_config: &MintConfig, scheduler: &mut ScanScheduler) -> Result<()> {
+        let pool_filter = PoolFilter::new(mint_config.pinned_pools.as_ref(), self.config.bot.max_pools_per_mint)?
+            .with_disabled_pools(&mint_config.disabled_pools)?
+            .with_min_liquidity(mint_config.min_liquidity_lamports);
+        let pool_data = self
+            .token_fetcher
             .initialize_pool_data(
                 &mint_config.mint,
-                &wallet_address, // Use derived wallet address
-                mint_config.raydium_pool_list.as_ref(),
-                mint_config.raydium_cp_pool_list.as_ref(),
-                mint_config.pump_pool_list.as_ref(),
-                mint_config.meteora_dlmm_pool_list.as_ref(),
-                mint_config.whirlpool_pool_list.as_ref(),
-                mint_config.raydium_clmm_pool_list.as_ref(),
-                mint_config.meteora_damm_pool_list.as_ref(),
-                mint_config.solfi_pool_list.as_ref(),
-                mint_config.meteora_damm_v2_pool_list.as_ref(),
-                mint_config.vertigo_pool_list.as_ref(),
+                &self.wallets.primary().pubkey().to_string(),
+                &mint_config.pool_lists(),
+                &pool_filter,
             )
-            .await
-        {
-            Ok(pool_data) => {
-                println!("Successfully loaded pool data for mint: {}", mint_config.mint);
-                println!("  - Raydium pools: {}", pool_data.raydium_pools.len());
-                println!("  - Pump pools: {}", pool_data.pump_pools.len());
-                println!("  - Whirlpool pools: {}", pool_data.whirlpool_pools.len());
-
-                // Fetch token price
-                match market_fetcher.fetch_token_price(&mint_config.mint).await {
-                    Ok(price) => {
-                        println!(
-                            "Token price: ${:.6} USD, {:.6} SOL (source: {})",
-                            price.price_usd, price.price_sol, price.source
-                        );
-                    }
-                    Err(e) => {
-                        println!("Failed to fetch token price: {}", e);
-                    }
+            .await?;
+        session::stats().record_pools_scanned(pool_data.pool_count());
+        if !scheduler.record_pool_count(&mint_config.mint, pool_data.pool_count()) {
+            return Ok(());
+        }
+
+        let current_slot = self.rpc_client.get_slot()?;
+        match self.markets.get(&mint_config.mint) {
+            Some(pools) => self.refresh_prices(pools, current_slot).await,
+            None => {
+                let pools = self.load_market(&pool_data, current_slot).await;
+                self.markets.insert(mint_config.mint.clone(), pools);
+            }
+        }
+        let pools = self.markets[&mint_config.mint].clone();
+        let deepest = pools
+            .iter()
+            .filter_map(|(_, pool)| self.prices.get(&pool.pool_address))
+            .max_by_key(|(price, _)| price.liquidity);
+        if let Some((price, _)) = deepest {
+            scheduler.record_price(&mint_config.mint, price.price);
+        }
+
+        let pool_infos = swappable_pools(&pools);
+        let min_slot = current_slot.saturating_sub(self.config.bot.max_slot_lag);
+        let routes = find_cycles_in_store(&pool_infos, &self.prices, &sol_mint(), MAX_ROUTE_HOPS, min_slot);
+        session::stats().record_opportunities(routes.len());
+        metrics().opportunities_found.inc_by(routes.len() as u64);
+        if routes.is_empty() {
+            return Ok(());
+        }
+        println!("Found {} cycles through mint {}", routes.len(), mint_config.mint);
+
+        let wallet_signer = self.wallets.next();
+        let mut queue = OpportunityQueue::new();
+        for route in routes.iter().take(MAX_ROUTES_PER_MINT) {
+            match self.quote(mint_config, route, &pools, wallet_signer.as_ref(), current_slot).await {
+                Ok(opportunity) => queue.push(opportunity),
+                Err(e) => println!("Skipping route through {:?}: {}", route.pools, e),
+            }
+        }
+        while let Some(opportunity) = queue.pop_valid(self.rpc_client.get_block_height()?) {
+            let route = &opportunity.route.route;
+            match self.trade(mint_config, &opportunity, &pools, wallet_signer.as_ref()).await {
+                Ok(submission) => {
+                    println!("Submitted route through {:?}: {:?}", route.pools, submission);
+                    record_pnl(&mut self.pnl, &self.rpc_client, &submission, &mint_config.mint);
+                    break;
                 }
+                Err(e) => println!("Skipping route through {:?}: {}", route.pools, e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Load the `PoolInfo` of every pool in `pool_data` and price them as of
+    /// `slot`, then stream the vaults of its reserve-priced pools so moves
+    /// trigger a rescan of the mint. Pricing first gives the stream each
+    /// pool's fee. Without a stream the mint is still scanned on its schedule.
+    async fn load_market(&self, pool_data: &MintPoolData, slot: u64) -> Vec<(&'a dyn Dex, PoolInfo)> {
+        let dex_registry = self.dex_registry;
+        let mut by_dex: HashMap<&'static str, Vec<String>> = HashMap::new();
+        for (dex_name, pool) in pool_data.pool_addresses() {
+            by_dex.entry(dex_name).or_default().push(pool.to_string());
+        }
 
-                // Calculate arbitrage opportunities
-                match market_fetcher
-                    .calculate_arbitrage_opportunities(&pool_data)
-                    .await
-                {
-                    Ok(opportunities) => {
-                        if opportunities.is_empty() {
-                            println!("No significant arbitrage opportunities found");
-                        } else {
-                            println!("Found {} arbitrage opportunities:", opportunities.len());
-                            for (i, opp) in opportunities.iter().enumerate() {
-                                println!(
-                                    "  {}. {}: Buy on {} at {:.6}, Sell on {} at {:.6} ({}% profit)",
-                                    i + 1,
-                                    opp.token_mint,
-                                    opp.best_buy_dex,
-                                    opp.best_buy_price,
-                                    opp.best_sell_dex,
-                                    opp.best_sell_price,
-                                    opp.potential_profit_percent
-                                );
-                            }
+        let mut pools = Vec::new();
+        for (dex_name, addresses) in by_dex {
+            let Some(dex) = dex_registry.get(dex_name) else {
+                continue;
+            };
+            match dex.fetch_pools(&addresses, &pool_data.mint).await {
+                Ok(fetched) => pools.extend(fetched.into_iter().map(|pool| (dex, pool))),
+                Err(e) => println!("Failed to load {} pools for mint {}: {}", dex_name, pool_data.mint, e),
+            }
+        }
+        self.refresh_prices(&pools, slot).await;
+
+        match self.subscriber.subscribe(pool_data) {
+            Ok(mut updates) => {
+                let moved = self.moved.clone();
+                let mint = pool_data.mint.to_string();
+                tokio::spawn(async move {
+                    while updates.recv().await.is_some() {
+                        if moved.send(mint.clone()).is_err() {
+                            break;
                         }
                     }
-                    Err(e) => {
-                        println!("Failed to calculate arbitrage opportunities: {}", e);
+                });
+            }
+            Err(e) => println!("Failed to stream vaults for mint {}: {}", pool_data.mint, e),
+        }
+        pools
+    }
+
+    /// Price every pool into the store as of `slot`
+    async fn refresh_prices(&self, pools: &[(&dyn Dex, PoolInfo)], slot: u64) {
+        for (dex, pool) in pools {
+            match dex.calculate_price(pool).await {
+                Ok(price) => self.prices.update(pool.pool_address, price, slot),
+                Err(e) => debug!("Failed to price {} pool {}: {}", dex.name(), pool.pool_address, e),
+            }
+        }
+    }
+
+    /// Size `route` for the current reserves of its pools and the balance of
+    /// `wallet_signer`, and quote it hop by hop. Routes with a pool whose
+    /// reserves lag `current_slot` by more than `max_slot_lag` are rejected,
+    /// as are routes already sent within `bot.dedup_window_ms` whose reserves
+    /// have not materially moved since.
+    async fn quote(
+        &mut self,
+        mint_config: &MintConfig,
+        route: &ArbRoute,
+        pools: &[(&dyn Dex, PoolInfo)],
+        wallet_signer: &dyn Signer,
+        current_slot: u64,
+    ) -> Result<ArbitrageOpportunity> {
+        let hops = route_hops(route, pools)?;
+        let reserves = hop_reserves(route, &hops, current_slot, self.config.bot.max_slot_lag).await?;
+        if !self.dedup.should_send(route, &reserves) {
+            return Err(anyhow!("Already sent against the same reserves"));
+        }
+        let mut amount_in = optimal_input(route, &reserves);
+        if amount_in == 0 {
+            return Err(anyhow!("No input size is profitable at the current reserves"));
+        }
+        // Without a flash loan the wallet funds the input itself
+        if self.flashloan.is_none() {
+            let balance = WalletBalance::fetch(&self.rpc_client, &wallet_signer.pubkey())?;
+            amount_in = cap_input_to_balance(
+                amount_in,
+                &balance,
+                self.config.bot.fee_buffer_lamports,
+                hops[0].dex.min_viable_input(hops[0].pool),
+                self.config.bot.wsol_unwrap_threshold_lamports.is_none(),
+            )?;
+        }
+
+        let ranked = rank_routes(vec![route.clone()], pools, amount_in, 1)
+            .await
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("Route does not quote a profit for an input of {}", amount_in))?;
+        let (blockhash, last_valid_block_height) = self.blockhash_cache.get_with_expiry()?;
+        Ok(ArbitrageOpportunity {
+            mint: Pubkey::from_str(&mint_config.mint)?,
+            route: ranked,
+            reserves,
+            blockhash,
+            last_valid_block_height,
+        })
+    }
+
+    /// Build, sign and submit a quoted `opportunity` from `wallet_signer`,
+    /// the wallet it was sized for
+    async fn trade(
+        &mut self,
+        mint_config: &MintConfig,
+        opportunity: &ArbitrageOpportunity,
+        pools: &[(&dyn Dex, PoolInfo)],
+        wallet_signer: &dyn Signer,
+    ) -> Result<Submission> {
+        let ranked = &opportunity.route;
+        let hops = route_hops(&ranked.route, pools)?;
+        let quoted = ArbRoute {
+            estimated_profit_lamports: ranked.net_profit,
+            ..ranked.route.clone()
+        };
+        let submission = execute_route(
+            &self.config,
+            mint_config,
+            &self.rpc_client,
+            opportunity.blockhash,
+            &self.blockhash_cache,
+            &self.senders,
+            wallet_signer,
+            self.flashloan.as_ref(),
+            &quoted,
+            &hops,
+            &ranked.amounts,
+        )
+        .await?;
+        self.dedup.record_sent(&ranked.route, &opportunity.reserves);
+        Ok(submission)
+    }
+
+    /// Scan with a reloaded config from the next pass on. Mints whose pool
+    /// selection changed have their pools loaded again; RPC, wallet and fetch
+    /// settings only change on restart.
+    fn reload(&mut self, config: Config, scheduler: &mut ScanScheduler) {
+        for mint_config in &config.routing.mint_config_list {
+            let unchanged = self
+                .config
+                .routing
+                .mint_config_list
+                .iter()
+                .any(|old| old.mint == mint_config.mint && old.same_pools(mint_config));
+            if !unchanged {
+                self.markets.remove(&mint_config.mint);
+                self.token_fetcher.forget_mint(&mint_config.mint);
+            }
+        }
+        self.markets
+            .retain(|mint, _| config.routing.mint_config_list.iter().any(|mc| mc.mint == *mint));
+        scheduler.reconfigure(&config.routing.mint_config_list);
+        println!("Config reloaded: scanning {} mints", config.routing.mint_config_list.len());
+        self.config = config;
+    }
+
+    /// Close the vault streams, save the pool cache and PnL log and unwrap
+    /// any WSOL left
+    fn finish(&mut self) {
+        self.subscriber.close();
+        self.token_fetcher.persist_cache();
+        if let Err(e) = self.pnl.flush() {
+            println!("Failed to flush PnL log: {}", e);
+        }
+        let pnl = self.pnl.stats();
+        println!(
+            "PnL: {} trades, {} lamports net, {} lamports fees, {:.1}% won",
+            pnl.trades,
+            pnl.total_profit,
+            pnl.total_fees,
+            pnl.win_rate * 100.0
+        );
+        let cache_stats = self.token_fetcher.cache_stats();
+        println!(
+            "Cache: {} pool entries, {} accounts ({} hits, {} misses)",
+            cache_stats.pool_entries,
+            cache_stats.account_entries,
+            cache_stats.account_hits,
+            cache_stats.account_misses
+        );
+
+        // Final unwrap so no WSOL is left behind on shutdown
+        if self.config.bot.wsol_unwrap_threshold_lamports.is_some() && !self.config.bot.dry_run {
+            for wallet in self.wallets.signers() {
+                match wsol::wsol_balance(&self.rpc_client, &wallet.pubkey()) {
+                    Ok(balance) if balance > 0 => {
+                        if let Err(e) = wsol::unwrap_all(&self.rpc_client, wallet.as_ref()) {
+                            println!("Failed to unwrap WSOL of {} on shutdown: {}", wallet.pubkey(), e);
+                        }
                     }
+                    Ok(_) => {}
+                    Err(e) => println!("Failed to read WSOL balance of {}: {}", wallet.pubkey(), e),
                 }
             }
-            Err(e) => {
-                println!("Failed to load pool data for mint {}: {}", mint_config.mint, e);
+        }
+    }
+}
+
+/// The hop through each pool of `route`, from the pools loaded for its mint
+fn route_hops<'p>(route: &ArbRoute, pools: &'p [(&'p dyn Dex, PoolInfo)]) -> Result<Vec<RouteHop<'p>>> {
+    route
+        .pools
+        .iter()
+        .map(|address| {
+            pools
+                .iter()
+                .find(|(_, pool)| pool.pool_address == *address)
+                .map(|(dex, pool)| RouteHop { dex: *dex, pool })
+                .ok_or_else(|| anyhow!("Route uses unknown pool {}", address))
+        })
+        .collect()
+}
+
+/// Current `(reserve_in, reserve_out)` of each hop, in trade direction.
+/// Fails if any pool's reserves were read more than `max_slot_lag` slots
+/// before `current_slot`.
+async fn hop_reserves(
+    route: &ArbRoute,
+    hops: &[RouteHop<'_>],
+    current_slot: u64,
+    max_slot_lag: u64,
+) -> Result<Vec<(u64, u64)>> {
+    let mut reserves = Vec::with_capacity(hops.len());
+    for (hop, from) in hops.iter().zip(&route.mints) {
+        let pool_reserves = hop.dex.fetch_reserves(hop.pool).await?;
+        pool_reserves.ensure_fresh(current_slot, max_slot_lag)?;
+        reserves.push(if hop.pool.base_mint == *from {
+            (pool_reserves.base_reserve, pool_reserves.token_reserve)
+        } else {
+            (pool_reserves.token_reserve, pool_reserves.base_reserve)
+        });
+    }
+    Ok(reserves)
+}
+
+/// Load the pools configured for `mint` and print them by DEX
+async fn fetch(config: &Config, mint: &str) -> Result<()> {
+    let mint_config = mint_config(config, mint)?;
+    let wallet_signer = load_signer(&config.wallet)?;
+    let rpc_pool = Arc::new(RpcPool::from_config(&config.rpc)?);
+    let mut token_fetcher = TokenFetcher::new(rpc_pool, token_fetch_config(config));
+    let pool_filter = PoolFilter::new(mint_config.pinned_pools.as_ref(), config.bot.max_pools_per_mint)?
+        .with_disabled_pools(&mint_config.disabled_pools)?
+        .with_min_liquidity(mint_config.min_liquidity_lamports);
+
+    let pool_data = token_fetcher
+        .initialize_pool_data(
+            &mint_config.mint,
+            &wallet_signer.pubkey().to_string(),
+            &mint_config.pool_lists(),
+            &pool_filter,
+        )
+        .await?;
+
+    println!("{} pools for mint {}:", pool_data.pool_count(), mint);
+    for (dex, pool) in pool_data.pool_addresses() {
+        println!("  {:<16} {}", dex, pool);
+    }
+    Ok(())
+}
+
+/// Find pools trading `mint` on one DEX, or on every DEX that supports discovery
+async fn discover(config: &Config, mint: &str, dex_name: Option<&str>) -> Result<()> {
+    let mint = Pubkey::from_str(mint)?;
+    let rpc_pool = Arc::new(RpcPool::from_config(&config.rpc)?);
+    let dex_registry = build_dex_registry(rpc_pool.clone(), &token_fetch_config(config));
+    let token_fetcher = TokenFetcher::new(rpc_pool, token_fetch_config(config));
+
+    let dexes = match dex_name {
+        Some(name) => vec![dex_registry
+            .get(name)
+            .ok_or_else(|| anyhow!("No DEX registered as {}", name))?],
+        None => dex_registry
+            .all_dexes()
+            .into_iter()
+            .filter(|dex| !dex.mint_offsets().is_empty())
+            .collect(),
+    };
+
+    for dex in dexes {
+        match token_fetcher.discover_pools(dex, &mint).await {
+            Ok(pools) => {
+                for pool in pools {
+                    println!("  {:<16} {}", dex.name(), pool.pool_address);
+                }
             }
+            Err(e) => eprintln!("Failed to discover {} pools: {}", dex.name(), e),
         }
     }
+    Ok(())
+}
 
-    // Start price monitoring (this would run indefinitely in a real bot)
-    println!("\nStarting price monitoring...");
-    let mints: Vec<String> = config
+/// Quote `amount` through one configured pool against its current reserves
+async fn quote(config: &Config, pool: &str, amount: u64, sell: bool) -> Result<()> {
+    let (mint_config, dex_name) = find_pool(config, pool)?;
+    let rpc_pool = Arc::new(RpcPool::from_config(&config.rpc)?);
+    let dex_registry = build_dex_registry(rpc_pool, &token_fetch_config(config));
+    let dex = dex_registry
+        .get(dex_name)
+        .ok_or_else(|| anyhow!("No DEX registered for {} pools", dex_name))?;
+
+    let mint = Pubkey::from_str(&mint_config.mint)?;
+    let pool_info = dex
+        .fetch_pools(&[pool.to_string()], &mint)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("Failed to load {} pool {}", dex_name, pool))?;
+    let pool_info = if sell { pool_info.reversed() } else { pool_info };
+
+    let reserves = dex.fetch_reserves(&pool_info).await?;
+    let amount_out = dex.quote(&pool_info, amount, &reserves).await?;
+    println!(
+        "{} {} -> {} {} (reserves at slot {})",
+        amount, pool_info.base_mint, amount_out, pool_info.token_mint, reserves.slot
+    );
+    Ok(())
+}
+
+/// Build the transaction for a route file and, per `action`, submit it per
+/// the config or simulate and explain it
+async fn send(config: &Config, route_path: &Path, action: RouteAction) -> Result<()> {
+    let spec = RouteSpec::from_file(route_path)?;
+    let route = spec.to_route()?;
+    let mint_config = config
         .routing
         .mint_config_list
         .iter()
-        .map(|mc| mc.mint.clone())
+        .find(|mc| route.mints.iter().any(|mint| mint.to_string() == mc.mint))
+        .ok_or_else(|| anyhow!("No mint of the route is in routing.mint_config_list"))?;
+
+    let wallet_signer = load_signer(&config.wallet)?;
+    let rpc_pool = Arc::new(RpcPool::from_config(&config.rpc)?);
+    let rpc_client = rpc_pool.primary();
+    let dex_registry = build_dex_registry(rpc_pool.clone(), &token_fetch_config(config));
+
+    // Each hop sells mints[i] for mints[i + 1], so load the pool around its output
+    let mut pools = Vec::with_capacity(spec.hops.len());
+    for (i, hop) in spec.hops.iter().enumerate() {
+        let dex = dex_registry
+            .get(&hop.dex)
+            .ok_or_else(|| anyhow!("Unknown DEX {} in route", hop.dex))?;
+        let mut pool_info = dex
+            .fetch_pools(std::slice::from_ref(&hop.pool), &route.mints[i + 1])
+            .await?
+            .into_iter()
+            .next()
+            .ok_or_else(|| anyhow!("Failed to load {} pool {}", hop.dex, hop.pool))?;
+        // Reserve-priced pools carry a snapshot, which `min_viable_input`
+        // needs to size the smallest useful input
+        if pool_info.pool_type != PoolType::ConcentratedLiquidity {
+            let reserves = dex.fetch_reserves(&pool_info).await?;
+            pool_info.reserves = Some((reserves.token_reserve, reserves.base_reserve));
+        }
+        pools.push((dex, pool_info));
+    }
+    let hops: Vec<RouteHop> = pools.iter().map(|(dex, pool)| RouteHop { dex: *dex, pool }).collect();
+
+    // Without a flash loan the wallet funds the route's input itself, and
+    // route amounts are fixed, so an input the wallet can't cover is an error
+    let flashloan = match config.flashloan.as_ref().filter(|f| f.enabled) {
+        Some(flashloan_config) => Some(Flashloan::load(&rpc_client, flashloan_config)?),
+        None => None,
+    };
+    // With bulk unwrapping configured, proceeds stay wrapped and fund later
+    // trades; otherwise each trade wraps its input and unwraps at the end
+    let wrap_native = config.bot.wsol_unwrap_threshold_lamports.is_none();
+    if flashloan.is_none() && route.mints[0] == sol_mint() {
+        let balance = WalletBalance::fetch(&rpc_client, &wallet_signer.pubkey())?;
+        let min_input = hops[0].dex.min_viable_input(hops[0].pool);
+        let available = cap_input_to_balance(
+            spec.amounts[0],
+            &balance,
+            config.bot.fee_buffer_lamports,
+            min_input,
+            wrap_native,
+        )?;
+        if available < spec.amounts[0] {
+            return Err(anyhow!(
+                "Route input of {} lamports exceeds the {} lamports the wallet can spend",
+                spec.amounts[0],
+                available
+            ));
+        }
+    }
+
+    if let RouteAction::Explain { verbose } = action {
+        let prepared = prepare_route(
+            config,
+            mint_config,
+            &rpc_client,
+            wallet_signer.as_ref(),
+            flashloan.as_ref(),
+            &route,
+            &hops,
+            &spec.amounts,
+        )?;
+        return explain_route(&rpc_client, &prepared, verbose);
+    }
+
+    let blockhash_cache = BlockhashCache::new(rpc_client.clone(), BLOCKHASH_MAX_AGE);
+    let submission = execute_route(
+        config,
+        mint_config,
+        &rpc_client,
+        blockhash_cache.get()?,
+        &blockhash_cache,
+        &SendingEndpoint::from_config(config)?,
+        wallet_signer.as_ref(),
+        flashloan.as_ref(),
+        &route,
+        &hops,
+        &spec.amounts,
+    )
+    .await?;
+    println!("Submitted route: {:?}", submission);
+    record_pnl(&mut pnl_tracker(config), &rpc_client, &submission, &mint_config.mint);
+    Ok(())
+}
+
+/// Vault subscriber writing into `prices`, at `rpc.subscribe_commitment`,
+/// that only triggers a rescan past `bot.rescan_threshold_bps`
+fn pool_subscriber(config: &Config, rpc_client: Arc<RpcClient>, prices: PriceStore) -> PoolSubscriber {
+    PoolSubscriber::with_price_store(config.rpc.ws_url(), rpc_client, prices)
+        .with_commitment(config.rpc.subscription_commitment())
+        .with_rescan_threshold_bps(config.bot.rescan_threshold_bps)
+}
+
+/// Trade log at `bot.pnl_log_path`, with earlier trades read back
+fn pnl_tracker(config: &Config) -> PnlTracker {
+    PnlTracker::new(config.bot.pnl_log_path.as_ref().map(PathBuf::from), DEFAULT_MAX_TRADES)
+}
+
+/// Read back the profit of a submission that landed on chain
+fn record_pnl(pnl: &mut PnlTracker, rpc_client: &RpcClient, submission: &Submission, mint: &str) {
+    let Some(signature) = submission.landed_signature() else {
+        return;
+    };
+    if let Err(e) = pnl.record_landed(rpc_client, &signature, mint) {
+        println!("Failed to record PnL of {}: {}", signature, e);
+    }
+}
+
+/// A quoted route's transaction, with everything it is built from so it
+/// can be signed over any blockhash
+struct RouteTransaction<'a> {
+    route: &'a ArbRoute,
+    hops: &'a [RouteHop<'a>],
+    amounts: &'a [u64],
+    slippage_bps: u16,
+    wallet_signer: &'a dyn Signer,
+    compute_unit_limit: u32,
+    compute_unit_price: u64,
+    lookup_tables: Vec<AddressLookupTableAccount>,
+    flashloan: Option<&'a Flashloan>,
+    wrap_native: bool,
+    create_atas: Vec<Instruction>,
+    payouts: Vec<Instruction>,
+}
+
+impl RouteTransaction<'_> {
+    fn sign(&self, blockhash: Hash) -> Result<VersionedTransaction> {
+        build_arb_transaction(
+            self.route,
+            self.hops,
+            self.amounts,
+            self.slippage_bps,
+            self.wallet_signer,
+            self.compute_unit_limit,
+            self.compute_unit_price,
+            &self.lookup_tables,
+            blockhash,
+            self.flashloan,
+            self.wrap_native,
+            &self.create_atas,
+            &self.payouts,
+        )
+    }
+}
+
+/// Prepare the transaction of a quoted route, where `amounts` holds the
+/// quoted amount at each mint. Fails unless the route's estimated profit
+/// clears `min_profit_lamports` after everything it pays: fees, tip, flash
+/// loan fee, profit fee and rent for new token accounts.
+#[allow(clippy::too_many_arguments)]
+fn prepare_route<'a>(
+    config: &Config,
+    mint_config: &MintConfig,
+    rpc_client: &RpcClient,
+    wallet_signer: &'a dyn Signer,
+    flashloan: Option<&'a Flashloan>,
+    route: &'a ArbRoute,
+    hops: &'a [RouteHop<'a>],
+    amounts: &'a [u64],
+) -> Result<RouteTransaction<'a>> {
+    // With bulk unwrapping configured, proceeds stay wrapped and fund later
+    // trades; otherwise each trade wraps its input and unwraps at the end
+    let wrap_native = config.bot.wsol_unwrap_threshold_lamports.is_none();
+    let lookup_tables = load_lookup_tables(
+        rpc_client,
+        mint_config.lookup_table_accounts.as_deref().unwrap_or_default(),
+    );
+    // The WSOL account of a SOL-funded route is created alongside the wrap
+    let starts_in_sol = route.mints[0] == sol_mint();
+    let ata_mints: Vec<Pubkey> = route
+        .mints
+        .iter()
+        .copied()
+        .filter(|mint| !(starts_in_sol && *mint == sol_mint()))
+        .collect();
+    let (create_atas, ata_rent_lamports) = missing_ata_instructions(rpc_client, &wallet_signer.pubkey(), &ata_mints)?;
+
+    // Everything the transaction pays besides its swaps comes out of the profit
+    // Everything else is known first so the priority fee can bid from what's left
+    let jito_config = config.jito.as_ref().filter(|jito| jito.enabled);
+    let profit_fee = profit_fee_lamports(config, route.estimated_profit_lamports.max(0) as u64);
+    let mut costs = TradeCosts {
+        fee_lamports: 0,
+        tip_lamports: jito_config.map_or(0, |jito| jito.tip_lamports),
+        flashloan_fee_lamports: flashloan.map_or(0, |flashloan| {
+            flashloan.fee_lamports(flashloan.borrow_amount(amounts[0]))
+        }),
+        profit_fee_lamports: profit_fee,
+        ata_rent_lamports,
+    };
+    let compute_unit_price = route_compute_unit_price_for(config, mint_config, rpc_client, route, costs.total())?;
+    costs.fee_lamports = estimated_fee_lamports(1, config.bot.compute_unit_limit, compute_unit_price);
+    let net_profit = OpportunityEvaluator::from_config(&config.bot)
+        .check_profit(route.estimated_profit_lamports, costs.total())?;
+    println!("Expected profit after costs of {:?}: {} lamports", costs, net_profit);
+
+    let mut payouts: Vec<_> = profit_fee_instruction(config, &wallet_signer.pubkey(), profit_fee)?
+        .into_iter()
         .collect();
+    if let Some(jito_config) = jito_config {
+        payouts.push(jito::tip_instruction(&wallet_signer.pubkey(), jito_config)?);
+    }
+    Ok(RouteTransaction {
+        route,
+        hops,
+        amounts,
+        slippage_bps: mint_config.slippage_bps,
+        wallet_signer,
+        compute_unit_limit: config.bot.compute_unit_limit,
+        compute_unit_price,
+        lookup_tables,
+        flashloan,
+        wrap_native,
+        create_atas,
+        payouts,
+    })
+}
+
+/// Build, sign and submit a quoted route; see `prepare_route`. It is signed
+/// over `blockhash`, and signed again over one refreshed in `blockhash_cache`
+/// if that has expired by the time it is sent.
+#[allow(clippy::too_many_arguments)]
+async fn execute_route(
+    config: &Config,
+    mint_config: &MintConfig,
+    rpc_client: &RpcClient,
+    blockhash: Hash,
+    blockhash_cache: &BlockhashCache,
+    senders: &[SendingEndpoint],
+    wallet_signer: &dyn Signer,
+    flashloan: Option<&Flashloan>,
+    route: &ArbRoute,
+    hops: &[RouteHop<'_>],
+    amounts: &[u64],
+) -> Result<Submission> {
+    let prepared = prepare_route(config, mint_config, rpc_client, wallet_signer, flashloan, route, hops, amounts)?;
+    let tx = prepared.sign(blockhash)?;
+
+    let submission = submit(&tx, config, rpc_client, senders, &|| prepared.sign(blockhash_cache.refresh()?)).await?;
+    session::stats().record_submitted(1);
+    Ok(submission)
+}
 
-    // Uncomment the following line to start continuous price monitoring
-    // price_monitor.start_monitoring(mints).await;
+/// Simulate a prepared route and print how its simulated profit compares to
+/// the quote: the change of the wallet and its start-mint token account and,
+/// with `verbose`, of every account the transaction touches plus the logs
+fn explain_route(rpc_client: &RpcClient, prepared: &RouteTransaction<'_>, verbose: bool) -> Result<()> {
+    let tx = prepared.sign(rpc_client.get_latest_blockhash()?)?;
+    let wallet = prepared.wallet_signer.pubkey();
+    let start_mint = prepared.route.mints[0];
+    let start_token_account = get_associated_token_address_with_program_id(
+        &wallet,
+        &start_mint,
+        &rpc_client.get_account(&start_mint)?.owner,
+    );
+    let simulation = simulate_profit(
+        rpc_client,
+        &tx,
+        &[wallet, start_token_account],
+        tx.message.static_account_keys(),
+        verbose,
+    )?;
 
-    println!("Enhanced token fetch logic demonstration completed!");
-    println!("The bot is now ready for production use with improved error handling, caching, and retry logic.");
+    println!(
+        "Quoted profit: {} base units, simulated: {} base units, {:?} compute units",
+        prepared.route.estimated_profit_lamports, simulation.profit, simulation.units_consumed
+    );
+    if let Some(deltas) = &simulation.account_deltas {
+        for delta in deltas {
+            match delta.token_delta {
+                Some(token_delta) => println!(
+                    "  {}: {} lamports, {} tokens",
+                    delta.address, delta.lamports_delta, token_delta
+                ),
+                None => println!("  {}: {} lamports", delta.address, delta.lamports_delta),
+            }
+        }
+        for log in &simulation.logs {
+            println!("  {}", log);
+        }
+    }
+    Ok(())
+}
+
+fn mint_config<'a>(config: &'a Config, mint: &str) -> Result<&'a MintConfig> {
+    config
+        .routing
+        .mint_config_list
+        .iter()
+        .find(|mc| mc.mint == mint)
+        .ok_or_else(|| anyhow!("Mint {} is not in routing.mint_config_list", mint))
+}
+
+/// The mint config listing `pool`, and the DEX it is listed under
+fn find_pool<'a>(config: &'a Config, pool: &str) -> Result<(&'a MintConfig, &'static str)> {
+    for mint_config in &config.routing.mint_config_list {
+        for (dex, _, pools) in mint_config.pool_list_fields() {
+            if pools.is_some_and(|pools| pools.iter().any(|p| p == pool)) {
+                return Ok((mint_config, dex));
+            }
+        }
+    }
+    Err(anyhow!("Pool {} is not in any routing.mint_config_list entry", pool))
+}
+
+fn token_fetch_config(config: &Config) -> TokenFetchConfig {
+    TokenFetchConfig {
+        max_retries: 3,
+        retry_delay_ms: 1000,
+        max_retry_delay_ms: 10_000,
+        batch_size: 10,
+        timeout_ms: config.rpc.max_request_timeout_ms(),
+        enable_caching: true,
+        cache_ttl_seconds: 300,
+        max_cache_entries: config.bot.max_cache_entries,
+        account_cache_ttl_seconds: config.bot.account_cache_ttl_seconds,
+        max_rpc_calls_per_scan: config.bot.max_rpc_calls_per_scan,
+        compress_cache: config.bot.compress_cache,
+        cache_path: config.bot.cache_path.clone(),
+        follow_pump_migrations: config.bot.follow_pump_migrations,
+        pump_migrations: config.routing.pump_migrations.clone(),
+        decode_threads: config.bot.decode_threads,
+        raydium_swap_version: config.bot.raydium_swap_version,
+        max_additional_accounts: config.bot.max_additional_accounts,
+        disabled_dexes: config.routing.disabled_dexes(),
+    }
 }
@@ -0,0 +1,348 @@
+//! Pricing math shared by DEXes with the same pool model
+
+use crate::error::{BotError, Result};
+
+const BPS_DENOMINATOR: u128 = 10_000;
+
+/// Output of a constant-product swap after deducting the input fee
+pub fn constant_product_out(amount_in: u64, reserve_in: u64, reserve_out: u64, fee_bps: u16) -> u64 {
+    if reserve_in == 0 || reserve_out == 0 {
+        return 0;
+    }
+    let fee_bps = (fee_bps as u128).min(BPS_DENOMINATOR);
+    let amount_in_after_fee = amount_in as u128 * (BPS_DENOMINATOR - fee_bps) / BPS_DENOMINATOR;
+    let numerator = amount_in_after_fee * reserve_out as u128;
+    let denominator = reserve_in as u128 + amount_in_after_fee;
+    (numerator / denominator) as u64
+}
+
+/// `amount` left after a Token-2022 transfer fee of `fee_bps`, which the token
+/// program rounds up
+pub fn deduct_transfer_fee(amount: u64, fee_bps: u16) -> u64 {
+    let fee_bps = (fee_bps as u128).min(BPS_DENOMINATOR);
+    let fee = (amount as u128 * fee_bps).div_ceil(BPS_DENOMINATOR);
+    amount - fee as u64
+}
+
+/// Minimum acceptable output for a quote of `expected_out`, allowing
+/// `slippage_bps` of adverse movement. Zero slippage requires the full quote;
+/// values above 10000 are clamped, allowing any output.
+pub fn min_out_with_slippage(expected_out: u64, slippage_bps: u16) -> u64 {
+    let slippage_bps = (slippage_bps as u128).min(BPS_DENOMINATOR);
+    (expected_out as u128 * (BPS_DENOMINATOR - slippage_bps) / BPS_DENOMINATOR) as u64
+}
+
+/// Smallest input to a constant-product swap that yields a nonzero output
+/// after fees and rounding, or `u64::MAX` if no input can
+pub fn constant_product_min_input(reserve_in: u64, reserve_out: u64, fee_bps: u16) -> u64 {
+    if reserve_in == 0 || reserve_out <= 1 || fee_bps as u128 >= BPS_DENOMINATOR {
+        return u64::MAX;
+    }
+
+    // out >= 1 requires in_after_fee * (reserve_out - 1) >= reserve_in
+    let min_after_fee = (reserve_in as u128).div_ceil(reserve_out as u128 - 1);
+    // floor(amount_in * (10000 - fee) / 10000) >= min_after_fee
+    let fee_multiplier = BPS_DENOMINATOR - fee_bps as u128;
+    let min_input = (min_after_fee * BPS_DENOMINATOR).div_ceil(fee_multiplier);
+    u64::try_from(min_input).unwrap_or(u64::MAX)
+}
+
+/// Quote a constant-product swap against an explicit reserve snapshot, so the
+/// math can be exercised without RPC
+pub fn quote_constant_product(reserve_in: u64, reserve_out: u64, amount_in: u64, fee_bps: u16) -> Result<u64> {
+    if reserve_in == 0 || reserve_out == 0 {
+        return Err(BotError::PriceCalculation(format!(
+            "Cannot quote against empty reserves ({} in, {} out)",
+            reserve_in, reserve_out
+        )));
+    }
+    if fee_bps as u128 >= BPS_DENOMINATOR {
+        return Err(BotError::PriceCalculation(format!("Invalid fee of {} bps", fee_bps)));
+    }
+    Ok(constant_product_out(amount_in, reserve_in, reserve_out, fee_bps))
+}
+
+/// State of a concentrated-liquidity pool at its current tick
+#[derive(Debug, Clone, Copy)]
+pub struct ClmmSnapshot {
+    /// Active liquidity in the current tick range
+    pub liquidity: u128,
+    /// Current price as a Q64.64 square root of token1 per token0
+    pub sqrt_price_x64: u128,
+}
+
+/// Quote a concentrated-liquidity swap that stays within the snapshot's tick
+/// range. Tick crossings are not modelled, so the quote is only accurate for
+/// swaps small enough not to leave the active range.
+pub fn quote_clmm_snapshot(snapshot: &ClmmSnapshot, amount_in: u64, fee_bps: u16, zero_for_one: bool) -> Result<u64> {
+    if snapshot.liquidity == 0 || snapshot.sqrt_price_x64 == 0 {
+        return Err(BotError::PriceCalculation(
+            "Cannot quote against a pool with no active liquidity".to_string(),
+        ));
+    }
+    if fee_bps as u128 >= BPS_DENOMINATOR {
+        return Err(BotError::PriceCalculation(format!("Invalid fee of {} bps", fee_bps)));
+    }
+
+    let amount_in_after_fee =
+        (amount_in as u128 * (BPS_DENOMINATOR - fee_bps as u128) / BPS_DENOMINATOR) as f64;
+    let liquidity = snapshot.liquidity as f64;
+    let sqrt_price = snapshot.sqrt_price_x64 as f64 / 2f64.powi(64);
+
+    let amount_out = if zero_for_one {
+        // token0 in: sqrt price falls, token1 out
+        let next_sqrt_price = liquidity * sqrt_price / (liquidity + amount_in_after_fee * sqrt_price);
+        liquidity * (sqrt_price - next_sqrt_price)
+    } else {
+        // token1 in: sqrt price rises, token0 out
+        let next_sqrt_price = sqrt_price + amount_in_after_fee / liquidity;
+        liquidity * (1.0 / sqrt_price - 1.0 / next_sqrt_price)
+    };
+
+    Ok(amount_out.max(0.0).floor().min(u64::MAX as f64) as u64)
+}
+
+/// An initialized tick and the liquidity added when crossing it left to right
+#[derive(Debug, Clone, Copy)]
+pub struct InitializedTick {
+    pub tick_index: i32,
+    pub liquidity_net: i128,
+}
+
+/// Square root of the price at a tick, `1.0001 ^ (tick / 2)`
+pub fn tick_to_sqrt_price(tick_index: i32) -> f64 {
+    1.0001f64.powf(tick_index as f64 / 2.0)
+}
+
+/// Quote a concentrated-liquidity swap that may cross the given initialized
+/// ticks, adjusting active liquidity at each crossing. `ticks` need not be
+/// sorted; those on the wrong side of the current price are ignored. Past the
+/// last known tick the remaining input is swapped at the liquidity in effect
+/// there, and the swap stops early if liquidity runs out.
+pub fn quote_clmm_with_ticks(
+    snapshot: &ClmmSnapshot,
+    ticks: &[InitializedTick],
+    amount_in: u64,
+    fee_bps: u16,
+    zero_for_one: bool,
+) -> Result<u64> {
+    if snapshot.liquidity == 0 || snapshot.sqrt_price_x64 == 0 {
+        return Err(BotError::PriceCalculation(
+            "Cannot quote against a pool with no active liquidity".to_string(),
+        ));
+    }
+    if fee_bps as u128 >= BPS_DENOMINATOR {
+        return Err(BotError::PriceCalculation(format!("Invalid fee of {} bps", fee_bps)));
+    }
+
+    let mut remaining = (amount_in as u128 * (BPS_DENOMINATOR - fee_bps as u128) / BPS_DENOMINATOR) as f64;
+    let mut liquidity = snapshot.liquidity as f64;
+    let mut sqrt_price = snapshot.sqrt_price_x64 as f64 / 2f64.powi(64);
+    let mut amount_out = 0.0;
+
+    // Ticks in the order the price reaches them
+    let mut crossings: Vec<(f64, f64)> = ticks
+        .iter()
+        .map(|tick| (tick_to_sqrt_price(tick.tick_index), tick.liquidity_net as f64))
+        .filter(|(tick_sqrt_price, _)| {
+            if zero_for_one {
+                *tick_sqrt_price < sqrt_price
+            } else {
+                *tick_sqrt_price > sqrt_price
+            }
+        })
+        .collect();
+    if zero_for_one {
+        crossings.sort_by(|a, b| b.0.total_cmp(&a.0));
+    } else {
+        crossings.sort_by(|a, b| a.0.total_cmp(&b.0));
+    }
+
+    for (tick_sqrt_price, liquidity_net) in crossings {
+        if remaining <= 0.0 || liquidity <= 0.0 {
+            break;
+        }
+        let (input_to_tick, output_to_tick) = if zero_for_one {
+            (
+                liquidity * (1.0 / tick_sqrt_price - 1.0 / sqrt_price),
+                liquidity * (sqrt_price - tick_sqrt_price),
+            )
+        } else {
+            (
+                liquidity * (tick_sqrt_price - sqrt_price),
+                liquidity * (1.0 / sqrt_price - 1.0 / tick_sqrt_price),
+            )
+        };
+        if remaining < input_to_tick {
+            break;
+        }
+
+        amount_out += output_to_tick;
+        remaining -= input_to_tick;
+        sqrt_price = tick_sqrt_price;
+        // Moving left removes the liquidity a tick added when crossed rightwards
+        if zero_for_one {
+            liquidity -= liquidity_net;
+        } else {
+            liquidity += liquidity_net;
+        }
+    }
+
+    if remaining > 0.0 && liquidity > 0.0 {
+        amount_out += if zero_for_one {
+            let next_sqrt_price = liquidity * sqrt_price / (liquidity + remaining * sqrt_price);
+            liquidity * (sqrt_price - next_sqrt_price)
+        } else {
+            let next_sqrt_price = sqrt_price + remaining / liquidity;
+            liquidity * (1.0 / sqrt_price - 1.0 / next_sqrt_price)
+        };
+    }
+
+    Ok(amount_out.max(0.0).floor().min(u64::MAX as f64) as u64)
+}
+
+/// Price of token0 in token1 from a Q64.64 square-root price, adjusted for
+/// the decimals of both mints
+pub fn sqrt_price_x64_to_price(sqrt_price_x64: u128, decimals_0: u8, decimals_1: u8) -> f64 {
+    let sqrt_price = sqrt_price_x64 as f64 / 2f64.powi(64);
+    sqrt_price * sqrt_price * 10f64.powi(decimals_0 as i32 - decimals_1 as i32)
+}
+
+/// Denominator of the `fee_rate` Raydium CLMM configs and Whirlpools store,
+/// which is in hundredths of a basis point
+pub const CLMM_FEE_RATE_DENOMINATOR: u32 = 1_000_000;
+
+/// Fee tier of a concentrated-liquidity pool in basis points, from its
+/// `fee_rate`. Rounds up so a sub-basis-point tier is never quoted as cheaper
+/// than it is.
+pub fn clmm_fee_tier_bps(fee_rate: u32) -> u16 {
+    fee_rate.div_ceil(CLMM_FEE_RATE_DENOMINATOR / 10_000).min(u16::MAX as u32) as u16
+}
+
+/// Fraction of the input a concentrated-liquidity pool keeps as its fee,
+/// from its `fee_rate`
+pub fn clmm_fee_fraction(fee_rate: u32) -> f64 {
+    fee_rate as f64 / CLMM_FEE_RATE_DENOMINATOR as f64
+}
+
+/// Price of token X in token Y at a DLMM bin, before decimal adjustment:
+/// `(1 + bin_step / 10000) ^ active_id`
+pub fn dlmm_bin_price(active_id: i32, bin_step: u16) -> f64 {
+    (1.0 + bin_step as f64 / BPS_DENOMINATOR as f64).powi(active_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deduct_transfer_fee_rounds_the_fee_up() {
+        assert_eq!(deduct_transfer_fee(10_000, 0), 10_000);
+        assert_eq!(deduct_transfer_fee(10_000, 100), 9_900);
+        // 1% of 150 is 1.5, charged as 2
+        assert_eq!(deduct_transfer_fee(150, 100), 148);
+        assert_eq!(deduct_transfer_fee(10_000, 10_000), 0);
+        assert_eq!(deduct_transfer_fee(10_000, u16::MAX), 0);
+    }
+
+    #[test]
+    fn clmm_fee_tier_bps_maps_the_standard_tiers() {
+        assert_eq!(clmm_fee_tier_bps(100), 1);
+        assert_eq!(clmm_fee_tier_bps(500), 5);
+        assert_eq!(clmm_fee_tier_bps(3_000), 30);
+        assert_eq!(clmm_fee_tier_bps(10_000), 100);
+        assert_eq!(clmm_fee_tier_bps(0), 0);
+    }
+
+    #[test]
+    fn clmm_fee_tier_bps_rounds_sub_bps_rates_up() {
+        // 0.01 bps and 2.5 bps tiers are charged as 1 and 3 bps
+        assert_eq!(clmm_fee_tier_bps(1), 1);
+        assert_eq!(clmm_fee_tier_bps(250), 3);
+        assert_eq!(clmm_fee_tier_bps(u32::MAX), u16::MAX);
+    }
+
+    #[test]
+    fn constant_product_min_input_is_the_smallest_input_with_output() {
+        for (reserve_in, reserve_out, fee_bps) in [(1_000, 1_000, 25), (10_000_000_000, 3_000_000, 100), (7, 5, 30)] {
+            let min_input = constant_product_min_input(reserve_in, reserve_out, fee_bps);
+            assert!(constant_product_out(min_input, reserve_in, reserve_out, fee_bps) >= 1);
+            assert_eq!(constant_product_out(min_input - 1, reserve_in, reserve_out, fee_bps), 0);
+        }
+        assert_eq!(constant_product_min_input(1_000, 1_000, 25), 3);
+    }
+
+    #[test]
+    fn constant_product_min_input_without_viable_input() {
+        assert_eq!(constant_product_min_input(0, 1_000, 25), u64::MAX);
+        assert_eq!(constant_product_min_input(1_000, 1, 25), u64::MAX);
+        assert_eq!(constant_product_min_input(1_000, 1_000, 10_000), u64::MAX);
+    }
+
+    #[test]
+    fn quote_constant_product_tiny_reserves() {
+        assert_eq!(quote_constant_product(1, 1, 1, 25).unwrap(), 0);
+        // The last unit of a reserve can never be drained
+        assert_eq!(quote_constant_product(1, 1, 10_000, 25).unwrap(), 0);
+        assert_eq!(quote_constant_product(2, 2, 10_000, 0).unwrap(), 1);
+    }
+
+    #[test]
+    fn quote_constant_product_huge_reserves_do_not_overflow() {
+        assert_eq!(quote_constant_product(u64::MAX, u64::MAX, 1_000_000, 25).unwrap(), 997_499);
+        assert_eq!(
+            quote_constant_product(u64::MAX, u64::MAX, u64::MAX, 25).unwrap(),
+            9_211_828_392_252_955_061
+        );
+    }
+
+    #[test]
+    fn quote_constant_product_imbalanced_reserves() {
+        assert_eq!(quote_constant_product(1_000_000_000_000_000_000, 1, 1_000, 25).unwrap(), 0);
+        assert_eq!(
+            quote_constant_product(1, 1_000_000_000_000_000_000, 1_000, 25).unwrap(),
+            998_997_995_991_983_967
+        );
+    }
+
+    #[test]
+    fn quote_constant_product_rejects_empty_reserves_and_full_fee() {
+        assert!(quote_constant_product(0, 1_000, 1, 25).is_err());
+        assert!(quote_constant_product(1_000, 0, 1, 25).is_err());
+        assert!(quote_constant_product(1_000, 1_000, 1, 10_000).is_err());
+    }
+
+    /// Price 1.0 with deep liquidity, so small swaps fill close to 1:1
+    const PAR: ClmmSnapshot = ClmmSnapshot {
+        liquidity: 1_000_000_000_000,
+        sqrt_price_x64: 1 << 64,
+    };
+
+    #[test]
+    fn quote_clmm_snapshot_fills_near_spot_in_both_directions() {
+        for zero_for_one in [true, false] {
+            let out = quote_clmm_snapshot(&PAR, 1_000_000, 0, zero_for_one).unwrap();
+            assert!((999_990..=1_000_000).contains(&out), "{}", out);
+            let out = quote_clmm_snapshot(&PAR, 1_000_000, 30, zero_for_one).unwrap();
+            assert!((996_990..=997_000).contains(&out), "{}", out);
+        }
+    }
+
+    #[test]
+    fn quote_clmm_snapshot_thin_liquidity_has_price_impact() {
+        let thin = ClmmSnapshot {
+            liquidity: 1_000_000,
+            ..PAR
+        };
+        // out = L * a / (L + a) at price 1, half the input for a == L
+        let out = quote_clmm_snapshot(&thin, 1_000_000, 0, true).unwrap();
+        assert!((499_990..=500_000).contains(&out), "{}", out);
+    }
+
+    #[test]
+    fn quote_clmm_snapshot_rejects_empty_pool_and_full_fee() {
+        let empty = ClmmSnapshot { liquidity: 0, ..PAR };
+        assert!(quote_clmm_snapshot(&empty, 1_000, 0, true).is_err());
+        assert!(quote_clmm_snapshot(&PAR, 1_000, 10_000, true).is_err());
+    }
+}
@@ -0,0 +1,31 @@
+//! Token symbols from Metaplex token metadata accounts
+
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+
+pub const METADATA_PROGRAM_ID: &str = "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s";
+
+/// Offset of the borsh `symbol` string: key (1), update authority (32),
+/// mint (32), then the name, stored as a 4 byte length and 32 padded bytes
+const SYMBOL_OFFSET: usize = 1 + 32 + 32 + 4 + 32;
+/// Symbols are padded to this many bytes
+const MAX_SYMBOL_LEN: usize = 10;
+
+pub fn metadata_program_id() -> Pubkey {
+    Pubkey::from_str(METADATA_PROGRAM_ID).unwrap()
+}
+
+/// Metadata account of `mint`: `["metadata", program_id, mint]`
+pub fn metadata_address(mint: &Pubkey) -> Pubkey {
+    let program_id = metadata_program_id();
+    Pubkey::find_program_address(&[b"metadata", program_id.as_ref(), mint.as_ref()], &program_id).0
+}
+
+/// Symbol from a metadata account, with the padding stripped
+pub fn metadata_symbol(data: &[u8]) -> Option<String> {
+    let len_bytes = data.get(SYMBOL_OFFSET..SYMBOL_OFFSET + 4)?;
+    let len = (u32::from_le_bytes(len_bytes.try_into().unwrap()) as usize).min(MAX_SYMBOL_LEN);
+    let bytes = data.get(SYMBOL_OFFSET + 4..SYMBOL_OFFSET + 4 + len)?;
+    let symbol = String::from_utf8_lossy(bytes).trim_end_matches('\0').trim().to_string();
+    (!symbol.is_empty()).then_some(symbol)
+}
@@ -30,3 +30,16 @@ pub fn damm_v2_pool_authority() -> Pubkey {
 }
 
 pub const BIN_ARRAY: &[u8] = b"bin_array";
+
+pub const VAULT_TOKEN_VAULT_SEED: &[u8] = b"token_vault";
+pub const VAULT_LP_MINT_SEED: &[u8] = b"lp_mint";
+
+/// Token account holding a Meteora vault's liquidity: `["token_vault", vault]`
+pub fn vault_token_vault(vault: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[VAULT_TOKEN_VAULT_SEED, vault.as_ref()], &vault_program_id()).0
+}
+
+/// LP mint of a Meteora vault: `["lp_mint", vault]`
+pub fn vault_lp_mint(vault: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[VAULT_LP_MINT_SEED, vault.as_ref()], &vault_program_id()).0
+}
@@ -0,0 +1,132 @@
+use crate::dex::meteora::constants::{vault_lp_mint, vault_token_vault};
+use crate::error::{BotError, Result};
+use solana_program::pubkey::Pubkey;
+
+// Offsets into the dynamic-amm `Pool` account, after the 8-byte discriminator
+// (lp_mint sits at 8)
+pub const DAMM_TOKEN_A_MINT_OFFSET: usize = 40;
+pub const DAMM_TOKEN_B_MINT_OFFSET: usize = 72;
+const DAMM_A_VAULT_OFFSET: usize = 104;
+const DAMM_B_VAULT_OFFSET: usize = 136;
+const DAMM_A_VAULT_LP_OFFSET: usize = 168;
+const DAMM_B_VAULT_LP_OFFSET: usize = 200;
+// a_vault_lp_bump (1) and enabled (1) sit between the LP accounts and the fee accounts
+const DAMM_ADMIN_TOKEN_A_FEE_OFFSET: usize = 234;
+const DAMM_ADMIN_TOKEN_B_FEE_OFFSET: usize = 266;
+// admin (32) sits between the fee accounts and `fees`
+const DAMM_TRADE_FEE_NUMERATOR_OFFSET: usize = 330;
+const DAMM_TRADE_FEE_DENOMINATOR_OFFSET: usize = 338;
+
+/// One side of a DAMM v1 pool. Liquidity sits in a shared Meteora vault, and
+/// the pool owns a share of it through its vault LP tokens. The vault's token
+/// account and LP mint are not stored in the pool; they are PDAs of the vault.
+#[derive(Debug, Clone, Copy)]
+pub struct DammSide {
+    pub mint: Pubkey,
+    pub vault: Pubkey,
+    pub token_vault: Pubkey,
+    pub vault_lp_mint: Pubkey,
+    pub vault_lp: Pubkey,
+    pub admin_fee: Pubkey,
+}
+
+#[derive(Debug)]
+pub struct DammInfo {
+    pub a: DammSide,
+    pub b: DammSide,
+    pub trade_fee_numerator: u64,
+    pub trade_fee_denominator: u64,
+}
+
+impl DammInfo {
+    pub fn load_checked(data: &[u8]) -> Result<Self> {
+        if data.len() < DAMM_TRADE_FEE_DENOMINATOR_OFFSET + 8 {
+            return Err(BotError::PoolParse("Invalid data length for DammInfo".to_string()));
+        }
+
+        let pubkey = |offset: usize| Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap());
+        let u64_at = |offset: usize| u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap());
+
+        Ok(Self {
+            a: DammSide {
+                mint: pubkey(DAMM_TOKEN_A_MINT_OFFSET),
+                vault: pubkey(DAMM_A_VAULT_OFFSET),
+                token_vault: vault_token_vault(&pubkey(DAMM_A_VAULT_OFFSET)),
+                vault_lp_mint: vault_lp_mint(&pubkey(DAMM_A_VAULT_OFFSET)),
+                vault_lp: pubkey(DAMM_A_VAULT_LP_OFFSET),
+                admin_fee: pubkey(DAMM_ADMIN_TOKEN_A_FEE_OFFSET),
+            },
+            b: DammSide {
+                mint: pubkey(DAMM_TOKEN_B_MINT_OFFSET),
+                vault: pubkey(DAMM_B_VAULT_OFFSET),
+                token_vault: vault_token_vault(&pubkey(DAMM_B_VAULT_OFFSET)),
+                vault_lp_mint: vault_lp_mint(&pubkey(DAMM_B_VAULT_OFFSET)),
+                vault_lp: pubkey(DAMM_B_VAULT_LP_OFFSET),
+                admin_fee: pubkey(DAMM_ADMIN_TOKEN_B_FEE_OFFSET),
+            },
+            trade_fee_numerator: u64_at(DAMM_TRADE_FEE_NUMERATOR_OFFSET),
+            trade_fee_denominator: u64_at(DAMM_TRADE_FEE_DENOMINATOR_OFFSET),
+        })
+    }
+
+    /// `(token side, base side)` for the given trading mint
+    pub fn sides_for(&self, mint: &Pubkey) -> Option<(DammSide, DammSide)> {
+        if *mint == self.a.mint {
+            Some((self.a, self.b))
+        } else if *mint == self.b.mint {
+            Some((self.b, self.a))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A pool account with distinct keys at every field the decoder reads
+    fn fixture() -> (Vec<u8>, [Pubkey; 8]) {
+        let keys: [Pubkey; 8] = std::array::from_fn(|_| Pubkey::new_unique());
+        let mut data = vec![0u8; 944];
+        for (offset, key) in [40, 72, 104, 136, 168, 200, 234, 266].into_iter().zip(&keys) {
+            data[offset..offset + 32].copy_from_slice(key.as_ref());
+        }
+        data[330..338].copy_from_slice(&25u64.to_le_bytes());
+        data[338..346].copy_from_slice(&10_000u64.to_le_bytes());
+        (data, keys)
+    }
+
+    #[test]
+    fn decodes_the_dynamic_amm_pool_layout() {
+        let (data, [mint_a, mint_b, a_vault, b_vault, a_vault_lp, b_vault_lp, fee_a, fee_b]) = fixture();
+        let info = DammInfo::load_checked(&data).unwrap();
+
+        assert_eq!(info.a.mint, mint_a);
+        assert_eq!(info.b.mint, mint_b);
+        assert_eq!(info.a.vault, a_vault);
+        assert_eq!(info.b.vault, b_vault);
+        assert_eq!(info.a.vault_lp, a_vault_lp);
+        assert_eq!(info.b.vault_lp, b_vault_lp);
+        assert_eq!(info.a.admin_fee, fee_a);
+        assert_eq!(info.b.admin_fee, fee_b);
+        assert_eq!(info.a.token_vault, vault_token_vault(&a_vault));
+        assert_eq!(info.b.vault_lp_mint, vault_lp_mint(&b_vault));
+        assert_eq!((info.trade_fee_numerator, info.trade_fee_denominator), (25, 10_000));
+    }
+
+    #[test]
+    fn sides_follow_the_requested_mint() {
+        let (data, [mint_a, mint_b, ..]) = fixture();
+        let info = DammInfo::load_checked(&data).unwrap();
+
+        let (token, base) = info.sides_for(&mint_b).unwrap();
+        assert_eq!((token.mint, base.mint), (mint_b, mint_a));
+        assert!(info.sides_for(&Pubkey::new_unique()).is_none());
+    }
+
+    #[test]
+    fn rejects_accounts_shorter_than_the_fees() {
+        assert!(DammInfo::load_checked(&[0u8; 345]).is_err());
+    }
+}
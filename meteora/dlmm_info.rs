@@ -1,7 +1,7 @@
 use crate::dex::meteora::{
     constants::{dlmm_program_id, BIN_ARRAY},
 };
-use anyhow::Result;
+use crate::error::{BotError, Result};
 use solana_sdk::pubkey::Pubkey;
 use std::mem::size_of;
 
@@ -101,7 +101,7 @@ pub struct DlmmInfo {
 impl DlmmInfo {
     pub fn load_checked(data: &[u8]) -> Result<Self> {
         if data.len() < 8 + size_of::<LbPair>() {
-            return Err(anyhow::anyhow!("Invalid data length for DlmmInfo"));
+            return Err(BotError::PoolParse("Invalid data length for DlmmInfo".to_string()));
         }
 
         let raw_lb_pair = &data[8..8 + size_of::<LbPair>()];
@@ -186,7 +186,7 @@ impl DlmmInfo {
 impl LbPair {
     pub fn from_bytes(data: &[u8]) -> Result<Self> {
         if data.len() < size_of::<Self>() {
-            return Err(anyhow::anyhow!("Data is too small for LbPair"));
+            return Err(BotError::PoolParse("Data is too small for LbPair".to_string()));
         }
 
         let lb_pair = unsafe { std::ptr::read_unaligned(data.as_ptr() as *const LbPair) };
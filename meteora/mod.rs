@@ -1,3 +1,7 @@
 pub mod constants;
+pub mod damm_info;
 pub mod dammv2_info;
 pub mod dlmm_info;
+pub mod unified;
+
+pub use unified::{MeteoraDammDex, MeteoraDammV2Dex, MeteoraDlmmDex};
@@ -0,0 +1,771 @@
+//! Unified Meteora DEX implementations using the Dex trait
+
+use crate::dex::traits::{
+    validate_swap_amounts, verify_pool_owner, Dex, DexCapabilities, PoolInfo, PoolReserves, PoolType, PriceInfo,
+};
+use crate::dex::math::{dlmm_bin_price, quote_constant_product, sqrt_price_x64_to_price};
+use crate::dex::meteora::{
+    constants::*,
+    damm_info::{DammInfo, DAMM_TOKEN_A_MINT_OFFSET, DAMM_TOKEN_B_MINT_OFFSET},
+    dammv2_info::get_dammv2_info,
+    dlmm_info::DlmmInfo,
+};
+use crate::dex::vault::{
+    decimal_adjusted_price, fetch_pool_reserves, pool_reserves, token_account_amount, MintDecimalsCache,
+};
+use crate::dex::rpc::{blocking, RpcLike};
+use async_trait::async_trait;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use std::sync::Arc;
+use crate::dex_boilerplate;
+use crate::error::{BotError, Result};
+
+const DAMM_V2_POOL_MIN_LEN: usize = 296;
+/// Offsets of `token_a_mint` and `token_b_mint` in a DAMM v2 pool account
+const DAMM_V2_POOL_MINT_OFFSETS: [usize; 2] = [168, 200];
+const DAMM_V2_CLIFF_FEE_NUMERATOR_OFFSET: usize = 8;
+const DAMM_V2_DYNAMIC_FEE_INITIALIZED_OFFSET: usize = 56;
+const DAMM_V2_VARIABLE_FEE_CONTROL_OFFSET: usize = 68;
+const DAMM_V2_DYNAMIC_BIN_STEP_OFFSET: usize = 72;
+const DAMM_V2_VOLATILITY_ACCUMULATOR_OFFSET: usize = 120;
+/// Offset of the Q64.64 `sqrt_price`, token B per token A, in a DAMM v2 pool:
+/// after the mints, vaults, whitelisted vault, partner, liquidity, padding,
+/// four fee counters and the min and max sqrt prices
+const DAMM_V2_SQRT_PRICE_OFFSET: usize = 456;
+const DAMM_V2_FEE_DENOMINATOR: f64 = 1_000_000_000.0;
+/// Scale between `(volatility * bin_step)^2 * control` and a fee numerator
+const DAMM_V2_VARIABLE_FEE_SCALE: u128 = 100_000_000_000;
+/// Anchor discriminator of an instruction named `swap`, `sha256("global:swap")[..8]`.
+/// DAMM v1 and DAMM v2 both name their swap instruction `swap`, so both use it.
+const ANCHOR_SWAP_DISCRIMINATOR: [u8; 8] = [0xf8, 0xc6, 0x9e, 0x91, 0xe1, 0x75, 0x87, 0xc8];
+
+/// DLMM fee rates are expressed over 1e9
+const DLMM_FEE_PRECISION: f64 = 1_000_000_000.0;
+/// Offsets of `token_x_mint` and `token_y_mint` in an `LbPair` account
+const DLMM_PAIR_MINT_OFFSETS: [usize; 2] = [88, 120];
+
+#[derive(Clone)]
+pub struct MeteoraDammV2Dex {
+    rpc_client: Arc<dyn RpcLike>,
+    decimals: Arc<MintDecimalsCache>,
+}
+
+#[async_trait]
+impl Dex for MeteoraDammV2Dex {
+    dex_boilerplate!("meteora_damm_v2", damm_v2_program_id());
+
+    async fn fetch_pools(&self, pool_addresses: &[String], token_mint: &Pubkey) -> Result<Vec<PoolInfo>> {
+        let mut pools = Vec::new();
+
+        for pool_address in pool_addresses {
+            match self.fetch_single_pool(pool_address, token_mint).await {
+                Ok(pool) => pools.push(pool),
+                Err(e) => {
+                    tracing::error!("Failed to fetch Meteora DAMM v2 pool {}: {}", pool_address, e);
+                }
+            }
+        }
+
+        Ok(pools)
+    }
+
+    async fn calculate_price(&self, pool_info: &PoolInfo) -> Result<PriceInfo> {
+        let (dex, pool_info) = (self.clone(), pool_info.clone());
+        blocking(move || dex.read_price(&pool_info)).await
+    }
+
+    fn capabilities(&self) -> DexCapabilities {
+        // Priced from the pool's sqrt price but quoted as constant-product from
+        // vault balances, matching `PoolType::ConstantProduct`, so it is not
+        // routed as a CLMM
+        DexCapabilities {
+            supports_token_2022: true,
+            ..DexCapabilities::default()
+        }
+    }
+
+    async fn fetch_reserves(&self, pool_info: &PoolInfo) -> Result<PoolReserves> {
+        let (rpc_client, pool_info) = (self.rpc_client.clone(), pool_info.clone());
+        blocking(move || fetch_pool_reserves(rpc_client.as_ref(), &pool_info)).await
+    }
+
+    fn parse_pool_account(&self, address: &Pubkey, data: &[u8], token_mint: &Pubkey) -> Result<PoolInfo> {
+        let pool_pubkey = *address;
+
+        if data.len() < DAMM_V2_POOL_MIN_LEN {
+            return Err(BotError::PoolParse(format!(
+                "Invalid data length for Meteora DAMM v2 pool: {}",
+                pool_pubkey
+            )));
+        }
+
+        let (mint_a, mint_b, vault_a, vault_b) = get_dammv2_info(data);
+
+        if *token_mint != mint_a && *token_mint != mint_b {
+            return Err(BotError::PoolParse(format!(
+                "Mint {} not found in Meteora DAMM v2 pool {}",
+                token_mint,
+                pool_pubkey
+            )));
+        }
+
+        let (token_mint_final, base_mint, token_vault, base_vault) = if *token_mint == mint_a {
+            (mint_a, mint_b, vault_a, vault_b)
+        } else {
+            (mint_b, mint_a, vault_b, vault_a)
+        };
+
+        // Liquidity lives in the vault accounts, so swaps and pricing need both
+        let mut additional_accounts = std::collections::HashMap::new();
+        additional_accounts.insert("token_a_vault".to_string(), vault_a);
+        additional_accounts.insert("token_b_vault".to_string(), vault_b);
+
+        Ok(PoolInfo {
+            pool_address: pool_pubkey,
+            token_mint: token_mint_final,
+            base_mint,
+            token_vault,
+            base_vault,
+            fee_wallet: None,
+            additional_accounts,
+            // Quoted from vault balances, which matches full-range positions
+            pool_type: PoolType::ConstantProduct,
+            reserves: None,
+            token_decimals: None,
+            base_decimals: None,
+            token_symbol: None,
+            token_transfer_fee_bps: 0,
+            base_transfer_fee_bps: 0,
+        })
+    }
+
+    fn mint_offsets(&self) -> &'static [usize] {
+        &DAMM_V2_POOL_MINT_OFFSETS
+    }
+
+    fn get_swap_instruction_data(&self, _pool_info: &PoolInfo, amount_in: u64, minimum_out: u64) -> Result<Vec<u8>> {
+        validate_swap_amounts(amount_in, minimum_out)?;
+
+        let mut data = Vec::with_capacity(24);
+        data.extend_from_slice(&ANCHOR_SWAP_DISCRIMINATOR);
+        data.extend_from_slice(&amount_in.to_le_bytes());
+        data.extend_from_slice(&minimum_out.to_le_bytes());
+        Ok(data)
+    }
+}
+
+/// Reserves read from the external vault accounts of a DAMM v2 pool, with
+/// the pool's current price and fee
+#[derive(Debug, Clone, Copy)]
+pub struct DammV2Reserves {
+    pub token_reserve: u64,
+    pub base_reserve: u64,
+    pub fee: f64,
+    /// Q64.64 square-root price of token A in token B
+    pub sqrt_price: u128,
+}
+
+impl MeteoraDammV2Dex {
+    pub fn new(rpc_client: Arc<dyn RpcLike>) -> Self {
+        Self {
+            decimals: Arc::new(MintDecimalsCache::new(rpc_client.clone())),
+            rpc_client,
+        }
+    }
+
+    /// Quote a swap against explicitly supplied reserves instead of live accounts
+    pub fn quote_with_reserves(&self, reserve_in: u64, reserve_out: u64, amount_in: u64, fee_bps: u16) -> Result<u64> {
+        quote_constant_product(reserve_in, reserve_out, amount_in, fee_bps)
+    }
+
+    /// `calculate_price`, blocking on the account reads
+    fn read_price(&self, pool_info: &PoolInfo) -> Result<PriceInfo> {
+        let reserves = self.fetch_vault_reserves(pool_info)?;
+        let (token_decimals, base_decimals) = self.decimals.for_pool(pool_info)?;
+
+        // Liquidity may sit in a narrow price range, so the vault balances say
+        // little about the price. sqrt_price is B per A; flip it when the
+        // tracked token is mint B.
+        let token_is_a = pool_info.token_vault == pool_info.additional_account("token_a_vault")?;
+        let (decimals_a, decimals_b) = if token_is_a {
+            (token_decimals, base_decimals)
+        } else {
+            (base_decimals, token_decimals)
+        };
+        let price_b_per_a = sqrt_price_x64_to_price(reserves.sqrt_price, decimals_a, decimals_b);
+        let price = if token_is_a {
+            price_b_per_a
+        } else if price_b_per_a > 0.0 {
+            1.0 / price_b_per_a
+        } else {
+            return Err(BotError::PriceCalculation(format!(
+                "Meteora DAMM v2 pool {} has a zero sqrt price",
+                pool_info.pool_address
+            )));
+        };
+
+        Ok(pool_info.with_transfer_fees(PriceInfo {
+            price,
+            liquidity: reserves.base_reserve,
+            fee: reserves.fee,
+        }))
+    }
+
+    async fn fetch_single_pool(&self, pool_address: &str, token_mint: &Pubkey) -> Result<PoolInfo> {
+        let (dex, pool_address, token_mint) = (self.clone(), pool_address.to_string(), *token_mint);
+        blocking(move || dex.read_single_pool(&pool_address, &token_mint)).await
+    }
+
+    fn read_single_pool(&self, pool_address: &str, token_mint: &Pubkey) -> Result<PoolInfo> {
+        let pool_pubkey = Pubkey::from_str(pool_address)?;
+        let account = self.rpc_client.get_account(&pool_pubkey)?;
+
+        verify_pool_owner(&account, &damm_v2_program_id())?;
+
+        self.parse_pool_account(&pool_pubkey, &account.data, token_mint)
+    }
+
+    /// Read the actual token balances of the pool vaults in one batched call
+    pub fn fetch_vault_reserves(&self, pool_info: &PoolInfo) -> Result<DammV2Reserves> {
+        let accounts = self.rpc_client.get_multiple_accounts(&[
+            pool_info.pool_address,
+            pool_info.token_vault,
+            pool_info.base_vault,
+        ])?;
+
+        let (pool, token_vault, base_vault) = match accounts.as_slice() {
+            [Some(pool), Some(token_vault), Some(base_vault)] => (pool, token_vault, base_vault),
+            _ => {
+                return Err(BotError::AccountFetch(format!(
+                    "Missing pool or vault account for Meteora DAMM v2 pool {}",
+                    pool_info.pool_address
+                )))
+            }
+        };
+
+        Ok(DammV2Reserves {
+            token_reserve: token_account_amount(&token_vault.data)?,
+            base_reserve: token_account_amount(&base_vault.data)?,
+            fee: damm_v2_fee(&pool.data)?,
+            sqrt_price: damm_v2_sqrt_price(&pool.data)?,
+        })
+    }
+}
+
+fn damm_v2_sqrt_price(data: &[u8]) -> Result<u128> {
+    let bytes = data
+        .get(DAMM_V2_SQRT_PRICE_OFFSET..DAMM_V2_SQRT_PRICE_OFFSET + 16)
+        .ok_or_else(|| BotError::PoolParse("Invalid data length for Meteora DAMM v2 sqrt price".to_string()))?;
+    Ok(u128::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+/// Current DAMM v2 fee: the cliff base fee plus the volatility-driven
+/// dynamic fee when the pool has it enabled. Fee-scheduler decay of the base
+/// fee is not applied, so scheduled pools are priced at their cliff fee.
+fn damm_v2_fee(data: &[u8]) -> Result<f64> {
+    let slice = |offset: usize, len: usize| {
+        data.get(offset..offset + len)
+            .ok_or_else(|| BotError::PoolParse("Invalid data length for Meteora DAMM v2 pool fees".to_string()))
+    };
+
+    let base_fee_numerator = u64::from_le_bytes(slice(DAMM_V2_CLIFF_FEE_NUMERATOR_OFFSET, 8)?.try_into().unwrap());
+
+    let dynamic_fee_numerator = if slice(DAMM_V2_DYNAMIC_FEE_INITIALIZED_OFFSET, 1)?[0] != 0 {
+        let variable_fee_control =
+            u32::from_le_bytes(slice(DAMM_V2_VARIABLE_FEE_CONTROL_OFFSET, 4)?.try_into().unwrap()) as u128;
+        let bin_step = u16::from_le_bytes(slice(DAMM_V2_DYNAMIC_BIN_STEP_OFFSET, 2)?.try_into().unwrap()) as u128;
+        let volatility_accumulator =
+            u128::from_le_bytes(slice(DAMM_V2_VOLATILITY_ACCUMULATOR_OFFSET, 16)?.try_into().unwrap());
+
+        let square_vfa_bin = volatility_accumulator
+            .saturating_mul(bin_step)
+            .saturating_pow(2);
+        let variable_fee = square_vfa_bin.saturating_mul(variable_fee_control);
+        variable_fee.saturating_add(DAMM_V2_VARIABLE_FEE_SCALE - 1) / DAMM_V2_VARIABLE_FEE_SCALE
+    } else {
+        0
+    };
+
+    Ok((base_fee_numerator as u128 + dynamic_fee_numerator) as f64 / DAMM_V2_FEE_DENOMINATOR)
+}
+
+#[derive(Clone)]
+pub struct MeteoraDlmmDex {
+    rpc_client: Arc<dyn RpcLike>,
+    decimals: Arc<MintDecimalsCache>,
+}
+
+#[async_trait]
+impl Dex for MeteoraDlmmDex {
+    dex_boilerplate!("meteora_dlmm", dlmm_program_id());
+
+    async fn fetch_pools(&self, pool_addresses: &[String], token_mint: &Pubkey) -> Result<Vec<PoolInfo>> {
+        let mut pools = Vec::new();
+
+        for pool_address in pool_addresses {
+            match self.fetch_single_pool(pool_address, token_mint).await {
+                Ok(pool) => pools.push(pool),
+                Err(e) => {
+                    tracing::error!("Failed to fetch Meteora DLMM pair {}: {}", pool_address, e);
+                }
+            }
+        }
+
+        Ok(pools)
+    }
+
+    async fn calculate_price(&self, pool_info: &PoolInfo) -> Result<PriceInfo> {
+        let (dex, pool_info) = (self.clone(), pool_info.clone());
+        blocking(move || dex.read_price(&pool_info)).await
+    }
+
+    async fn fetch_reserves(&self, pool_info: &PoolInfo) -> Result<PoolReserves> {
+        let (rpc_client, pool_info) = (self.rpc_client.clone(), pool_info.clone());
+        blocking(move || fetch_pool_reserves(rpc_client.as_ref(), &pool_info)).await
+    }
+
+    fn parse_pool_account(&self, address: &Pubkey, data: &[u8], token_mint: &Pubkey) -> Result<PoolInfo> {
+        let pair_pubkey = *address;
+
+        let dlmm_info = DlmmInfo::load_checked(data)?;
+
+        let (token_mint_final, base_mint, token_vault, base_vault) = if *token_mint == dlmm_info.token_x_mint {
+            (dlmm_info.token_x_mint, dlmm_info.token_y_mint, dlmm_info.token_x_vault, dlmm_info.token_y_vault)
+        } else if *token_mint == dlmm_info.token_y_mint {
+            (dlmm_info.token_y_mint, dlmm_info.token_x_mint, dlmm_info.token_y_vault, dlmm_info.token_x_vault)
+        } else {
+            return Err(BotError::PoolParse(format!(
+                "Mint {} not found in Meteora DLMM pair {}",
+                token_mint,
+                pair_pubkey
+            )));
+        };
+
+        // Swaps walk the bin arrays around the active bin and update the oracle
+        let mut additional_accounts = std::collections::HashMap::new();
+        additional_accounts.insert("oracle".to_string(), dlmm_info.oracle);
+        for (i, bin_array) in dlmm_info.calculate_bin_arrays(&pair_pubkey)?.into_iter().enumerate() {
+            additional_accounts.insert(format!("bin_array_{}", i), bin_array);
+        }
+
+        Ok(PoolInfo {
+            pool_address: pair_pubkey,
+            token_mint: token_mint_final,
+            base_mint,
+            token_vault,
+            base_vault,
+            fee_wallet: None,
+            additional_accounts,
+            pool_type: PoolType::ConcentratedLiquidity,
+            reserves: None,
+            token_decimals: None,
+            base_decimals: None,
+            token_symbol: None,
+            token_transfer_fee_bps: 0,
+            base_transfer_fee_bps: 0,
+        })
+    }
+
+    fn mint_offsets(&self) -> &'static [usize] {
+        &DLMM_PAIR_MINT_OFFSETS
+    }
+
+    fn get_swap_instruction_data(&self, _pool_info: &PoolInfo, amount_in: u64, minimum_out: u64) -> Result<Vec<u8>> {
+        validate_swap_amounts(amount_in, minimum_out)?;
+
+        // An empty instruction would only revert on-chain
+        Err(BotError::Validation("Meteora DLMM swap encoding not implemented".to_string()))
+    }
+
+    fn capabilities(&self) -> DexCapabilities {
+        DexCapabilities {
+            supports_token_2022: true,
+            concentrated_liquidity: true,
+            needs_oracle: true,
+            ..DexCapabilities::default()
+        }
+    }
+}
+
+impl MeteoraDlmmDex {
+    pub fn new(rpc_client: Arc<dyn RpcLike>) -> Self {
+        Self {
+            decimals: Arc::new(MintDecimalsCache::new(rpc_client.clone())),
+            rpc_client,
+        }
+    }
+
+    fn fetch_lb_pair(&self, pair_pubkey: &Pubkey) -> Result<DlmmInfo> {
+        let account = self.rpc_client.get_account(pair_pubkey)?;
+
+        verify_pool_owner(&account, &dlmm_program_id())?;
+
+        DlmmInfo::load_checked(&account.data)
+    }
+
+    /// `calculate_price`, blocking on the account reads
+    fn read_price(&self, pool_info: &PoolInfo) -> Result<PriceInfo> {
+        // The active bin moves with every swap, so read it fresh
+        let dlmm_info = self.fetch_lb_pair(&pool_info.pool_address)?;
+        let (decimals_x, decimals_y) =
+            self.decimals.get_pair(&dlmm_info.token_x_mint, &dlmm_info.token_y_mint)?;
+
+        // Bin prices are Y per X; flip when the tracked token is Y
+        let price_y_per_x = dlmm_bin_price(dlmm_info.active_id, dlmm_info.lb_pair.bin_step)
+            * 10f64.powi(decimals_x as i32 - decimals_y as i32);
+        let price = if pool_info.token_mint == dlmm_info.token_x_mint {
+            price_y_per_x
+        } else if price_y_per_x > 0.0 {
+            1.0 / price_y_per_x
+        } else {
+            return Err(BotError::PriceCalculation(format!(
+                "Meteora DLMM pair {} has a zero bin price",
+                pool_info.pool_address
+            )));
+        };
+
+        let (_, base_reserve) = pool_reserves(self.rpc_client.as_ref(), pool_info)?;
+
+        // Base fee only; the variable fee depends on recent volatility
+        let base_fee = dlmm_info.lb_pair.parameters.base_factor as f64
+            * dlmm_info.lb_pair.bin_step as f64
+            * 10.0
+            / DLMM_FEE_PRECISION;
+
+        Ok(pool_info.with_transfer_fees(PriceInfo {
+            price,
+            liquidity: base_reserve,
+            fee: base_fee,
+        }))
+    }
+
+    async fn fetch_single_pool(&self, pool_address: &str, token_mint: &Pubkey) -> Result<PoolInfo> {
+        let (dex, pool_address, token_mint) = (self.clone(), pool_address.to_string(), *token_mint);
+        blocking(move || dex.read_single_pool(&pool_address, &token_mint)).await
+    }
+
+    fn read_single_pool(&self, pool_address: &str, token_mint: &Pubkey) -> Result<PoolInfo> {
+        let pair_pubkey = Pubkey::from_str(pool_address)?;
+        let account = self.rpc_client.get_account(&pair_pubkey)?;
+
+        verify_pool_owner(&account, &dlmm_program_id())?;
+
+        let mut pool_info = self.parse_pool_account(&pair_pubkey, &account.data, token_mint)?;
+        self.decimals.fill(&mut pool_info)?;
+        Ok(pool_info)
+    }
+}
+
+/// Offset of the `supply` field in an SPL Token / Token-2022 mint
+const MINT_SUPPLY_OFFSET: usize = 36;
+
+#[derive(Clone)]
+pub struct MeteoraDammDex {
+    rpc_client: Arc<dyn RpcLike>,
+    decimals: Arc<MintDecimalsCache>,
+}
+
+#[async_trait]
+impl Dex for MeteoraDammDex {
+    dex_boilerplate!("meteora_damm", damm_program_id());
+
+    async fn fetch_pools(&self, pool_addresses: &[String], token_mint: &Pubkey) -> Result<Vec<PoolInfo>> {
+        let mut pools = Vec::new();
+
+        for pool_address in pool_addresses {
+            match self.fetch_single_pool(pool_address, token_mint).await {
+                Ok(pool) => pools.push(pool),
+                Err(e) => {
+                    tracing::error!("Failed to fetch Meteora DAMM pool {}: {}", pool_address, e);
+                }
+            }
+        }
+
+        Ok(pools)
+    }
+
+    async fn calculate_price(&self, pool_info: &PoolInfo) -> Result<PriceInfo> {
+        let (dex, pool_info) = (self.clone(), pool_info.clone());
+        blocking(move || dex.read_price(&pool_info)).await
+    }
+
+    async fn fetch_reserves(&self, pool_info: &PoolInfo) -> Result<PoolReserves> {
+        let (rpc_client, pool_info) = (self.rpc_client.clone(), pool_info.clone());
+        blocking(move || fetch_pool_reserves(rpc_client.as_ref(), &pool_info)).await
+    }
+
+    fn parse_pool_account(&self, address: &Pubkey, data: &[u8], token_mint: &Pubkey) -> Result<PoolInfo> {
+        let pool_pubkey = *address;
+
+        let damm_info = DammInfo::load_checked(data)?;
+        let (token_side, base_side) = damm_info.sides_for(token_mint).ok_or_else(|| {
+            BotError::PoolParse(format!("Mint {} not found in Meteora DAMM pool {}", token_mint, pool_pubkey))
+        })?;
+
+        // `token_vault`/`base_vault` are the shared vault-program accounts; the
+        // token accounts and LP positions behind them go in additional accounts
+        let mut additional_accounts = std::collections::HashMap::new();
+        additional_accounts.insert("token_x_token_vault".to_string(), token_side.token_vault);
+        additional_accounts.insert("token_sol_token_vault".to_string(), base_side.token_vault);
+        additional_accounts.insert("token_x_lp_mint".to_string(), token_side.vault_lp_mint);
+        additional_accounts.insert("token_sol_lp_mint".to_string(), base_side.vault_lp_mint);
+        additional_accounts.insert("token_x_pool_lp".to_string(), token_side.vault_lp);
+        additional_accounts.insert("token_sol_pool_lp".to_string(), base_side.vault_lp);
+        additional_accounts.insert("admin_token_fee_x".to_string(), token_side.admin_fee);
+        additional_accounts.insert("admin_token_fee_sol".to_string(), base_side.admin_fee);
+
+        Ok(PoolInfo {
+            pool_address: pool_pubkey,
+            token_mint: token_side.mint,
+            base_mint: base_side.mint,
+            token_vault: token_side.vault,
+            base_vault: base_side.vault,
+            fee_wallet: None,
+            additional_accounts,
+            pool_type: PoolType::ConstantProduct,
+            reserves: None,
+            token_decimals: None,
+            base_decimals: None,
+            token_symbol: None,
+            token_transfer_fee_bps: 0,
+            base_transfer_fee_bps: 0,
+        })
+    }
+
+    fn mint_offsets(&self) -> &'static [usize] {
+        &[DAMM_TOKEN_A_MINT_OFFSET, DAMM_TOKEN_B_MINT_OFFSET]
+    }
+
+    fn get_swap_instruction_data(&self, _pool_info: &PoolInfo, amount_in: u64, minimum_out: u64) -> Result<Vec<u8>> {
+        validate_swap_amounts(amount_in, minimum_out)?;
+
+        let mut data = Vec::with_capacity(24);
+        data.extend_from_slice(&ANCHOR_SWAP_DISCRIMINATOR);
+        data.extend_from_slice(&amount_in.to_le_bytes());
+        data.extend_from_slice(&minimum_out.to_le_bytes());
+        Ok(data)
+    }
+}
+
+impl MeteoraDammDex {
+    pub fn new(rpc_client: Arc<dyn RpcLike>) -> Self {
+        Self {
+            decimals: Arc::new(MintDecimalsCache::new(rpc_client.clone())),
+            rpc_client,
+        }
+    }
+
+    /// `calculate_price`, blocking on the account reads
+    fn read_price(&self, pool_info: &PoolInfo) -> Result<PriceInfo> {
+        let accounts = self.rpc_client.get_multiple_accounts(&[
+            pool_info.pool_address,
+            pool_info.additional_account("token_x_token_vault")?,
+            pool_info.additional_account("token_x_pool_lp")?,
+            pool_info.additional_account("token_x_lp_mint")?,
+            pool_info.additional_account("token_sol_token_vault")?,
+            pool_info.additional_account("token_sol_pool_lp")?,
+            pool_info.additional_account("token_sol_lp_mint")?,
+        ])?;
+
+        let accounts: Vec<_> = accounts.into_iter().collect::<Option<_>>().ok_or_else(|| {
+            BotError::AccountFetch(format!(
+                "Missing pool or vault account for Meteora DAMM pool {}",
+                pool_info.pool_address
+            ))
+        })?;
+
+        // The pool owns `pool_lp / lp_supply` of each shared vault
+        let pool_share = |token_vault: &[u8], pool_lp: &[u8], lp_mint: &[u8]| -> Result<u64> {
+            let vault_amount = token_account_amount(token_vault)?;
+            let pool_lp = token_account_amount(pool_lp)?;
+            let lp_supply = mint_supply(lp_mint)?;
+            if lp_supply == 0 {
+                return Ok(0);
+            }
+            Ok((vault_amount as u128 * pool_lp as u128 / lp_supply as u128) as u64)
+        };
+        let token_reserve = pool_share(&accounts[1].data, &accounts[2].data, &accounts[3].data)?;
+        let base_reserve = pool_share(&accounts[4].data, &accounts[5].data, &accounts[6].data)?;
+
+        let (token_decimals, base_decimals) =
+            self.decimals.for_pool(pool_info)?;
+        let price = decimal_adjusted_price(token_reserve, base_reserve, token_decimals, base_decimals)
+            .ok_or_else(|| {
+                BotError::PriceCalculation(format!(
+                    "Meteora DAMM pool {} has no token liquidity",
+                    pool_info.pool_address
+                ))
+            })?;
+
+        let damm_info = DammInfo::load_checked(&accounts[0].data)?;
+        let fee = if damm_info.trade_fee_denominator == 0 {
+            0.0
+        } else {
+            damm_info.trade_fee_numerator as f64 / damm_info.trade_fee_denominator as f64
+        };
+
+        Ok(pool_info.with_transfer_fees(PriceInfo {
+            price,
+            liquidity: base_reserve,
+            fee,
+        }))
+    }
+
+    async fn fetch_single_pool(&self, pool_address: &str, token_mint: &Pubkey) -> Result<PoolInfo> {
+        let (dex, pool_address, token_mint) = (self.clone(), pool_address.to_string(), *token_mint);
+        blocking(move || dex.read_single_pool(&pool_address, &token_mint)).await
+    }
+
+    fn read_single_pool(&self, pool_address: &str, token_mint: &Pubkey) -> Result<PoolInfo> {
+        let pool_pubkey = Pubkey::from_str(pool_address)?;
+        let account = self.rpc_client.get_account(&pool_pubkey)?;
+
+        verify_pool_owner(&account, &damm_program_id())?;
+
+        let mut pool_info = self.parse_pool_account(&pool_pubkey, &account.data, token_mint)?;
+        self.decimals.fill(&mut pool_info)?;
+        Ok(pool_info)
+    }
+}
+
+fn mint_supply(data: &[u8]) -> Result<u64> {
+    let bytes = data
+        .get(MINT_SUPPLY_OFFSET..MINT_SUPPLY_OFFSET + 8)
+        .ok_or_else(|| BotError::Parse("Invalid data length for mint account".to_string()))?;
+    Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::rpc::MockRpc;
+    use solana_sdk::{account::Account, hash::hash};
+
+    /// A DAMM v2 pool, its two vaults and their mints: token A has 6
+    /// decimals and B 9, the pool charges a 0.25% cliff fee, and its sqrt
+    /// price puts A at 0.001 B. The vaults hold 1,000 A and 5 B, a ratio of
+    /// 0.005 that the price must not be read from.
+    struct DammV2Fixture {
+        pool: Pubkey,
+        mint_a: Pubkey,
+        mint_b: Pubkey,
+        vault_a: Pubkey,
+        vault_b: Pubkey,
+        rpc: MockRpc,
+    }
+
+    fn account(data: Vec<u8>, owner: Pubkey) -> Account {
+        Account {
+            lamports: 1,
+            data,
+            owner,
+            ..Account::default()
+        }
+    }
+
+    fn damm_v2_fixture() -> DammV2Fixture {
+        let (pool, mint_a, mint_b, vault_a, vault_b) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+
+        let mut pool_data = vec![0u8; 1112];
+        pool_data[DAMM_V2_CLIFF_FEE_NUMERATOR_OFFSET..][..8].copy_from_slice(&2_500_000u64.to_le_bytes());
+        for (offset, key) in [(168, mint_a), (200, mint_b), (232, vault_a), (264, vault_b)] {
+            pool_data[offset..offset + 32].copy_from_slice(key.as_ref());
+        }
+        // 1 raw B per raw A, which is 0.001 B per A across 6 and 9 decimals
+        pool_data[DAMM_V2_SQRT_PRICE_OFFSET..][..16].copy_from_slice(&(1u128 << 64).to_le_bytes());
+
+        let vault = |amount: u64| {
+            let mut data = vec![0u8; 165];
+            data[64..72].copy_from_slice(&amount.to_le_bytes());
+            account(data, spl_token::ID)
+        };
+        let mint = |decimals: u8| {
+            let mut data = vec![0u8; 82];
+            data[44] = decimals;
+            account(data, spl_token::ID)
+        };
+
+        let rpc = MockRpc::new()
+            .with_account(pool, account(pool_data, damm_v2_program_id()))
+            .with_account(vault_a, vault(1_000_000_000))
+            .with_account(vault_b, vault(5_000_000_000))
+            .with_account(mint_a, mint(6))
+            .with_account(mint_b, mint(9));
+        DammV2Fixture {
+            pool,
+            mint_a,
+            mint_b,
+            vault_a,
+            vault_b,
+            rpc,
+        }
+    }
+
+    #[tokio::test]
+    async fn damm_v2_is_priced_from_its_sqrt_price() {
+        let fixture = damm_v2_fixture();
+        let dex = MeteoraDammV2Dex::new(Arc::new(fixture.rpc));
+
+        let pool_a = dex.fetch_single_pool(&fixture.pool.to_string(), &fixture.mint_a).await.unwrap();
+        assert_eq!(pool_a.additional_account("token_a_vault").unwrap(), fixture.vault_a);
+        assert_eq!(pool_a.additional_account("token_b_vault").unwrap(), fixture.vault_b);
+        let price = dex.calculate_price(&pool_a).await.unwrap();
+        assert!((price.price - 0.001).abs() < 1e-12, "price {}", price.price);
+        assert_eq!(price.liquidity, 5_000_000_000);
+        assert!((price.fee - 0.0025).abs() < 1e-12);
+
+        // Tracking mint B flips the price to A per B
+        let pool_b = dex.fetch_single_pool(&fixture.pool.to_string(), &fixture.mint_b).await.unwrap();
+        let price = dex.calculate_price(&pool_b).await.unwrap();
+        assert!((price.price - 1_000.0).abs() < 1e-6, "price {}", price.price);
+        assert_eq!(price.liquidity, 1_000_000_000);
+    }
+
+    #[test]
+    fn damm_v2_reserves_are_the_vault_balances() {
+        let fixture = damm_v2_fixture();
+        let dex = MeteoraDammV2Dex::new(Arc::new(fixture.rpc));
+        let pool_info = dex.read_single_pool(&fixture.pool.to_string(), &fixture.mint_b).unwrap();
+        assert_eq!((pool_info.token_vault, pool_info.base_vault), (fixture.vault_b, fixture.vault_a));
+
+        let reserves = dex.fetch_vault_reserves(&pool_info).unwrap();
+        assert_eq!(reserves.token_reserve, 5_000_000_000);
+        assert_eq!(reserves.base_reserve, 1_000_000_000);
+        assert_eq!(reserves.sqrt_price, 1u128 << 64);
+    }
+
+    #[test]
+    fn damm_swaps_use_the_anchor_swap_discriminator() {
+        assert_eq!(ANCHOR_SWAP_DISCRIMINATOR, hash(b"global:swap").to_bytes()[..8]);
+        let pool = PoolInfo::mock(PoolType::ConstantProduct);
+        for data in [
+            MeteoraDammDex::new(Arc::new(MockRpc::new())).get_swap_instruction_data(&pool, 1_000, 900),
+            MeteoraDammV2Dex::new(Arc::new(MockRpc::new())).get_swap_instruction_data(&pool, 1_000, 900),
+        ] {
+            assert_eq!(data.unwrap()[..8], ANCHOR_SWAP_DISCRIMINATOR);
+        }
+    }
+
+    #[test]
+    fn dlmm_swap_encoding_is_refused_until_implemented() {
+        let dex = MeteoraDlmmDex::new(Arc::new(MockRpc::new()));
+        let pool = PoolInfo::mock(PoolType::ConcentratedLiquidity);
+        assert!(matches!(
+            dex.get_swap_instruction_data(&pool, 1_000, 900),
+            Err(BotError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn damm_discovery_filters_on_the_pool_mints() {
+        let dex = MeteoraDammDex::new(Arc::new(MockRpc::new()));
+        assert_eq!(dex.mint_offsets(), &[40, 72]);
+    }
+}
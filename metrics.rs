@@ -0,0 +1,112 @@
+//! Prometheus metrics for scans, opportunities and transaction landings
+
+use crate::config::MetricsConfig;
+use hyper::{
+    header::CONTENT_TYPE,
+    service::{make_service_fn, service_fn},
+    Body, Request, Response, Server,
+};
+use prometheus::{Encoder, Histogram, HistogramOpts, IntCounter, Registry, TextEncoder};
+use std::{convert::Infallible, net::SocketAddr, sync::OnceLock};
+use tracing::{error, info};
+
+static METRICS: OnceLock<Metrics> = OnceLock::new();
+
+/// Global metrics shared by every component of the bot
+pub fn metrics() -> &'static Metrics {
+    METRICS.get_or_init(Metrics::new)
+}
+
+/// Counters and histograms exported on the metrics endpoint
+pub struct Metrics {
+    registry: Registry,
+    pub pools_fetched: IntCounter,
+    pub opportunities_found: IntCounter,
+    /// Queued opportunities dropped because their blockhash expired unsent
+    pub opportunities_expired: IntCounter,
+    pub txs_submitted: IntCounter,
+    pub txs_landed: IntCounter,
+    pub txs_reverted: IntCounter,
+    /// Duration of `TokenFetcher::initialize_pool_data`, cache hits excluded
+    pub pool_data_seconds: Histogram,
+    /// Duration of one full pass over the due mints
+    pub scan_seconds: Histogram,
+}
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new();
+        let counter = |name: &str, help: &str| {
+            let counter = IntCounter::new(name, help).expect("valid counter");
+            registry
+                .register(Box::new(counter.clone()))
+                .expect("unique metric name");
+            counter
+        };
+        let histogram = |name: &str, help: &str| {
+            let histogram = Histogram::with_opts(HistogramOpts::new(name, help)).expect("valid histogram");
+            registry
+                .register(Box::new(histogram.clone()))
+                .expect("unique metric name");
+            histogram
+        };
+
+        Self {
+            pools_fetched: counter("mev_pools_fetched_total", "Pools fetched and decoded"),
+            opportunities_found: counter("mev_opportunities_found_total", "Arbitrage opportunities found"),
+            opportunities_expired: counter(
+                "mev_opportunities_expired_total",
+                "Queued opportunities dropped unsent after their blockhash expired",
+            ),
+            txs_submitted: counter("mev_txs_submitted_total", "Transactions handed to an endpoint or block engine"),
+            txs_landed: counter("mev_txs_landed_total", "Submitted transactions that confirmed"),
+            txs_reverted: counter("mev_txs_reverted_total", "Submitted transactions that landed with an error"),
+            pool_data_seconds: histogram(
+                "mev_initialize_pool_data_seconds",
+                "Time to load pool data for one mint",
+            ),
+            scan_seconds: histogram("mev_scan_seconds", "Time for one scan pass over the due mints"),
+            registry,
+        }
+    }
+
+    /// Render every metric in the Prometheus text format
+    pub fn encode(&self) -> String {
+        let mut buffer = Vec::new();
+        if let Err(e) = TextEncoder::new().encode(&self.registry.gather(), &mut buffer) {
+            error!("Failed to encode metrics: {}", e);
+        }
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+}
+
+/// Serve the metrics in the Prometheus text format on `addr`, on every path,
+/// until the server fails
+pub async fn serve(addr: SocketAddr) -> anyhow::Result<()> {
+    let make_service = make_service_fn(|_| async {
+        Ok::<_, Infallible>(service_fn(|_: Request<Body>| async {
+            let response = Response::builder()
+                .header(CONTENT_TYPE, TextEncoder::new().format_type())
+                .body(Body::from(metrics().encode()))
+                .expect("valid response");
+            Ok::<_, Infallible>(response)
+        }))
+    });
+
+    info!("Serving metrics on {}", addr);
+    Server::try_bind(&addr)?.serve(make_service).await?;
+    Ok(())
+}
+
+/// Start the metrics endpoint in the background when `[metrics]` is enabled
+pub fn spawn(config: Option<&MetricsConfig>) {
+    let Some(config) = config.filter(|c| c.enabled) else {
+        return;
+    };
+    let addr = config.socket_addr();
+    tokio::spawn(async move {
+        if let Err(e) = serve(addr).await {
+            error!("Metrics endpoint on {} stopped: {}", addr, e);
+        }
+    });
+}
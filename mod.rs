@@ -1,4 +1,8 @@
 pub mod traits;
+pub mod rpc;
+pub mod math;
+pub mod vault;
+pub mod metadata;
 pub mod meteora;
 pub mod pump;
 pub mod raydium;
@@ -6,5 +10,8 @@ pub mod solfi;
 pub mod vertigo;
 pub mod whirlpool;
 
+#[cfg(test)]
+pub(crate) mod fixtures;
+
 // Re-export common types for easier access
-pub use traits::{Dex, DexRegistry, PoolInfo, PriceInfo};
+pub use traits::{Dex, DexCapabilities, DexRegistry, PoolInfo, PoolReserves, PoolType, PriceInfo};
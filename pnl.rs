@@ -0,0 +1,238 @@
+//! Per-trade profit and loss, read back from landed transactions
+
+use crate::{
+    chain::constants::{LAMPORTS_PER_SIGNATURE, SOL_MINT},
+    error::{BotError, Result},
+    session,
+};
+use serde::{Deserialize, Serialize};
+use solana_client::{rpc_client::RpcClient, rpc_config::RpcTransactionConfig};
+use solana_sdk::{commitment_config::CommitmentConfig, signature::Signature};
+use solana_transaction_status::{
+    option_serializer::OptionSerializer, UiTransactionEncoding, UiTransactionTokenBalance,
+};
+use std::{
+    collections::VecDeque,
+    fs::{File, OpenOptions},
+    io::{BufRead, BufReader, BufWriter, Write},
+    path::PathBuf,
+};
+use tracing::{info, warn};
+
+/// Trades kept in memory for aggregate stats
+pub const DEFAULT_MAX_TRADES: usize = 1_000;
+
+/// Outcome of one landed trade, from the payer's point of view
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradePnl {
+    pub signature: String,
+    pub mint: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    /// Payer SOL plus WSOL before the transaction, in lamports
+    pub sol_in: u64,
+    /// Payer SOL plus WSOL after the transaction, in lamports
+    pub sol_out: u64,
+    /// Total transaction fee, base fee included
+    pub fee_lamports: u64,
+    /// Fee paid above the per-signature base fee
+    pub priority_fee_lamports: u64,
+    /// `sol_out - sol_in`, so fees are already deducted
+    pub net_profit_lamports: i64,
+}
+
+impl TradePnl {
+    pub fn is_win(&self) -> bool {
+        self.net_profit_lamports > 0
+    }
+}
+
+/// Aggregate results over the tracked trades
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct PnlStats {
+    pub trades: usize,
+    pub wins: usize,
+    pub total_profit: i64,
+    pub total_fees: u64,
+    /// Fraction of trades with positive net profit, `0.0` with no trades
+    pub win_rate: f64,
+}
+
+/// Rolling log of landed trades. The most recent `max_trades` are kept in
+/// memory, and each trade is appended to the log file as a JSON line when a
+/// path is set; existing lines are read back on creation so stats carry over
+/// restarts. Log writes are buffered until `flush` or drop.
+pub struct PnlTracker {
+    log_path: Option<PathBuf>,
+    log: Option<BufWriter<File>>,
+    max_trades: usize,
+    trades: VecDeque<TradePnl>,
+}
+
+impl PnlTracker {
+    pub fn new(log_path: Option<PathBuf>, max_trades: usize) -> Self {
+        let mut tracker = Self {
+            log_path,
+            log: None,
+            max_trades: max_trades.max(1),
+            trades: VecDeque::new(),
+        };
+        if let Err(e) = tracker.load() {
+            warn!("Failed to read PnL log: {}", e);
+        }
+        tracker
+    }
+
+    /// Read the landed transaction, work out its profit and record it
+    pub fn record_landed(&mut self, rpc_client: &RpcClient, signature: &Signature, mint: &str) -> Result<TradePnl> {
+        let trade = fetch_trade_pnl(rpc_client, signature, mint)?;
+        info!(
+            "Trade {} on {}: {} lamports net ({} fee)",
+            trade.signature, trade.mint, trade.net_profit_lamports, trade.fee_lamports
+        );
+        session::stats().record_landed(
+            mint,
+            trade.net_profit_lamports + trade.fee_lamports as i64,
+            trade.fee_lamports,
+        );
+        self.push(trade.clone())?;
+        Ok(trade)
+    }
+
+    /// Add a trade to the rolling window and the log file
+    pub fn push(&mut self, trade: TradePnl) -> Result<()> {
+        if let Some(path) = &self.log_path {
+            if self.log.is_none() {
+                let file = OpenOptions::new().create(true).append(true).open(path)?;
+                self.log = Some(BufWriter::new(file));
+            }
+            if let Some(log) = self.log.as_mut() {
+                writeln!(log, "{}", serde_json::to_string(&trade)?)?;
+            }
+        }
+        self.trades.push_back(trade);
+        while self.trades.len() > self.max_trades {
+            self.trades.pop_front();
+        }
+        Ok(())
+    }
+
+    /// Write any buffered trades through to the log file
+    pub fn flush(&mut self) -> Result<()> {
+        if let Some(log) = self.log.as_mut() {
+            log.flush()?;
+        }
+        Ok(())
+    }
+
+    pub fn trades(&self) -> impl Iterator<Item = &TradePnl> {
+        self.trades.iter()
+    }
+
+    pub fn stats(&self) -> PnlStats {
+        let trades = self.trades.len();
+        let wins = self.trades.iter().filter(|t| t.is_win()).count();
+        PnlStats {
+            trades,
+            wins,
+            total_profit: self.trades.iter().map(|t| t.net_profit_lamports).sum(),
+            total_fees: self.trades.iter().map(|t| t.fee_lamports).sum(),
+            win_rate: if trades == 0 { 0.0 } else { wins as f64 / trades as f64 },
+        }
+    }
+
+    fn load(&mut self) -> Result<()> {
+        let Some(path) = self.log_path.as_ref().filter(|p| p.exists()) else {
+            return Ok(());
+        };
+        let reader = BufReader::new(std::fs::File::open(path)?);
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            match serde_json::from_str::<TradePnl>(&line) {
+                Ok(trade) => {
+                    self.trades.push_back(trade);
+                    if self.trades.len() > self.max_trades {
+                        self.trades.pop_front();
+                    }
+                }
+                Err(e) => warn!("Skipping malformed PnL log line: {}", e),
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for PnlTracker {
+    fn drop(&mut self) {
+        if let Err(e) = self.flush() {
+            warn!("Failed to flush PnL log: {}", e);
+        }
+    }
+}
+
+/// Profit of a confirmed transaction from the fee payer's SOL and WSOL
+/// balances before and after it
+pub fn fetch_trade_pnl(rpc_client: &RpcClient, signature: &Signature, mint: &str) -> Result<TradePnl> {
+    let confirmed = rpc_client.get_transaction_with_config(
+        signature,
+        RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(CommitmentConfig::confirmed()),
+            max_supported_transaction_version: Some(0),
+        },
+    )?;
+
+    let tx = confirmed
+        .transaction
+        .transaction
+        .decode()
+        .ok_or_else(|| BotError::Parse(format!("Undecodable transaction {}", signature)))?;
+    let meta = confirmed
+        .transaction
+        .meta
+        .ok_or_else(|| BotError::Parse(format!("Transaction {} has no status meta", signature)))?;
+    if let Some(err) = &meta.err {
+        warn!("Transaction {} landed with error: {}", signature, err);
+    }
+
+    let payer = tx
+        .message
+        .static_account_keys()
+        .first()
+        .ok_or_else(|| BotError::Parse(format!("Transaction {} has no accounts", signature)))?
+        .to_string();
+    let (Some(pre_sol), Some(post_sol)) = (meta.pre_balances.first(), meta.post_balances.first()) else {
+        return Err(BotError::Parse(format!("Transaction {} has no balances", signature)));
+    };
+
+    let sol_in = pre_sol + wsol_balance(&meta.pre_token_balances, &payer);
+    let sol_out = post_sol + wsol_balance(&meta.post_token_balances, &payer);
+    let base_fee = LAMPORTS_PER_SIGNATURE * tx.signatures.len() as u64;
+
+    Ok(TradePnl {
+        signature: signature.to_string(),
+        mint: mint.to_string(),
+        slot: confirmed.slot,
+        block_time: confirmed.block_time,
+        sol_in,
+        sol_out,
+        fee_lamports: meta.fee,
+        priority_fee_lamports: meta.fee.saturating_sub(base_fee),
+        net_profit_lamports: sol_out as i64 - sol_in as i64,
+    })
+}
+
+/// WSOL held by `owner` across the token balances of a transaction
+fn wsol_balance(balances: &OptionSerializer<Vec<UiTransactionTokenBalance>>, owner: &str) -> u64 {
+    let OptionSerializer::Some(balances) = balances else {
+        return 0;
+    };
+    balances
+        .iter()
+        .filter(|b| b.mint == SOL_MINT && matches!(&b.owner, OptionSerializer::Some(o) if o == owner))
+        .filter_map(|b| b.ui_token_amount.amount.parse::<u64>().ok())
+        .sum()
+}
@@ -1,35 +1,37 @@
-use std::str::FromStr;
-
-use anyhow::Result;
+use crate::error::{BotError, Result};
 use solana_program::pubkey::Pubkey;
 
+use super::constants::coin_creator_vault_authority;
+
+/// Offset of `base_mint` in a Pump AMM `Pool` account, after the
+/// discriminator, `pool_bump`, `index` and `creator`
+const POOL_FIELDS_OFFSET: usize = 8 + 1 + 2 + 32;
+/// Length of `base_mint` through `lp_supply`: five pubkeys and a u64
+const POOL_FIELDS_LEN: usize = 5 * 32 + 8;
+
 #[derive(Debug)]
 pub struct PumpAmmInfo {
     pub base_mint: Pubkey,
     pub quote_mint: Pubkey,
     pub pool_base_token_account: Pubkey,
     pub pool_quote_token_account: Pubkey,
+    /// `Pubkey::default()` for pools created before creator fees existed
+    pub coin_creator: Pubkey,
     pub coin_creator_vault_authority: Pubkey,
 }
 
 impl PumpAmmInfo {
     pub fn load_checked(data: &[u8]) -> Result<Self> {
-        let data = &data[8 + 1 + 2 + 32..];
-
-        if data.len() < 4 * 32 + 8 {
-            // 4 Pubkeys (32 bytes each) + lp_supply (8 bytes)
-            return Err(anyhow::anyhow!("Invalid data length for PumpAmmInfo"));
+        if data.len() < POOL_FIELDS_OFFSET + POOL_FIELDS_LEN {
+            return Err(BotError::PoolParse("Invalid data length for PumpAmmInfo".to_string()));
         }
+        let data = &data[POOL_FIELDS_OFFSET..];
 
         let base_mint = Pubkey::from(<[u8; 32]>::try_from(&data[0..32]).unwrap());
         let quote_mint = Pubkey::from(<[u8; 32]>::try_from(&data[32..64]).unwrap());
         let pool_base_token_account = Pubkey::from(<[u8; 32]>::try_from(&data[96..128]).unwrap());
         let pool_quote_token_account = Pubkey::from(<[u8; 32]>::try_from(&data[128..160]).unwrap());
 
-        let pump_program_id =
-            Pubkey::from_str("pAMMBay6oceH9fJKBRHGP5D4bD4sWpmSwMn52FMfXEA").unwrap();
-        println!("data: {:?}", data.len());
-
         let coin_creator = if data.len() < 257 {
             Pubkey::default()
         } else {
@@ -37,25 +39,201 @@ impl PumpAmmInfo {
                 data[168..200].try_into().unwrap(),
             )
         };
-        let key = Pubkey::find_program_address(
-            &[b"creator_vault", coin_creator.as_ref()],
-            &pump_program_id,
-        );
-
-        println!("coin_creator: {:?}", key.0);
 
         Ok(Self {
             base_mint,
             quote_mint,
             pool_base_token_account,
             pool_quote_token_account,
-            coin_creator_vault_authority: key.0,
+            coin_creator,
+            coin_creator_vault_authority: coin_creator_vault_authority(&coin_creator),
         })
     }
 }
 
+/// Offsets in the Pump AMM `GlobalConfig` account: the discriminator and
+/// admin come first, then `lp_fee_basis_points` and
+/// `protocol_fee_basis_points`, then `disable_flags` and eight protocol fee
+/// recipients before `coin_creator_fee_basis_points`
+const GLOBAL_CONFIG_LP_FEE_OFFSET: usize = 8 + 32;
+const GLOBAL_CONFIG_PROTOCOL_FEE_OFFSET: usize = GLOBAL_CONFIG_LP_FEE_OFFSET + 8;
+const GLOBAL_CONFIG_COIN_CREATOR_FEE_OFFSET: usize = GLOBAL_CONFIG_PROTOCOL_FEE_OFFSET + 8 + 1 + 8 * 32;
+
+/// Total swap fee from the Pump AMM `GlobalConfig` account: the LP, protocol
+/// and coin creator shares. Accounts written before creator fees existed end
+/// before that field, which then counts as zero.
+pub fn global_config_fee_bps(data: &[u8]) -> Result<u16> {
+    let read = |offset: usize| {
+        data.get(offset..offset + 8)
+            .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+    };
+    let (Some(lp_fee), Some(protocol_fee)) = (
+        read(GLOBAL_CONFIG_LP_FEE_OFFSET),
+        read(GLOBAL_CONFIG_PROTOCOL_FEE_OFFSET),
+    ) else {
+        return Err(BotError::PoolParse("Invalid data length for Pump GlobalConfig".to_string()));
+    };
+    let coin_creator_fee = read(GLOBAL_CONFIG_COIN_CREATOR_FEE_OFFSET).unwrap_or(0);
+
+    let total = lp_fee + protocol_fee + coin_creator_fee;
+    if total >= 10_000 {
+        return Err(BotError::PoolParse(format!("Pump GlobalConfig fee of {} bps is invalid", total)));
+    }
+    Ok(total as u16)
+}
+
 pub fn get_pump_info(data: &[u8]) -> Result<Pubkey> {
     Ok(Pubkey::new_from_array(
         data[168..200].try_into().unwrap(),
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::pump::constants::pump_program_id;
+    use std::str::FromStr;
+
+    /// Pool account with the given creator written after `lp_supply`, or cut
+    /// off before it like pools created before creator fees existed
+    fn pool_account(coin_creator: Option<Pubkey>) -> Vec<u8> {
+        let mut data = vec![0u8; POOL_FIELDS_OFFSET + POOL_FIELDS_LEN];
+        let fields = &mut data[POOL_FIELDS_OFFSET..];
+        for (i, key) in [0, 1, 3, 4].into_iter().zip(1u8..) {
+            fields[i * 32..(i + 1) * 32].fill(key);
+        }
+        if let Some(coin_creator) = coin_creator {
+            data.extend_from_slice(coin_creator.as_ref());
+            data.resize(POOL_FIELDS_OFFSET + 257, 0);
+        }
+        data
+    }
+
+    fn key(address: &str) -> Pubkey {
+        Pubkey::from_str(address).unwrap()
+    }
+
+    /// Canonical (index 0) WSOL pool of a pump.fun token, laid out as the
+    /// program writes it: pool bump, index and creator, then the mints, the
+    /// LP mint, the pool's ATAs of both mints, `lp_supply` and the coin creator
+    fn canonical_pool_account() -> Vec<u8> {
+        let mut data = vec![0u8; 300];
+        data[8] = 255;
+        data[11..43].copy_from_slice(key("9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM").as_ref());
+        let fields = [
+            key("9BB6NFEcjBCtnNLFko2FqVQBq8HHM13kCyYcdQbgpump"),
+            key("So11111111111111111111111111111111111111112"),
+            Pubkey::new_unique(),
+            key("BNJ9sB5ERSnZzD5M58dZD1BqBnUCTxRNTNkuNX1TeRY5"),
+            key("JCZA6bhox4bgFPPFxJCUdX9joMyP7fVgyyNvV17RH1ev"),
+        ];
+        for (i, field) in fields.iter().enumerate() {
+            let offset = POOL_FIELDS_OFFSET + i * 32;
+            data[offset..offset + 32].copy_from_slice(field.as_ref());
+        }
+        data[203..211].copy_from_slice(&4_193_388_154_317u64.to_le_bytes());
+        data[211..243].copy_from_slice(key("9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM").as_ref());
+        data
+    }
+
+    #[test]
+    fn load_checked_decodes_a_canonical_pool() {
+        let pool = key("8QSmuHRTRfY8unQNBdUG3p2RWLfLuzNGmK6YfC1VXUYd");
+        let creator = key("9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM");
+
+        let info = PumpAmmInfo::load_checked(&canonical_pool_account()).unwrap();
+
+        assert_eq!(info.base_mint, key("9BB6NFEcjBCtnNLFko2FqVQBq8HHM13kCyYcdQbgpump"));
+        assert_eq!(info.quote_mint, key("So11111111111111111111111111111111111111112"));
+        assert_eq!(
+            Pubkey::find_program_address(
+                &[b"pool", &0u16.to_le_bytes(), creator.as_ref(), info.base_mint.as_ref(), info.quote_mint.as_ref()],
+                &pump_program_id(),
+            )
+            .0,
+            pool
+        );
+        assert_eq!(
+            info.pool_base_token_account,
+            spl_associated_token_account::get_associated_token_address(&pool, &info.base_mint)
+        );
+        assert_eq!(
+            info.pool_quote_token_account,
+            spl_associated_token_account::get_associated_token_address(&pool, &info.quote_mint)
+        );
+        assert_eq!(info.coin_creator, creator);
+        assert_eq!(
+            info.coin_creator_vault_authority,
+            key("8zW8T4CkMsCAA1VVJeYj8F6MZaQGWYKDuwXSPSD924aP")
+        );
+    }
+
+    #[test]
+    fn load_checked_rejects_accounts_too_short_for_the_pool_fields() {
+        for len in [0, POOL_FIELDS_OFFSET - 1, POOL_FIELDS_OFFSET, POOL_FIELDS_OFFSET + POOL_FIELDS_LEN - 1] {
+            assert!(
+                matches!(PumpAmmInfo::load_checked(&vec![0u8; len]), Err(BotError::PoolParse(_))),
+                "{} bytes",
+                len
+            );
+        }
+    }
+
+    #[test]
+    fn coin_creator_vault_authority_matches_known_pda() {
+        let coin_creator = Pubkey::from_str("9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM").unwrap();
+        assert_eq!(
+            coin_creator_vault_authority(&coin_creator),
+            Pubkey::from_str("8zW8T4CkMsCAA1VVJeYj8F6MZaQGWYKDuwXSPSD924aP").unwrap()
+        );
+    }
+
+    #[test]
+    fn load_checked_derives_the_vault_authority_from_the_creator() {
+        let coin_creator = Pubkey::new_unique();
+        let info = PumpAmmInfo::load_checked(&pool_account(Some(coin_creator))).unwrap();
+
+        assert_eq!(info.base_mint, Pubkey::new_from_array([1; 32]));
+        assert_eq!(info.quote_mint, Pubkey::new_from_array([2; 32]));
+        assert_eq!(info.pool_base_token_account, Pubkey::new_from_array([3; 32]));
+        assert_eq!(info.pool_quote_token_account, Pubkey::new_from_array([4; 32]));
+        assert_eq!(info.coin_creator, coin_creator);
+        assert_eq!(info.coin_creator_vault_authority, coin_creator_vault_authority(&coin_creator));
+    }
+
+    #[test]
+    fn load_checked_without_a_creator_uses_the_default_creator_vault() {
+        let info = PumpAmmInfo::load_checked(&pool_account(None)).unwrap();
+
+        assert_eq!(info.coin_creator, Pubkey::default());
+        assert_eq!(info.coin_creator_vault_authority, coin_creator_vault_authority(&Pubkey::default()));
+    }
+
+    fn global_config(lp_fee: u64, protocol_fee: u64, coin_creator_fee: Option<u64>) -> Vec<u8> {
+        let mut data = vec![0u8; GLOBAL_CONFIG_COIN_CREATOR_FEE_OFFSET];
+        data[GLOBAL_CONFIG_LP_FEE_OFFSET..GLOBAL_CONFIG_LP_FEE_OFFSET + 8].copy_from_slice(&lp_fee.to_le_bytes());
+        data[GLOBAL_CONFIG_PROTOCOL_FEE_OFFSET..GLOBAL_CONFIG_PROTOCOL_FEE_OFFSET + 8]
+            .copy_from_slice(&protocol_fee.to_le_bytes());
+        if let Some(coin_creator_fee) = coin_creator_fee {
+            data.extend_from_slice(&coin_creator_fee.to_le_bytes());
+            data.extend_from_slice(&[0u8; 32]);
+        }
+        data
+    }
+
+    #[test]
+    fn global_config_fee_sums_every_share() {
+        assert_eq!(global_config_fee_bps(&global_config(20, 5, Some(5))).unwrap(), 30);
+    }
+
+    #[test]
+    fn global_config_fee_before_creator_fees() {
+        assert_eq!(global_config_fee_bps(&global_config(20, 5, None)).unwrap(), 25);
+    }
+
+    #[test]
+    fn global_config_fee_rejects_short_or_invalid_data() {
+        assert!(global_config_fee_bps(&[0u8; 47]).is_err());
+        assert!(global_config_fee_bps(&global_config(10_000, 0, None)).is_err());
+    }
+}
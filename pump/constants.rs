@@ -8,6 +8,21 @@ pub fn pump_program_id() -> Pubkey {
     Pubkey::from_str(PUMP_PROGRAM_ID).unwrap()
 }
 
+pub const CREATOR_VAULT_SEED: &[u8] = b"creator_vault";
+
+/// PDA that owns a coin creator's fee vault: `["creator_vault", coin_creator]`
+pub fn coin_creator_vault_authority(coin_creator: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[CREATOR_VAULT_SEED, coin_creator.as_ref()], &pump_program_id()).0
+}
+
 pub fn pump_fee_wallet() -> Pubkey {
     Pubkey::from_str(PUMP_FEE_WALLET).unwrap()
 }
+
+pub fn pump_global_config() -> Pubkey {
+    Pubkey::from_str("ADyA8hdefvWN2dbGGWFotbzWxrAvLW83WG6QCVXvJKqw").unwrap()
+}
+
+pub fn pump_event_authority() -> Pubkey {
+    Pubkey::from_str("GS4CU59F31iL7aR2Q8zVS8DRrcRnXX1yjQ66TqNVQnaR").unwrap()
+}
@@ -1,22 +1,50 @@
 //! Unified Pump DEX implementation using the Dex trait
 
-use crate::dex::traits::{Dex, PoolInfo, PriceInfo};
-use crate::dex::pump::{amm_info::PumpAmmInfo, constants::*};
+use crate::dex::traits::{
+    validate_swap_amounts, verify_pool_owner, Dex, DexCapabilities, PoolInfo, PoolReserves, PoolType, PriceInfo,
+};
+use crate::dex::pump::{
+    amm_info::{global_config_fee_bps, PumpAmmInfo},
+    constants::*,
+};
+use crate::dex::math::{constant_product_min_input, quote_constant_product};
+use crate::dex::vault::{decimal_adjusted_price, fetch_pool_reserves, pool_reserves, MintDecimalsCache};
+use crate::dex::rpc::{blocking, RpcLike};
 use async_trait::async_trait;
-use solana_client::rpc_client::RpcClient;
-use solana_sdk::pubkey::Pubkey;
-use std::sync::Arc;
-use spl_associated_token_account;
-use anyhow::Result;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_program,
+};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use spl_associated_token_account::{self, get_associated_token_address_with_program_id};
+use crate::dex_boilerplate;
+use crate::error::{BotError, Result};
 
+/// Anchor discriminators of the Pump AMM `buy` and `sell` instructions
+const PUMP_BUY_DISCRIMINATOR: [u8; 8] = [102, 6, 61, 18, 1, 218, 235, 234];
+const PUMP_SELL_DISCRIMINATOR: [u8; 8] = [51, 230, 133, 164, 1, 127, 131, 173];
+
+/// Pump AMM swap fee in total, used until the `GlobalConfig` account has been
+/// read: 20 bps to LPs, 5 bps protocol and 5 bps coin creator, per
+/// https://github.com/pump-fun/pump-public-docs
+const PUMP_FEE_BPS: u16 = 30;
+/// Offsets of `base_mint` and `quote_mint` in a Pump AMM pool account
+const PUMP_POOL_MINT_OFFSETS: [usize; 2] = [43, 75];
+
+#[derive(Clone)]
 pub struct PumpDex {
-    rpc_client: Arc<RpcClient>,
+    rpc_client: Arc<dyn RpcLike>,
+    decimals: Arc<MintDecimalsCache>,
+    /// Swap fee read from the `GlobalConfig` account, once it has been read
+    fee_bps: Arc<Mutex<Option<u16>>>,
 }
 
-dex_boilerplate!(PumpDex, "pump", pump_program_id());
-
 #[async_trait]
 impl Dex for PumpDex {
+    dex_boilerplate!("pump", pump_program_id());
+
     async fn fetch_pools(&self, pool_addresses: &[String], token_mint: &Pubkey) -> Result<Vec<PoolInfo>> {
         let mut pools = Vec::new();
 
@@ -33,47 +61,26 @@ impl Dex for PumpDex {
     }
 
     async fn calculate_price(&self, pool_info: &PoolInfo) -> Result<PriceInfo> {
-        // Pump.fun has a bonding curve pricing mechanism
-        // For now, return a placeholder - would need actual bonding curve calculation
-        Ok(PriceInfo {
-            price: 0.0, // TODO: Implement Pump.fun bonding curve pricing
-            liquidity: 0, // TODO: Calculate actual liquidity
-            fee: 0.01, // Pump.fun fee
-        })
+        let (dex, pool_info) = (self.clone(), pool_info.clone());
+        blocking(move || dex.read_price(&pool_info)).await
     }
 
-    fn get_swap_instruction_data(&self, pool_info: &PoolInfo, amount_in: u64, minimum_out: u64) -> Result<Vec<u8>> {
-        // TODO: Implement Pump.fun swap instruction encoding
-        Ok(Vec::new())
-    }
-}
-
-impl PumpDex {
-    pub fn new(rpc_client: Arc<RpcClient>) -> Self {
-        Self { rpc_client }
+    async fn fetch_reserves(&self, pool_info: &PoolInfo) -> Result<PoolReserves> {
+        let (dex, pool_info) = (self.clone(), pool_info.clone());
+        blocking(move || {
+            dex.load_fee_bps();
+            fetch_pool_reserves(dex.rpc_client.as_ref(), &pool_info)
+        })
+        .await
     }
 
-    async fn fetch_single_pool(&self, pool_address: &str, token_mint: &Pubkey) -> Result<PoolInfo> {
-        let pool_pubkey = Pubkey::from_str(pool_address)?;
-        let account = self.rpc_client.get_account(&pool_pubkey)?;
+    fn parse_pool_account(&self, address: &Pubkey, data: &[u8], token_mint: &Pubkey) -> Result<PoolInfo> {
+        let pool_pubkey = *address;
 
-        if account.owner != pump_program_id() {
-            return Err(anyhow::anyhow!(
-                "Account is not owned by Pump program: {}",
-                pool_address
-            ));
-        }
-
-        let amm_info = PumpAmmInfo::load_checked(&account.data)?;
-
-        let (token_vault, base_vault) = if crate::chain::constants::sol_mint() == amm_info.base_mint {
-            (amm_info.pool_base_token_account, amm_info.pool_quote_token_account)
-        } else if crate::chain::constants::sol_mint() == amm_info.quote_mint {
-            (amm_info.pool_quote_token_account, amm_info.pool_base_token_account)
-        } else {
-            (amm_info.pool_base_token_account, amm_info.pool_quote_token_account)
-        };
+        let amm_info = PumpAmmInfo::load_checked(data)?;
 
+        // Both are derived for an SPL Token quote mint; swaps re-derive them
+        // under whichever program owns the quote mint
         let fee_token_wallet = spl_associated_token_account::get_associated_token_address(
             &pump_fee_wallet(),
             &amm_info.quote_mint,
@@ -84,14 +91,31 @@ impl PumpDex {
             &amm_info.quote_mint,
         );
 
-        let (token_mint_final, base_mint) = if *token_mint == amm_info.base_mint {
-            (amm_info.base_mint, amm_info.quote_mint)
+        // The SOL side is whichever mint is not `token_mint`; vaults follow the
+        // same orientation so `base_vault` always holds SOL for SOL pairs
+        let (token_mint_final, base_mint, token_vault, base_vault) = if *token_mint == amm_info.base_mint {
+            (
+                amm_info.base_mint,
+                amm_info.quote_mint,
+                amm_info.pool_base_token_account,
+                amm_info.pool_quote_token_account,
+            )
         } else {
-            (amm_info.quote_mint, amm_info.base_mint)
+            (
+                amm_info.quote_mint,
+                amm_info.base_mint,
+                amm_info.pool_quote_token_account,
+                amm_info.pool_base_token_account,
+            )
         };
 
         let mut additional_accounts = std::collections::HashMap::new();
         additional_accounts.insert("coin_creator_vault_ata".to_string(), coin_creator_vault_ata);
+        additional_accounts.insert(
+            "coin_creator_vault_authority".to_string(),
+            amm_info.coin_creator_vault_authority,
+        );
+        additional_accounts.insert("pool_base_mint".to_string(), amm_info.base_mint);
 
         Ok(PoolInfo {
             pool_address: pool_pubkey,
@@ -101,6 +125,408 @@ impl PumpDex {
             base_vault,
             fee_wallet: Some(fee_token_wallet),
             additional_accounts,
+            // The Pump AMM is a constant-product pool, not the launch bonding curve
+            pool_type: PoolType::ConstantProduct,
+            reserves: None,
+            token_decimals: None,
+            base_decimals: None,
+            token_symbol: None,
+            token_transfer_fee_bps: 0,
+            base_transfer_fee_bps: 0,
         })
     }
+
+    fn mint_offsets(&self) -> &'static [usize] {
+        &PUMP_POOL_MINT_OFFSETS
+    }
+
+    fn get_swap_instruction_data(&self, pool_info: &PoolInfo, amount_in: u64, minimum_out: u64) -> Result<Vec<u8>> {
+        validate_swap_amounts(amount_in, minimum_out)?;
+
+        // Pump swaps are named from the pool's base side: receiving base is a
+        // `buy` with an exact output, paying base in is a `sell`
+        let buying_base = pool_info.token_mint == pool_info.additional_account("pool_base_mint")?;
+        let mut data = Vec::with_capacity(24);
+        if buying_base {
+            // `buy` takes an exact output, so ask for the quoted amount and let
+            // `amount_in` cap what may be paid for it
+            let (token_reserve, base_reserve) = pool_reserves(self.rpc_client.as_ref(), pool_info)?;
+            let base_amount_out = self.quote_with_reserves(
+                base_reserve,
+                token_reserve,
+                pool_info.amount_in_after_transfer_fee(amount_in),
+                self.fee_bps(),
+            )?;
+            if pool_info.amount_out_after_transfer_fee(base_amount_out) < minimum_out {
+                return Err(BotError::Transaction(format!(
+                    "Pump pool {} quotes {} out for {} in, below the minimum of {}",
+                    pool_info.pool_address, base_amount_out, amount_in, minimum_out
+                )));
+            }
+            data.extend_from_slice(&PUMP_BUY_DISCRIMINATOR);
+            data.extend_from_slice(&base_amount_out.to_le_bytes());
+            data.extend_from_slice(&amount_in.to_le_bytes()); // max_quote_amount_in
+        } else {
+            data.extend_from_slice(&PUMP_SELL_DISCRIMINATOR);
+            data.extend_from_slice(&amount_in.to_le_bytes()); // base_amount_in
+            data.extend_from_slice(&minimum_out.to_le_bytes()); // min_quote_amount_out
+        }
+        Ok(data)
+    }
+
+    fn build_swap_instruction(
+        &self,
+        pool_info: &PoolInfo,
+        user: &Pubkey,
+        amount_in: u64,
+        minimum_out: u64,
+    ) -> Result<Instruction> {
+        let data = self.get_swap_instruction_data(pool_info, amount_in, minimum_out)?;
+        let pool_base_mint = pool_info.additional_account("pool_base_mint")?;
+        let buying_base = pool_info.token_mint == pool_base_mint;
+        let (quote_mint, pool_base_account, pool_quote_account) = if buying_base {
+            (pool_info.base_mint, pool_info.token_vault, pool_info.base_vault)
+        } else {
+            (pool_info.token_mint, pool_info.base_vault, pool_info.token_vault)
+        };
+
+        // Either side may be a Token-2022 mint, so token accounts are derived
+        // under the program that owns each mint
+        let base_token_program = self.decimals.token_program(&pool_base_mint)?;
+        let quote_token_program = self.decimals.token_program(&quote_mint)?;
+        let protocol_fee_account =
+            get_associated_token_address_with_program_id(&pump_fee_wallet(), &quote_mint, &quote_token_program);
+        let coin_creator_vault_authority = pool_info.additional_account("coin_creator_vault_authority")?;
+        let coin_creator_vault_ata = get_associated_token_address_with_program_id(
+            &coin_creator_vault_authority,
+            &quote_mint,
+            &quote_token_program,
+        );
+
+        let mut accounts = vec![
+            AccountMeta::new(pool_info.pool_address, false),
+            AccountMeta::new(*user, true),
+            AccountMeta::new_readonly(pump_global_config(), false),
+            AccountMeta::new_readonly(pool_base_mint, false),
+            AccountMeta::new_readonly(quote_mint, false),
+            AccountMeta::new(
+                get_associated_token_address_with_program_id(user, &pool_base_mint, &base_token_program),
+                false,
+            ),
+            AccountMeta::new(
+                get_associated_token_address_with_program_id(user, &quote_mint, &quote_token_program),
+                false,
+            ),
+            AccountMeta::new(pool_base_account, false),
+            AccountMeta::new(pool_quote_account, false),
+            AccountMeta::new_readonly(pump_fee_wallet(), false),
+            AccountMeta::new(protocol_fee_account, false),
+            AccountMeta::new_readonly(base_token_program, false),
+            AccountMeta::new_readonly(quote_token_program, false),
+            AccountMeta::new_readonly(system_program::ID, false),
+            AccountMeta::new_readonly(spl_associated_token_account::ID, false),
+            AccountMeta::new_readonly(pump_event_authority(), false),
+            AccountMeta::new_readonly(pump_program_id(), false),
+            AccountMeta::new(coin_creator_vault_ata, false),
+            AccountMeta::new_readonly(coin_creator_vault_authority, false),
+        ];
+
+        if buying_base {
+            let (global_volume_accumulator, _) =
+                Pubkey::find_program_address(&[b"global_volume_accumulator"], &pump_program_id());
+            let (user_volume_accumulator, _) = Pubkey::find_program_address(
+                &[b"user_volume_accumulator", user.as_ref()],
+                &pump_program_id(),
+            );
+            accounts.push(AccountMeta::new(global_volume_accumulator, false));
+            accounts.push(AccountMeta::new(user_volume_accumulator, false));
+        }
+
+        Ok(Instruction {
+            program_id: pump_program_id(),
+            accounts,
+            data,
+        })
+    }
+
+    fn requires_fee_wallet(&self) -> bool {
+        true
+    }
+
+    fn capabilities(&self) -> DexCapabilities {
+        DexCapabilities {
+            supports_token_2022: true,
+            supports_swap: true,
+            ..DexCapabilities::default()
+        }
+    }
+
+    fn min_viable_input(&self, pool_info: &PoolInfo) -> u64 {
+        match pool_info.reserves {
+            Some((token_reserve, base_reserve)) => {
+                constant_product_min_input(base_reserve, token_reserve, self.fee_bps())
+            }
+            None => 1,
+        }
+    }
+
+    async fn quote(&self, pool_info: &PoolInfo, amount_in: u64, reserves: &PoolReserves) -> Result<u64> {
+        let amount_out = self.quote_with_reserves(
+            reserves.base_reserve,
+            reserves.token_reserve,
+            pool_info.amount_in_after_transfer_fee(amount_in),
+            self.fee_bps(),
+        )?;
+        Ok(pool_info.amount_out_after_transfer_fee(amount_out))
+    }
+}
+
+impl PumpDex {
+    /// Quote a swap against explicitly supplied reserves instead of live accounts
+    pub fn quote_with_reserves(&self, reserve_in: u64, reserve_out: u64, amount_in: u64, fee_bps: u16) -> Result<u64> {
+        quote_constant_product(reserve_in, reserve_out, amount_in, fee_bps)
+    }
+
+    pub fn new(rpc_client: Arc<dyn RpcLike>) -> Self {
+        Self {
+            decimals: Arc::new(MintDecimalsCache::new(rpc_client.clone())),
+            rpc_client,
+            fee_bps: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Price from the pool's vault balances, blocking on the reads
+    fn read_price(&self, pool_info: &PoolInfo) -> Result<PriceInfo> {
+        // Migrated Pump pools are constant-product between the two pool token
+        // accounts, so the marginal price is the reserve ratio
+        let (token_reserve, base_reserve) = pool_reserves(self.rpc_client.as_ref(), pool_info)?;
+        let (token_decimals, base_decimals) =
+            self.decimals.for_pool(pool_info)?;
+
+        let price = decimal_adjusted_price(token_reserve, base_reserve, token_decimals, base_decimals)
+            .ok_or_else(|| {
+                BotError::PriceCalculation(format!("Pump pool {} has an empty token vault", pool_info.pool_address))
+            })?;
+
+        Ok(pool_info.with_transfer_fees(PriceInfo {
+            price,
+            liquidity: base_reserve,
+            fee: self.load_fee_bps() as f64 / 10_000.0,
+        }))
+    }
+
+    /// Total swap fee read from the `GlobalConfig` account so far, or
+    /// `PUMP_FEE_BPS` until `load_fee_bps` has read it. Never touches the RPC,
+    /// so it is safe to call from async code.
+    fn fee_bps(&self) -> u16 {
+        self.fee_bps.lock().unwrap().unwrap_or(PUMP_FEE_BPS)
+    }
+
+    /// Total swap fee from the `GlobalConfig` account, read once. Until a read
+    /// succeeds `PUMP_FEE_BPS` is used, and the read is retried on next use.
+    /// Blocks on the read, so async callers go through `blocking`.
+    fn load_fee_bps(&self) -> u16 {
+        let mut fee_bps = self.fee_bps.lock().unwrap();
+        if let Some(fee_bps) = *fee_bps {
+            return fee_bps;
+        }
+        let read = self
+            .rpc_client
+            .get_account(&pump_global_config())
+            .map_err(BotError::from)
+            .and_then(|account| global_config_fee_bps(&account.data));
+        match read {
+            Ok(read) => *fee_bps.insert(read),
+            Err(e) => {
+                tracing::warn!("Failed to read Pump global config, assuming a {} bps fee: {}", PUMP_FEE_BPS, e);
+                PUMP_FEE_BPS
+            }
+        }
+    }
+
+    async fn fetch_single_pool(&self, pool_address: &str, token_mint: &Pubkey) -> Result<PoolInfo> {
+        let (dex, pool_address, token_mint) = (self.clone(), pool_address.to_string(), *token_mint);
+        blocking(move || dex.read_single_pool(&pool_address, &token_mint)).await
+    }
+
+    fn read_single_pool(&self, pool_address: &str, token_mint: &Pubkey) -> Result<PoolInfo> {
+        let pool_pubkey = Pubkey::from_str(pool_address)?;
+        let account = self.rpc_client.get_account(&pool_pubkey)?;
+
+        verify_pool_owner(&account, &pump_program_id())?;
+
+        let mut pool_info = self.parse_pool_account(&pool_pubkey, &account.data, token_mint)?;
+        self.decimals.fill(&mut pool_info)?;
+        self.load_fee_bps();
+        Ok(pool_info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::constants::{sol_mint, token_2022_program_id};
+    use crate::dex::math::{constant_product_out, deduct_transfer_fee};
+    use crate::dex::{fixtures::load_account, rpc::MockRpc};
+    use solana_sdk::account::Account;
+    use spl_associated_token_account::get_associated_token_address;
+
+    fn mint_account(owner: Pubkey, decimals: u8) -> Account {
+        let mut data = vec![0u8; 82];
+        data[44] = decimals;
+        Account {
+            lamports: 1_461_600,
+            data,
+            owner,
+            ..Account::default()
+        }
+    }
+
+    /// A pool whose base side is `base_mint`, oriented to sell SOL for it,
+    /// holding 500 tokens per SOL
+    fn pool(base_mint: Pubkey) -> PoolInfo {
+        let mut pool = PoolInfo::mock(PoolType::ConstantProduct);
+        pool.token_mint = base_mint;
+        pool.base_mint = sol_mint();
+        pool.fee_wallet = Some(Pubkey::new_unique());
+        pool.reserves = Some((500_000_000_000, 100_000_000_000));
+        pool.additional_accounts.insert("pool_base_mint".to_string(), base_mint);
+        pool.additional_accounts
+            .insert("coin_creator_vault_authority".to_string(), Pubkey::new_unique());
+        pool
+    }
+
+    #[test]
+    fn pump_pool_fixture_parses_into_every_pool_info_field() {
+        let (address, account) = load_account("pump_amm_wsol_pool.json");
+        let token = Pubkey::from_str("9BB6NFEcjBCtnNLFko2FqVQBq8HHM13kCyYcdQbgpump").unwrap();
+        let creator_vault_authority = coin_creator_vault_authority(
+            &Pubkey::from_str("9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM").unwrap(),
+        );
+        assert_eq!(account.owner, pump_program_id());
+        let dex = PumpDex::new(Arc::new(MockRpc::new()));
+
+        let pool = dex.parse_pool_account(&address, &account.data, &token).unwrap();
+
+        assert_eq!(pool.pool_address, address);
+        assert_eq!(pool.token_mint, token);
+        assert_eq!(pool.base_mint, sol_mint());
+        assert_eq!(pool.token_vault, get_associated_token_address(&address, &token));
+        assert_eq!(pool.base_vault, get_associated_token_address(&address, &sol_mint()));
+        assert_eq!(pool.fee_wallet, Some(get_associated_token_address(&pump_fee_wallet(), &sol_mint())));
+        assert_eq!(pool.additional_accounts.len(), 3);
+        assert_eq!(pool.additional_account("pool_base_mint").unwrap(), token);
+        assert_eq!(pool.additional_account("coin_creator_vault_authority").unwrap(), creator_vault_authority);
+        assert_eq!(
+            pool.additional_account("coin_creator_vault_ata").unwrap(),
+            get_associated_token_address(&creator_vault_authority, &sol_mint())
+        );
+        assert_eq!(pool.pool_type, PoolType::ConstantProduct);
+        assert_eq!(pool.reserves, None);
+        assert_eq!((pool.token_decimals, pool.base_decimals), (None, None));
+        assert_eq!(pool.token_symbol, None);
+        assert_eq!((pool.token_transfer_fee_bps, pool.base_transfer_fee_bps), (0, 0));
+    }
+
+    #[test]
+    fn buy_asks_for_the_quoted_output_and_caps_the_input() {
+        let base_mint = Pubkey::new_unique();
+        let rpc = MockRpc::new()
+            .with_account(base_mint, mint_account(spl_token::ID, 6))
+            .with_account(sol_mint(), mint_account(spl_token::ID, 9));
+        let dex = PumpDex::new(Arc::new(rpc));
+
+        let ix = dex
+            .build_swap_instruction(&pool(base_mint), &Pubkey::new_unique(), 1_000_000_000, 4_000_000_000)
+            .unwrap();
+
+        let quoted = constant_product_out(1_000_000_000, 100_000_000_000, 500_000_000_000, PUMP_FEE_BPS);
+        assert_eq!(&ix.data[..8], &PUMP_BUY_DISCRIMINATOR);
+        assert_eq!(u64::from_le_bytes(ix.data[8..16].try_into().unwrap()), quoted);
+        assert_eq!(u64::from_le_bytes(ix.data[16..24].try_into().unwrap()), 1_000_000_000);
+    }
+
+    #[test]
+    fn buy_below_minimum_out_is_rejected() {
+        let base_mint = Pubkey::new_unique();
+        let rpc = MockRpc::new()
+            .with_account(base_mint, mint_account(spl_token::ID, 6))
+            .with_account(sol_mint(), mint_account(spl_token::ID, 9));
+        let dex = PumpDex::new(Arc::new(rpc));
+
+        let result = dex.build_swap_instruction(&pool(base_mint), &Pubkey::new_unique(), 1_000_000_000, 5_000_000_000);
+        assert!(matches!(result, Err(BotError::Transaction(_))));
+    }
+
+    #[test]
+    fn swap_accounts_follow_each_mints_token_program() {
+        let base_mint = Pubkey::new_unique();
+        let rpc = MockRpc::new()
+            .with_account(base_mint, mint_account(token_2022_program_id(), 6))
+            .with_account(sol_mint(), mint_account(spl_token::ID, 9));
+        let dex = PumpDex::new(Arc::new(rpc));
+        let user = Pubkey::new_unique();
+
+        let ix = dex.build_swap_instruction(&pool(base_mint), &user, 1_000_000, 500).unwrap();
+
+        assert_eq!(
+            ix.accounts[5].pubkey,
+            get_associated_token_address_with_program_id(&user, &base_mint, &token_2022_program_id())
+        );
+        assert_eq!(
+            ix.accounts[6].pubkey,
+            get_associated_token_address_with_program_id(&user, &sol_mint(), &spl_token::ID)
+        );
+        assert_eq!(ix.accounts[11].pubkey, token_2022_program_id());
+        assert_eq!(ix.accounts[12].pubkey, spl_token::ID);
+    }
+
+    fn token_account(amount: u64) -> Account {
+        let mut data = vec![0u8; 165];
+        data[64..72].copy_from_slice(&amount.to_le_bytes());
+        Account { data, owner: spl_token::ID, ..Account::default() }
+    }
+
+    #[tokio::test]
+    async fn price_of_a_vault_snapshot_is_the_decimal_adjusted_reserve_ratio() {
+        // 206.9M tokens against 84 SOL
+        let mut pool = pool(Pubkey::new_unique());
+        pool.reserves = None;
+        pool.token_decimals = Some(6);
+        pool.base_decimals = Some(9);
+        // GlobalConfig: 20 bps LP, 5 bps protocol, then 5 bps to the coin creator
+        let mut global_config = vec![0u8; 321];
+        global_config[40..48].copy_from_slice(&20u64.to_le_bytes());
+        global_config[48..56].copy_from_slice(&5u64.to_le_bytes());
+        global_config[313..321].copy_from_slice(&5u64.to_le_bytes());
+        let rpc = MockRpc::new()
+            .with_account(pool.token_vault, token_account(206_900_000_000_000))
+            .with_account(pool.base_vault, token_account(84_000_000_000))
+            .with_account(pump_global_config(), Account { data: global_config, ..Account::default() });
+        let dex = PumpDex::new(Arc::new(rpc));
+
+        let price = dex.calculate_price(&pool).await.unwrap();
+        let expected = 84.0 / 206_900_000.0;
+        assert!((price.price - expected).abs() / expected < 1e-12);
+        assert_eq!(price.liquidity, 84_000_000_000);
+        assert!((price.fee - 0.003).abs() < 1e-12);
+    }
+
+    #[tokio::test]
+    async fn quote_deducts_the_transfer_fee_of_a_fee_bearing_mint() {
+        let dex = PumpDex::new(Arc::new(MockRpc::new()));
+        let reserves = PoolReserves {
+            token_reserve: 500_000_000_000,
+            base_reserve: 100_000_000_000,
+            slot: 0,
+        };
+        let plain = pool(Pubkey::new_unique());
+        let mut fee_bearing = plain.clone();
+        fee_bearing.token_transfer_fee_bps = 100;
+
+        let gross = dex.quote(&plain, 1_000_000_000, &reserves).await.unwrap();
+        let net = dex.quote(&fee_bearing, 1_000_000_000, &reserves).await.unwrap();
+        assert_eq!(net, deduct_transfer_fee(gross, 100));
+        assert!(net < gross);
+    }
 }
@@ -1,10 +1,10 @@
 use solana_program::pubkey::Pubkey;
-use anyhow::Result;
+use crate::error::{BotError, Result};
 
 const COIN_VAULT_OFFSET: usize = 336; // coinVault/tokenVaultA
 const PC_VAULT_OFFSET: usize = 368; // pcVault/tokenVaultB
-const COIN_MINT_OFFSET: usize = 400; // coinMint/tokenMintA
-const PC_MINT_OFFSET: usize = 432; // pcMint/tokenMintB
+pub const COIN_MINT_OFFSET: usize = 400; // coinMint/tokenMintA
+pub const PC_MINT_OFFSET: usize = 432; // pcMint/tokenMintB
 
 #[derive(Debug)]
 pub struct RaydiumAmmInfo {
@@ -17,7 +17,7 @@ pub struct RaydiumAmmInfo {
 impl RaydiumAmmInfo {
     pub fn load_checked(data: &[u8]) -> Result<Self> {
         if data.len() < PC_MINT_OFFSET + 32 {
-            return Err(anyhow::anyhow!("Invalid data length for RaydiumAmmInfo"));
+            return Err(BotError::PoolParse("Invalid data length for RaydiumAmmInfo".to_string()));
         }
         
         let coin_vault = Pubkey::new_from_array(
@@ -49,3 +49,53 @@ impl RaydiumAmmInfo {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::raydium::constants::{raydium_authority, raydium_program_id};
+    use std::str::FromStr;
+
+    fn key(address: &str) -> Pubkey {
+        Pubkey::from_str(address).unwrap()
+    }
+
+    /// AMM v4 account of the SOL/USDC pool 58oQChx4yWmvKdwLLZzBi4ChoCc2fqCUWBkwMihLYQo2,
+    /// with its vaults and mints at their `AmmInfo` offsets and the rest zeroed
+    fn sol_usdc_pool_account() -> Vec<u8> {
+        let mut data = vec![0u8; 752];
+        for (offset, address) in [
+            (COIN_VAULT_OFFSET, "DQyrAcCrDXQ7NeoqGgDCZwBvWDcYmFCjSb9JtteuvPpz"),
+            (PC_VAULT_OFFSET, "HLmqeL62xR1QoZ1HKKbXRrdN1p3phKpxRMb2VVopvBBz"),
+            (COIN_MINT_OFFSET, "So11111111111111111111111111111111111111112"),
+            (PC_MINT_OFFSET, "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"),
+        ] {
+            data[offset..offset + 32].copy_from_slice(key(address).as_ref());
+        }
+        data
+    }
+
+    #[test]
+    fn load_checked_reads_the_vaults_and_mints() {
+        let info = RaydiumAmmInfo::load_checked(&sol_usdc_pool_account()).unwrap();
+
+        assert_eq!(info.coin_vault, key("DQyrAcCrDXQ7NeoqGgDCZwBvWDcYmFCjSb9JtteuvPpz"));
+        assert_eq!(info.pc_vault, key("HLmqeL62xR1QoZ1HKKbXRrdN1p3phKpxRMb2VVopvBBz"));
+        assert_eq!(info.coin_mint, key("So11111111111111111111111111111111111111112"));
+        assert_eq!(info.pc_mint, key("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"));
+    }
+
+    #[test]
+    fn load_checked_rejects_accounts_too_short_for_the_mints() {
+        assert!(RaydiumAmmInfo::load_checked(&[]).is_err());
+        assert!(RaydiumAmmInfo::load_checked(&sol_usdc_pool_account()[..PC_MINT_OFFSET + 31]).is_err());
+    }
+
+    #[test]
+    fn amm_authority_is_the_program_pda() {
+        let (authority, nonce) = Pubkey::find_program_address(&[b"amm authority"], &raydium_program_id());
+
+        assert_eq!(authority, raydium_authority());
+        assert_eq!(nonce, 254);
+    }
+}
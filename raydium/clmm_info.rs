@@ -1,4 +1,4 @@
-use anyhow::Result;
+use crate::error::{BotError, Result};
 use solana_program::pubkey::Pubkey;
 
 pub const TICK_ARRAY_SEED: &str = "tick_array";
@@ -103,10 +103,8 @@ pub struct PoolState {
 
 impl PoolState {
     pub fn load_checked(data: &[u8]) -> Result<Self> {
-        if data.len() < 8 + 32 + 32 + 32 + 32 + 32 + 32 + 4 + 2 {
-            return Err(anyhow::anyhow!(
-                "Invalid data length for RaydiumClmmPoolState"
-            ));
+        if data.len() < 8 + 1 + 32 * 7 + 1 + 1 + 2 + 16 + 16 + 4 {
+            return Err(BotError::PoolParse("Invalid data length for RaydiumClmmPoolState".to_string()));
         }
 
         let data = &data[8..]; // Skip the discriminator
@@ -146,6 +144,8 @@ impl PoolState {
         let observation_key = Pubkey::new_from_array(observation_key);
         offset += 32;
 
+        let mint_decimals_0 = data[offset];
+        let mint_decimals_1 = data[offset + 1];
         offset += 2;
 
         let mut tick_spacing_bytes = [0u8; 2];
@@ -153,9 +153,14 @@ impl PoolState {
         let tick_spacing = u16::from_le_bytes(tick_spacing_bytes);
         offset += 2;
 
+        let mut liquidity_bytes = [0u8; 16];
+        liquidity_bytes.copy_from_slice(&data[offset..offset + 16]);
+        let liquidity = u128::from_le_bytes(liquidity_bytes);
         offset += 16;
 
-        // Skip sqrt_price_x64
+        let mut sqrt_price_bytes = [0u8; 16];
+        sqrt_price_bytes.copy_from_slice(&data[offset..offset + 16]);
+        let sqrt_price_x64 = u128::from_le_bytes(sqrt_price_bytes);
         offset += 16;
 
         let mut tick_current_bytes = [0u8; 4];
@@ -170,18 +175,81 @@ impl PoolState {
             token_vault_0,
             token_vault_1,
             observation_key,
+            mint_decimals_0,
+            mint_decimals_1,
             tick_spacing,
+            liquidity,
+            sqrt_price_x64,
             tick_current,
             ..Default::default()
         })
     }
 }
 
+#[cfg(test)]
+impl PoolState {
+    /// Account bytes with the fields `load_checked` reads at their on-chain
+    /// offsets and everything else zeroed, sized like a real `PoolState`
+    pub(crate) fn account_data(&self) -> Vec<u8> {
+        let mut data = vec![0u8; 1544];
+        // Discriminator and bump come first
+        let mut offset = 9;
+        for key in [
+            self.amm_config,
+            self.owner,
+            self.token_mint_0,
+            self.token_mint_1,
+            self.token_vault_0,
+            self.token_vault_1,
+            self.observation_key,
+        ] {
+            data[offset..offset + 32].copy_from_slice(key.as_ref());
+            offset += 32;
+        }
+        data[offset] = self.mint_decimals_0;
+        data[offset + 1] = self.mint_decimals_1;
+        offset += 2;
+        data[offset..offset + 2].copy_from_slice(&self.tick_spacing.to_le_bytes());
+        offset += 2;
+        data[offset..offset + 16].copy_from_slice(&self.liquidity.to_le_bytes());
+        offset += 16;
+        data[offset..offset + 16].copy_from_slice(&self.sqrt_price_x64.to_le_bytes());
+        offset += 16;
+        data[offset..offset + 4].copy_from_slice(&self.tick_current.to_le_bytes());
+        data
+    }
+}
+
+/// Size of one `TickState` in a CLMM tick array account
+const TICK_STATE_LEN: usize = 168;
+/// Offset of the first tick: discriminator, pool id and start index
+const TICK_ARRAY_TICKS_OFFSET: usize = 8 + 32 + 4;
+
+/// Initialized ticks of a CLMM `TickArrayState` account as
+/// `(tick, liquidity_net)` pairs
+pub fn initialized_ticks(data: &[u8]) -> Result<Vec<(i32, i128)>> {
+    if data.len() < TICK_ARRAY_TICKS_OFFSET + TICK_STATE_LEN * TICK_ARRAY_SIZE_USIZE {
+        return Err(BotError::PoolParse("Invalid data length for RaydiumClmmTickArrayState".to_string()));
+    }
+
+    let mut ticks = Vec::new();
+    for i in 0..TICK_ARRAY_SIZE_USIZE {
+        let tick_data = &data[TICK_ARRAY_TICKS_OFFSET + i * TICK_STATE_LEN..];
+        let tick = i32::from_le_bytes(tick_data[0..4].try_into().unwrap());
+        let liquidity_net = i128::from_le_bytes(tick_data[4..20].try_into().unwrap());
+        let liquidity_gross = u128::from_le_bytes(tick_data[20..36].try_into().unwrap());
+        if liquidity_gross != 0 {
+            ticks.push((tick, liquidity_net));
+        }
+    }
+    Ok(ticks)
+}
+
 pub fn compute_tick_array_start_index(tick: i32, tick_spacing: u16) -> i32 {
     let ticks_in_array = TICK_ARRAY_SIZE * tick_spacing as i32;
     let mut start = tick / ticks_in_array;
     if tick < 0 && tick % ticks_in_array != 0 {
-        start = start - 1
+        start -= 1
     }
     start * ticks_in_array
 }
@@ -213,3 +281,68 @@ pub fn get_tick_array_pubkeys(
 
     Ok(result)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pool_state_reads_liquidity_and_price_at_their_offsets() {
+        let mut data = vec![0u8; 1544];
+        data[73..105].copy_from_slice(Pubkey::new_from_array([1; 32]).as_ref());
+        data[105..137].copy_from_slice(Pubkey::new_from_array([2; 32]).as_ref());
+        data[233] = 9;
+        data[234] = 6;
+        data[235..237].copy_from_slice(&60u16.to_le_bytes());
+        data[237..253].copy_from_slice(&123_456_789u128.to_le_bytes());
+        data[253..269].copy_from_slice(&(7u128 << 64).to_le_bytes());
+        data[269..273].copy_from_slice(&(-1_234i32).to_le_bytes());
+
+        let state = PoolState::load_checked(&data).unwrap();
+        assert_eq!(state.token_mint_0, Pubkey::new_from_array([1; 32]));
+        assert_eq!(state.token_mint_1, Pubkey::new_from_array([2; 32]));
+        assert_eq!((state.mint_decimals_0, state.mint_decimals_1), (9, 6));
+        assert_eq!(state.tick_spacing, 60);
+        assert_eq!(state.liquidity, 123_456_789);
+        assert_eq!(state.sqrt_price_x64, 7u128 << 64);
+        assert_eq!(state.tick_current, -1_234);
+
+        // The test encoder writes the same layout
+        let encoded = PoolState {
+            token_mint_0: state.token_mint_0,
+            token_mint_1: state.token_mint_1,
+            mint_decimals_0: 9,
+            mint_decimals_1: 6,
+            tick_spacing: 60,
+            liquidity: 123_456_789,
+            sqrt_price_x64: 7u128 << 64,
+            tick_current: -1_234,
+            ..PoolState::default()
+        }
+        .account_data();
+        assert_eq!(encoded, data);
+    }
+
+    #[test]
+    fn pool_state_shorter_than_the_tick_is_rejected() {
+        assert!(matches!(PoolState::load_checked(&[0u8; 272]), Err(BotError::PoolParse(_))));
+        assert!(PoolState::load_checked(&[0u8; 273]).is_ok());
+    }
+
+    #[test]
+    fn initialized_ticks_skips_ticks_without_liquidity() {
+        let mut data = vec![0u8; TICK_ARRAY_TICKS_OFFSET + TICK_STATE_LEN * TICK_ARRAY_SIZE_USIZE];
+        let mut set_tick = |slot: usize, tick: i32, liquidity_net: i128, liquidity_gross: u128| {
+            let tick_data = &mut data[TICK_ARRAY_TICKS_OFFSET + slot * TICK_STATE_LEN..];
+            tick_data[0..4].copy_from_slice(&tick.to_le_bytes());
+            tick_data[4..20].copy_from_slice(&liquidity_net.to_le_bytes());
+            tick_data[20..36].copy_from_slice(&liquidity_gross.to_le_bytes());
+        };
+        set_tick(3, -600, -5_000, 5_000);
+        set_tick(10, 0, 0, 0);
+        set_tick(59, 3_540, 7_000, 7_000);
+
+        assert_eq!(initialized_ticks(&data).unwrap(), vec![(-600, -5_000), (3_540, 7_000)]);
+        assert!(matches!(initialized_ticks(&data[..data.len() - 1]), Err(BotError::PoolParse(_))));
+    }
+}
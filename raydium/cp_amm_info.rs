@@ -1,13 +1,13 @@
 use solana_program::pubkey::Pubkey;
-use anyhow::Result;
+use crate::error::{BotError, Result};
 
 const AMM_CONFIG_OFFSET: usize = 8; // amm_config
 // const POOL_CREATOR_OFFSET: usize = 40; // pool_creator
 const TOKEN_0_VAULT_OFFSET: usize = 72; // token_0_vault
 const TOKEN_1_VAULT_OFFSET: usize = 104; // token_1_vault
 // const LP_MINT_OFFSET: usize = 136; // lp_mint
-const TOKEN_0_MINT_OFFSET: usize = 168; // token_0_mint
-const TOKEN_1_MINT_OFFSET: usize = 200; // token_1_mint
+pub const TOKEN_0_MINT_OFFSET: usize = 168; // token_0_mint
+pub const TOKEN_1_MINT_OFFSET: usize = 200; // token_1_mint
 // const TOKEN_0_PROGRAM_OFFSET: usize = 232; // token_0_program
 // const TOKEN_1_PROGRAM_OFFSET: usize = 264; // token_1_program
 const OBSERVATION_KEY_OFFSET: usize = 296; // observation_key
@@ -25,7 +25,7 @@ pub struct RaydiumCpAmmInfo {
 impl RaydiumCpAmmInfo {
     pub fn load_checked(data: &[u8]) -> Result<Self> {
         if data.len() < OBSERVATION_KEY_OFFSET + 32 {
-            return Err(anyhow::anyhow!("Invalid data length for RaydiumCpAmmInfo"));
+            return Err(BotError::PoolParse("Invalid data length for RaydiumCpAmmInfo".to_string()));
         }
         
         let token_0_vault = Pubkey::new_from_array(
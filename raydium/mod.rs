@@ -8,4 +8,4 @@ pub use amm_info::RaydiumAmmInfo;
 pub use constants::*;
 pub use cp_amm_info::RaydiumCpAmmInfo;
 pub use clmm_info::{PoolState, get_tick_array_pubkeys};
-pub use unified::RaydiumDex;
+pub use unified::{RaydiumClmmDex, RaydiumCpDex, RaydiumDex};
@@ -1,21 +1,87 @@
 //! Unified Raydium DEX implementation using the Dex trait
 
-use crate::dex::traits::{Dex, PoolInfo, PriceInfo};
-use crate::dex::raydium::{amm_info::RaydiumAmmInfo, constants::*};
+use crate::dex::traits::{
+    validate_swap_amounts, verify_pool_owner, Dex, DexCapabilities, PoolInfo, PoolReserves, PoolType, PriceInfo,
+};
+use crate::dex::raydium::{
+    amm_info::{RaydiumAmmInfo, COIN_MINT_OFFSET, PC_MINT_OFFSET},
+    clmm_info::{get_tick_array_pubkeys, initialized_ticks, PoolState},
+    constants::*,
+    cp_amm_info::{RaydiumCpAmmInfo, TOKEN_0_MINT_OFFSET, TOKEN_1_MINT_OFFSET},
+};
+use crate::dex::math::{
+    clmm_fee_fraction, clmm_fee_tier_bps, constant_product_min_input, quote_clmm_snapshot, quote_clmm_with_ticks,
+    quote_constant_product, sqrt_price_x64_to_price, ClmmSnapshot, InitializedTick,
+};
+use crate::dex::vault::{decimal_adjusted_price, fetch_pool_reserves, pool_reserves, MintDecimalsCache};
+use crate::dex::rpc::{blocking, RpcLike};
 use async_trait::async_trait;
-use solana_client::rpc_client::RpcClient;
-use solana_sdk::{account::Account, pubkey::Pubkey};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+};
+use spl_associated_token_account::get_associated_token_address;
+use std::str::FromStr;
 use std::sync::Arc;
-use anyhow::Result;
+use crate::dex_boilerplate;
+use crate::error::{BotError, Result};
 
-pub struct RaydiumDex {
-    rpc_client: Arc<RpcClient>,
+/// Raydium AMM v4 swap fee
+const RAYDIUM_FEE_BPS: u16 = 25;
+
+/// Swap-base-in instruction layouts of the Raydium AMM v4 program
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RaydiumSwapVersion {
+    /// `swapBaseIn`, discriminator 9
+    V1,
+    /// `swapBaseInV2`, discriminator 16, which drops the unused OpenBook accounts
+    V2,
 }
 
-dex_boilerplate!(RaydiumDex, "raydium", raydium_program_id());
+impl RaydiumSwapVersion {
+    /// Layout used when no version is configured
+    pub const CURRENT: Self = RaydiumSwapVersion::V1;
+
+    pub fn from_id(version: u8) -> Result<Self> {
+        match version {
+            1 => Ok(RaydiumSwapVersion::V1),
+            2 => Ok(RaydiumSwapVersion::V2),
+            other => Err(BotError::Transaction(format!(
+                "Unsupported Raydium swap instruction version: {}",
+                other
+            ))),
+        }
+    }
+
+    pub fn discriminator(self) -> u8 {
+        match self {
+            RaydiumSwapVersion::V1 => 9,
+            RaydiumSwapVersion::V2 => 16,
+        }
+    }
+
+    /// Encode `amount_in` and `minimum_out` in this version's layout:
+    /// `[discriminator: u8][amount_in: u64 LE][minimum_out: u64 LE]`
+    pub fn encode(self, amount_in: u64, minimum_out: u64) -> Vec<u8> {
+        let mut data = Vec::with_capacity(17);
+        data.push(self.discriminator());
+        data.extend_from_slice(&amount_in.to_le_bytes());
+        data.extend_from_slice(&minimum_out.to_le_bytes());
+        data
+    }
+}
+
+#[derive(Clone)]
+pub struct RaydiumDex {
+    rpc_client: Arc<dyn RpcLike>,
+    swap_version: Option<u8>,
+    decimals: Arc<MintDecimalsCache>,
+}
 
 #[async_trait]
 impl Dex for RaydiumDex {
+    dex_boilerplate!("raydium", raydium_program_id());
+
     async fn fetch_pools(&self, pool_addresses: &[String], token_mint: &Pubkey) -> Result<Vec<PoolInfo>> {
         let mut pools = Vec::new();
 
@@ -32,62 +98,745 @@ impl Dex for RaydiumDex {
     }
 
     async fn calculate_price(&self, pool_info: &PoolInfo) -> Result<PriceInfo> {
-        // For now, return a placeholder - would need actual pool state to calculate real price
-        // This would require fetching the pool state and calculating based on token reserves
-        Ok(PriceInfo {
-            price: 0.0, // TODO: Implement actual price calculation
-            liquidity: 0, // TODO: Calculate actual liquidity
-            fee: 0.0025, // Raydium standard fee
+        let (dex, pool_info) = (self.clone(), pool_info.clone());
+        blocking(move || dex.read_price(&pool_info)).await
+    }
+
+    async fn fetch_reserves(&self, pool_info: &PoolInfo) -> Result<PoolReserves> {
+        let (rpc_client, pool_info) = (self.rpc_client.clone(), pool_info.clone());
+        blocking(move || fetch_pool_reserves(rpc_client.as_ref(), &pool_info)).await
+    }
+
+    fn parse_pool_account(&self, address: &Pubkey, data: &[u8], token_mint: &Pubkey) -> Result<PoolInfo> {
+        let pool_pubkey = *address;
+
+        let amm_info = RaydiumAmmInfo::load_checked(data)?;
+
+        // Orient vaults with the mints so the token side is always `token_mint`,
+        // whichever of coin/pc holds SOL
+        let (token_mint_final, base_mint, token_vault, base_vault) = if *token_mint == amm_info.coin_mint {
+            (amm_info.coin_mint, amm_info.pc_mint, amm_info.coin_vault, amm_info.pc_vault)
+        } else {
+            (amm_info.pc_mint, amm_info.coin_mint, amm_info.pc_vault, amm_info.coin_vault)
+        };
+
+        // The program orders its vaults coin-then-pc regardless of direction
+        let mut additional_accounts = std::collections::HashMap::new();
+        additional_accounts.insert("coin_vault".to_string(), amm_info.coin_vault);
+        additional_accounts.insert("pc_vault".to_string(), amm_info.pc_vault);
+
+        Ok(PoolInfo {
+            pool_address: pool_pubkey,
+            token_mint: token_mint_final,
+            base_mint,
+            token_vault,
+            base_vault,
+            fee_wallet: None, // Raydium doesn't have a separate fee wallet
+            additional_accounts,
+            pool_type: PoolType::ConstantProduct,
+            reserves: None,
+            token_decimals: None,
+            base_decimals: None,
+            token_symbol: None,
+            token_transfer_fee_bps: 0,
+            base_transfer_fee_bps: 0,
         })
     }
 
+    fn mint_offsets(&self) -> &'static [usize] {
+        &[COIN_MINT_OFFSET, PC_MINT_OFFSET]
+    }
+
     fn get_swap_instruction_data(&self, pool_info: &PoolInfo, amount_in: u64, minimum_out: u64) -> Result<Vec<u8>> {
-        // TODO: Implement Raydium swap instruction encoding
-        // This would encode the swap instruction according to Raydium's program interface
-        Ok(Vec::new())
+        // A zero-input swap would be accepted on-chain and just burn fees
+        if amount_in == 0 {
+            return Err(BotError::Transaction(format!(
+                "Raydium swap on pool {} has zero amount_in",
+                pool_info.pool_address
+            )));
+        }
+        validate_swap_amounts(amount_in, minimum_out)?;
+
+        let version = match self.swap_version {
+            Some(id) => RaydiumSwapVersion::from_id(id)?,
+            None => RaydiumSwapVersion::CURRENT,
+        };
+        Ok(version.encode(amount_in, minimum_out))
+    }
+
+    fn build_swap_instruction(
+        &self,
+        pool_info: &PoolInfo,
+        user: &Pubkey,
+        amount_in: u64,
+        minimum_out: u64,
+    ) -> Result<Instruction> {
+        validate_swap_amounts(amount_in, minimum_out)?;
+
+        // V1 also takes the OpenBook market accounts, which the pool decoder
+        // doesn't read, so full instructions always use the V2 layout
+        let coin_vault = pool_info.additional_account("coin_vault")?;
+        let pc_vault = pool_info.additional_account("pc_vault")?;
+
+        let accounts = vec![
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new(pool_info.pool_address, false),
+            AccountMeta::new_readonly(raydium_authority(), false),
+            AccountMeta::new(coin_vault, false),
+            AccountMeta::new(pc_vault, false),
+            AccountMeta::new(get_associated_token_address(user, &pool_info.base_mint), false),
+            AccountMeta::new(get_associated_token_address(user, &pool_info.token_mint), false),
+            AccountMeta::new_readonly(*user, true),
+        ];
+
+        Ok(Instruction {
+            program_id: raydium_program_id(),
+            accounts,
+            data: RaydiumSwapVersion::V2.encode(amount_in, minimum_out),
+        })
+    }
+
+    fn capabilities(&self) -> DexCapabilities {
+        // AMM v4 only supports the original SPL Token program
+        DexCapabilities {
+            supports_swap: true,
+            ..DexCapabilities::default()
+        }
+    }
+
+    fn min_viable_input(&self, pool_info: &PoolInfo) -> u64 {
+        match pool_info.reserves {
+            Some((token_reserve, base_reserve)) => {
+                constant_product_min_input(base_reserve, token_reserve, RAYDIUM_FEE_BPS)
+            }
+            None => 1,
+        }
+    }
+
+    async fn quote(&self, pool_info: &PoolInfo, amount_in: u64, reserves: &PoolReserves) -> Result<u64> {
+        let amount_out = self.quote_with_reserves(
+            reserves.base_reserve,
+            reserves.token_reserve,
+            pool_info.amount_in_after_transfer_fee(amount_in),
+            RAYDIUM_FEE_BPS,
+        )?;
+        Ok(pool_info.amount_out_after_transfer_fee(amount_out))
     }
 }
 
 impl RaydiumDex {
-    pub fn new(rpc_client: Arc<RpcClient>) -> Self {
-        Self { rpc_client }
+    /// Quote a swap against explicitly supplied reserves instead of live accounts
+    pub fn quote_with_reserves(&self, reserve_in: u64, reserve_out: u64, amount_in: u64, fee_bps: u16) -> Result<u64> {
+        quote_constant_product(reserve_in, reserve_out, amount_in, fee_bps)
+    }
+
+    pub fn new(rpc_client: Arc<dyn RpcLike>) -> Self {
+        Self {
+            decimals: Arc::new(MintDecimalsCache::new(rpc_client.clone())),
+            rpc_client,
+            swap_version: None,
+        }
+    }
+
+    /// Override the swap instruction layout, e.g. after a Raydium program upgrade
+    pub fn with_swap_version(mut self, swap_version: Option<u8>) -> Self {
+        self.swap_version = swap_version;
+        self
+    }
+
+    /// `calculate_price`, blocking on the account reads
+    fn read_price(&self, pool_info: &PoolInfo) -> Result<PriceInfo> {
+        // Vaults are oriented by `token_mint`, so the price is always base per token
+        let (token_reserve, base_reserve) = pool_reserves(self.rpc_client.as_ref(), pool_info)?;
+        let (token_decimals, base_decimals) =
+            self.decimals.for_pool(pool_info)?;
+
+        let price = decimal_adjusted_price(token_reserve, base_reserve, token_decimals, base_decimals)
+            .ok_or_else(|| {
+                BotError::PriceCalculation(format!("Raydium pool {} has an empty token vault", pool_info.pool_address))
+            })?;
+
+        Ok(pool_info.with_transfer_fees(PriceInfo {
+            price,
+            liquidity: base_reserve,
+            fee: RAYDIUM_FEE_BPS as f64 / 10_000.0,
+        }))
     }
 
     async fn fetch_single_pool(&self, pool_address: &str, token_mint: &Pubkey) -> Result<PoolInfo> {
+        let (dex, pool_address, token_mint) = (self.clone(), pool_address.to_string(), *token_mint);
+        blocking(move || dex.read_single_pool(&pool_address, &token_mint)).await
+    }
+
+    fn read_single_pool(&self, pool_address: &str, token_mint: &Pubkey) -> Result<PoolInfo> {
         let pool_pubkey = Pubkey::from_str(pool_address)?;
         let account = self.rpc_client.get_account(&pool_pubkey)?;
 
-        if account.owner != raydium_program_id() {
-            return Err(anyhow::anyhow!(
-                "Account is not owned by Raydium program: {}",
-                pool_address
-            ));
+        verify_pool_owner(&account, &raydium_program_id())?;
+
+        let mut pool_info = self.parse_pool_account(&pool_pubkey, &account.data, token_mint)?;
+        self.decimals.fill(&mut pool_info)?;
+        Ok(pool_info)
+    }
+}
+
+/// Offset of `trade_fee_rate` in a Raydium CLMM `AmmConfig` account
+const CLMM_TRADE_FEE_RATE_OFFSET: usize = 47;
+/// Offsets of `token_mint_0` and `token_mint_1` in a CLMM `PoolState` account
+const CLMM_POOL_MINT_OFFSETS: [usize; 2] = [73, 105];
+
+#[derive(Clone)]
+pub struct RaydiumClmmDex {
+    rpc_client: Arc<dyn RpcLike>,
+}
+
+#[async_trait]
+impl Dex for RaydiumClmmDex {
+    dex_boilerplate!("raydium_clmm", raydium_clmm_program_id());
+
+    async fn fetch_pools(&self, pool_addresses: &[String], token_mint: &Pubkey) -> Result<Vec<PoolInfo>> {
+        let mut pools = Vec::new();
+
+        for pool_address in pool_addresses {
+            match self.fetch_single_pool(pool_address, token_mint).await {
+                Ok(pool) => pools.push(pool),
+                Err(e) => {
+                    tracing::error!("Failed to fetch Raydium CLMM pool {}: {}", pool_address, e);
+                }
+            }
+        }
+
+        Ok(pools)
+    }
+
+    async fn calculate_price(&self, pool_info: &PoolInfo) -> Result<PriceInfo> {
+        let (dex, pool_info) = (self.clone(), pool_info.clone());
+        blocking(move || dex.read_price(&pool_info)).await
+    }
+
+    async fn fetch_reserves(&self, pool_info: &PoolInfo) -> Result<PoolReserves> {
+        let (rpc_client, pool_info) = (self.rpc_client.clone(), pool_info.clone());
+        blocking(move || fetch_pool_reserves(rpc_client.as_ref(), &pool_info)).await
+    }
+
+    fn parse_pool_account(&self, address: &Pubkey, data: &[u8], token_mint: &Pubkey) -> Result<PoolInfo> {
+        let pool_pubkey = *address;
+
+        let pool_state = PoolState::load_checked(data)?;
+
+        let (token_mint_final, base_mint, token_vault, base_vault, token_decimals, base_decimals) =
+            if *token_mint == pool_state.token_mint_0 {
+                (
+                    pool_state.token_mint_0,
+                    pool_state.token_mint_1,
+                    pool_state.token_vault_0,
+                    pool_state.token_vault_1,
+                    pool_state.mint_decimals_0,
+                    pool_state.mint_decimals_1,
+                )
+            } else if *token_mint == pool_state.token_mint_1 {
+                (
+                    pool_state.token_mint_1,
+                    pool_state.token_mint_0,
+                    pool_state.token_vault_1,
+                    pool_state.token_vault_0,
+                    pool_state.mint_decimals_1,
+                    pool_state.mint_decimals_0,
+                )
+            } else {
+                return Err(BotError::PoolParse(format!(
+                    "Mint {} not found in Raydium CLMM pool {}",
+                    token_mint,
+                    pool_pubkey
+                )));
+            };
+
+        // Swaps need the config, observation and the tick arrays around the
+        // current tick, which shift as the price moves
+        let mut additional_accounts = std::collections::HashMap::new();
+        additional_accounts.insert("amm_config".to_string(), pool_state.amm_config);
+        additional_accounts.insert("observation_state".to_string(), pool_state.observation_key);
+        let tick_arrays = get_tick_array_pubkeys(
+            &pool_pubkey,
+            pool_state.tick_current,
+            pool_state.tick_spacing,
+            &[-1, 0, 1],
+            &raydium_clmm_program_id(),
+        )?;
+        for (i, tick_array) in tick_arrays.into_iter().enumerate() {
+            additional_accounts.insert(format!("tick_array_{}", i), tick_array);
+        }
+
+        Ok(PoolInfo {
+            pool_address: pool_pubkey,
+            token_mint: token_mint_final,
+            base_mint,
+            token_vault,
+            base_vault,
+            fee_wallet: None,
+            additional_accounts,
+            pool_type: PoolType::ConcentratedLiquidity,
+            reserves: None,
+            token_decimals: Some(token_decimals),
+            base_decimals: Some(base_decimals),
+            token_symbol: None,
+            token_transfer_fee_bps: 0,
+            base_transfer_fee_bps: 0,
+        })
+    }
+
+    fn mint_offsets(&self) -> &'static [usize] {
+        &CLMM_POOL_MINT_OFFSETS
+    }
+
+    fn get_swap_instruction_data(&self, _pool_info: &PoolInfo, amount_in: u64, minimum_out: u64) -> Result<Vec<u8>> {
+        validate_swap_amounts(amount_in, minimum_out)?;
+
+        Err(BotError::Validation("Raydium CLMM swap encoding not implemented".to_string()))
+    }
+
+    fn capabilities(&self) -> DexCapabilities {
+        DexCapabilities {
+            supports_token_2022: true,
+            concentrated_liquidity: true,
+            ..DexCapabilities::default()
         }
+    }
+
+    async fn quote(&self, pool_info: &PoolInfo, amount_in: u64, _reserves: &PoolReserves) -> Result<u64> {
+        let (dex, pool_info) = (self.clone(), pool_info.clone());
+        blocking(move || dex.read_quote(&pool_info, amount_in)).await
+    }
+}
+
+/// `trade_fee_rate` of a CLMM `AmmConfig` account, in hundredths of a basis point
+fn clmm_trade_fee_rate(amm_config: &[u8]) -> Result<u32> {
+    let fee_bytes = amm_config
+        .get(CLMM_TRADE_FEE_RATE_OFFSET..CLMM_TRADE_FEE_RATE_OFFSET + 4)
+        .ok_or_else(|| BotError::PoolParse("Invalid data length for Raydium CLMM config".to_string()))?;
+    Ok(u32::from_le_bytes(fee_bytes.try_into().unwrap()))
+}
 
-        let amm_info = RaydiumAmmInfo::load_checked(&account.data)?;
+impl RaydiumClmmDex {
+    pub fn new(rpc_client: Arc<dyn RpcLike>) -> Self {
+        Self { rpc_client }
+    }
+
+    /// Quote a swap against a supplied liquidity and price snapshot instead of
+    /// live accounts. Tick crossings are not modelled.
+    pub fn quote_with_snapshot(
+        &self,
+        snapshot: &ClmmSnapshot,
+        amount_in: u64,
+        fee_bps: u16,
+        zero_for_one: bool,
+    ) -> Result<u64> {
+        quote_clmm_snapshot(snapshot, amount_in, fee_bps, zero_for_one)
+    }
 
-        let (token_vault, base_vault) = if crate::chain::constants::sol_mint() == amm_info.coin_mint {
-            (amm_info.coin_vault, amm_info.pc_vault)
-        } else if crate::chain::constants::sol_mint() == amm_info.pc_mint {
-            (amm_info.pc_vault, amm_info.coin_vault)
+    /// `calculate_price`, blocking on the account reads
+    fn read_price(&self, pool_info: &PoolInfo) -> Result<PriceInfo> {
+        // The current sqrt price moves with every swap, so read it fresh
+        let amm_config = pool_info.additional_account("amm_config")?;
+        let accounts = self
+            .rpc_client
+            .get_multiple_accounts(&[pool_info.pool_address, amm_config])?;
+        let (pool, amm_config) = match accounts.as_slice() {
+            [Some(pool), Some(amm_config)] => (pool, amm_config),
+            _ => {
+                return Err(BotError::AccountFetch(format!(
+                    "Missing pool or config account for Raydium CLMM pool {}",
+                    pool_info.pool_address
+                )))
+            }
+        };
+        let pool_state = PoolState::load_checked(&pool.data)?;
+
+        // sqrt_price_x64 is mint 1 per mint 0; flip it when the tracked token is mint 1
+        let price_1_per_0 = sqrt_price_x64_to_price(
+            pool_state.sqrt_price_x64,
+            pool_state.mint_decimals_0,
+            pool_state.mint_decimals_1,
+        );
+        let price = if pool_info.token_mint == pool_state.token_mint_0 {
+            price_1_per_0
+        } else if price_1_per_0 > 0.0 {
+            1.0 / price_1_per_0
         } else {
-            (amm_info.coin_vault, amm_info.pc_vault)
+            return Err(BotError::PriceCalculation(format!(
+                "Raydium CLMM pool {} has a zero sqrt price",
+                pool_info.pool_address
+            )));
         };
 
-        let (token_mint_final, base_mint) = if *token_mint == amm_info.coin_mint {
-            (amm_info.coin_mint, amm_info.pc_mint)
+        let fee = clmm_fee_fraction(clmm_trade_fee_rate(&amm_config.data)?);
+
+        let (_, base_reserve) = pool_reserves(self.rpc_client.as_ref(), pool_info)?;
+
+        Ok(pool_info.with_transfer_fees(PriceInfo {
+            price,
+            liquidity: base_reserve,
+            fee,
+        }))
+    }
+
+    /// `quote`, blocking on the account reads
+    fn read_quote(&self, pool_info: &PoolInfo, amount_in: u64) -> Result<u64> {
+        // Price and liquidity live in the pool account, and crossings need the
+        // tick arrays around the current tick
+        let mut keys = vec![pool_info.pool_address, pool_info.additional_account("amm_config")?];
+        for i in 0..3 {
+            keys.push(pool_info.additional_account(&format!("tick_array_{}", i))?);
+        }
+        let accounts = self.rpc_client.get_multiple_accounts(&keys)?;
+        let (pool, amm_config) = match (&accounts[0], &accounts[1]) {
+            (Some(pool), Some(amm_config)) => (pool, amm_config),
+            _ => {
+                return Err(BotError::AccountFetch(format!(
+                    "Missing pool or config account for Raydium CLMM pool {}",
+                    pool_info.pool_address
+                )))
+            }
+        };
+        let pool_state = PoolState::load_checked(&pool.data)?;
+        // Rounded up, so a sub-bps tier quotes slightly under what the pool pays out
+        let fee_bps = clmm_fee_tier_bps(clmm_trade_fee_rate(&amm_config.data)?);
+
+        // Tick arrays that were never initialized simply have no ticks
+        let mut ticks = Vec::new();
+        for tick_array in accounts[2..].iter().flatten() {
+            ticks.extend(
+                initialized_ticks(&tick_array.data)?
+                    .into_iter()
+                    .map(|(tick_index, liquidity_net)| InitializedTick { tick_index, liquidity_net }),
+            );
+        }
+
+        let snapshot = ClmmSnapshot {
+            liquidity: pool_state.liquidity,
+            sqrt_price_x64: pool_state.sqrt_price_x64,
+        };
+        let zero_for_one = pool_info.base_mint == pool_state.token_mint_0;
+        let amount_in = pool_info.amount_in_after_transfer_fee(amount_in);
+        let amount_out = quote_clmm_with_ticks(&snapshot, &ticks, amount_in, fee_bps, zero_for_one)?;
+        Ok(pool_info.amount_out_after_transfer_fee(amount_out))
+    }
+
+    async fn fetch_single_pool(&self, pool_address: &str, token_mint: &Pubkey) -> Result<PoolInfo> {
+        let (dex, pool_address, token_mint) = (self.clone(), pool_address.to_string(), *token_mint);
+        blocking(move || dex.read_single_pool(&pool_address, &token_mint)).await
+    }
+
+    fn read_single_pool(&self, pool_address: &str, token_mint: &Pubkey) -> Result<PoolInfo> {
+        let pool_pubkey = Pubkey::from_str(pool_address)?;
+        let account = self.rpc_client.get_account(&pool_pubkey)?;
+
+        verify_pool_owner(&account, &raydium_clmm_program_id())?;
+
+        self.parse_pool_account(&pool_pubkey, &account.data, token_mint)
+    }
+}
+
+/// Anchor discriminator of the CP-Swap `swap_base_input` instruction
+const CP_SWAP_BASE_INPUT_DISCRIMINATOR: [u8; 8] = [143, 190, 90, 218, 196, 30, 51, 222];
+/// Offset of `trade_fee_rate` in a CP-Swap `AmmConfig` account
+const CP_TRADE_FEE_RATE_OFFSET: usize = 12;
+/// CP-Swap fee rates are in hundredths of a basis point
+const CP_FEE_RATE_DENOMINATOR: f64 = 1_000_000.0;
+
+#[derive(Clone)]
+pub struct RaydiumCpDex {
+    rpc_client: Arc<dyn RpcLike>,
+    decimals: Arc<MintDecimalsCache>,
+}
+
+#[async_trait]
+impl Dex for RaydiumCpDex {
+    dex_boilerplate!("raydium_cp", raydium_cp_program_id());
+
+    async fn fetch_pools(&self, pool_addresses: &[String], token_mint: &Pubkey) -> Result<Vec<PoolInfo>> {
+        let mut pools = Vec::new();
+
+        for pool_address in pool_addresses {
+            match self.fetch_single_pool(pool_address, token_mint).await {
+                Ok(pool) => pools.push(pool),
+                Err(e) => {
+                    tracing::error!("Failed to fetch Raydium CP pool {}: {}", pool_address, e);
+                }
+            }
+        }
+
+        Ok(pools)
+    }
+
+    async fn calculate_price(&self, pool_info: &PoolInfo) -> Result<PriceInfo> {
+        let (dex, pool_info) = (self.clone(), pool_info.clone());
+        blocking(move || dex.read_price(&pool_info)).await
+    }
+
+    async fn fetch_reserves(&self, pool_info: &PoolInfo) -> Result<PoolReserves> {
+        let (rpc_client, pool_info) = (self.rpc_client.clone(), pool_info.clone());
+        blocking(move || fetch_pool_reserves(rpc_client.as_ref(), &pool_info)).await
+    }
+
+    fn parse_pool_account(&self, address: &Pubkey, data: &[u8], token_mint: &Pubkey) -> Result<PoolInfo> {
+        let pool_pubkey = *address;
+
+        let cp_info = RaydiumCpAmmInfo::load_checked(data)?;
+
+        let (token_mint_final, base_mint, token_vault, base_vault) = if *token_mint == cp_info.token_0_mint {
+            (cp_info.token_0_mint, cp_info.token_1_mint, cp_info.token_0_vault, cp_info.token_1_vault)
+        } else if *token_mint == cp_info.token_1_mint {
+            (cp_info.token_1_mint, cp_info.token_0_mint, cp_info.token_1_vault, cp_info.token_0_vault)
         } else {
-            (amm_info.pc_mint, amm_info.coin_mint)
+            return Err(BotError::PoolParse(format!(
+                "Mint {} not found in Raydium CP pool {}",
+                token_mint,
+                pool_pubkey
+            )));
         };
 
+        let mut additional_accounts = std::collections::HashMap::new();
+        additional_accounts.insert("amm_config".to_string(), cp_info.amm_config);
+        additional_accounts.insert("observation_state".to_string(), cp_info.observation_key);
+
         Ok(PoolInfo {
             pool_address: pool_pubkey,
             token_mint: token_mint_final,
             base_mint,
             token_vault,
             base_vault,
-            fee_wallet: None, // Raydium doesn't have a separate fee wallet
-            additional_accounts: std::collections::HashMap::new(),
+            fee_wallet: None,
+            additional_accounts,
+            pool_type: PoolType::ConstantProduct,
+            reserves: None,
+            token_decimals: None,
+            base_decimals: None,
+            token_symbol: None,
+            token_transfer_fee_bps: 0,
+            base_transfer_fee_bps: 0,
         })
     }
+
+    fn mint_offsets(&self) -> &'static [usize] {
+        &[TOKEN_0_MINT_OFFSET, TOKEN_1_MINT_OFFSET]
+    }
+
+    fn get_swap_instruction_data(&self, _pool_info: &PoolInfo, amount_in: u64, minimum_out: u64) -> Result<Vec<u8>> {
+        validate_swap_amounts(amount_in, minimum_out)?;
+
+        let mut data = Vec::with_capacity(24);
+        data.extend_from_slice(&CP_SWAP_BASE_INPUT_DISCRIMINATOR);
+        data.extend_from_slice(&amount_in.to_le_bytes());
+        data.extend_from_slice(&minimum_out.to_le_bytes());
+        Ok(data)
+    }
+
+    fn capabilities(&self) -> DexCapabilities {
+        DexCapabilities {
+            supports_token_2022: true,
+            ..DexCapabilities::default()
+        }
+    }
+}
+
+impl RaydiumCpDex {
+    pub fn new(rpc_client: Arc<dyn RpcLike>) -> Self {
+        Self {
+            decimals: Arc::new(MintDecimalsCache::new(rpc_client.clone())),
+            rpc_client,
+        }
+    }
+
+    /// `calculate_price`, blocking on the account reads
+    fn read_price(&self, pool_info: &PoolInfo) -> Result<PriceInfo> {
+        // Vault balances still hold uncollected protocol and fund fees, so
+        // this slightly overstates both reserves
+        let (token_reserve, base_reserve) = pool_reserves(self.rpc_client.as_ref(), pool_info)?;
+        let (token_decimals, base_decimals) =
+            self.decimals.for_pool(pool_info)?;
+
+        let price = decimal_adjusted_price(token_reserve, base_reserve, token_decimals, base_decimals)
+            .ok_or_else(|| {
+                BotError::PriceCalculation(format!(
+                    "Raydium CP pool {} has an empty token vault",
+                    pool_info.pool_address
+                ))
+            })?;
+
+        let amm_config = self
+            .rpc_client
+            .get_account(&pool_info.additional_account("amm_config")?)?;
+        let fee_bytes = amm_config
+            .data
+            .get(CP_TRADE_FEE_RATE_OFFSET..CP_TRADE_FEE_RATE_OFFSET + 8)
+            .ok_or_else(|| BotError::PoolParse("Invalid data length for Raydium CP config".to_string()))?;
+        let fee = u64::from_le_bytes(fee_bytes.try_into().unwrap()) as f64 / CP_FEE_RATE_DENOMINATOR;
+
+        Ok(pool_info.with_transfer_fees(PriceInfo {
+            price,
+            liquidity: base_reserve,
+            fee,
+        }))
+    }
+
+    async fn fetch_single_pool(&self, pool_address: &str, token_mint: &Pubkey) -> Result<PoolInfo> {
+        let (dex, pool_address, token_mint) = (self.clone(), pool_address.to_string(), *token_mint);
+        blocking(move || dex.read_single_pool(&pool_address, &token_mint)).await
+    }
+
+    fn read_single_pool(&self, pool_address: &str, token_mint: &Pubkey) -> Result<PoolInfo> {
+        let pool_pubkey = Pubkey::from_str(pool_address)?;
+        let account = self.rpc_client.get_account(&pool_pubkey)?;
+
+        verify_pool_owner(&account, &raydium_cp_program_id())?;
+
+        let mut pool_info = self.parse_pool_account(&pool_pubkey, &account.data, token_mint)?;
+        self.decimals.fill(&mut pool_info)?;
+        Ok(pool_info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::constants::sol_mint;
+    use crate::dex::{fixtures::load_account, rpc::MockRpc};
+    use solana_sdk::account::Account;
+
+    fn pool() -> PoolInfo {
+        PoolInfo::mock(PoolType::ConstantProduct)
+    }
+
+    #[test]
+    fn swap_base_in_matches_the_on_chain_layout() {
+        let dex = RaydiumDex::new(Arc::new(MockRpc::new()));
+        let data = dex
+            .get_swap_instruction_data(&pool(), 0x0102_0304_0506_0708, 0x1112_1314_1516_1718)
+            .unwrap();
+        assert_eq!(
+            data,
+            vec![
+                9, // swapBaseIn
+                0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01, // amount_in
+                0x18, 0x17, 0x16, 0x15, 0x14, 0x13, 0x12, 0x11, // minimum_out
+            ]
+        );
+    }
+
+    #[test]
+    fn raydium_pool_fixture_parses_into_every_pool_info_field() {
+        let (address, account) = load_account("raydium_amm_v4_sol_usdc.json");
+        let usdc = Pubkey::from_str("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").unwrap();
+        let coin_vault = Pubkey::from_str("DQyrAcCrDXQ7NeoqGgDCZwBvWDcYmFCjSb9JtteuvPpz").unwrap();
+        let pc_vault = Pubkey::from_str("HLmqeL62xR1QoZ1HKKbXRrdN1p3phKpxRMb2VVopvBBz").unwrap();
+        assert_eq!(account.owner, raydium_program_id());
+        let dex = RaydiumDex::new(Arc::new(MockRpc::new()));
+
+        // USDC is the pc side, so the vaults are oriented pc-then-coin
+        let pool = dex.parse_pool_account(&address, &account.data, &usdc).unwrap();
+
+        assert_eq!(pool.pool_address, address);
+        assert_eq!(pool.token_mint, usdc);
+        assert_eq!(pool.base_mint, sol_mint());
+        assert_eq!(pool.token_vault, pc_vault);
+        assert_eq!(pool.base_vault, coin_vault);
+        assert_eq!(pool.fee_wallet, None);
+        assert_eq!(pool.additional_accounts.len(), 2);
+        assert_eq!(pool.additional_account("coin_vault").unwrap(), coin_vault);
+        assert_eq!(pool.additional_account("pc_vault").unwrap(), pc_vault);
+        assert_eq!(pool.pool_type, PoolType::ConstantProduct);
+        assert_eq!(pool.reserves, None);
+        assert_eq!((pool.token_decimals, pool.base_decimals), (None, None));
+        assert_eq!(pool.token_symbol, None);
+        assert_eq!((pool.token_transfer_fee_bps, pool.base_transfer_fee_bps), (0, 0));
+    }
+
+    #[test]
+    fn swap_base_in_v2_changes_only_the_discriminator() {
+        let dex = RaydiumDex::new(Arc::new(MockRpc::new())).with_swap_version(Some(2));
+        let data = dex.get_swap_instruction_data(&pool(), 1_000, 900).unwrap();
+        assert_eq!(data[0], 16);
+        assert_eq!(&data[1..], &RaydiumSwapVersion::V1.encode(1_000, 900)[1..]);
+    }
+
+    #[test]
+    fn zero_amount_in_is_a_transaction_error() {
+        let dex = RaydiumDex::new(Arc::new(MockRpc::new()));
+        assert!(matches!(
+            dex.get_swap_instruction_data(&pool(), 0, 900),
+            Err(BotError::Transaction(_))
+        ));
+    }
+
+    fn token_account(amount: u64) -> Account {
+        let mut data = vec![0u8; 165];
+        data[64..72].copy_from_slice(&amount.to_le_bytes());
+        Account { data, owner: spl_token::id(), ..Account::default() }
+    }
+
+    #[tokio::test]
+    async fn clmm_pool_account_is_decoded_and_priced_from_its_sqrt_price() {
+        let (pool_address, token, sol) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let state = PoolState {
+            amm_config: Pubkey::new_unique(),
+            token_mint_0: token,
+            token_mint_1: sol,
+            token_vault_0: Pubkey::new_unique(),
+            token_vault_1: Pubkey::new_unique(),
+            observation_key: Pubkey::new_unique(),
+            mint_decimals_0: 6,
+            mint_decimals_1: 9,
+            tick_spacing: 60,
+            liquidity: 1_000_000_000,
+            // One raw unit of SOL per raw unit of token, i.e. 0.001 SOL per token
+            sqrt_price_x64: 1u128 << 64,
+            tick_current: -120,
+            ..PoolState::default()
+        };
+        let mut amm_config = vec![0u8; 64];
+        amm_config[CLMM_TRADE_FEE_RATE_OFFSET..CLMM_TRADE_FEE_RATE_OFFSET + 4].copy_from_slice(&2_500u32.to_le_bytes());
+        let rpc = MockRpc::new()
+            .with_account(
+                pool_address,
+                Account { data: state.account_data(), owner: raydium_clmm_program_id(), ..Account::default() },
+            )
+            .with_account(state.amm_config, Account { data: amm_config, ..Account::default() })
+            .with_account(state.token_vault_0, token_account(5_000_000_000))
+            .with_account(state.token_vault_1, token_account(42_000_000_000));
+        let dex = RaydiumClmmDex::new(Arc::new(rpc));
+
+        let pool = dex.fetch_single_pool(&pool_address.to_string(), &token).await.unwrap();
+        assert_eq!((pool.token_mint, pool.base_mint), (token, sol));
+        assert_eq!((pool.token_vault, pool.base_vault), (state.token_vault_0, state.token_vault_1));
+        assert_eq!((pool.token_decimals, pool.base_decimals), (Some(6), Some(9)));
+        assert_eq!(pool.additional_account("amm_config").unwrap(), state.amm_config);
+        assert_eq!(pool.additional_account("observation_state").unwrap(), state.observation_key);
+        let tick_arrays =
+            get_tick_array_pubkeys(&pool_address, -120, 60, &[-1, 0, 1], &raydium_clmm_program_id()).unwrap();
+        for (i, tick_array) in tick_arrays.iter().enumerate() {
+            assert_eq!(pool.additional_account(&format!("tick_array_{}", i)).unwrap(), *tick_array);
+        }
+
+        let price = dex.calculate_price(&pool).await.unwrap();
+        assert!((price.price - 0.001).abs() < 1e-12);
+        assert!((price.fee - 0.0025).abs() < 1e-12);
+        assert_eq!(price.liquidity, 42_000_000_000);
+
+        // Tracking mint 1 instead inverts the price and swaps the vaults
+        let pool = dex.fetch_single_pool(&pool_address.to_string(), &sol).await.unwrap();
+        assert_eq!((pool.token_vault, pool.base_vault), (state.token_vault_1, state.token_vault_0));
+        let price = dex.calculate_price(&pool).await.unwrap();
+        assert!((price.price - 1_000.0).abs() < 1e-6);
+        assert_eq!(price.liquidity, 5_000_000_000);
+    }
+
+    #[test]
+    fn clmm_swap_encoding_is_refused() {
+        let dex = RaydiumClmmDex::new(Arc::new(MockRpc::new()));
+        assert!(matches!(
+            dex.get_swap_instruction_data(&PoolInfo::mock(PoolType::ConcentratedLiquidity), 1_000, 900),
+            Err(BotError::Validation(_))
+        ));
+    }
 }
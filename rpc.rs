@@ -0,0 +1,292 @@
+//! The account reads DEX adapters and the token fetcher make, behind a trait
+//! so they can run against canned accounts instead of a live node
+
+use solana_account_decoder::parse_token::UiTokenAmount;
+use solana_client::{
+    client_error::Result as ClientResult,
+    rpc_client::RpcClient,
+    rpc_config::RpcProgramAccountsConfig,
+    rpc_response::RpcResult,
+};
+use solana_sdk::{account::Account, commitment_config::CommitmentConfig, pubkey::Pubkey};
+
+use crate::error::{BotError, Result};
+
+#[cfg(test)]
+pub use mock::MockRpc;
+
+/// Account reads used by the DEX adapters and the token fetcher, with the
+/// same signatures as the `RpcClient` methods they stand for
+pub trait RpcLike: Send + Sync {
+    /// Commitment reads are made at when none is given
+    fn commitment(&self) -> CommitmentConfig;
+
+    fn get_account(&self, pubkey: &Pubkey) -> ClientResult<Account>;
+
+    fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> ClientResult<Vec<Option<Account>>>;
+
+    fn get_multiple_accounts_with_commitment(
+        &self,
+        pubkeys: &[Pubkey],
+        commitment: CommitmentConfig,
+    ) -> RpcResult<Vec<Option<Account>>>;
+
+    fn get_token_account_balance(&self, pubkey: &Pubkey) -> ClientResult<UiTokenAmount>;
+
+    fn get_program_accounts_with_config(
+        &self,
+        program_id: &Pubkey,
+        config: RpcProgramAccountsConfig,
+    ) -> ClientResult<Vec<(Pubkey, Account)>>;
+
+    fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> ClientResult<u64>;
+}
+
+impl RpcLike for RpcClient {
+    fn commitment(&self) -> CommitmentConfig {
+        RpcClient::commitment(self)
+    }
+
+    fn get_account(&self, pubkey: &Pubkey) -> ClientResult<Account> {
+        RpcClient::get_account(self, pubkey)
+    }
+
+    fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> ClientResult<Vec<Option<Account>>> {
+        RpcClient::get_multiple_accounts(self, pubkeys)
+    }
+
+    fn get_multiple_accounts_with_commitment(
+        &self,
+        pubkeys: &[Pubkey],
+        commitment: CommitmentConfig,
+    ) -> RpcResult<Vec<Option<Account>>> {
+        RpcClient::get_multiple_accounts_with_commitment(self, pubkeys, commitment)
+    }
+
+    fn get_token_account_balance(&self, pubkey: &Pubkey) -> ClientResult<UiTokenAmount> {
+        RpcClient::get_token_account_balance(self, pubkey)
+    }
+
+    fn get_program_accounts_with_config(
+        &self,
+        program_id: &Pubkey,
+        config: RpcProgramAccountsConfig,
+    ) -> ClientResult<Vec<(Pubkey, Account)>> {
+        RpcClient::get_program_accounts_with_config(self, program_id, config)
+    }
+
+    fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> ClientResult<u64> {
+        RpcClient::get_minimum_balance_for_rent_exemption(self, data_len)
+    }
+}
+
+/// Run a closure making `RpcLike` reads on tokio's blocking pool. The reads
+/// block for a full round trip, so async adapter methods hand them off here
+/// rather than stall the runtime worker polling them.
+pub async fn blocking<T, F>(read: F) -> Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T> + Send + 'static,
+{
+    tokio::task::spawn_blocking(read)
+        .await
+        .map_err(|e| BotError::Rpc(format!("Blocking RPC read did not complete: {}", e)))?
+}
+
+#[cfg(test)]
+mod mock {
+    use super::RpcLike;
+    use crate::{
+        dex::vault::{mint_decimals, token_account_amount},
+        error::BotError,
+    };
+    use solana_account_decoder::{
+        parse_account_data::SplTokenAdditionalData,
+        parse_token::{token_amount_to_ui_amount_v2, UiTokenAmount},
+    };
+    use solana_client::{
+        client_error::{ClientError, ClientErrorKind, Result as ClientResult},
+        rpc_config::RpcProgramAccountsConfig,
+        rpc_filter::RpcFilterType,
+        rpc_response::{Response, RpcResponseContext, RpcResult},
+    };
+    use solana_sdk::{account::Account, commitment_config::CommitmentConfig, pubkey::Pubkey, rent::Rent};
+    use std::{
+        collections::HashMap,
+        sync::{
+            atomic::{AtomicU64, Ordering},
+            RwLock,
+        },
+    };
+
+    /// Offset of the `mint` field in an SPL Token / Token-2022 account
+    const TOKEN_ACCOUNT_MINT_OFFSET: usize = 0;
+
+    /// In-memory `RpcLike` serving accounts set up front, for exercising
+    /// adapters and the token fetcher without a node. Reads of unknown
+    /// accounts fail the way `RpcClient` does for accounts that don't exist,
+    /// every read reports the current `slot`, and `fail_next` makes the next
+    /// calls fail as if the node were down.
+    #[derive(Debug, Default)]
+    pub struct MockRpc {
+        accounts: RwLock<HashMap<Pubkey, Account>>,
+        slot: AtomicU64,
+        calls: AtomicU64,
+        failures: AtomicU64,
+    }
+
+    impl MockRpc {
+        pub fn new() -> Self {
+            Self::default()
+        }
+
+        pub fn with_account(self, pubkey: Pubkey, account: Account) -> Self {
+            self.set_account(pubkey, account);
+            self
+        }
+
+        pub fn with_slot(self, slot: u64) -> Self {
+            self.set_slot(slot);
+            self
+        }
+
+        /// Add or replace an account, e.g. to move a vault balance between reads
+        pub fn set_account(&self, pubkey: Pubkey, account: Account) {
+            self.accounts.write().unwrap().insert(pubkey, account);
+        }
+
+        pub fn remove_account(&self, pubkey: &Pubkey) {
+            self.accounts.write().unwrap().remove(pubkey);
+        }
+
+        pub fn set_slot(&self, slot: u64) {
+            self.slot.store(slot, Ordering::SeqCst);
+        }
+
+        /// Fail the next `calls` calls with a connection error
+        pub fn fail_next(&self, calls: u64) {
+            self.failures.store(calls, Ordering::SeqCst);
+        }
+
+        /// Number of RPC calls made so far, batched reads and failed calls
+        /// counting once each
+        pub fn calls(&self) -> u64 {
+            self.calls.load(Ordering::SeqCst)
+        }
+
+        fn lookup(&self, pubkey: &Pubkey) -> Option<Account> {
+            self.accounts.read().unwrap().get(pubkey).cloned()
+        }
+
+        /// Count a call, failing it while `fail_next` failures remain
+        fn record_call(&self) -> ClientResult<()> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let failing = self
+                .failures
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |left| left.checked_sub(1))
+                .is_ok();
+            if failing {
+                return Err(ClientError::from(ClientErrorKind::Custom("connection refused".to_string())));
+            }
+            Ok(())
+        }
+    }
+
+    impl RpcLike for MockRpc {
+        fn commitment(&self) -> CommitmentConfig {
+            CommitmentConfig::default()
+        }
+
+        fn get_account(&self, pubkey: &Pubkey) -> ClientResult<Account> {
+            self.record_call()?;
+            self.lookup(pubkey).ok_or_else(|| account_not_found(pubkey))
+        }
+
+        fn get_multiple_accounts(&self, pubkeys: &[Pubkey]) -> ClientResult<Vec<Option<Account>>> {
+            self.record_call()?;
+            Ok(pubkeys.iter().map(|pubkey| self.lookup(pubkey)).collect())
+        }
+
+        fn get_multiple_accounts_with_commitment(
+            &self,
+            pubkeys: &[Pubkey],
+            _commitment: CommitmentConfig,
+        ) -> RpcResult<Vec<Option<Account>>> {
+            let value = self.get_multiple_accounts(pubkeys)?;
+            Ok(Response {
+                context: RpcResponseContext::new(self.slot.load(Ordering::SeqCst)),
+                value,
+            })
+        }
+
+        /// Balance read from the token account's data, with the decimals of
+        /// its mint when that account is set and 0 otherwise
+        fn get_token_account_balance(&self, pubkey: &Pubkey) -> ClientResult<UiTokenAmount> {
+            let account = self.get_account(pubkey)?;
+            let invalid = |e: BotError| ClientError::from(ClientErrorKind::Custom(format!("{}: {}", pubkey, e)));
+            let amount = token_account_amount(&account.data).map_err(invalid)?;
+            let decimals = account
+                .data
+                .get(TOKEN_ACCOUNT_MINT_OFFSET..TOKEN_ACCOUNT_MINT_OFFSET + 32)
+                .and_then(|mint| self.lookup(&Pubkey::new_from_array(mint.try_into().unwrap())))
+                .and_then(|mint| mint_decimals(&mint.data).ok())
+                .unwrap_or(0);
+            Ok(token_amount_to_ui_amount_v2(
+                amount,
+                &SplTokenAdditionalData::with_decimals(decimals),
+            ))
+        }
+
+        /// Accounts owned by `program_id` that pass every `DataSize` and
+        /// `Memcmp` filter; other filters are ignored
+        fn get_program_accounts_with_config(
+            &self,
+            program_id: &Pubkey,
+            config: RpcProgramAccountsConfig,
+        ) -> ClientResult<Vec<(Pubkey, Account)>> {
+            self.record_call()?;
+            let filters = config.filters.unwrap_or_default();
+            let matches = |account: &Account| {
+                filters.iter().all(|filter| match filter {
+                    RpcFilterType::DataSize(size) => account.data.len() as u64 == *size,
+                    RpcFilterType::Memcmp(memcmp) => memcmp.bytes_match(&account.data),
+                    _ => true,
+                })
+            };
+            Ok(self
+                .accounts
+                .read()
+                .unwrap()
+                .iter()
+                .filter(|(_, account)| account.owner == *program_id && matches(account))
+                .map(|(pubkey, account)| (*pubkey, account.clone()))
+                .collect())
+        }
+
+        /// Rent-exempt minimum under the default rent parameters, which
+        /// mainnet uses
+        fn get_minimum_balance_for_rent_exemption(&self, data_len: usize) -> ClientResult<u64> {
+            self.record_call()?;
+            Ok(Rent::default().minimum_balance(data_len))
+        }
+    }
+
+    fn account_not_found(pubkey: &Pubkey) -> ClientError {
+        ClientError::from(ClientErrorKind::Custom(format!("AccountNotFound: pubkey={}", pubkey)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::blocking;
+
+    #[tokio::test]
+    async fn blocking_reads_leave_the_runtime_free() {
+        // The read waits on a value the runtime sends after it started, which
+        // deadlocks a single-threaded runtime unless the read runs elsewhere
+        let (tx, rx) = std::sync::mpsc::channel();
+        let read = blocking(move || Ok(rx.recv().unwrap()));
+        let (value, ()) = tokio::join!(read, async move { tx.send(7u64).unwrap() });
+        assert_eq!(value.unwrap(), 7);
+    }
+}
@@ -0,0 +1,311 @@
+//! Scan scheduling that favours volatile mints
+
+use crate::{
+    chain::clock::{Clock, SystemClock},
+    config::{BotConfig, MintConfig},
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tracing::{debug, warn};
+
+/// Per-mint scan state
+#[derive(Debug)]
+struct MintSchedule {
+    process_delay: Duration,
+    prices: VecDeque<f64>,
+    last_scanned: Option<Instant>,
+    pool_count: Option<usize>,
+}
+
+/// Decides which mints to scan next.
+///
+/// Each mint keeps a short rolling window of observed prices. Mints whose
+/// volatility reaches `target_volatility_bps` are scanned every
+/// `process_delay`; calmer mints are backed off linearly toward
+/// `max_interval`, so the RPC budget goes where opportunities are likely.
+pub struct ScanScheduler {
+    mints: HashMap<String, MintSchedule>,
+    window: usize,
+    target_volatility_bps: f64,
+    max_interval: Duration,
+    min_pools_for_arb: usize,
+    clock: Arc<dyn Clock>,
+}
+
+impl ScanScheduler {
+    pub fn new(
+        mint_configs: &[MintConfig],
+        window: usize,
+        target_volatility_bps: u64,
+        max_interval: Duration,
+        clock: Arc<dyn Clock>,
+    ) -> Self {
+        let mints = mint_configs
+            .iter()
+            .map(|mc| {
+                (
+                    mc.mint.clone(),
+                    MintSchedule {
+                        process_delay: Duration::from_millis(mc.process_delay),
+                        prices: VecDeque::with_capacity(window),
+                        last_scanned: None,
+                        pool_count: None,
+                    },
+                )
+            })
+            .collect();
+
+        Self {
+            mints,
+            window: window.max(2),
+            target_volatility_bps: target_volatility_bps.max(1) as f64,
+            max_interval,
+            min_pools_for_arb: 2,
+            clock,
+        }
+    }
+
+    /// Replace the scheduled mints with `mint_configs`, as after a config
+    /// reload. Mints kept keep their price history and last scan time but take
+    /// the new `process_delay`, and their pool count is forgotten so a changed
+    /// pool list gets scanned again.
+    pub fn reconfigure(&mut self, mint_configs: &[MintConfig]) {
+        let mut previous = std::mem::take(&mut self.mints);
+        for mc in mint_configs {
+            let process_delay = Duration::from_millis(mc.process_delay);
+            let schedule = match previous.remove(&mc.mint) {
+                Some(schedule) => MintSchedule {
+                    process_delay,
+                    pool_count: None,
+                    ..schedule
+                },
+                None => MintSchedule {
+                    process_delay,
+                    prices: VecDeque::with_capacity(self.window),
+                    last_scanned: None,
+                    pool_count: None,
+                },
+            };
+            self.mints.insert(mc.mint.clone(), schedule);
+        }
+    }
+
+    /// Minimum decoded pools a mint needs before it is worth scanning
+    pub fn with_min_pools_for_arb(mut self, min_pools_for_arb: usize) -> Self {
+        self.min_pools_for_arb = min_pools_for_arb;
+        self
+    }
+
+    pub fn from_config(bot: &BotConfig, mint_configs: &[MintConfig]) -> Self {
+        Self::new(
+            mint_configs,
+            bot.volatility_window,
+            bot.volatility_target_bps,
+            Duration::from_millis(bot.max_scan_interval_ms),
+            Arc::new(SystemClock),
+        )
+        .with_min_pools_for_arb(bot.min_pools_for_arb)
+    }
+
+    /// Record how many pools were decoded for a mint. Returns false, and logs
+    /// the skip, if there are too few venues to arbitrage between.
+    pub fn record_pool_count(&mut self, mint: &str, count: usize) -> bool {
+        if let Some(schedule) = self.mints.get_mut(mint) {
+            schedule.pool_count = Some(count);
+        }
+        let actionable = count >= self.min_pools_for_arb;
+        if !actionable {
+            warn!(
+                "Skipping mint {}: {} pool(s) decoded, at least {} required to arbitrage",
+                mint, count, self.min_pools_for_arb
+            );
+        }
+        actionable
+    }
+
+    /// Whether a mint has enough pools to arbitrage; unknown counts are assumed sufficient
+    pub fn is_actionable(&self, mint: &str) -> bool {
+        self.mints
+            .get(mint)
+            .and_then(|schedule| schedule.pool_count)
+            .is_none_or(|count| count >= self.min_pools_for_arb)
+    }
+
+    /// Record an observed price for a mint
+    pub fn record_price(&mut self, mint: &str, price: f64) {
+        if !price.is_finite() || price <= 0.0 {
+            return;
+        }
+        if let Some(schedule) = self.mints.get_mut(mint) {
+            if schedule.prices.len() == self.window {
+                schedule.prices.pop_front();
+            }
+            schedule.prices.push_back(price);
+        }
+    }
+
+    /// Mark a mint as scanned now
+    pub fn mark_scanned(&mut self, mint: &str) {
+        let now = self.clock.now();
+        if let Some(schedule) = self.mints.get_mut(mint) {
+            schedule.last_scanned = Some(now);
+        }
+    }
+
+    /// Standard deviation of relative price changes over the window, in basis points
+    pub fn volatility_bps(&self, mint: &str) -> f64 {
+        let Some(schedule) = self.mints.get(mint) else {
+            return 0.0;
+        };
+        let returns: Vec<f64> = schedule
+            .prices
+            .iter()
+            .zip(schedule.prices.iter().skip(1))
+            .map(|(prev, next)| (next - prev) / prev)
+            .collect();
+        if returns.is_empty() {
+            return 0.0;
+        }
+
+        let mean = returns.iter().sum::<f64>() / returns.len() as f64;
+        let variance =
+            returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / returns.len() as f64;
+        variance.sqrt() * 10_000.0
+    }
+
+    /// Scan priority in `[0, 1]`; 1 means volatile enough to scan at `process_delay`.
+    /// Mints without price history yet get full priority.
+    pub fn priority(&self, mint: &str) -> f64 {
+        match self.mints.get(mint) {
+            Some(schedule) if schedule.prices.len() >= 2 => {
+                (self.volatility_bps(mint) / self.target_volatility_bps).min(1.0)
+            }
+            Some(_) => 1.0,
+            None => 0.0,
+        }
+    }
+
+    /// Current interval between scans of a mint
+    pub fn interval(&self, mint: &str) -> Duration {
+        let Some(schedule) = self.mints.get(mint) else {
+            return self.max_interval;
+        };
+        let min = schedule.process_delay;
+        let max = self.max_interval.max(min);
+        let backoff = (max - min).mul_f64(1.0 - self.priority(mint));
+        min + backoff
+    }
+
+    /// Mints due for a scan, highest priority first
+    pub fn due_mints(&self) -> Vec<String> {
+        let now = self.clock.now();
+        let mut due: Vec<(String, f64)> = self
+            .mints
+            .iter()
+            .filter(|(mint, _)| self.is_actionable(mint))
+            .filter(|(mint, schedule)| match schedule.last_scanned {
+                Some(last) => now.duration_since(last) >= self.interval(mint),
+                None => true,
+            })
+            .map(|(mint, _)| (mint.clone(), self.priority(mint)))
+            .collect();
+
+        due.sort_by(|a, b| b.1.total_cmp(&a.1));
+        for (mint, priority) in &due {
+            debug!(
+                "Scan priority for {}: {:.2} (volatility {:.1} bps, interval {:?})",
+                mint,
+                priority,
+                self.volatility_bps(mint),
+                self.interval(mint)
+            );
+        }
+
+        due.into_iter().map(|(mint, _)| mint).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{chain::clock::MockClock, config::Config};
+
+    fn mint_configs(mints: &[(&str, u64)]) -> Vec<MintConfig> {
+        let template = Config::from_toml(
+            r#"
+            [bot]
+            compute_unit_limit = 200000
+
+            [wallet]
+            private_key = ""
+
+            [[routing.mint_config_list]]
+            mint = "So11111111111111111111111111111111111111112"
+            process_delay = 0
+            pump_pool_list = ["11111111111111111111111111111111"]
+            "#,
+        )
+        .routing
+        .mint_config_list
+        .remove(0);
+        mints
+            .iter()
+            .map(|&(mint, process_delay)| MintConfig {
+                mint: mint.to_string(),
+                process_delay,
+                ..template.clone()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn single_pool_mints_are_skipped() {
+        let mut scheduler = ScanScheduler::new(
+            &mint_configs(&[("single", 0), ("pair", 0)]),
+            4,
+            100,
+            Duration::from_secs(10),
+            Arc::new(MockClock::new()),
+        );
+
+        assert!(!scheduler.record_pool_count("single", 1));
+        assert!(scheduler.record_pool_count("pair", 2));
+        assert!(!scheduler.is_actionable("single"));
+        assert_eq!(scheduler.due_mints(), vec!["pair".to_string()]);
+
+        // A higher `min_pools_for_arb` skips the pair too
+        let mut scheduler = scheduler.with_min_pools_for_arb(3);
+        assert!(!scheduler.record_pool_count("pair", 2));
+        assert!(scheduler.due_mints().is_empty());
+    }
+
+    #[test]
+    fn reconfigure_keeps_history_and_takes_new_delays() {
+        let clock = Arc::new(MockClock::new());
+        let mut scheduler = ScanScheduler::new(
+            &mint_configs(&[("kept", 1_000), ("dropped", 1_000)]),
+            4,
+            100,
+            Duration::from_secs(10),
+            clock.clone(),
+        );
+        for price in [1.0, 1.1, 1.0] {
+            scheduler.record_price("kept", price);
+        }
+        scheduler.mark_scanned("kept");
+        assert!(!scheduler.record_pool_count("kept", 1));
+
+        scheduler.reconfigure(&mint_configs(&[("kept", 0), ("added", 0)]));
+
+        // The price history survives, and the forgotten pool count makes the
+        // mint actionable again under its new zero delay
+        assert!(scheduler.volatility_bps("kept") > 0.0);
+        assert!(scheduler.is_actionable("kept"));
+        let mut due = scheduler.due_mints();
+        due.sort();
+        assert_eq!(due, vec!["added".to_string(), "kept".to_string()]);
+    }
+}
@@ -0,0 +1,213 @@
+//! Session-wide statistics and the end-of-run summary report
+
+use crate::{
+    config::{ReportConfig, ReportFormat},
+    error::Result,
+};
+use serde::Serialize;
+use std::{
+    collections::HashMap,
+    fmt::Write as _,
+    sync::{
+        atomic::{AtomicI64, AtomicU64, Ordering},
+        Mutex, OnceLock,
+    },
+    time::Instant,
+};
+use tracing::error;
+
+static SESSION_STATS: OnceLock<SessionStats> = OnceLock::new();
+
+/// Global session statistics shared by every component of the bot
+pub fn stats() -> &'static SessionStats {
+    SESSION_STATS.get_or_init(SessionStats::new)
+}
+
+/// Counters accumulated over the lifetime of a bot session
+#[derive(Debug)]
+pub struct SessionStats {
+    started_at: Instant,
+    pools_scanned: AtomicU64,
+    opportunities_found: AtomicU64,
+    opportunities_expired: AtomicU64,
+    txs_submitted: AtomicU64,
+    txs_landed: AtomicU64,
+    total_profit_lamports: AtomicI64,
+    fees_paid_lamports: AtomicU64,
+    profit_by_mint: Mutex<HashMap<String, i64>>,
+}
+
+impl SessionStats {
+    pub fn new() -> Self {
+        Self {
+            started_at: Instant::now(),
+            pools_scanned: AtomicU64::new(0),
+            opportunities_found: AtomicU64::new(0),
+            opportunities_expired: AtomicU64::new(0),
+            txs_submitted: AtomicU64::new(0),
+            txs_landed: AtomicU64::new(0),
+            total_profit_lamports: AtomicI64::new(0),
+            fees_paid_lamports: AtomicU64::new(0),
+            profit_by_mint: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn record_pools_scanned(&self, count: usize) {
+        self.pools_scanned.fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    pub fn record_opportunities(&self, count: usize) {
+        self.opportunities_found.fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    /// Record opportunities dropped because their blockhash expired before sending
+    pub fn record_expired(&self, count: usize) {
+        self.opportunities_expired
+            .fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    pub fn record_submitted(&self, count: usize) {
+        self.txs_submitted.fetch_add(count as u64, Ordering::Relaxed);
+    }
+
+    /// Record a landed trade with its gross profit and the fees paid to land it
+    pub fn record_landed(&self, mint: &str, profit_lamports: i64, fees_lamports: u64) {
+        self.txs_landed.fetch_add(1, Ordering::Relaxed);
+        self.total_profit_lamports
+            .fetch_add(profit_lamports, Ordering::Relaxed);
+        self.fees_paid_lamports
+            .fetch_add(fees_lamports, Ordering::Relaxed);
+
+        let net = profit_lamports - fees_lamports as i64;
+        if let Ok(mut by_mint) = self.profit_by_mint.lock() {
+            *by_mint.entry(mint.to_string()).or_insert(0) += net;
+        }
+    }
+
+    /// Build a point-in-time summary of the session
+    pub fn summary(&self, top_mints: usize) -> SessionSummary {
+        let total_profit_lamports = self.total_profit_lamports.load(Ordering::Relaxed);
+        let fees_paid_lamports = self.fees_paid_lamports.load(Ordering::Relaxed);
+
+        let mut top: Vec<MintProfit> = self
+            .profit_by_mint
+            .lock()
+            .map(|by_mint| {
+                by_mint
+                    .iter()
+                    .map(|(mint, net)| MintProfit {
+                        mint: mint.clone(),
+                        net_profit_lamports: *net,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        top.sort_by_key(|m| std::cmp::Reverse(m.net_profit_lamports));
+        top.truncate(top_mints);
+
+        SessionSummary {
+            duration_secs: self.started_at.elapsed().as_secs(),
+            pools_scanned: self.pools_scanned.load(Ordering::Relaxed),
+            opportunities_found: self.opportunities_found.load(Ordering::Relaxed),
+            opportunities_expired: self.opportunities_expired.load(Ordering::Relaxed),
+            txs_submitted: self.txs_submitted.load(Ordering::Relaxed),
+            txs_landed: self.txs_landed.load(Ordering::Relaxed),
+            total_profit_lamports,
+            net_profit_lamports: total_profit_lamports - fees_paid_lamports as i64,
+            top_mints: top,
+        }
+    }
+}
+
+impl Default for SessionStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Net profit attributed to a single mint
+#[derive(Debug, Clone, Serialize)]
+pub struct MintProfit {
+    pub mint: String,
+    pub net_profit_lamports: i64,
+}
+
+/// End-of-run report aggregated from the session statistics
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionSummary {
+    pub duration_secs: u64,
+    pub pools_scanned: u64,
+    pub opportunities_found: u64,
+    pub opportunities_expired: u64,
+    pub txs_submitted: u64,
+    pub txs_landed: u64,
+    pub total_profit_lamports: i64,
+    pub net_profit_lamports: i64,
+    pub top_mints: Vec<MintProfit>,
+}
+
+impl SessionSummary {
+    /// Render the summary in the requested format
+    pub fn render(&self, format: ReportFormat) -> Result<String> {
+        match format {
+            ReportFormat::Json => Ok(serde_json::to_string_pretty(self)?),
+            ReportFormat::Text => {
+                let mut out = String::new();
+                let _ = writeln!(out, "Session summary ({}s)", self.duration_secs);
+                let _ = writeln!(out, "  Pools scanned:       {}", self.pools_scanned);
+                let _ = writeln!(out, "  Opportunities found: {}", self.opportunities_found);
+                let _ = writeln!(out, "  Expired unsent:      {}", self.opportunities_expired);
+                let _ = writeln!(out, "  Txs submitted:       {}", self.txs_submitted);
+                let _ = writeln!(out, "  Txs landed:          {}", self.txs_landed);
+                let _ = writeln!(out, "  Total profit:        {} lamports", self.total_profit_lamports);
+                let _ = writeln!(out, "  Net profit:          {} lamports", self.net_profit_lamports);
+                if !self.top_mints.is_empty() {
+                    let _ = writeln!(out, "  Top mints by profit:");
+                    for (i, entry) in self.top_mints.iter().enumerate() {
+                        let _ = writeln!(
+                            out,
+                            "    {}. {}: {} lamports",
+                            i + 1,
+                            entry.mint,
+                            entry.net_profit_lamports
+                        );
+                    }
+                }
+                Ok(out)
+            }
+        }
+    }
+
+    /// Print the summary and optionally write it to the configured file
+    pub fn emit(&self, config: &ReportConfig) -> Result<()> {
+        let rendered = self.render(config.format)?;
+        println!("{}", rendered);
+
+        if let Some(path) = &config.output_path {
+            std::fs::write(path, &rendered)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Emits the session summary when dropped, so the report is produced on every
+/// exit path out of `main`, including early returns on error and panics.
+pub struct ShutdownReport {
+    config: ReportConfig,
+}
+
+impl ShutdownReport {
+    pub fn new(config: ReportConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Drop for ShutdownReport {
+    fn drop(&mut self) {
+        let summary = stats().summary(self.config.top_mints);
+        if let Err(e) = summary.emit(&self.config) {
+            error!("Failed to write session summary: {}", e);
+        }
+    }
+}
@@ -0,0 +1,61 @@
+//! Ctrl-C handling: stop between scan iterations and bound the time spent
+//! cleaning up
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tracing::{error, info, warn};
+
+/// Exit status used when cleanup overruns `shutdown_timeout_secs`, the
+/// conventional code for a process ended by SIGINT
+const FORCED_EXIT_CODE: i32 = 130;
+
+/// Shared flag set once a shutdown has been requested. Clones observe the
+/// same flag, so long-running loops can poll it between iterations.
+#[derive(Debug, Clone, Default)]
+pub struct Shutdown {
+    requested: Arc<AtomicBool>,
+}
+
+impl Shutdown {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.requested.load(Ordering::SeqCst)
+    }
+
+    pub fn request(&self) {
+        self.requested.store(true, Ordering::SeqCst);
+    }
+
+    /// Listen for Ctrl-C in the background. The first one sets the flag so the
+    /// current scan iteration can finish and clean up; if the process is still
+    /// running `timeout` later, or a second Ctrl-C arrives, it exits at once.
+    pub fn listen(&self, timeout: Duration) {
+        let shutdown = self.clone();
+        tokio::spawn(async move {
+            if let Err(e) = tokio::signal::ctrl_c().await {
+                error!("Failed to listen for Ctrl-C: {}", e);
+                return;
+            }
+            info!("Shutdown requested, finishing the current scan iteration");
+            shutdown.request();
+
+            tokio::select! {
+                _ = tokio::time::sleep(timeout) => {
+                    warn!("Cleanup did not finish within {:?}, exiting", timeout);
+                }
+                _ = tokio::signal::ctrl_c() => {
+                    warn!("Second Ctrl-C received, exiting without cleanup");
+                }
+            }
+            std::process::exit(FORCED_EXIT_CODE);
+        });
+    }
+}
@@ -0,0 +1,274 @@
+//! Transaction signing, decoupled from where the wallet key is held
+
+use crate::{
+    config::{SignerType, WalletConfig},
+    error::{BotError, Result},
+};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signature},
+    signer::{Signer, SignerError},
+};
+use std::{
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc,
+    },
+    thread,
+};
+
+/// Signer shared by the transaction builder and sender
+pub type WalletSigner = Arc<dyn Signer + Send + Sync>;
+
+/// Hook for a key custody service that signs on the bot's behalf
+pub trait RemoteSigningService: Send + Sync {
+    /// Sign the serialized transaction message and return the signature
+    fn sign(&self, message: &[u8]) -> Result<Signature>;
+}
+
+/// Adapts a `RemoteSigningService` to solana's `Signer`, so remote custody can
+/// be used anywhere an in-memory keypair would be
+pub struct RemoteSigner {
+    pubkey: Pubkey,
+    service: Box<dyn RemoteSigningService>,
+}
+
+impl RemoteSigner {
+    pub fn new(pubkey: Pubkey, service: Box<dyn RemoteSigningService>) -> Self {
+        Self { pubkey, service }
+    }
+}
+
+impl Signer for RemoteSigner {
+    fn try_pubkey(&self) -> std::result::Result<Pubkey, SignerError> {
+        Ok(self.pubkey)
+    }
+
+    fn try_sign_message(&self, message: &[u8]) -> std::result::Result<Signature, SignerError> {
+        self.service
+            .sign(message)
+            .map_err(|e| SignerError::Custom(e.to_string()))
+    }
+
+    fn is_interactive(&self) -> bool {
+        false
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct SignRequest {
+    pubkey: String,
+    message: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct SignResponse {
+    signature: String,
+}
+
+struct SignJob {
+    message: Vec<u8>,
+    reply: mpsc::Sender<Result<Signature>>,
+}
+
+/// Signing service reached over HTTP. Posts the base58 message and pubkey as
+/// JSON and expects `{"signature": "<base58>"}` back.
+///
+/// `Signer` is synchronous, so requests run on a dedicated thread with its own
+/// runtime. Callers block only on the reply, whichever runtime (if any) they
+/// are on.
+pub struct HttpSigningService {
+    jobs: mpsc::Sender<SignJob>,
+}
+
+impl HttpSigningService {
+    pub fn new(url: String, pubkey: Pubkey) -> Result<Self> {
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        let (jobs, requests) = mpsc::channel::<SignJob>();
+        thread::Builder::new()
+            .name("remote-signer".to_string())
+            .spawn(move || {
+                let client = reqwest::Client::new();
+                // Ends when the service, and with it the sender, is dropped
+                for job in requests {
+                    let result = runtime.block_on(request_signature(&client, &url, &pubkey, &job.message));
+                    let _ = job.reply.send(result);
+                }
+            })?;
+        Ok(Self { jobs })
+    }
+}
+
+async fn request_signature(
+    client: &reqwest::Client,
+    url: &str,
+    pubkey: &Pubkey,
+    message: &[u8],
+) -> Result<Signature> {
+    let request = SignRequest {
+        pubkey: pubkey.to_string(),
+        message: bs58::encode(message).into_string(),
+    };
+    let response = client
+        .post(url)
+        .json(&request)
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| BotError::Transaction(format!("Remote signer request failed: {}", e)))?;
+    let body: SignResponse = response
+        .json()
+        .await
+        .map_err(|e| BotError::Transaction(format!("Invalid remote signer response: {}", e)))?;
+
+    Signature::from_str(&body.signature)
+        .map_err(|e| BotError::Parse(format!("Invalid signature from remote signer: {}", e)))
+}
+
+impl RemoteSigningService for HttpSigningService {
+    fn sign(&self, message: &[u8]) -> Result<Signature> {
+        let stopped = || BotError::Transaction("Remote signer thread stopped".to_string());
+        let (reply, signature) = mpsc::channel();
+        self.jobs
+            .send(SignJob {
+                message: message.to_vec(),
+                reply,
+            })
+            .map_err(|_| stopped())?;
+        signature.recv().map_err(|_| stopped())?
+    }
+}
+
+/// Wallets handed out round-robin, one per trade, so concurrent submissions
+/// do not contend on a single fee payer
+pub struct WalletPool {
+    signers: Vec<WalletSigner>,
+    next: AtomicUsize,
+}
+
+impl WalletPool {
+    pub fn new(keypairs: Vec<Keypair>) -> Result<Self> {
+        Self::from_signers(
+            keypairs
+                .into_iter()
+                .map(|keypair| Arc::new(keypair) as WalletSigner)
+                .collect(),
+        )
+    }
+
+    pub fn from_signers(signers: Vec<WalletSigner>) -> Result<Self> {
+        if signers.is_empty() {
+            return Err(BotError::Validation("wallet pool needs at least one keypair".to_string()));
+        }
+        Ok(Self {
+            signers,
+            next: AtomicUsize::new(0),
+        })
+    }
+
+    /// Every wallet configured by `private_key` and `private_keys`, or the
+    /// remote signer alone
+    pub fn from_config(config: &WalletConfig) -> Result<Self> {
+        match config.signer_type {
+            SignerType::Keypair => Self::new(config.keypairs()?),
+            SignerType::Remote => Self::from_signers(vec![load_signer(config)?]),
+        }
+    }
+
+    /// Signer for the next trade
+    pub fn next(&self) -> WalletSigner {
+        let index = self.next.fetch_add(1, Ordering::Relaxed) % self.signers.len();
+        self.signers[index].clone()
+    }
+
+    /// The first configured wallet, which pool data is loaded for
+    pub fn primary(&self) -> &WalletSigner {
+        &self.signers[0]
+    }
+
+    pub fn signers(&self) -> &[WalletSigner] {
+        &self.signers
+    }
+
+    pub fn len(&self) -> usize {
+        self.signers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.signers.is_empty()
+    }
+
+    pub fn pubkeys(&self) -> Vec<Pubkey> {
+        self.signers.iter().map(|signer| signer.pubkey()).collect()
+    }
+}
+
+/// Build the wallet signer selected by `signer_type`
+pub fn load_signer(config: &WalletConfig) -> Result<WalletSigner> {
+    match config.signer_type {
+        SignerType::Keypair => {
+            // The first configured wallet; `WalletPool` rotates through all of them
+            let keypair = config.keypairs()?.into_iter().next().ok_or_else(|| {
+                BotError::Validation("no wallet private key configured".to_string())
+            })?;
+            Ok(Arc::new(keypair))
+        }
+        SignerType::Remote => {
+            let url = config.remote_signer_url.clone().ok_or_else(|| {
+                BotError::Validation("remote_signer_url is required for a remote signer".to_string())
+            })?;
+            let pubkey = config.remote_signer_pubkey.as_deref().ok_or_else(|| {
+                BotError::Validation(
+                    "remote_signer_pubkey is required for a remote signer".to_string(),
+                )
+            })?;
+            let pubkey = Pubkey::from_str(pubkey)?;
+            Ok(Arc::new(RemoteSigner::new(
+                pubkey,
+                Box::new(HttpSigningService::new(url, pubkey)?),
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn remote_signer_runs_on_current_thread_runtime() {
+        // Nothing listens on the discard port, so the request fails rather
+        // than hangs; the point is that signing returns instead of panicking
+        let signer = RemoteSigner::new(
+            Pubkey::new_unique(),
+            Box::new(HttpSigningService::new("http://127.0.0.1:9".to_string(), Pubkey::new_unique()).unwrap()),
+        );
+        let result = signer.try_sign_message(b"message");
+        assert!(matches!(result, Err(SignerError::Custom(_))));
+    }
+
+    #[test]
+    fn wallet_pool_rotates_through_every_wallet() {
+        let keypairs = vec![Keypair::new(), Keypair::new(), Keypair::new()];
+        let pubkeys: Vec<Pubkey> = keypairs.iter().map(|keypair| keypair.pubkey()).collect();
+        let pool = WalletPool::new(keypairs).unwrap();
+
+        let handed_out: Vec<Pubkey> = (0..6).map(|_| pool.next().pubkey()).collect();
+        assert_eq!(handed_out, [pubkeys.clone(), pubkeys.clone()].concat());
+        assert_eq!(pool.primary().pubkey(), pubkeys[0]);
+    }
+
+    #[test]
+    fn wallet_pool_of_one_always_hands_out_the_same_wallet() {
+        let keypair = Keypair::new();
+        let pubkey = keypair.pubkey();
+        let pool = WalletPool::new(vec![keypair]).unwrap();
+
+        assert!((0..3).all(|_| pool.next().pubkey() == pubkey));
+        assert!(WalletPool::new(Vec::new()).is_err());
+    }
+}
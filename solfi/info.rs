@@ -1,6 +1,16 @@
-use anyhow::Result;
+use crate::error::Result;
 use solana_sdk::pubkey::Pubkey;
 
+/// Offsets of the keys in a SolFi market account. SolFi's base mint is the
+/// traded token and its quote mint the base asset, so they decode into the
+/// `token_*` and `base_*` fields respectively.
+const SOLFI_KEY_OFFSETS: [(&str, usize); 4] = [
+    ("token_mint", 2664),
+    ("base_mint", 2696),
+    ("token_vault", 2736),
+    ("base_vault", 2768),
+];
+
 pub struct SolfiInfo {
     pub base_mint: Pubkey,
     pub quote_mint: Pubkey,
@@ -8,24 +18,58 @@ pub struct SolfiInfo {
     pub quote_vault: Pubkey,
 }
 
+/// The keys `parse_pool_account!` decodes from a SolFi market account
+#[derive(Default)]
+struct SolfiKeys {
+    token_mint: Pubkey,
+    base_mint: Pubkey,
+    token_vault: Pubkey,
+    base_vault: Pubkey,
+}
+
 impl SolfiInfo {
     pub fn load_checked(data: &[u8]) -> Result<Self> {
-        let (base_mint, quote_mint, base_vault, quote_vault) = load_pubkeys(data);
+        let keys = crate::parse_pool_account!(data, SolfiKeys, SOLFI_KEY_OFFSETS)?;
 
         Ok(Self {
-            base_mint,
-            quote_mint,
-            base_vault,
-            quote_vault,
+            base_mint: keys.token_mint,
+            quote_mint: keys.base_mint,
+            base_vault: keys.token_vault,
+            quote_vault: keys.base_vault,
         })
     }
 }
 
-fn load_pubkeys(data: &[u8]) -> (Pubkey, Pubkey, Pubkey, Pubkey) {
-    (
-        Pubkey::new_from_array(data[2664..2696].try_into().unwrap()),
-        Pubkey::new_from_array(data[2696..2728].try_into().unwrap()),
-        Pubkey::new_from_array(data[2736..2768].try_into().unwrap()),
-        Pubkey::new_from_array(data[2768..2800].try_into().unwrap()),
-    )
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::BotError;
+
+    #[test]
+    fn load_checked_reads_the_mints_and_vaults() {
+        let mut data = vec![0u8; 2800];
+        let keys: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        for ((_, offset), key) in SOLFI_KEY_OFFSETS.iter().zip(&keys) {
+            data[*offset..offset + 32].copy_from_slice(key.as_ref());
+        }
+
+        let info = SolfiInfo::load_checked(&data).unwrap();
+
+        assert_eq!(
+            [info.base_mint, info.quote_mint, info.base_vault, info.quote_vault],
+            [keys[0], keys[1], keys[2], keys[3]]
+        );
+    }
+
+    #[test]
+    fn truncated_accounts_name_the_missing_field() {
+        let data = vec![0u8; 2790];
+
+        match SolfiInfo::load_checked(&data) {
+            Err(BotError::PoolParse(message)) => {
+                assert!(message.contains("base_vault at offset 2768"), "{}", message)
+            }
+            other => panic!("expected a parse error, got {:?}", other.map(|info| info.base_mint)),
+        }
+    }
 }
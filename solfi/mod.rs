@@ -1,2 +1,5 @@
 pub mod constants;
 pub mod info;
+pub mod unified;
+
+pub use unified::SolfiDex;
@@ -0,0 +1,194 @@
+//! Unified SolFi DEX implementation using the Dex trait
+
+use crate::dex::traits::{
+    validate_swap_amounts, verify_pool_owner, Dex, PoolInfo, PoolReserves, PoolType, PriceInfo,
+};
+use crate::dex::solfi::{constants::solfi_program_id, info::SolfiInfo};
+use crate::dex::vault::{decimal_adjusted_price, fetch_pool_reserves, pool_reserves, MintDecimalsCache};
+use crate::dex::rpc::{blocking, RpcLike};
+use async_trait::async_trait;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use std::sync::Arc;
+use crate::dex_boilerplate;
+use crate::error::{BotError, Result};
+
+/// Smallest SolFi market account that holds the mint and vault keys
+const SOLFI_POOL_MIN_LEN: usize = 2800;
+/// Offsets of the base and quote mints in a SolFi pool account
+const SOLFI_POOL_MINT_OFFSETS: [usize; 2] = [2664, 2696];
+
+#[derive(Clone)]
+pub struct SolfiDex {
+    rpc_client: Arc<dyn RpcLike>,
+    decimals: Arc<MintDecimalsCache>,
+}
+
+#[async_trait]
+impl Dex for SolfiDex {
+    dex_boilerplate!("solfi", solfi_program_id());
+
+    async fn fetch_pools(&self, pool_addresses: &[String], token_mint: &Pubkey) -> Result<Vec<PoolInfo>> {
+        let mut pools = Vec::new();
+
+        for pool_address in pool_addresses {
+            match self.fetch_single_pool(pool_address, token_mint).await {
+                Ok(pool) => pools.push(pool),
+                Err(e) => {
+                    tracing::error!("Failed to fetch SolFi pool {}: {}", pool_address, e);
+                }
+            }
+        }
+
+        Ok(pools)
+    }
+
+    async fn calculate_price(&self, pool_info: &PoolInfo) -> Result<PriceInfo> {
+        let (dex, pool_info) = (self.clone(), pool_info.clone());
+        blocking(move || dex.read_price(&pool_info)).await
+    }
+
+    async fn fetch_reserves(&self, pool_info: &PoolInfo) -> Result<PoolReserves> {
+        let (rpc_client, pool_info) = (self.rpc_client.clone(), pool_info.clone());
+        blocking(move || fetch_pool_reserves(rpc_client.as_ref(), &pool_info)).await
+    }
+
+    fn parse_pool_account(&self, address: &Pubkey, data: &[u8], token_mint: &Pubkey) -> Result<PoolInfo> {
+        let pool_pubkey = *address;
+
+        if data.len() < SOLFI_POOL_MIN_LEN {
+            return Err(BotError::PoolParse(format!(
+                "Invalid data length for SolFi pool: {}",
+                pool_pubkey
+            )));
+        }
+
+        let solfi_info = SolfiInfo::load_checked(data)?;
+
+        let (token_mint_final, base_mint, token_vault, base_vault) = if *token_mint == solfi_info.base_mint {
+            (solfi_info.base_mint, solfi_info.quote_mint, solfi_info.base_vault, solfi_info.quote_vault)
+        } else if *token_mint == solfi_info.quote_mint {
+            (solfi_info.quote_mint, solfi_info.base_mint, solfi_info.quote_vault, solfi_info.base_vault)
+        } else {
+            return Err(BotError::PoolParse(format!(
+                "Mint {} not found in SolFi pool {}",
+                token_mint,
+                pool_pubkey
+            )));
+        };
+
+        Ok(PoolInfo {
+            pool_address: pool_pubkey,
+            token_mint: token_mint_final,
+            base_mint,
+            token_vault,
+            base_vault,
+            fee_wallet: None,
+            additional_accounts: std::collections::HashMap::new(),
+            pool_type: PoolType::Oracle,
+            reserves: None,
+            token_decimals: None,
+            base_decimals: None,
+            token_symbol: None,
+            token_transfer_fee_bps: 0,
+            base_transfer_fee_bps: 0,
+        })
+    }
+
+    fn mint_offsets(&self) -> &'static [usize] {
+        &SOLFI_POOL_MINT_OFFSETS
+    }
+
+    fn get_swap_instruction_data(&self, _pool_info: &PoolInfo, amount_in: u64, minimum_out: u64) -> Result<Vec<u8>> {
+        validate_swap_amounts(amount_in, minimum_out)?;
+
+        Err(BotError::Validation("SolFi swap encoding not implemented".to_string()))
+    }
+}
+
+impl SolfiDex {
+    pub fn new(rpc_client: Arc<dyn RpcLike>) -> Self {
+        Self {
+            decimals: Arc::new(MintDecimalsCache::new(rpc_client.clone())),
+            rpc_client,
+        }
+    }
+
+    /// `calculate_price`, blocking on the account reads
+    fn read_price(&self, pool_info: &PoolInfo) -> Result<PriceInfo> {
+        // SolFi quotes from its own curve, so the reserve ratio is only an
+        // approximation of the executable price
+        let (token_reserve, base_reserve) = pool_reserves(self.rpc_client.as_ref(), pool_info)?;
+        let (token_decimals, base_decimals) =
+            self.decimals.for_pool(pool_info)?;
+
+        let price = decimal_adjusted_price(token_reserve, base_reserve, token_decimals, base_decimals)
+            .ok_or_else(|| {
+                BotError::PriceCalculation(format!("SolFi pool {} has an empty token vault", pool_info.pool_address))
+            })?;
+
+        Ok(pool_info.with_transfer_fees(PriceInfo {
+            price,
+            liquidity: base_reserve,
+            fee: 0.0, // Spread is built into the curve rather than charged separately
+        }))
+    }
+
+    async fn fetch_single_pool(&self, pool_address: &str, token_mint: &Pubkey) -> Result<PoolInfo> {
+        let (dex, pool_address, token_mint) = (self.clone(), pool_address.to_string(), *token_mint);
+        blocking(move || dex.read_single_pool(&pool_address, &token_mint)).await
+    }
+
+    fn read_single_pool(&self, pool_address: &str, token_mint: &Pubkey) -> Result<PoolInfo> {
+        let pool_pubkey = Pubkey::from_str(pool_address)?;
+        let account = self.rpc_client.get_account(&pool_pubkey)?;
+
+        verify_pool_owner(&account, &solfi_program_id())?;
+
+        let mut pool_info = self.parse_pool_account(&pool_pubkey, &account.data, token_mint)?;
+        self.decimals.fill(&mut pool_info)?;
+        Ok(pool_info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::rpc::MockRpc;
+
+    fn dex() -> SolfiDex {
+        SolfiDex::new(Arc::new(MockRpc::new()))
+    }
+
+    #[test]
+    fn parsed_pools_are_oracle_markets() {
+        let mut data = vec![0u8; SOLFI_POOL_MIN_LEN];
+        let base_mint = Pubkey::new_unique();
+        let quote_mint = Pubkey::new_unique();
+        data[2664..2696].copy_from_slice(base_mint.as_ref());
+        data[2696..2728].copy_from_slice(quote_mint.as_ref());
+
+        let pool = dex().parse_pool_account(&Pubkey::new_unique(), &data, &base_mint).unwrap();
+
+        assert_eq!(pool.pool_type, PoolType::Oracle);
+        assert_eq!(pool.base_mint, quote_mint);
+    }
+
+    #[tokio::test]
+    async fn oracle_pools_are_not_quoted_with_constant_product() {
+        let pool = PoolInfo::mock(PoolType::Oracle);
+        let reserves = PoolReserves { token_reserve: 1_000_000, base_reserve: 1_000_000, slot: 0 };
+
+        assert!(matches!(dex().quote(&pool, 1_000, &reserves).await, Err(BotError::Dex(_))));
+    }
+
+    #[test]
+    fn swap_encoding_is_refused() {
+        let pool = PoolInfo::mock(PoolType::Oracle);
+
+        assert!(matches!(
+            dex().get_swap_instruction_data(&pool, 1_000, 1),
+            Err(BotError::Validation(_))
+        ));
+    }
+}
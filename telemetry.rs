@@ -0,0 +1,80 @@
+//! Tracing subscriber setup, with optional OpenTelemetry export
+
+use crate::config::TelemetryConfig;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+
+/// Flushes exported spans when dropped. Hold it for the lifetime of `main`.
+pub struct TelemetryGuard {
+    #[cfg(feature = "otel")]
+    otel_enabled: bool,
+}
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        #[cfg(feature = "otel")]
+        if self.otel_enabled {
+            opentelemetry::global::shutdown_tracer_provider();
+        }
+    }
+}
+
+/// Install the global tracing subscriber. When built with the `otel` feature
+/// and a `[telemetry]` section is configured, spans are also exported to the
+/// OTLP collector; otherwise no OpenTelemetry layer is installed at all.
+pub fn init(config: Option<&TelemetryConfig>) -> TelemetryGuard {
+    let fmt_layer = tracing_subscriber::fmt::layer().with_line_number(true);
+    let registry = tracing_subscriber::registry()
+        .with(EnvFilter::from_default_env())
+        .with(fmt_layer);
+
+    #[cfg(feature = "otel")]
+    {
+        if let Some(config) = config.filter(|c| c.enabled) {
+            match otel_layer(config) {
+                Ok(layer) => {
+                    registry.with(layer).init();
+                    return TelemetryGuard { otel_enabled: true };
+                }
+                Err(e) => eprintln!("Failed to start OpenTelemetry export: {}", e),
+            }
+        }
+        registry.init();
+        TelemetryGuard { otel_enabled: false }
+    }
+
+    #[cfg(not(feature = "otel"))]
+    {
+        if config.is_some_and(|c| c.enabled) {
+            eprintln!("Telemetry is configured but the bot was built without the `otel` feature");
+        }
+        registry.init();
+        TelemetryGuard {}
+    }
+}
+
+#[cfg(feature = "otel")]
+fn otel_layer<S>(
+    config: &TelemetryConfig,
+) -> anyhow::Result<tracing_opentelemetry::OpenTelemetryLayer<S, opentelemetry_sdk::trace::Tracer>>
+where
+    S: tracing::Subscriber + for<'span> tracing_subscriber::registry::LookupSpan<'span>,
+{
+    use opentelemetry::KeyValue;
+    use opentelemetry_otlp::WithExportConfig;
+    use opentelemetry_sdk::{trace, Resource};
+
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(config.otlp_endpoint.clone()),
+        )
+        .with_trace_config(trace::config().with_resource(Resource::new(vec![KeyValue::new(
+            "service.name",
+            config.service_name.clone(),
+        )])))
+        .install_batch(opentelemetry_sdk::runtime::Tokio)?;
+
+    Ok(tracing_opentelemetry::layer().with_tracer(tracer))
+}
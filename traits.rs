@@ -1,9 +1,38 @@
 //! Unified DEX trait system for eliminating repetitive code across DEX implementations
 
 use async_trait::async_trait;
-use solana_sdk::pubkey::Pubkey;
+use solana_sdk::{account::Account, instruction::Instruction, pubkey::Pubkey};
 use std::collections::HashMap;
-use anyhow::Result;
+use crate::error::{BotError, Result};
+
+/// Pricing model of a pool, independent of which DEX operates it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PoolType {
+    /// x * y = k over the full price range
+    ConstantProduct,
+    /// Liquidity concentrated in ticks or bins around the current price
+    ConcentratedLiquidity,
+    /// Price set by a curve over virtual reserves
+    BondingCurve,
+    /// Proactive market maker quoting from its own oracle-driven curve; vault
+    /// balances are inventory, not the price
+    Oracle,
+}
+
+impl PoolType {
+    /// Spot price of the token in base units from reserves, for models where
+    /// reserves alone determine the price. Concentrated liquidity needs the
+    /// pool's current sqrt price or active bin instead, and oracle markets
+    /// price off-chain, so both return `None`.
+    pub fn spot_price(&self, token_reserve: u64, base_reserve: u64) -> Option<f64> {
+        match self {
+            PoolType::ConstantProduct | PoolType::BondingCurve if token_reserve > 0 => {
+                Some(base_reserve as f64 / token_reserve as f64)
+            }
+            _ => None,
+        }
+    }
+}
 
 /// Common pool information that all DEXes must provide
 #[derive(Debug, Clone)]
@@ -15,6 +44,124 @@ pub struct PoolInfo {
     pub base_vault: Pubkey,
     pub fee_wallet: Option<Pubkey>,
     pub additional_accounts: HashMap<String, Pubkey>,
+    pub pool_type: PoolType,
+    /// (token_reserve, base_reserve) snapshot, when the decoder has read the vaults
+    pub reserves: Option<(u64, u64)>,
+    /// Mint decimals, once known, so pricing does not re-read the mints
+    pub token_decimals: Option<u8>,
+    pub base_decimals: Option<u8>,
+    /// Token symbol from Metaplex metadata, for logs
+    pub token_symbol: Option<String>,
+    /// Token-2022 transfer fee of each mint in basis points, 0 when none
+    pub token_transfer_fee_bps: u16,
+    pub base_transfer_fee_bps: u16,
+}
+
+impl PoolInfo {
+    /// A pool with fresh random addresses, 6-decimal tokens against a
+    /// 9-decimal base and nothing else set, for tests to adjust
+    #[cfg(test)]
+    pub(crate) fn mock(pool_type: PoolType) -> Self {
+        Self {
+            pool_address: Pubkey::new_unique(),
+            token_mint: Pubkey::new_unique(),
+            base_mint: Pubkey::new_unique(),
+            token_vault: Pubkey::new_unique(),
+            base_vault: Pubkey::new_unique(),
+            fee_wallet: None,
+            additional_accounts: HashMap::new(),
+            pool_type,
+            reserves: None,
+            token_decimals: Some(6),
+            base_decimals: Some(9),
+            token_symbol: None,
+            token_transfer_fee_bps: 0,
+            base_transfer_fee_bps: 0,
+        }
+    }
+
+    /// The same pool seen from the other side, for building the opposite leg
+    pub fn reversed(&self) -> Self {
+        Self {
+            token_mint: self.base_mint,
+            base_mint: self.token_mint,
+            token_vault: self.base_vault,
+            base_vault: self.token_vault,
+            reserves: self.reserves.map(|(token, base)| (base, token)),
+            token_decimals: self.base_decimals,
+            base_decimals: self.token_decimals,
+            token_symbol: None,
+            token_transfer_fee_bps: self.base_transfer_fee_bps,
+            base_transfer_fee_bps: self.token_transfer_fee_bps,
+            ..self.clone()
+        }
+    }
+
+    /// `(token_decimals, base_decimals)` when both are known
+    pub fn decimals(&self) -> Option<(u8, u8)> {
+        self.token_decimals.zip(self.base_decimals)
+    }
+
+    /// Base tokens that reach the pool when selling `amount_in`, after the
+    /// base mint's transfer fee
+    pub fn amount_in_after_transfer_fee(&self, amount_in: u64) -> u64 {
+        crate::dex::math::deduct_transfer_fee(amount_in, self.base_transfer_fee_bps)
+    }
+
+    /// Tokens that reach the seller out of a pool output of `amount_out`,
+    /// after the token mint's transfer fee
+    pub fn amount_out_after_transfer_fee(&self, amount_out: u64) -> u64 {
+        crate::dex::math::deduct_transfer_fee(amount_out, self.token_transfer_fee_bps)
+    }
+
+    /// `price_info` with both mints' transfer fees folded into its fee, so a
+    /// round trip priced from it pays them too
+    pub fn with_transfer_fees(&self, price_info: PriceInfo) -> PriceInfo {
+        let keep = (1.0 - self.token_transfer_fee_bps as f64 / 10_000.0)
+            * (1.0 - self.base_transfer_fee_bps as f64 / 10_000.0);
+        PriceInfo {
+            fee: 1.0 - (1.0 - price_info.fee) * keep,
+            ..price_info
+        }
+    }
+
+    /// Look up a DEX-specific account the decoder stored on the pool
+    pub fn additional_account(&self, name: &str) -> Result<Pubkey> {
+        self.additional_accounts.get(name).copied().ok_or_else(|| {
+            BotError::PoolParse(format!(
+                "Pool {} is missing the {} account",
+                self.pool_address, name
+            ))
+        })
+    }
+}
+
+/// Vault balances of a pool as of `slot`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolReserves {
+    pub token_reserve: u64,
+    pub base_reserve: u64,
+    pub slot: u64,
+}
+
+impl PoolReserves {
+    /// Slots elapsed since the reserves were read
+    pub fn age(&self, current_slot: u64) -> u64 {
+        current_slot.saturating_sub(self.slot)
+    }
+
+    /// Reject reserves read more than `max_age_slots` before `current_slot`,
+    /// since a quote against them no longer reflects the pool
+    pub fn ensure_fresh(&self, current_slot: u64, max_age_slots: u64) -> Result<()> {
+        let age = self.age(current_slot);
+        if age > max_age_slots {
+            return Err(BotError::Validation(format!(
+                "Reserves from slot {} are {} slots old (max {})",
+                self.slot, age, max_age_slots
+            )));
+        }
+        Ok(())
+    }
 }
 
 /// Price information for a token pair
@@ -25,6 +172,22 @@ pub struct PriceInfo {
     pub fee: f64,
 }
 
+/// Features a DEX supports, for generic routing and transaction decisions
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DexCapabilities {
+    /// Pools may hold Token-2022 mints
+    pub supports_token_2022: bool,
+    /// Pools concentrate liquidity in ticks or bins
+    pub concentrated_liquidity: bool,
+    /// Swaps must pass an oracle account
+    pub needs_oracle: bool,
+    /// Pools can lend the base token for a flashloan
+    pub flashloan_source: bool,
+    /// `build_swap_instruction` encodes swaps, so routes may pass through
+    /// these pools. Price-only adapters leave it unset.
+    pub supports_swap: bool,
+}
+
 /// Unified DEX trait that all DEX implementations must satisfy
 #[async_trait]
 pub trait Dex: Send + Sync {
@@ -40,8 +203,150 @@ pub trait Dex: Send + Sync {
     /// Calculate price for a specific pool
     async fn calculate_price(&self, pool_info: &PoolInfo) -> Result<PriceInfo>;
 
+    /// Read both vault balances of a pool in one call, with the slot they
+    /// were observed at
+    async fn fetch_reserves(&self, pool_info: &PoolInfo) -> Result<PoolReserves>;
+
+    /// Parse an already fetched pool account, orienting it around `token_mint`.
+    /// Callers are responsible for checking the account owner.
+    fn parse_pool_account(&self, address: &Pubkey, data: &[u8], token_mint: &Pubkey) -> Result<PoolInfo>;
+
+    /// Byte offsets of the two mint fields in this DEX's pool accounts, for
+    /// finding pools with `getProgramAccounts` memcmp filters. Empty when its
+    /// pools can't be discovered that way.
+    fn mint_offsets(&self) -> &'static [usize] {
+        &[]
+    }
+
     /// Get swap instruction data (DEX-specific)
     fn get_swap_instruction_data(&self, pool_info: &PoolInfo, amount_in: u64, minimum_out: u64) -> Result<Vec<u8>>;
+
+    /// Build the complete swap instruction, accounts included, that sells
+    /// `amount_in` of `pool_info.base_mint` for `pool_info.token_mint` from
+    /// `user`'s associated token accounts. Build the opposite leg from
+    /// `PoolInfo::reversed`.
+    fn build_swap_instruction(
+        &self,
+        pool_info: &PoolInfo,
+        _user: &Pubkey,
+        _amount_in: u64,
+        _minimum_out: u64,
+    ) -> Result<Instruction> {
+        Err(BotError::Dex(format!(
+            "{} cannot build swap instructions for pool {}",
+            self.name(),
+            pool_info.pool_address
+        )))
+    }
+
+    /// Whether swaps on this DEX must pass a protocol fee wallet account
+    fn requires_fee_wallet(&self) -> bool {
+        false
+    }
+
+    /// Feature flags for this DEX
+    fn capabilities(&self) -> DexCapabilities {
+        DexCapabilities::default()
+    }
+
+    /// Smallest base-token `amount_in` that yields a nonzero output after fees
+    /// for the pool's current reserves. Inputs below this quote to zero.
+    fn min_viable_input(&self, _pool_info: &PoolInfo) -> u64 {
+        1
+    }
+
+    /// Exact `token_mint` output for selling `amount_in` of `base_mint`.
+    ///
+    /// The default prices reserve-based pools with the constant-product
+    /// formula against `reserves` and the fee reported by `calculate_price`.
+    /// Token-2022 transfer fees on either mint are deducted from the result.
+    /// Concentrated liquidity DEXes override this with tick math and read
+    /// their own pool state rather than `reserves`; oracle markets are not
+    /// quoted.
+    async fn quote(&self, pool_info: &PoolInfo, amount_in: u64, reserves: &PoolReserves) -> Result<u64> {
+        match pool_info.pool_type {
+            PoolType::ConstantProduct | PoolType::BondingCurve => {
+                let fee = self.calculate_price(pool_info).await?.fee;
+                let fee_bps = (fee * 10_000.0).round() as u16;
+                let amount_out = crate::dex::math::quote_constant_product(
+                    reserves.base_reserve,
+                    reserves.token_reserve,
+                    pool_info.amount_in_after_transfer_fee(amount_in),
+                    fee_bps,
+                )?;
+                Ok(pool_info.amount_out_after_transfer_fee(amount_out))
+            }
+            PoolType::ConcentratedLiquidity => Err(BotError::Dex(format!(
+                "{} does not quote concentrated liquidity pool {}",
+                self.name(),
+                pool_info.pool_address
+            ))),
+            PoolType::Oracle => Err(BotError::Dex(format!(
+                "{} does not quote oracle pool {}",
+                self.name(),
+                pool_info.pool_address
+            ))),
+        }
+    }
+}
+
+/// Resolve the fee wallet to use when building swap accounts for a pool.
+///
+/// DEXes that don't take a fee wallet yield `Ok(None)`; a DEX that requires
+/// one but whose decoder produced none is an error rather than a default key.
+pub fn fee_wallet_for(dex: &dyn Dex, pool_info: &PoolInfo) -> Result<Option<Pubkey>> {
+    match pool_info.fee_wallet {
+        None if dex.requires_fee_wallet() => Err(BotError::Transaction(format!(
+            "{} pool {} requires a fee wallet but none was decoded",
+            dex.name(),
+            pool_info.pool_address
+        ))),
+        fee_wallet => Ok(fee_wallet),
+    }
+}
+
+/// Default cap on the entries a decoder may add to `PoolInfo::additional_accounts`
+pub const DEFAULT_MAX_ADDITIONAL_ACCOUNTS: usize = 8;
+
+/// Reject pools whose decoder produced more additional accounts than `max`,
+/// which would otherwise bloat the swap transaction
+pub fn check_additional_accounts(pool_info: &PoolInfo, max: usize) -> Result<()> {
+    if pool_info.additional_accounts.len() > max {
+        return Err(BotError::PoolParse(format!(
+            "Pool {} has {} additional accounts (max {})",
+            pool_info.pool_address,
+            pool_info.additional_accounts.len(),
+            max
+        )));
+    }
+    Ok(())
+}
+
+/// Reject accounts not owned by the DEX program, which would otherwise decode
+/// into a `PoolInfo` of garbage
+pub fn verify_pool_owner(account: &Account, expected: &Pubkey) -> Result<()> {
+    if account.owner != *expected {
+        return Err(BotError::Validation(format!(
+            "Pool account is owned by {}, expected {}",
+            account.owner, expected
+        )));
+    }
+    Ok(())
+}
+
+/// Reject degenerate swap amounts before any instruction is encoded
+pub fn validate_swap_amounts(amount_in: u64, minimum_out: u64) -> Result<()> {
+    if amount_in == 0 {
+        return Err(BotError::Validation(
+            "amount_in must be greater than zero".to_string(),
+        ));
+    }
+    if minimum_out == 0 {
+        return Err(BotError::Validation(
+            "minimum_out must be greater than zero".to_string(),
+        ));
+    }
+    Ok(())
 }
 
 /// Registry for managing all DEX implementations
@@ -49,6 +354,12 @@ pub struct DexRegistry {
     dexes: HashMap<&'static str, Box<dyn Dex>>,
 }
 
+impl Default for DexRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl DexRegistry {
     pub fn new() -> Self {
         Self {
@@ -69,24 +380,116 @@ impl DexRegistry {
     }
 }
 
-/// Macro for generating common DEX boilerplate
+/// Macro for generating common DEX boilerplate: the `name` and `program_id`
+/// methods, invoked inside the `impl Dex` block
 #[macro_export]
 macro_rules! dex_boilerplate {
-    ($struct_name:ident, $name:expr, $program_id:expr) => {
-        impl $struct_name {
-            pub fn new() -> Self {
-                Self
-            }
+    ($name:expr, $program_id:expr) => {
+        fn name(&self) -> &'static str {
+            $name
         }
 
-        impl Dex for $struct_name {
-            fn name(&self) -> &'static str {
-                $name
-            }
+        fn program_id(&self) -> Pubkey {
+            $program_id
+        }
+    };
+}
 
-            fn program_id(&self) -> Pubkey {
-                $program_id
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::{pump::unified::PumpDex, raydium::unified::RaydiumDex, rpc::MockRpc};
+    use std::sync::Arc;
+
+    #[test]
+    fn raydium_and_pump_report_their_own_capabilities() {
+        let rpc = Arc::new(MockRpc::new());
+        let dexes: Vec<Box<dyn Dex>> = vec![Box::new(RaydiumDex::new(rpc.clone())), Box::new(PumpDex::new(rpc))];
+
+        // AMM v4 only takes SPL Token mints; Pump pools may hold Token-2022
+        let swap_only = DexCapabilities {
+            supports_swap: true,
+            ..DexCapabilities::default()
+        };
+        assert_eq!(dexes[0].capabilities(), swap_only);
+        assert_eq!(
+            dexes[1].capabilities(),
+            DexCapabilities {
+                supports_token_2022: true,
+                ..swap_only
             }
+        );
+        assert!(dexes.iter().all(|dex| {
+            let capabilities = dex.capabilities();
+            !capabilities.concentrated_liquidity && !capabilities.needs_oracle && !capabilities.flashloan_source
+        }));
+    }
+
+    #[test]
+    fn a_missing_fee_wallet_is_only_an_error_where_one_is_required() {
+        let rpc = Arc::new(MockRpc::new());
+        let (raydium, pump) = (RaydiumDex::new(rpc.clone()), PumpDex::new(rpc));
+        let mut pool = PoolInfo::mock(PoolType::ConstantProduct);
+        pool.fee_wallet = None;
+
+        assert_eq!(fee_wallet_for(&raydium, &pool).unwrap(), None);
+        assert!(matches!(fee_wallet_for(&pump, &pool), Err(BotError::Transaction(_))));
+
+        let fee_wallet = Pubkey::new_unique();
+        pool.fee_wallet = Some(fee_wallet);
+        assert_eq!(fee_wallet_for(&pump, &pool).unwrap(), Some(fee_wallet));
+        assert_eq!(fee_wallet_for(&raydium, &pool).unwrap(), Some(fee_wallet));
+    }
+
+    #[test]
+    fn additional_accounts_beyond_the_cap_are_rejected() {
+        let mut pool = PoolInfo::mock(PoolType::ConstantProduct);
+        pool.additional_accounts.clear();
+        for i in 0..DEFAULT_MAX_ADDITIONAL_ACCOUNTS {
+            pool.additional_accounts.insert(format!("account_{}", i), Pubkey::new_unique());
         }
-    };
+        assert!(check_additional_accounts(&pool, DEFAULT_MAX_ADDITIONAL_ACCOUNTS).is_ok());
+
+        pool.additional_accounts.insert("one_too_many".to_string(), Pubkey::new_unique());
+        assert!(matches!(
+            check_additional_accounts(&pool, DEFAULT_MAX_ADDITIONAL_ACCOUNTS),
+            Err(BotError::PoolParse(_))
+        ));
+    }
+
+    #[test]
+    fn validate_swap_amounts_rejects_zero_input() {
+        assert!(matches!(validate_swap_amounts(0, 1), Err(BotError::Validation(_))));
+    }
+
+    #[test]
+    fn validate_swap_amounts_rejects_zero_minimum_out() {
+        assert!(matches!(validate_swap_amounts(1, 0), Err(BotError::Validation(_))));
+    }
+
+    #[test]
+    fn validate_swap_amounts_accepts_positive_amounts() {
+        assert!(validate_swap_amounts(1, 1).is_ok());
+        assert!(validate_swap_amounts(u64::MAX, u64::MAX).is_ok());
+    }
+
+    #[test]
+    fn oracle_pools_have_no_reserve_spot_price() {
+        assert_eq!(PoolType::Oracle.spot_price(100, 200), None);
+        assert_eq!(PoolType::ConstantProduct.spot_price(100, 200), Some(2.0));
+    }
+
+    #[test]
+    fn ensure_fresh_allows_reserves_up_to_the_slot_lag() {
+        let reserves = PoolReserves {
+            token_reserve: 1,
+            base_reserve: 1,
+            slot: 100,
+        };
+        assert!(reserves.ensure_fresh(100, 0).is_ok());
+        assert!(reserves.ensure_fresh(110, 10).is_ok());
+        assert!(matches!(reserves.ensure_fresh(111, 10), Err(BotError::Validation(_))));
+        // Reserves read after the caller's slot are not stale
+        assert!(reserves.ensure_fresh(90, 0).is_ok());
+    }
 }
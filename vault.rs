@@ -0,0 +1,328 @@
+//! Vault balance and mint decimals reads shared by reserve-priced DEXes
+
+use crate::error::{BotError, Result};
+use solana_sdk::pubkey::Pubkey;
+use std::{collections::HashMap, sync::Arc, sync::Mutex};
+
+use crate::dex::{
+    rpc::RpcLike,
+    traits::{PoolInfo, PoolReserves},
+};
+
+/// Offset of the `amount` field in an SPL Token / Token-2022 account
+const TOKEN_ACCOUNT_AMOUNT_OFFSET: usize = 64;
+
+/// Offset of the `decimals` field in an SPL Token / Token-2022 mint
+const MINT_DECIMALS_OFFSET: usize = 44;
+
+/// Token-2022 extensions start after the account type byte, which sits right
+/// after a base-account-sized region so mints and token accounts line up
+const EXTENSIONS_OFFSET: usize = 165 + 1;
+/// `ExtensionType::TransferFeeConfig`
+const TRANSFER_FEE_CONFIG_EXTENSION: u16 = 1;
+/// Mint extensions that require an account extension on every token
+/// account of the mint, with the size of that account extension's data:
+/// `TransferFeeConfig`, `NonTransferable`, `TransferHook`,
+/// `ConfidentialTransferFeeConfig` and `Pausable`
+const REQUIRED_ACCOUNT_EXTENSIONS: [(u16, usize); 5] = [(1, 8), (9, 0), (14, 1), (16, 64), (26, 0)];
+/// Size of an SPL token account, which Token-2022 accounts extend
+const TOKEN_ACCOUNT_LEN: usize = 165;
+/// Size of a TLV extension header: a u16 type and a u16 length
+const EXTENSION_HEADER_LEN: usize = 4;
+/// Offsets of `transfer_fee_basis_points` in the older and newer `TransferFee`
+/// of a `TransferFeeConfig`: two authorities and the withheld amount come
+/// first, and each `TransferFee` is an epoch, a maximum fee, then the rate
+const OLDER_TRANSFER_FEE_BPS_OFFSET: usize = 32 + 32 + 8 + 8 + 8;
+const NEWER_TRANSFER_FEE_BPS_OFFSET: usize = OLDER_TRANSFER_FEE_BPS_OFFSET + 2 + 8 + 8;
+
+pub fn token_account_amount(data: &[u8]) -> Result<u64> {
+    let bytes = data
+        .get(TOKEN_ACCOUNT_AMOUNT_OFFSET..TOKEN_ACCOUNT_AMOUNT_OFFSET + 8)
+        .ok_or_else(|| BotError::Parse("Invalid data length for token account".to_string()))?;
+    Ok(u64::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+pub fn mint_decimals(data: &[u8]) -> Result<u8> {
+    data.get(MINT_DECIMALS_OFFSET)
+        .copied()
+        .ok_or_else(|| BotError::Parse("Invalid data length for mint account".to_string()))
+}
+
+/// Transfer fee of a Token-2022 mint in basis points, or 0 for mints without
+/// a `TransferFeeConfig`. When a fee change is scheduled the higher of the old
+/// and new rates is used, so the fee is never underestimated around the
+/// epoch it takes effect.
+pub fn transfer_fee_bps(data: &[u8]) -> u16 {
+    let mut offset = EXTENSIONS_OFFSET;
+    while let Some(header) = data.get(offset..offset + 4) {
+        let extension_type = u16::from_le_bytes([header[0], header[1]]);
+        let length = u16::from_le_bytes([header[2], header[3]]) as usize;
+        let value = &data[offset + 4..(offset + 4 + length).min(data.len())];
+        if extension_type == TRANSFER_FEE_CONFIG_EXTENSION {
+            let rate = |at: usize| value.get(at..at + 2).map_or(0, |b| u16::from_le_bytes([b[0], b[1]]));
+            return rate(OLDER_TRANSFER_FEE_BPS_OFFSET).max(rate(NEWER_TRANSFER_FEE_BPS_OFFSET));
+        }
+        offset += 4 + length;
+    }
+    0
+}
+
+/// Size of a Token-2022 associated token account for a mint with the given
+/// data: the base account and its account-type byte, the `ImmutableOwner`
+/// extension every ATA gets, and the account extensions the mint's own
+/// extensions require
+pub fn token_2022_ata_len(mint_data: &[u8]) -> usize {
+    let mut len = TOKEN_ACCOUNT_LEN + 1 + EXTENSION_HEADER_LEN;
+    let mut offset = EXTENSIONS_OFFSET;
+    while let Some(header) = mint_data.get(offset..offset + EXTENSION_HEADER_LEN) {
+        let extension_type = u16::from_le_bytes([header[0], header[1]]);
+        let length = u16::from_le_bytes([header[2], header[3]]) as usize;
+        if let Some((_, account_len)) = REQUIRED_ACCOUNT_EXTENSIONS.iter().find(|(t, _)| *t == extension_type) {
+            len += EXTENSION_HEADER_LEN + account_len;
+        }
+        offset += EXTENSION_HEADER_LEN + length;
+    }
+    len
+}
+
+/// Read `(token_reserve, base_reserve)` for a pool, preferring the snapshot
+/// already on the `PoolInfo` over a fresh vault fetch
+pub fn pool_reserves(rpc_client: &dyn RpcLike, pool_info: &PoolInfo) -> Result<(u64, u64)> {
+    if let Some(reserves) = pool_info.reserves {
+        return Ok(reserves);
+    }
+
+    let accounts = rpc_client.get_multiple_accounts(&[pool_info.token_vault, pool_info.base_vault])?;
+    match accounts.as_slice() {
+        [Some(token_vault), Some(base_vault)] => Ok((
+            token_account_amount(&token_vault.data)?,
+            token_account_amount(&base_vault.data)?,
+        )),
+        _ => Err(BotError::AccountFetch(format!(
+            "Missing vault account for pool {}",
+            pool_info.pool_address
+        ))),
+    }
+}
+
+/// Read both vaults of a pool in one call, recording the slot the balances
+/// were observed at
+pub fn fetch_pool_reserves(rpc_client: &dyn RpcLike, pool_info: &PoolInfo) -> Result<PoolReserves> {
+    let response = rpc_client.get_multiple_accounts_with_commitment(
+        &[pool_info.token_vault, pool_info.base_vault],
+        rpc_client.commitment(),
+    )?;
+    match response.value.as_slice() {
+        [Some(token_vault), Some(base_vault)] => Ok(PoolReserves {
+            token_reserve: token_account_amount(&token_vault.data)?,
+            base_reserve: token_account_amount(&base_vault.data)?,
+            slot: response.context.slot,
+        }),
+        _ => Err(BotError::AccountFetch(format!(
+            "Missing vault account for pool {}",
+            pool_info.pool_address
+        ))),
+    }
+}
+
+/// Spot price of one whole token in whole base units
+pub fn decimal_adjusted_price(
+    token_reserve: u64,
+    base_reserve: u64,
+    token_decimals: u8,
+    base_decimals: u8,
+) -> Option<f64> {
+    if token_reserve == 0 {
+        return None;
+    }
+    let raw = base_reserve as f64 / token_reserve as f64;
+    Some(raw * 10f64.powi(token_decimals as i32 - base_decimals as i32))
+}
+
+/// Mint decimals, fetched once per mint and kept for the life of the process,
+/// along with each mint's transfer fee and owning token program
+pub struct MintDecimalsCache {
+    rpc_client: Arc<dyn RpcLike>,
+    decimals: Mutex<HashMap<Pubkey, u8>>,
+    transfer_fees: Mutex<HashMap<Pubkey, u16>>,
+    token_programs: Mutex<HashMap<Pubkey, Pubkey>>,
+}
+
+impl MintDecimalsCache {
+    pub fn new(rpc_client: Arc<dyn RpcLike>) -> Self {
+        Self {
+            rpc_client,
+            decimals: Mutex::new(HashMap::new()),
+            transfer_fees: Mutex::new(HashMap::new()),
+            token_programs: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn get(&self, mint: &Pubkey) -> Result<u8> {
+        if let Some(decimals) = self.decimals.lock().unwrap().get(mint) {
+            return Ok(*decimals);
+        }
+
+        Ok(self.load(mint)?.0)
+    }
+
+    /// Token program owning `mint`, SPL Token or Token-2022, fetched with its
+    /// decimals when not yet cached
+    pub fn token_program(&self, mint: &Pubkey) -> Result<Pubkey> {
+        if let Some(program) = self.token_programs.lock().unwrap().get(mint) {
+            return Ok(*program);
+        }
+        Ok(self.load(mint)?.1)
+    }
+
+    /// Fetch a mint account and cache everything read from it
+    fn load(&self, mint: &Pubkey) -> Result<(u8, Pubkey)> {
+        let account = self.rpc_client.get_account(mint)?;
+        let decimals = mint_decimals(&account.data)?;
+        self.decimals.lock().unwrap().insert(*mint, decimals);
+        self.transfer_fees
+            .lock()
+            .unwrap()
+            .insert(*mint, transfer_fee_bps(&account.data));
+        self.token_programs.lock().unwrap().insert(*mint, account.owner);
+        Ok((decimals, account.owner))
+    }
+
+    /// Transfer fee of a mint read alongside its decimals, 0 if not yet fetched
+    pub fn transfer_fee_bps(&self, mint: &Pubkey) -> u16 {
+        self.transfer_fees.lock().unwrap().get(mint).copied().unwrap_or(0)
+    }
+
+    /// Decimals for both mints of a pool, fetching only the ones not yet cached
+    pub fn get_pair(&self, token_mint: &Pubkey, base_mint: &Pubkey) -> Result<(u8, u8)> {
+        let missing: Vec<Pubkey> = {
+            let cache = self.decimals.lock().unwrap();
+            [*token_mint, *base_mint]
+                .into_iter()
+                .filter(|mint| !cache.contains_key(mint))
+                .collect()
+        };
+
+        if !missing.is_empty() {
+            let accounts = self.rpc_client.get_multiple_accounts(&missing)?;
+            let mut cache = self.decimals.lock().unwrap();
+            let mut transfer_fees = self.transfer_fees.lock().unwrap();
+            let mut token_programs = self.token_programs.lock().unwrap();
+            for (mint, account) in missing.iter().zip(accounts) {
+                let account =
+                    account.ok_or_else(|| BotError::AccountFetch(format!("Mint account {} not found", mint)))?;
+                cache.insert(*mint, mint_decimals(&account.data)?);
+                transfer_fees.insert(*mint, transfer_fee_bps(&account.data));
+                token_programs.insert(*mint, account.owner);
+            }
+        }
+
+        Ok((self.get(token_mint)?, self.get(base_mint)?))
+    }
+
+    /// Decimals for a pool, using those already on the `PoolInfo` when set
+    pub fn for_pool(&self, pool_info: &PoolInfo) -> Result<(u8, u8)> {
+        match pool_info.decimals() {
+            Some(decimals) => Ok(decimals),
+            None => self.get_pair(&pool_info.token_mint, &pool_info.base_mint),
+        }
+    }
+
+    /// Record both mints' decimals and transfer fees on a freshly parsed pool
+    pub fn fill(&self, pool_info: &mut PoolInfo) -> Result<()> {
+        let (token_decimals, base_decimals) = self.get_pair(&pool_info.token_mint, &pool_info.base_mint)?;
+        pool_info.token_decimals = Some(token_decimals);
+        pool_info.base_decimals = Some(base_decimals);
+        pool_info.token_transfer_fee_bps = self.transfer_fee_bps(&pool_info.token_mint);
+        pool_info.base_transfer_fee_bps = self.transfer_fee_bps(&pool_info.base_mint);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chain::constants::token_2022_program_id;
+    use crate::dex::{rpc::MockRpc, traits::PoolType};
+    use solana_sdk::account::Account;
+
+    /// Token-2022 mint data carrying the given `(type, length)` extensions
+    fn mint_with_extensions(extensions: &[(u16, usize)]) -> Vec<u8> {
+        let mut data = vec![0u8; EXTENSIONS_OFFSET];
+        for (extension_type, length) in extensions {
+            data.extend_from_slice(&extension_type.to_le_bytes());
+            data.extend_from_slice(&(*length as u16).to_le_bytes());
+            data.extend(std::iter::repeat_n(0, *length));
+        }
+        data
+    }
+
+    /// Token-2022 mint with `decimals` and a `TransferFeeConfig` whose older
+    /// and newer rates are `older_bps` and `newer_bps`
+    fn fee_bearing_mint(decimals: u8, older_bps: u16, newer_bps: u16) -> Vec<u8> {
+        let mut data = mint_with_extensions(&[(TRANSFER_FEE_CONFIG_EXTENSION, 108)]);
+        data[MINT_DECIMALS_OFFSET] = decimals;
+        let value = EXTENSIONS_OFFSET + EXTENSION_HEADER_LEN;
+        data[value + OLDER_TRANSFER_FEE_BPS_OFFSET..][..2].copy_from_slice(&older_bps.to_le_bytes());
+        data[value + NEWER_TRANSFER_FEE_BPS_OFFSET..][..2].copy_from_slice(&newer_bps.to_le_bytes());
+        data
+    }
+
+    #[test]
+    fn transfer_fee_bps_reads_the_higher_scheduled_rate() {
+        assert_eq!(transfer_fee_bps(&fee_bearing_mint(6, 50, 50)), 50);
+        assert_eq!(transfer_fee_bps(&fee_bearing_mint(6, 50, 120)), 120);
+        assert_eq!(transfer_fee_bps(&fee_bearing_mint(6, 120, 50)), 120);
+    }
+
+    #[test]
+    fn transfer_fee_bps_is_zero_without_the_extension() {
+        assert_eq!(transfer_fee_bps(&[0u8; 82]), 0);
+        assert_eq!(transfer_fee_bps(&mint_with_extensions(&[(3, 32)])), 0);
+    }
+
+    #[test]
+    fn fill_records_the_transfer_fee_of_a_fee_bearing_mint() {
+        let mint_account = |data: Vec<u8>, owner: Pubkey| Account {
+            lamports: 1,
+            data,
+            owner,
+            ..Account::default()
+        };
+        let mut base_mint = vec![0u8; 82];
+        base_mint[MINT_DECIMALS_OFFSET] = 9;
+        let mut pool = PoolInfo::mock(PoolType::ConstantProduct);
+        pool.token_decimals = None;
+        pool.base_decimals = None;
+        let rpc = MockRpc::new()
+            .with_account(pool.token_mint, mint_account(fee_bearing_mint(6, 0, 250), token_2022_program_id()))
+            .with_account(pool.base_mint, mint_account(base_mint, spl_token::ID));
+        let cache = MintDecimalsCache::new(Arc::new(rpc));
+
+        cache.fill(&mut pool).unwrap();
+
+        assert_eq!(pool.decimals(), Some((6, 9)));
+        assert_eq!(pool.token_transfer_fee_bps, 250);
+        assert_eq!(pool.base_transfer_fee_bps, 0);
+        assert_eq!(cache.token_program(&pool.token_mint).unwrap(), token_2022_program_id());
+    }
+
+    #[test]
+    fn ata_len_without_mint_extensions_is_base_plus_immutable_owner() {
+        assert_eq!(token_2022_ata_len(&[0u8; 82]), 170);
+        assert_eq!(token_2022_ata_len(&mint_with_extensions(&[])), 170);
+    }
+
+    #[test]
+    fn ata_len_grows_with_transfer_fee_amount() {
+        assert_eq!(token_2022_ata_len(&mint_with_extensions(&[(TRANSFER_FEE_CONFIG_EXTENSION, 108)])), 182);
+    }
+
+    #[test]
+    fn ata_len_ignores_mint_only_extensions() {
+        // MintCloseAuthority and MetadataPointer add nothing to token accounts,
+        // TransferHook and NonTransferable do
+        let data = mint_with_extensions(&[(3, 32), (18, 64), (14, 64), (9, 0)]);
+        assert_eq!(token_2022_ata_len(&data), 170 + 5 + 4);
+    }
+}
@@ -1,4 +1,4 @@
-use anyhow::Result;
+use crate::error::{BotError, Result};
 use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::pubkey::Pubkey;
 
@@ -15,7 +15,7 @@ pub struct VertigoPool {
 impl VertigoPool {
     pub fn try_deserialize(data: &mut &[u8]) -> Result<Self> {
         Self::try_from_slice(data)
-            .map_err(|e| anyhow::anyhow!("Failed to deserialize VertigoPool: {}", e))
+            .map_err(|e| BotError::PoolParse(format!("Failed to deserialize VertigoPool: {}", e)))
     }
 }
 
@@ -23,17 +23,19 @@ impl VertigoPool {
 pub struct VertigoInfo {
     pub mint_a: Pubkey,
     pub mint_b: Pubkey,
+    pub owner: Pubkey,
     pub pool: Pubkey,
 }
 
 impl VertigoInfo {
     pub fn load_checked(data: &[u8], pool: &Pubkey) -> Result<Self> {
-        let mut data_slice = &data[..];
+        let mut data_slice = data;
         let vertigo_pool = VertigoPool::try_deserialize(&mut data_slice)?;
 
         Ok(Self {
             mint_a: vertigo_pool.mint_a,
             mint_b: vertigo_pool.mint_b,
+            owner: vertigo_pool.owner,
             pool: pool.to_owned(),
         })
     }
@@ -1,5 +1,7 @@
 pub mod constants;
 pub mod info;
+pub mod unified;
 pub mod utils;
 
+pub use unified::VertigoDex;
 pub use utils::derive_vault_address;
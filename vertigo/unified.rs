@@ -0,0 +1,173 @@
+//! Unified Vertigo DEX implementation using the Dex trait
+
+use crate::dex::traits::{
+    validate_swap_amounts, verify_pool_owner, Dex, PoolInfo, PoolReserves, PoolType, PriceInfo,
+};
+use crate::dex::vault::{decimal_adjusted_price, fetch_pool_reserves, pool_reserves, MintDecimalsCache};
+use crate::dex::vertigo::{constants::vertigo_program_id, info::VertigoInfo, utils::derive_vault_address};
+use crate::dex::rpc::{blocking, RpcLike};
+use async_trait::async_trait;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use std::sync::Arc;
+use crate::dex_boilerplate;
+use crate::error::{BotError, Result};
+
+/// Anchor discriminators of the Vertigo `buy` (mint A in) and `sell` (mint B in) instructions
+const VERTIGO_BUY_DISCRIMINATOR: [u8; 8] = [102, 6, 61, 18, 1, 218, 235, 234];
+const VERTIGO_SELL_DISCRIMINATOR: [u8; 8] = [51, 230, 133, 164, 1, 127, 131, 173];
+
+#[derive(Clone)]
+pub struct VertigoDex {
+    rpc_client: Arc<dyn RpcLike>,
+    decimals: Arc<MintDecimalsCache>,
+}
+
+#[async_trait]
+impl Dex for VertigoDex {
+    dex_boilerplate!("vertigo", vertigo_program_id());
+
+    async fn fetch_pools(&self, pool_addresses: &[String], token_mint: &Pubkey) -> Result<Vec<PoolInfo>> {
+        let mut pools = Vec::new();
+
+        for pool_address in pool_addresses {
+            match self.fetch_single_pool(pool_address, token_mint).await {
+                Ok(pool) => pools.push(pool),
+                Err(e) => {
+                    tracing::error!("Failed to fetch Vertigo pool {}: {}", pool_address, e);
+                }
+            }
+        }
+
+        Ok(pools)
+    }
+
+    async fn calculate_price(&self, pool_info: &PoolInfo) -> Result<PriceInfo> {
+        let (dex, pool_info) = (self.clone(), pool_info.clone());
+        blocking(move || dex.read_price(&pool_info)).await
+    }
+
+    async fn fetch_reserves(&self, pool_info: &PoolInfo) -> Result<PoolReserves> {
+        let (rpc_client, pool_info) = (self.rpc_client.clone(), pool_info.clone());
+        blocking(move || fetch_pool_reserves(rpc_client.as_ref(), &pool_info)).await
+    }
+
+    fn parse_pool_account(&self, address: &Pubkey, data: &[u8], token_mint: &Pubkey) -> Result<PoolInfo> {
+        let pool_pubkey = *address;
+
+        let vertigo_info = VertigoInfo::load_checked(data, &pool_pubkey)?;
+
+        let (token_mint_final, base_mint) = if *token_mint == vertigo_info.mint_a {
+            (vertigo_info.mint_a, vertigo_info.mint_b)
+        } else if *token_mint == vertigo_info.mint_b {
+            (vertigo_info.mint_b, vertigo_info.mint_a)
+        } else {
+            return Err(BotError::PoolParse(format!(
+                "Mint {} not found in Vertigo pool {}",
+                token_mint,
+                pool_pubkey
+            )));
+        };
+
+        // Vaults are PDAs of the pool and mint rather than stored on the pool
+        let (token_vault, _) = derive_vault_address(&pool_pubkey, &token_mint_final);
+        let (base_vault, _) = derive_vault_address(&pool_pubkey, &base_mint);
+
+        let mut additional_accounts = std::collections::HashMap::new();
+        additional_accounts.insert("pool_owner".to_string(), vertigo_info.owner);
+        additional_accounts.insert("mint_a".to_string(), vertigo_info.mint_a);
+
+        Ok(PoolInfo {
+            pool_address: pool_pubkey,
+            token_mint: token_mint_final,
+            base_mint,
+            token_vault,
+            base_vault,
+            fee_wallet: None,
+            additional_accounts,
+            pool_type: PoolType::ConstantProduct,
+            reserves: None,
+            token_decimals: None,
+            base_decimals: None,
+            token_symbol: None,
+            token_transfer_fee_bps: 0,
+            base_transfer_fee_bps: 0,
+        })
+    }
+
+    fn get_swap_instruction_data(&self, pool_info: &PoolInfo, amount_in: u64, minimum_out: u64) -> Result<Vec<u8>> {
+        validate_swap_amounts(amount_in, minimum_out)?;
+
+        // Swaps sell `base_mint` for `token_mint`, which is a `buy` when the
+        // input is the pool's mint A
+        let mint_a = pool_info.additional_account("mint_a")?;
+        let discriminator = if pool_info.base_mint == mint_a {
+            VERTIGO_BUY_DISCRIMINATOR
+        } else {
+            VERTIGO_SELL_DISCRIMINATOR
+        };
+
+        let mut data = Vec::with_capacity(24);
+        data.extend_from_slice(&discriminator);
+        data.extend_from_slice(&amount_in.to_le_bytes()); // amount
+        data.extend_from_slice(&minimum_out.to_le_bytes()); // limit
+        Ok(data)
+    }
+}
+
+impl VertigoDex {
+    pub fn new(rpc_client: Arc<dyn RpcLike>) -> Self {
+        Self {
+            decimals: Arc::new(MintDecimalsCache::new(rpc_client.clone())),
+            rpc_client,
+        }
+    }
+
+    /// `calculate_price`, blocking on the account reads
+    fn read_price(&self, pool_info: &PoolInfo) -> Result<PriceInfo> {
+        let (token_reserve, base_reserve) = pool_reserves(self.rpc_client.as_ref(), pool_info)?;
+        let (token_decimals, base_decimals) =
+            self.decimals.for_pool(pool_info)?;
+
+        let price = decimal_adjusted_price(token_reserve, base_reserve, token_decimals, base_decimals)
+            .ok_or_else(|| {
+                BotError::PriceCalculation(format!("Vertigo pool {} has an empty token vault", pool_info.pool_address))
+            })?;
+
+        Ok(pool_info.with_transfer_fees(PriceInfo {
+            price,
+            liquidity: base_reserve,
+            fee: 0.0, // Fees are set per pool by its owner and not decoded yet
+        }))
+    }
+
+    async fn fetch_single_pool(&self, pool_address: &str, token_mint: &Pubkey) -> Result<PoolInfo> {
+        let (dex, pool_address, token_mint) = (self.clone(), pool_address.to_string(), *token_mint);
+        blocking(move || dex.read_single_pool(&pool_address, &token_mint)).await
+    }
+
+    fn read_single_pool(&self, pool_address: &str, token_mint: &Pubkey) -> Result<PoolInfo> {
+        let pool_pubkey = Pubkey::from_str(pool_address)?;
+        let account = self.rpc_client.get_account(&pool_pubkey)?;
+
+        verify_pool_owner(&account, &vertigo_program_id())?;
+
+        let mut pool_info = self.parse_pool_account(&pool_pubkey, &account.data, token_mint)?;
+        self.decimals.fill(&mut pool_info)?;
+        Ok(pool_info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::rpc::MockRpc;
+
+    #[test]
+    fn vertigo_pools_are_not_discovered_by_mint() {
+        // The placeholder layout skips the Anchor discriminator, so its
+        // offsets can't be used for memcmp filters
+        let dex = VertigoDex::new(Arc::new(MockRpc::new()));
+        assert!(dex.mint_offsets().is_empty());
+    }
+}
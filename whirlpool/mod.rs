@@ -1,7 +1,10 @@
 pub mod constants;
 pub mod state;
+pub mod unified;
 pub mod utils;
 
+pub use unified::WhirlpoolDex;
+
 use crate::dex::whirlpool::state::{Whirlpool, TICK_ARRAY_SIZE};
 use crate::dex::whirlpool::constants::{MAX_TICK_INDEX, MIN_TICK_INDEX};
 use solana_program::instruction::AccountMeta;
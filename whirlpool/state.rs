@@ -76,6 +76,47 @@ impl Tick {
 
 pub const TICK_ARRAY_SIZE: usize = 88;
 
+impl Tick {
+    pub const LEN: usize = 1 + 16 + 16 + 16 + 16 + 16 * NUM_REWARDS;
+}
+
+impl TickArray {
+    pub const LEN: usize = 8 + 4 + Tick::LEN * TICK_ARRAY_SIZE + 32;
+
+    pub fn try_deserialize(data: &[u8]) -> Result<Self> {
+        if data.len() < Self::LEN {
+            return Err(Error::new(ErrorKind::InvalidData, "data too short for TickArray"));
+        }
+
+        let data = &data[8..];
+        let read_u128 = |offset: usize| u128::from_le_bytes(data[offset..offset + 16].try_into().unwrap());
+
+        let start_tick_index = i32::from_le_bytes(data[0..4].try_into().unwrap());
+
+        let mut ticks = [Tick::default(); TICK_ARRAY_SIZE];
+        for (i, tick) in ticks.iter_mut().enumerate() {
+            let offset = 4 + i * Tick::LEN;
+            tick.initialized = data[offset] != 0;
+            tick.liquidity_net = read_u128(offset + 1) as i128;
+            tick.liquidity_gross = read_u128(offset + 17);
+            tick.fee_growth_outside_a = read_u128(offset + 33);
+            tick.fee_growth_outside_b = read_u128(offset + 49);
+            for (j, growth) in tick.reward_growths_outside.iter_mut().enumerate() {
+                *growth = read_u128(offset + 65 + j * 16);
+            }
+        }
+
+        let offset = 4 + Tick::LEN * TICK_ARRAY_SIZE;
+        let whirlpool = Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap());
+
+        Ok(TickArray {
+            start_tick_index,
+            ticks,
+            whirlpool,
+        })
+    }
+}
+
 impl Whirlpool {
     pub fn try_deserialize(data: &[u8]) -> Result<Self> {
         if data.len() < Self::LEN {
@@ -176,30 +217,30 @@ impl Whirlpool {
             growth_global_x64: 0,
         }; NUM_REWARDS];
         
-        for i in 0..NUM_REWARDS {
+        for reward_info in reward_infos.iter_mut() {
             let mut mint_bytes = [0u8; 32];
             mint_bytes.copy_from_slice(&data[offset..offset+32]);
-            reward_infos[i].mint = Pubkey::new_from_array(mint_bytes);
+            reward_info.mint = Pubkey::new_from_array(mint_bytes);
             offset += 32;
             
             let mut vault_bytes = [0u8; 32];
             vault_bytes.copy_from_slice(&data[offset..offset+32]);
-            reward_infos[i].vault = Pubkey::new_from_array(vault_bytes);
+            reward_info.vault = Pubkey::new_from_array(vault_bytes);
             offset += 32;
             
             let mut authority_bytes = [0u8; 32];
             authority_bytes.copy_from_slice(&data[offset..offset+32]);
-            reward_infos[i].authority = Pubkey::new_from_array(authority_bytes);
+            reward_info.authority = Pubkey::new_from_array(authority_bytes);
             offset += 32;
             
             let mut emissions_bytes = [0u8; 16];
             emissions_bytes.copy_from_slice(&data[offset..offset+16]);
-            reward_infos[i].emissions_per_second_x64 = u128::from_le_bytes(emissions_bytes);
+            reward_info.emissions_per_second_x64 = u128::from_le_bytes(emissions_bytes);
             offset += 16;
             
             let mut growth_bytes = [0u8; 16];
             growth_bytes.copy_from_slice(&data[offset..offset+16]);
-            reward_infos[i].growth_global_x64 = u128::from_le_bytes(growth_bytes);
+            reward_info.growth_global_x64 = u128::from_le_bytes(growth_bytes);
             offset += 16;
         }
         
@@ -0,0 +1,274 @@
+//! Unified Orca Whirlpool DEX implementation using the Dex trait
+
+use crate::dex::traits::{
+    validate_swap_amounts, verify_pool_owner, Dex, DexCapabilities, PoolInfo, PoolReserves, PoolType, PriceInfo,
+};
+use crate::dex::math::{
+    clmm_fee_fraction, clmm_fee_tier_bps, quote_clmm_snapshot, quote_clmm_with_ticks, sqrt_price_x64_to_price,
+    ClmmSnapshot, InitializedTick,
+};
+use crate::dex::vault::{fetch_pool_reserves, pool_reserves, MintDecimalsCache};
+use crate::dex::whirlpool::{
+    constants::whirlpool_program_id,
+    state::{TickArray, Whirlpool},
+    update_tick_array_accounts_for_onchain,
+};
+use crate::dex::rpc::{blocking, RpcLike};
+use async_trait::async_trait;
+use solana_sdk::pubkey::Pubkey;
+use std::str::FromStr;
+use std::sync::Arc;
+use crate::dex_boilerplate;
+use crate::error::{BotError, Result};
+
+/// Offsets of `token_mint_a` and `token_mint_b` in a Whirlpool account
+const WHIRLPOOL_MINT_OFFSETS: [usize; 2] = [101, 181];
+
+#[derive(Clone)]
+pub struct WhirlpoolDex {
+    rpc_client: Arc<dyn RpcLike>,
+    decimals: Arc<MintDecimalsCache>,
+}
+
+#[async_trait]
+impl Dex for WhirlpoolDex {
+    dex_boilerplate!("whirlpool", whirlpool_program_id());
+
+    async fn fetch_pools(&self, pool_addresses: &[String], token_mint: &Pubkey) -> Result<Vec<PoolInfo>> {
+        let mut pools = Vec::new();
+
+        for pool_address in pool_addresses {
+            match self.fetch_single_pool(pool_address, token_mint).await {
+                Ok(pool) => pools.push(pool),
+                Err(e) => {
+                    tracing::error!("Failed to fetch Whirlpool {}: {}", pool_address, e);
+                }
+            }
+        }
+
+        Ok(pools)
+    }
+
+    async fn calculate_price(&self, pool_info: &PoolInfo) -> Result<PriceInfo> {
+        let (dex, pool_info) = (self.clone(), pool_info.clone());
+        blocking(move || dex.read_price(&pool_info)).await
+    }
+
+    async fn fetch_reserves(&self, pool_info: &PoolInfo) -> Result<PoolReserves> {
+        let (rpc_client, pool_info) = (self.rpc_client.clone(), pool_info.clone());
+        blocking(move || fetch_pool_reserves(rpc_client.as_ref(), &pool_info)).await
+    }
+
+    fn parse_pool_account(&self, address: &Pubkey, data: &[u8], token_mint: &Pubkey) -> Result<PoolInfo> {
+        let pool_pubkey = *address;
+
+        let whirlpool = Whirlpool::try_deserialize(data)
+            .map_err(|e| BotError::PoolParse(format!("Invalid Whirlpool {}: {}", pool_pubkey, e)))?;
+
+        let (token_mint_final, base_mint, token_vault, base_vault) = if *token_mint == whirlpool.token_mint_a {
+            (whirlpool.token_mint_a, whirlpool.token_mint_b, whirlpool.token_vault_a, whirlpool.token_vault_b)
+        } else if *token_mint == whirlpool.token_mint_b {
+            (whirlpool.token_mint_b, whirlpool.token_mint_a, whirlpool.token_vault_b, whirlpool.token_vault_a)
+        } else {
+            return Err(BotError::PoolParse(format!(
+                "Mint {} not found in Whirlpool {}",
+                token_mint,
+                pool_pubkey
+            )));
+        };
+
+        let (oracle, _) =
+            Pubkey::find_program_address(&[b"oracle", pool_pubkey.as_ref()], &whirlpool_program_id());
+
+        // Swaps take the vaults in A/B order plus the tick arrays around the
+        // current tick, which shift as the price moves
+        let mut additional_accounts = std::collections::HashMap::new();
+        additional_accounts.insert("oracle".to_string(), oracle);
+        additional_accounts.insert("token_vault_a".to_string(), whirlpool.token_vault_a);
+        additional_accounts.insert("token_vault_b".to_string(), whirlpool.token_vault_b);
+        let tick_arrays =
+            update_tick_array_accounts_for_onchain(&whirlpool, &pool_pubkey, &whirlpool_program_id());
+        for (i, tick_array) in tick_arrays.iter().enumerate() {
+            additional_accounts.insert(format!("tick_array_{}", i), tick_array.pubkey);
+        }
+
+        Ok(PoolInfo {
+            pool_address: pool_pubkey,
+            token_mint: token_mint_final,
+            base_mint,
+            token_vault,
+            base_vault,
+            fee_wallet: None,
+            additional_accounts,
+            pool_type: PoolType::ConcentratedLiquidity,
+            reserves: None,
+            token_decimals: None,
+            base_decimals: None,
+            token_symbol: None,
+            token_transfer_fee_bps: 0,
+            base_transfer_fee_bps: 0,
+        })
+    }
+
+    fn mint_offsets(&self) -> &'static [usize] {
+        &WHIRLPOOL_MINT_OFFSETS
+    }
+
+    fn get_swap_instruction_data(&self, _pool_info: &PoolInfo, amount_in: u64, minimum_out: u64) -> Result<Vec<u8>> {
+        validate_swap_amounts(amount_in, minimum_out)?;
+
+        // An empty instruction would only revert on-chain
+        Err(BotError::Validation("Whirlpool swap encoding not implemented".to_string()))
+    }
+
+    fn capabilities(&self) -> DexCapabilities {
+        DexCapabilities {
+            supports_token_2022: true,
+            concentrated_liquidity: true,
+            needs_oracle: true,
+            ..DexCapabilities::default()
+        }
+    }
+
+    async fn quote(&self, pool_info: &PoolInfo, amount_in: u64, _reserves: &PoolReserves) -> Result<u64> {
+        let (dex, pool_info) = (self.clone(), pool_info.clone());
+        blocking(move || dex.read_quote(&pool_info, amount_in)).await
+    }
+}
+
+impl WhirlpoolDex {
+    pub fn new(rpc_client: Arc<dyn RpcLike>) -> Self {
+        Self {
+            decimals: Arc::new(MintDecimalsCache::new(rpc_client.clone())),
+            rpc_client,
+        }
+    }
+
+    /// Quote a swap against a supplied liquidity and price snapshot instead of
+    /// live accounts. Tick crossings are not modelled.
+    pub fn quote_with_snapshot(
+        &self,
+        snapshot: &ClmmSnapshot,
+        amount_in: u64,
+        fee_bps: u16,
+        zero_for_one: bool,
+    ) -> Result<u64> {
+        quote_clmm_snapshot(snapshot, amount_in, fee_bps, zero_for_one)
+    }
+
+    fn fetch_whirlpool(&self, pool_pubkey: &Pubkey) -> Result<Whirlpool> {
+        let account = self.rpc_client.get_account(pool_pubkey)?;
+
+        verify_pool_owner(&account, &whirlpool_program_id())?;
+
+        Whirlpool::try_deserialize(&account.data)
+            .map_err(|e| BotError::PoolParse(format!("Invalid Whirlpool {}: {}", pool_pubkey, e)))
+    }
+
+    /// `calculate_price`, blocking on the account reads
+    fn read_price(&self, pool_info: &PoolInfo) -> Result<PriceInfo> {
+        // The current sqrt price moves with every swap, so read it fresh
+        let whirlpool = self.fetch_whirlpool(&pool_info.pool_address)?;
+        let (decimals_a, decimals_b) =
+            self.decimals.get_pair(&whirlpool.token_mint_a, &whirlpool.token_mint_b)?;
+
+        // sqrt_price is B per A; flip it when the tracked token is mint B
+        let price_b_per_a = sqrt_price_x64_to_price(whirlpool.sqrt_price, decimals_a, decimals_b);
+        let price = if pool_info.token_mint == whirlpool.token_mint_a {
+            price_b_per_a
+        } else if price_b_per_a > 0.0 {
+            1.0 / price_b_per_a
+        } else {
+            return Err(BotError::PriceCalculation(format!(
+                "Whirlpool {} has a zero sqrt price",
+                pool_info.pool_address
+            )));
+        };
+
+        let (_, base_reserve) = pool_reserves(self.rpc_client.as_ref(), pool_info)?;
+
+        Ok(pool_info.with_transfer_fees(PriceInfo {
+            price,
+            liquidity: base_reserve,
+            fee: clmm_fee_fraction(whirlpool.fee_rate as u32),
+        }))
+    }
+
+    /// `quote`, blocking on the account reads
+    fn read_quote(&self, pool_info: &PoolInfo, amount_in: u64) -> Result<u64> {
+        // Price and liquidity live in the whirlpool account, and crossings need
+        // the tick arrays the decoder stored around the current tick
+        let mut keys = vec![pool_info.pool_address];
+        keys.extend(
+            pool_info
+                .additional_accounts
+                .iter()
+                .filter(|(name, _)| name.starts_with("tick_array_"))
+                .map(|(_, key)| *key),
+        );
+        let accounts = self.rpc_client.get_multiple_accounts(&keys)?;
+        let pool = accounts[0].as_ref().ok_or_else(|| {
+            BotError::AccountFetch(format!("Missing account for Whirlpool {}", pool_info.pool_address))
+        })?;
+        let whirlpool = Whirlpool::try_deserialize(&pool.data)
+            .map_err(|e| BotError::PoolParse(format!("Invalid Whirlpool {}: {}", pool_info.pool_address, e)))?;
+        // Rounded up, so a sub-bps tier quotes slightly under what the pool pays out
+        let fee_bps = clmm_fee_tier_bps(whirlpool.fee_rate as u32);
+
+        // Tick arrays that were never initialized simply have no ticks
+        let mut ticks = Vec::new();
+        for account in accounts[1..].iter().flatten() {
+            let tick_array = TickArray::try_deserialize(&account.data)
+                .map_err(|e| BotError::PoolParse(format!("Invalid Whirlpool tick array: {}", e)))?;
+            for (i, tick) in tick_array.ticks.iter().enumerate() {
+                if tick.initialized {
+                    ticks.push(InitializedTick {
+                        tick_index: tick_array.start_tick_index + i as i32 * whirlpool.tick_spacing as i32,
+                        liquidity_net: tick.liquidity_net,
+                    });
+                }
+            }
+        }
+
+        let snapshot = ClmmSnapshot {
+            liquidity: whirlpool.liquidity,
+            sqrt_price_x64: whirlpool.sqrt_price,
+        };
+        let zero_for_one = pool_info.base_mint == whirlpool.token_mint_a;
+        let amount_in = pool_info.amount_in_after_transfer_fee(amount_in);
+        let amount_out = quote_clmm_with_ticks(&snapshot, &ticks, amount_in, fee_bps, zero_for_one)?;
+        Ok(pool_info.amount_out_after_transfer_fee(amount_out))
+    }
+
+    async fn fetch_single_pool(&self, pool_address: &str, token_mint: &Pubkey) -> Result<PoolInfo> {
+        let (dex, pool_address, token_mint) = (self.clone(), pool_address.to_string(), *token_mint);
+        blocking(move || dex.read_single_pool(&pool_address, &token_mint)).await
+    }
+
+    fn read_single_pool(&self, pool_address: &str, token_mint: &Pubkey) -> Result<PoolInfo> {
+        let pool_pubkey = Pubkey::from_str(pool_address)?;
+        let account = self.rpc_client.get_account(&pool_pubkey)?;
+
+        verify_pool_owner(&account, &whirlpool_program_id())?;
+
+        let mut pool_info = self.parse_pool_account(&pool_pubkey, &account.data, token_mint)?;
+        self.decimals.fill(&mut pool_info)?;
+        Ok(pool_info)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dex::rpc::MockRpc;
+
+    #[test]
+    fn swap_encoding_is_refused_until_implemented() {
+        let dex = WhirlpoolDex::new(Arc::new(MockRpc::new()));
+        let pool = PoolInfo::mock(PoolType::ConcentratedLiquidity);
+        assert!(matches!(
+            dex.get_swap_instruction_data(&pool, 1_000, 900),
+            Err(BotError::Validation(_))
+        ));
+    }
+}
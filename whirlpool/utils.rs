@@ -12,22 +12,18 @@ pub fn update_tick_array_accounts_for_onchain(
 
     if a_to_b {
         // A to B
-        if tick_array_count == 1 {
-            tick_array_offset = 1;
-        } else if tick_array_count == 2 {
+        if tick_array_count == 1 || tick_array_count == 2 {
             tick_array_offset = 1;
         }
     } else {
         // B to A
-        if tick_array_count == 1 {
-            tick_array_offset = -1;
-        } else if tick_array_count == 2 {
+        if tick_array_count == 1 || tick_array_count == 2 {
             tick_array_offset = -1;
         }
     }
 
-    let tick_array_start_index = tick_current_index as i32
-        - (tick_current_index as i32
+    let tick_array_start_index = tick_current_index
+        - (tick_current_index
             % (tick_spacing as i32 * crate::dex::whirlpool::state::TICK_ARRAY_SIZE as i32))
         + tick_array_offset * tick_spacing as i32 * crate::dex::whirlpool::state::TICK_ARRAY_SIZE as i32;
 